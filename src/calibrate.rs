@@ -0,0 +1,251 @@
+use std::{
+    fmt, fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use image::GrayImage;
+use log::info;
+use subtile_ocr_core::{ocr, Config, OcrOpt, SubtitlePipeline, TessdataProbe};
+
+use crate::opt::{ConvertStrategy, Opt};
+
+/// Number of subtitles decoded to compare the settings.
+const CALIBRATION_CUES: usize = 3;
+
+/// Directory of the candidate binarizations, next to the profile file.
+const CALIBRATION_DIR: &str = "calibration";
+
+/// Thresholds tried with an ink tolerance, for the `VobSub` binarization.
+const THRESHOLDS: [f32; 4] = [0.4, 0.5, 0.6, 0.7];
+
+/// Ink tolerance of the candidate binarizations with a threshold.
+const TOLERANCE: f32 = 0.1;
+
+/// Resolutions tried for the `OCR`.
+const DPIS: [i32; 3] = [100, 150, 300];
+
+/// Binarization settings of a candidate.
+#[derive(Clone, Copy, Debug)]
+struct Binarization {
+    threshold: f32,
+    ink_tolerance: f32,
+}
+
+/// Settings tried by the calibration, with the `OCR` results of the first subtitles.
+#[derive(Debug)]
+struct CalibrationCandidate {
+    /// Number of the binarization, in the names of its images.
+    binarization: usize,
+    /// Threshold of the binarization.
+    threshold: f32,
+    /// Ink tolerance of the binarization.
+    ink_tolerance: f32,
+    /// Resolution of the `OCR`.
+    dpi: i32,
+    /// Text recognized in each subtitle, on one line.
+    texts: Vec<String>,
+}
+
+/// Candidate settings of a calibration, for the user to pick one.
+///
+/// Displayed as the list of the candidates, numbered from 1.
+#[derive(Debug)]
+struct Calibration {
+    /// Directory of the images of the candidate binarizations.
+    dir: PathBuf,
+    /// Candidate settings, in the order of their numbers.
+    candidates: Vec<CalibrationCandidate>,
+}
+
+impl Calibration {
+    /// Apply the settings of the candidate `choice`, from 0, to `opt` and save them in the `profile` file.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `choice` is not the index of a candidate.
+    fn apply(&self, choice: usize, opt: &mut Opt, profile: &Path) -> anyhow::Result<()> {
+        let candidate = &self.candidates[choice];
+        opt.image.threshold = candidate.threshold;
+        opt.image.ink_tolerance = candidate.ink_tolerance;
+        opt.ocr.dpi = candidate.dpi;
+        let args = format!(
+            "--threshold={}\n--ink-tolerance={}\n--dpi={}\n",
+            candidate.threshold, candidate.ink_tolerance, candidate.dpi
+        );
+        profile
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(profile, args))
+            .with_context(|| {
+                format!(
+                    "Could not write the calibration profile {}",
+                    profile.display()
+                )
+            })
+    }
+}
+
+impl fmt::Display for Calibration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Candidate settings, with their binarizations in `{}/binarization<N>_cue<M>.png`:",
+            self.dir.display()
+        )?;
+        for (idx, candidate) in self.candidates.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>3}: binarization {} (threshold {}, ink tolerance {}), dpi {}",
+                idx + 1,
+                candidate.binarization,
+                candidate.threshold,
+                candidate.ink_tolerance,
+                candidate.dpi
+            )?;
+            for text in &candidate.texts {
+                writeln!(f, "       | {text}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Calibrate the settings of `opt` if a profile file is given, with the candidate
+/// picked by the user.
+pub fn run(opt: &mut Opt) -> anyhow::Result<()> {
+    let Some(profile) = opt.calibrate.clone() else {
+        return Ok(());
+    };
+    let calibration = calibrate(opt, &profile)?;
+    eprint!("{calibration}");
+    let Some(choice) =
+        prompt(calibration.candidates.len()).context("Could not read the calibration choice.")?
+    else {
+        info!("No candidate picked, the settings are kept");
+        return Ok(());
+    };
+    calibration.apply(choice, opt, &profile)?;
+    let profile = profile.display();
+    eprintln!("Settings saved to `{profile}`, reuse them with `subtile-ocr $(cat {profile}) ...`");
+    Ok(())
+}
+
+/// Try a grid of binarization settings and resolutions on the first subtitles of the input.
+///
+/// The candidate binarizations are written as PNG files in the `calibration` directory
+/// next to the `profile` file.
+fn calibrate(opt: &Opt, profile: &Path) -> anyhow::Result<Calibration> {
+    let input = opt
+        .input
+        .input
+        .as_deref()
+        .context("No input file to calibrate.")?;
+    let initial = Binarization {
+        threshold: opt.image.threshold,
+        ink_tolerance: opt.image.ink_tolerance,
+    };
+    // The binarization settings only apply to the palette luminance of `VobSub` images.
+    let mut binarizations = vec![initial];
+    if input.extension().is_some_and(|ext| ext == "idx")
+        && matches!(opt.image.convert, ConvertStrategy::PaletteLuma)
+    {
+        binarizations.extend(THRESHOLDS.map(|threshold| Binarization {
+            threshold,
+            ink_tolerance: TOLERANCE,
+        }));
+    }
+
+    let dir = profile
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(CALIBRATION_DIR);
+    fs::create_dir_all(&dir).with_context(|| {
+        format!(
+            "Could not create the calibration directory {}",
+            dir.display()
+        )
+    })?;
+    let tessdata_probe = TessdataProbe::default();
+    let mut candidates = Vec::new();
+    for (binarization_idx, binarization) in binarizations.iter().enumerate() {
+        let mut opt = opt.clone();
+        opt.image.threshold = binarization.threshold;
+        opt.image.ink_tolerance = binarization.ink_tolerance;
+        let config = Config::from(opt.clone());
+        let images = SubtitlePipeline::new(&config)
+            .images()?
+            .take(CALIBRATION_CUES)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .map(|(_, image)| image)
+            .collect::<Vec<_>>();
+        for (cue_idx, image) in images.iter().enumerate() {
+            let path = dir.join(format!(
+                "binarization{}_cue{}.png",
+                binarization_idx + 1,
+                cue_idx + 1
+            ));
+            image.save(&path).with_context(|| {
+                format!("Could not write the calibration image {}", path.display())
+            })?;
+        }
+        for dpi in DPIS {
+            let texts = recognize(&opt, &tessdata_probe, images.clone(), dpi)?;
+            candidates.push(CalibrationCandidate {
+                binarization: binarization_idx + 1,
+                threshold: binarization.threshold,
+                ink_tolerance: binarization.ink_tolerance,
+                dpi,
+                texts,
+            });
+        }
+    }
+
+    Ok(Calibration { dir, candidates })
+}
+
+/// Run the `OCR` of the calibration `images` at `dpi`, each text on one line.
+fn recognize(
+    opt: &Opt,
+    tessdata_probe: &TessdataProbe,
+    images: Vec<GrayImage>,
+    dpi: i32,
+) -> anyhow::Result<Vec<String>> {
+    // The language of the runs with OCR is checked by `Opt::check_lang`.
+    let lang = opt.ocr.lang.as_deref().unwrap_or_default();
+    let ocr_opt = OcrOpt::new(&opt.ocr.tessdata_dir, lang, &opt.ocr.config, dpi)
+        .with_tessdata_probe(Some(tessdata_probe));
+    let texts = ocr(images, &ocr_opt)?;
+    Ok(texts
+        .into_iter()
+        .map(|text| match text {
+            Ok(text) => text.split_whitespace().collect::<Vec<_>>().join(" "),
+            Err(err) => format!("<OCR failed: {err}>"),
+        })
+        .collect())
+}
+
+/// Ask the user to pick one of `count` candidates, `None` to keep the current settings.
+fn prompt(count: usize) -> io::Result<Option<usize>> {
+    let stdin = io::stdin();
+    loop {
+        eprint!("Pick the settings to use [1-{count}, empty to keep the current ones]: ");
+        io::stderr().flush()?;
+        // The console may not give UTF-8, like conhost with a legacy code page.
+        let mut line = Vec::new();
+        if stdin.lock().read_until(b'\n', &mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+        match line.parse::<usize>() {
+            Ok(choice) if (1..=count).contains(&choice) => return Ok(Some(choice - 1)),
+            _ => eprintln!("`{line}` is not a candidate number"),
+        }
+    }
+}
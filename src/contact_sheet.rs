@@ -0,0 +1,230 @@
+use std::{fs, mem, path::Path};
+
+use image::{imageops, GrayImage, ImageError, Luma};
+use log::info;
+use subtile_ocr_core::{format_timestamp, TimeSpan};
+
+/// Width of a sheet column, wider images are scaled down.
+const COLUMN_WIDTH: u32 = 720;
+
+/// Number of columns of a sheet.
+const COLUMNS: u32 = 2;
+
+/// Height above which a new sheet is started.
+const MAX_SHEET_HEIGHT: u32 = 4000;
+
+/// Space in pixels around the cells.
+const MARGIN: u32 = 8;
+
+/// Scale factor applied on the 5x7 glyphs of the labels.
+const GLYPH_SCALE: u32 = 2;
+
+/// Height of the label above each image.
+const LABEL_HEIGHT: u32 = 7 * GLYPH_SCALE + MARGIN;
+
+/// Luminance of the sheet background, to distinguish it from the images background.
+const SHEET_BACKGROUND: u8 = 160;
+
+/// 5x7 bitmap glyphs used to render the labels.
+const GLYPHS: [(char, [&str; 7]); 15] = [
+    (
+        '0',
+        [
+            ".###.", "#...#", "#..##", "#.#.#", "##..#", "#...#", ".###.",
+        ],
+    ),
+    (
+        '1',
+        [
+            "..#..", ".##..", "..#..", "..#..", "..#..", "..#..", ".###.",
+        ],
+    ),
+    (
+        '2',
+        [
+            ".###.", "#...#", "....#", "...#.", "..#..", ".#...", "#####",
+        ],
+    ),
+    (
+        '3',
+        [
+            "#####", "...#.", "..#..", "...#.", "....#", "#...#", ".###.",
+        ],
+    ),
+    (
+        '4',
+        [
+            "...#.", "..##.", ".#.#.", "#..#.", "#####", "...#.", "...#.",
+        ],
+    ),
+    (
+        '5',
+        [
+            "#####", "#....", "####.", "....#", "....#", "#...#", ".###.",
+        ],
+    ),
+    (
+        '6',
+        [
+            "..##.", ".#...", "#....", "####.", "#...#", "#...#", ".###.",
+        ],
+    ),
+    (
+        '7',
+        [
+            "#####", "....#", "...#.", "..#..", ".#...", ".#...", ".#...",
+        ],
+    ),
+    (
+        '8',
+        [
+            ".###.", "#...#", "#...#", ".###.", "#...#", "#...#", ".###.",
+        ],
+    ),
+    (
+        '9',
+        [
+            ".###.", "#...#", "#...#", ".####", "....#", "...#.", ".##..",
+        ],
+    ),
+    (
+        ':',
+        [
+            ".....", "..#..", "..#..", ".....", "..#..", "..#..", ".....",
+        ],
+    ),
+    (
+        ',',
+        [
+            ".....", ".....", ".....", ".....", "..#..", "..#..", ".#...",
+        ],
+    ),
+    (
+        '#',
+        [
+            ".#.#.", ".#.#.", "#####", ".#.#.", "#####", ".#.#.", ".#.#.",
+        ],
+    ),
+    (
+        '-',
+        [
+            ".....", ".....", ".....", "#####", ".....", ".....", ".....",
+        ],
+    ),
+    (
+        '>',
+        [
+            "#....", ".#...", "..#..", "...#.", "..#..", ".#...", "#....",
+        ],
+    ),
+];
+
+/// Write every `step`th image in contact sheets `sheet_NNN.png` in `dir`, labelled with their number and times.
+///
+/// # Errors
+///
+/// Will return an error if a sheet can't be written.
+#[profiling::function]
+pub fn write_contact_sheets(
+    dir: &Path,
+    times: &[TimeSpan],
+    images: &[GrayImage],
+    step: usize,
+) -> Result<(), ImageError> {
+    fs::create_dir_all(dir)?;
+
+    // Prepare the cells, with the images scaled down to the column width.
+    let cells = times
+        .iter()
+        .zip(images)
+        .enumerate()
+        .step_by(step.max(1))
+        .map(|(idx, (time, image))| {
+            let label = format!(
+                "#{} {} -> {}",
+                idx + 1,
+                format_timestamp(time.start, ','),
+                format_timestamp(time.end, ',')
+            );
+            let image = if image.width() > COLUMN_WIDTH {
+                let height = image.height() * COLUMN_WIDTH / image.width();
+                imageops::resize(image, COLUMN_WIDTH, height.max(1), imageops::Triangle)
+            } else {
+                image.clone()
+            };
+            (label, image)
+        })
+        .collect::<Vec<_>>();
+
+    // Group the cells by rows, and the rows by sheets.
+    let mut sheets = Vec::new();
+    let mut sheet: Vec<&[(String, GrayImage)]> = Vec::new();
+    let mut sheet_height = MARGIN;
+    for row in cells.chunks(COLUMNS as usize) {
+        let height = row_height(row);
+        if !sheet.is_empty() && sheet_height + height > MAX_SHEET_HEIGHT {
+            sheets.push(mem::take(&mut sheet));
+            sheet_height = MARGIN;
+        }
+        sheet.push(row);
+        sheet_height += height;
+    }
+    if !sheet.is_empty() {
+        sheets.push(sheet);
+    }
+
+    for (sheet_idx, rows) in sheets.iter().enumerate() {
+        let width = MARGIN + COLUMNS * (COLUMN_WIDTH + MARGIN);
+        let height = MARGIN + rows.iter().map(|row| row_height(row)).sum::<u32>();
+        let mut sheet = GrayImage::from_pixel(width, height, Luma([SHEET_BACKGROUND]));
+        let mut y = MARGIN;
+        for row in rows {
+            for (column, (label, image)) in (0..).zip(row.iter()) {
+                let x = MARGIN + column * (COLUMN_WIDTH + MARGIN);
+                draw_label(&mut sheet, x, y, label);
+                imageops::replace(&mut sheet, image, x.into(), (y + LABEL_HEIGHT).into());
+            }
+            y += row_height(row);
+        }
+        let path = dir.join(format!("sheet_{:03}.png", sheet_idx + 1));
+        sheet.save(path)?;
+    }
+    info!(
+        "{} subtitles images written in {} contact sheets in {}",
+        cells.len(),
+        sheets.len(),
+        dir.display()
+    );
+    Ok(())
+}
+
+/// Height of a row of cells, with the label and the margin.
+fn row_height(row: &[(String, GrayImage)]) -> u32 {
+    let image_height = row.iter().map(|(_, image)| image.height()).max();
+    LABEL_HEIGHT + image_height.unwrap_or(0) + MARGIN
+}
+
+/// Draw `text` in black, with its top left corner at `x`,`y`.
+fn draw_label(sheet: &mut GrayImage, x: u32, y: u32, text: &str) {
+    let advance = 6 * GLYPH_SCALE;
+    for (char_x, c) in (0..).map(|idx| x + idx * advance).zip(text.chars()) {
+        let Some((_, rows)) = GLYPHS.iter().find(|(glyph, _)| *glyph == c) else {
+            continue; // space or unknown char
+        };
+        for (row_y, row) in (0..).map(|idx| y + idx * GLYPH_SCALE).zip(rows) {
+            for (col_x, _) in (0..)
+                .map(|idx| char_x + idx * GLYPH_SCALE)
+                .zip(row.chars())
+                .filter(|(_, pixel)| *pixel == '#')
+            {
+                for (px, py) in
+                    (0..GLYPH_SCALE).flat_map(|dy| (0..GLYPH_SCALE).map(move |dx| (dx, dy)))
+                {
+                    if col_x + px < sheet.width() && row_y + py < sheet.height() {
+                        sheet.put_pixel(col_x + px, row_y + py, Luma([0]));
+                    }
+                }
+            }
+        }
+    }
+}
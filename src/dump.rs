@@ -0,0 +1,41 @@
+use std::{fs, path::Path};
+
+use anyhow::Context;
+use image::{DynamicImage, ImageFormat};
+use subtile_ocr_core::{format_timestamp, TimeSpan};
+
+/// Write `images` as PNG files in directory `dir`, named with `template` from their subtitle.
+///
+/// The placeholders of `template` are `{index}`, the number of the subtitle on 4 digits,
+/// and `{start}` and `{end}`, the times of the subtitle in `times` as `HH-MM-SS.mmm`.
+#[profiling::function]
+pub fn dump_images<Img: Into<DynamicImage>>(
+    dir: &str,
+    template: &str,
+    times: &[TimeSpan],
+    images: impl IntoIterator<Item = Img>,
+) -> anyhow::Result<()> {
+    let dir = Path::new(dir);
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create the dump directory {}", dir.display()))?;
+    for (idx, (time, image)) in times.iter().zip(images).enumerate() {
+        let name = template
+            .replace("{index}", &format!("{:04}", idx + 1))
+            .replace(
+                "{start}",
+                &file_timestamp(&format_timestamp(time.start, '.')),
+            )
+            .replace("{end}", &file_timestamp(&format_timestamp(time.end, '.')));
+        let path = dir.join(format!("{name}.png"));
+        image
+            .into()
+            .save_with_format(&path, ImageFormat::Png)
+            .with_context(|| format!("Failed to dump the subtitle image {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Make a timestamp formatted as `HH:MM:SS.mmm` usable in file names, as `HH-MM-SS.mmm`.
+fn file_timestamp(timestamp: &str) -> String {
+    timestamp.replace(':', "-")
+}
@@ -1,9 +1,21 @@
 //! Application to run OCR on a subtitles image format (like `VobSub`)
 
 use anyhow::Context;
-use clap::Parser;
-use log::LevelFilter;
-use subtile_ocr::{run, Opt};
+use clap::{CommandFactory, FromArgMatches, Parser};
+use log::{info, Level, LevelFilter};
+use opt::Opt;
+use std::{env, path::Path};
+use subtile_ocr_core::{
+    extract_subtitles, fix_idx, learn_corrections, palette_from_frame, run_inspected, self_test,
+    Config, DiagnosticsLogger, SelfTestReport,
+};
+use tools::Tools;
+
+mod calibrate;
+mod contact_sheet;
+mod dump;
+mod opt;
+mod tools;
 
 #[cfg(not(feature = "profile-with-puffin"))]
 use no_profiling as prof;
@@ -13,19 +25,77 @@ use puffin_profiling as prof;
 fn main() -> anyhow::Result<()> {
     let profiling_data = prof::init();
 
-    simple_logger::SimpleLogger::new()
+    let vobsubocr_mode = invoked_as_vobsubocr();
+    let opt = if vobsubocr_mode {
+        // The cli of vobsubocr is a subset of ours, only the displayed name changes.
+        let matches = Opt::command()
+            .name("vobsubocr")
+            .bin_name("vobsubocr")
+            .get_matches();
+        Opt::from_arg_matches(&matches).unwrap_or_else(|err| err.exit())
+    } else {
+        Opt::parse()
+    };
+    opt.check_lang();
+
+    let logger = simple_logger::SimpleLogger::new()
         .without_timestamps()
         .with_level(LevelFilter::Warn)
-        .env()
-        .init()
-        .unwrap();
-    let opt = Opt::parse();
-    let res = run(&opt).with_context(|| {
-        format!(
-            "Could not convert '{}' to 'srt'.",
-            opt.input.clone().display()
-        )
-    });
+        .with_colors(console_colors())
+        .env();
+    let diagnostics = match &opt.diagnostics_json {
+        Some(path) => {
+            let level = logger.max_level();
+            let diagnostics = DiagnosticsLogger::install(Box::new(logger), level, path)
+                .with_context(|| {
+                    format!("Could not create diagnostics file '{}'", path.display())
+                })?;
+            Some(diagnostics)
+        }
+        None => {
+            logger.init().context("Could not initialize the logger.")?;
+            None
+        }
+    };
+    if vobsubocr_mode {
+        info!("Running in vobsubocr compatibility mode");
+    }
+
+    let res = if opt.self_test {
+        let mut report = SelfTestReport::default();
+        let res = self_test(&Config::from(opt), &mut report);
+        print!("{report}");
+        res.context("The self-test didn't pass.")
+    } else if let (Some([ocr, corrected]), Some(model)) = (
+        opt.learn_corrections.as_deref(),
+        opt.text.corrections.as_deref(),
+    ) {
+        learn_corrections(ocr, corrected, model).context("Could not learn the corrections.")
+    } else if let Some(path) = &opt.palette_from_frame {
+        palette_from_frame(path)
+            .map(|palette| println!("{palette}"))
+            .context("Could not estimate the palette.")
+    } else if let Some(path) = opt.fix_idx.clone() {
+        fix_idx(&Config::from(opt), &path).context("Could not fix the idx file.")
+    } else if let Some(path) = opt.extract_only.clone() {
+        extract_subtitles(&Config::from(opt), &path)
+            .with_context(|| format!("Could not extract the subtitles to '{}'.", path.display()))
+    } else {
+        let mut opt = opt;
+        let input = opt.input.input.clone().unwrap_or_default();
+        calibrate::run(&mut opt)
+            .context("Could not calibrate the settings.")
+            .and_then(|()| {
+                let mut tools = Tools::from(&opt);
+                run_inspected(&Config::from(opt), &mut tools).with_context(|| {
+                    format!("Could not convert '{}' to subtitles text.", input.display())
+                })
+            })
+    };
+
+    if let (Some(diagnostics), Err(err)) = (diagnostics, &res) {
+        diagnostics.record(Level::Error, "subtile_ocr", &format!("{err:#}"));
+    }
 
     profiling::finish_frame!();
     prof::write_perf_file(profiling_data)?;
@@ -33,6 +103,31 @@ fn main() -> anyhow::Result<()> {
     res
 }
 
+/// Enable the colors escape sequences of the Windows console.
+///
+/// Return `false` if the console can't handle them, like the legacy conhost,
+/// to write the log messages without colors.
+#[cfg(windows)]
+fn console_colors() -> bool {
+    colored::control::set_virtual_terminal(true).is_ok()
+}
+
+/// The terminals handle the colors escape sequences outside of Windows.
+#[cfg(not(windows))]
+const fn console_colors() -> bool {
+    true
+}
+
+/// Check if the binary is run under the name of `vobsubocr`, e.g. from a symlink.
+fn invoked_as_vobsubocr() -> bool {
+    env::args_os()
+        .next()
+        .as_deref()
+        .map(Path::new)
+        .and_then(Path::file_stem)
+        .is_some_and(|name| name == "vobsubocr")
+}
+
 #[cfg(not(feature = "profile-with-puffin"))]
 mod no_profiling {
     pub struct Empty;
@@ -1,22 +1,216 @@
-use clap::{crate_description, crate_name, crate_version};
-use clap::{Parser, ValueHint};
-use leptess::Variable;
-use std::path::PathBuf;
-use thiserror::Error;
+use clap::{
+    crate_description, crate_version, error::ErrorKind, Args, CommandFactory, Parser, ValueEnum,
+    ValueHint,
+};
+use std::{convert::Infallible, num::NonZeroUsize, path::PathBuf, str::FromStr, time::Duration};
+use subtile_ocr_core::{
+    parse_anchor, parse_crop, parse_palette, Anchor, AssStyle, Config, Crop, CueFilter,
+    ImageConfig, InputConfig, OcrConfig, OutputConfig, Palette, TesseractVariable, TextConfig,
+    TimingConfig,
+};
 
-#[derive(Error, Debug)]
-enum Error {
-    #[error("No `=` in key-value pair {value}")]
-    ParseKeyValuePair { value: String },
+/// Handle application parameter from cli with Clap.
+#[derive(Parser, Clone, Debug)]
+#[clap(name = "subtile-ocr", about = crate_description!(), version = crate_version!())]
+pub struct Opt {
+    /// Only copy the selected subtitles to this file, without OCR.
+    ///
+    /// The subtitles kept by the filters are written without re-encoding, in
+    /// a `.sup` file for PGS input, or an `.idx` file (and its `.sub`) for `VobSub`.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub extract_only: Option<PathBuf>,
+
+    /// Only write a normalized copy of the input idx file (and its sub file) to this file, then exit.
+    ///
+    /// A `BOM`, CRLF line endings and reordered header lines are fixed, for idx files
+    /// written by tools which fail to open.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub fix_idx: Option<PathBuf>,
+
+    /// Only write the decoded subtitle images in PNG contact sheets in this directory, without OCR.
+    ///
+    /// Images are tiled with their number and times, to skim a whole track for garbage
+    /// frames, a wrong track or palette problems.
+    #[clap(long, value_hint = ValueHint::DirPath)]
+    pub contact_sheet: Option<PathBuf>,
+
+    /// Only put every Nth decoded subtitle image in the contact sheets.
+    #[clap(long, default_value = "1", requires = "contact_sheet")]
+    pub contact_sheet_step: usize,
+
+    /// Dump processed subtitle images into the working directory as PNG files.
+    #[clap(long)]
+    pub dump: bool,
+
+    /// Dump raw subtitle images into the working directory as PNG files.
+    #[clap(long)]
+    pub dump_raw: bool,
+
+    /// Template of the names of the dumped images, without the `.png` extension.
+    ///
+    /// Available placeholders are `{index}`, the number of the subtitle on 4 digits,
+    /// and `{start}` and `{end}`, its times as `HH-MM-SS.mmm`.
+    #[clap(long, default_value = "{index}_{start}_{end}")]
+    pub dump_name: String,
+
+    /// Only decode and prepare the subtitle images, without OCR.
+    ///
+    /// The images are still dumped with `--dump`, and the timings of the stages are logged
+    /// at the info level (e.g. with `RUST_LOG=info`), to tell decoding problems from OCR ones.
+    /// Tesseract and its language data aren't used.
+    #[clap(long, conflicts_with_all = ["extract_only", "contact_sheet", "calibrate"])]
+    pub no_ocr: bool,
+
+    /// Calibrate the binarization and resolution on the first subtitles before the run,
+    /// and save the picked settings in this profile file.
+    ///
+    /// A grid of settings is tried, with the candidate images written in a `calibration`
+    /// directory next to the profile file and their OCR results printed. The picked settings are used for the run,
+    /// and can be reused with `subtile-ocr $(cat <PROFILE>) ...`.
+    #[clap(long, value_name = "PROFILE", value_hint = ValueHint::FilePath, conflicts_with = "input_format")]
+    pub calibrate: Option<PathBuf>,
+
+    /// Check the installation by running the OCR on an embedded sample, then exit.
+    #[clap(long)]
+    pub self_test: bool,
+
+    /// Learn a correction model from an OCR output and its manually corrected version, then exit.
+    ///
+    /// Subtitles are paired by their times, which must be kept in the corrected file.
+    /// The model is written to the `--corrections` file, to correct the OCR of similar
+    /// subtitles (e.g. the other episodes of a show).
+    #[clap(long, num_args = 2, value_names = ["OCR_SRT", "CORRECTED_SRT"], requires = "corrections", value_hint = ValueHint::FilePath)]
+    pub learn_corrections: Option<Vec<PathBuf>>,
+
+    /// Estimate a palette from a screenshot of a video frame showing a subtitle, then exit.
+    ///
+    /// The palette is printed as an idx `palette:` line, usable with `--palette` or in the idx
+    /// file, for idx files without a palette.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub palette_from_frame: Option<PathBuf>,
+
+    /// Record the warnings and errors of the run in this file, one `JSON` object per line.
+    ///
+    /// Each record has the `level`, the `target` module and the `message` of the event
+    /// (e.g. skipped packets, empty subtitles, OCR failures), for automated quality checks.
+    /// The table of the recognized stream (stream id, language, number of subtitles, outputs)
+    /// is recorded too, with the `subtile_ocr::summary` target.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub diagnostics_json: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub input: InputArgs,
+
+    #[clap(flatten)]
+    pub image: ImageArgs,
+
+    #[clap(flatten)]
+    pub ocr: OcrArgs,
+
+    #[clap(flatten)]
+    pub timing: TimingArgs,
 
-    #[error("Invalid tesseract variable name: {value}")]
-    TesseractVariableName { value: String },
+    #[clap(flatten)]
+    pub text: TextArgs,
+
+    #[clap(flatten)]
+    pub output: OutputArgs,
 }
 
-/// Handle application parameter from cli with Clap.
-#[derive(Parser, Debug)]
-#[clap(name = crate_name!(), about = crate_description!(), version = crate_version!())]
-pub struct Opt {
+/// Options of the input subtitles and their decoding.
+#[derive(Args, Clone, Debug)]
+#[clap(next_help_heading = "Input")]
+pub struct InputArgs {
+    /// Set the path of the file to process, or `-` to read the subtitles from stdin.
+    ///
+    /// The input can be a `.sup` file of PGS subtitles, an `.idx` file (and its `.sub`)
+    /// of `VobSub` subtitles, a `.ts` recording with DVB subtitles, or a Matroska
+    /// `.mkv` or `.mks` file with `VobSub` or PGS subtitle tracks.
+    #[clap(name = "FILE", value_parser, value_hint = ValueHint::FilePath, required_unless_present_any = ["self_test", "learn_corrections", "palette_from_frame"])]
+    pub input: Option<PathBuf>,
+
+    /// Subtitle track of a Matroska input, with its id as listed by `mkvmerge --identify`.
+    ///
+    /// The first `VobSub` or PGS track is used if not present.
+    #[clap(long)]
+    pub track: Option<usize>,
+
+    /// Other input file appended to the timeline of the main input, e.g. the second part of a disc.
+    ///
+    /// Can be repeated: the subtitles of all the inputs are written in the same outputs,
+    /// in the order of the inputs.
+    #[clap(long, value_hint = ValueHint::FilePath, conflicts_with_all = ["trace", "extract_only", "fix_idx"])]
+    pub concat: Vec<PathBuf>,
+
+    /// Start time in milliseconds, in the timeline of the main input, of the `--concat` input at the same position.
+    ///
+    /// The inputs without an offset start at the end of the last subtitle of the previous ones.
+    /// `--time-offset` is applied on the whole timeline.
+    #[clap(long, requires = "concat", allow_negative_numbers = true)]
+    pub concat_offset: Vec<i64>,
+
+    /// Format of the subtitles read from stdin, with `-` as input.
+    #[clap(long, value_enum, required_if_eq("FILE", "-"))]
+    pub input_format: Option<InputFormat>,
+
+    /// Idx file of the `VobSub` stream read from stdin.
+    #[clap(long, value_hint = ValueHint::FilePath, required_if_eq("input_format", "vobsub"))]
+    pub idx: Option<PathBuf>,
+
+    /// Palette of the `VobSub` subtitles, instead of the one of the idx file.
+    ///
+    /// 16 hexadecimal RGB colors separated by commas, like the `palette:` line of an idx file.
+    /// The idx files without a `palette:` line use a palette inferred from the colors of their
+    /// subtitles otherwise, or the default palette of `VSFilter` if none can be inferred.
+    /// The `custom colors:` line of the idx file is ignored with this palette.
+    #[clap(long, value_parser = parse_palette)]
+    pub palette: Option<Palette>,
+
+    /// Salvage the complete subtitles of a truncated input, and report where its data ends.
+    ///
+    /// `PGS` parsing stops at the first error instead of failing, and the `VobSub`
+    /// subtitles lost after the end of the sub file are reported once.
+    #[clap(long)]
+    pub recover: bool,
+
+    /// Maximum width and height in pixels of the decoded subtitle images.
+    ///
+    /// Corrupt packets can declare absurd sizes: the subtitles with a larger image are
+    /// skipped with a warning. The `PGS` images are skipped before their decoding, which
+    /// would take huge amounts of memory, the `VobSub` ones only before their conversion.
+    #[clap(long, default_value = "4096")]
+    pub max_image_size: u32,
+
+    /// Maximum time in seconds without progress of the parsing of `PGS` or `VobSub` subtitles, `0` to wait forever.
+    ///
+    /// Malformed inputs can make the parsers spin: after this time without a new subtitle,
+    /// the input is abandoned with the position of the stuck subtitle. The abandoned PGS
+    /// parsing stops at its next read, other abandoned parsings keep running until they end,
+    /// or until the process exits: once 8 of them are still running, no other parsing is started.
+    #[clap(long, default_value = "30")]
+    pub parse_stall_timeout: u64,
+
+    /// Cache the decoded images in this directory, to skip the decoding in the next runs.
+    ///
+    /// The images are reused by the runs with the same input and decoding options,
+    /// e.g. when only the OCR options change.
+    #[clap(long, value_hint = ValueHint::DirPath, conflicts_with_all = ["dump_raw", "max_memory"])]
+    pub decode_cache: Option<PathBuf>,
+
+    /// Maximum memory of the subtitle images held at once, in MiB.
+    ///
+    /// The subtitles are decoded, filtered and recognized in batches, the next batch being
+    /// decoded during the OCR of the current one, instead of decoding all the images before
+    /// the OCR. The Tesseract instances are kept for the whole run, and the options needing
+    /// all the images at once are not available.
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..), conflicts_with_all = ["concat", "contact_sheet", "dump", "dump_raw", "extract_only", "no_ocr", "trace"])]
+    pub max_memory: Option<u64>,
+}
+
+/// Options of the conversion and selection of the subtitle images for OCR.
+#[derive(Args, Clone, Debug)]
+#[clap(next_help_heading = "Image conversion")]
+pub struct ImageArgs {
     /// Threshold for subtitle image binarization.
     ///
     /// Must be between 0.0 and 1.0. Only pixels with luminance above the
@@ -24,30 +218,120 @@ pub struct Opt {
     #[clap(short = 't', long, default_value = "0.6")]
     pub threshold: f32,
 
-    /// DPI of subtitle images.
+    /// Minimum alpha of the text pixels of the `PGS` and `DVB` subtitle images, between 0 and 255.
     ///
-    /// This setting doesn't strictly make sense for DVD subtitles, but it can
-    /// influence Tesseract's output.
-    #[clap(short = 'd', long, default_value = "150")]
-    pub dpi: i32,
+    /// The `VobSub` images are binarized with `--threshold` instead.
+    #[clap(long, default_value = "100")]
+    pub alpha_threshold: u8,
+
+    /// Minimum luminance of the text pixels of the `PGS` and `DVB` subtitle images, between 0 and 255.
+    ///
+    /// Lower it for low-contrast subtitles, like gray text with a dark outline.
+    #[clap(long, default_value = "100")]
+    pub luma_threshold: u8,
+
+    /// Strategy used to convert the `VobSub` images for OCR.
+    ///
+    /// `rgba-cluster` can work better than the palette luminance for tricky palettes,
+    /// e.g. when the text and its outline have close luminances. `otsu` adapts the
+    /// threshold to each image, for the unusual palettes which come out nearly all-black
+    /// with `--threshold`, and is also used for the `PGS` and `DVB` images.
+    #[clap(long, value_enum, default_value_t)]
+    pub convert: ConvertStrategy,
+
+    /// Luminance tolerance around `--threshold` for the anti-aliased edges of `VobSub` glyphs.
+    ///
+    /// With `palette-luma`, the palette colors within this distance of the threshold are
+    /// mapped to gray levels instead of being kept or dropped wholesale, and the result is
+    /// binarized with a threshold adapted to each image. Between 0.0 (disabled) and 0.5.
+    #[clap(long, default_value = "0.0")]
+    pub ink_tolerance: f32,
 
     /// Border in pixels to surround the each subtitle image for OCR.
     ///
-    /// This can have subtle effects on the quality of the OCR.
+    /// This can have subtle effects on the quality of the OCR. Subtitles with text
+    /// touching the edges of their image (e.g. cropped renders) get a larger border.
     #[clap(short = 'b', long, default_value = "10")]
     pub border: u32,
 
-    /// Output subtitle file; stdout if not present.
-    #[clap(short = 'o', long, value_parser, value_hint = ValueHint::FilePath)]
-    pub output: Option<PathBuf>,
+    /// Height in pixels of the text lines, to which bigger subtitles are downscaled before OCR.
+    ///
+    /// 4K Blu-ray subtitles have much bigger text than needed by the OCR, which only
+    /// costs time and memory: e.g. 60 keeps the lines of 1080p subtitles but shrinks
+    /// the 4K ones. Off by default, the images are kept at their size.
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub target_line_height: Option<u32>,
+
+    /// Only recognize the text in this region of the display, given as `x,y,width,height`.
+    ///
+    /// For discs where a logo or burnt-in text overlaps the subtitles. The pixels of the
+    /// decoded images out of the region are ignored, and the region must fit in the display
+    /// size of each stream.
+    #[clap(long, value_parser = parse_crop)]
+    pub crop: Option<Crop>,
+
+    /// Fill the glyphs of subtitles rendered as outlines only.
+    ///
+    /// Images where the background enclosed by the text is large compared to the text
+    /// itself (hollow letters from heavy outlined fonts) have it painted as text before OCR.
+    /// Only useful for such fonts, as it can degrade the OCR of regular subtitles.
+    #[clap(long)]
+    pub fill_hollow: bool,
+
+    /// Split the subtitle images with groups of lines far apart into separate subtitles.
+    ///
+    /// Some discs render unrelated texts in the same image, like a sign at the top and
+    /// the dialogue at the bottom. Each group is recognized as a subtitle with the same times,
+    /// instead of one subtitle with the unrelated lines concatenated.
+    #[clap(long)]
+    pub split_groups: bool,
 
+    /// Minimum number of text pixels of a subtitle image to be sent to OCR.
+    ///
+    /// Images with fewer pixels are almost always noise: they are skipped
+    /// and reported at the end. Shorthand for `--filter 'ink >= N'`, combined with `--filter`.
+    /// Set to 0 to send all images.
+    #[clap(long, default_value = "20")]
+    pub min_ink_pixels: usize,
+
+    /// Maximum duration in milliseconds of a subtitle repeating the previous image to be removed.
+    ///
+    /// Some DVDs re-emit the previous subtitle for a frame right before a new one.
+    /// Shorthand for `--filter '!(repeat == 1 && duration < N)'`, combined with `--filter`.
+    /// Set to 0 to keep these duplicates.
+    #[clap(long, default_value = "100")]
+    pub duplicate_max_duration: u32,
+
+    /// Only keep the subtitles matching this expression before OCR.
+    ///
+    /// Attributes `duration`, `start`, `end` (in ms, or with `ms`/`s` unit), `index`, `width`,
+    /// `height`, `ink` (text pixels) and `repeat` (1 if the image is the previous one) are
+    /// compared with `==`, `!=`, `<`, `<=`, `>`, `>=`, and combined with `&&`, `||`, `!` and
+    /// parentheses, e.g. `duration > 300ms && height < 200 && !repeat`, where `repeat` alone
+    /// is `repeat != 0`. The `index` is the number of the subtitle in the decoded input, as the
+    /// expression is evaluated before the other filters. The position on the display and the
+    /// forced flag of the subtitles aren't known at this step, so they can't be filtered on.
+    #[clap(long, value_parser = CueFilter::parse)]
+    pub filter: Option<CueFilter>,
+}
+
+/// Options of the OCR with Tesseract.
+#[derive(Args, Clone, Debug)]
+#[clap(next_help_heading = "OCR")]
+pub struct OcrArgs {
     /// Path to Tesseract's tessdata directory.
+    ///
+    /// If not present and the default directory of Tesseract doesn't work, the usual
+    /// install locations and `TESSDATA_PREFIX` are searched for the language data.
     #[clap(short = 'D', long, value_hint = ValueHint::DirPath)]
     pub tessdata_dir: Option<String>,
 
     /// The Tesseract language(s) to use for OCR.
+    ///
+    /// Required for the runs with OCR. Not needed with `--learn-corrections`, `--palette-from-frame`,
+    /// `--fix-idx`, `--extract-only`, `--contact-sheet` or `--no-ocr`, which run none.
     #[clap(short = 'l', long)]
-    pub lang: String,
+    pub lang: Option<String>,
 
     #[allow(clippy::doc_markdown)]
     /// Set values for config variables.
@@ -56,687 +340,740 @@ pub struct Opt {
     /// particularly useful option is `tessedit_char_blacklist=|\/`_~` or
     /// similar, to prevent the OCR from misidentifying characters as symbols
     /// rarely used in subtitles.
-    #[clap(short = 'c', long, value_parser = parse_key_val, number_of_values = 1)]
-    pub config: Vec<(Variable, String)>,
+    #[clap(short = 'c', long, value_parser = TesseractVariable::from_str, number_of_values = 1)]
+    pub config: Vec<TesseractVariable>,
 
-    /// Set the path of the file to process.
-    #[clap(name = "FILE", value_parser, value_hint = ValueHint::FilePath)]
-    pub input: PathBuf,
+    /// DPI of subtitle images.
+    ///
+    /// This setting doesn't strictly make sense for DVD subtitles, but it can
+    /// influence Tesseract's output.
+    #[clap(short = 'd', long, default_value = "150")]
+    pub dpi: i32,
 
-    /// Dump processed subtitle images into the working directory as PNG files.
+    /// Send each text line of a subtitle image separately to Tesseract.
+    ///
+    /// Lines are recognized with the page segmentation mode for a single line
+    /// (7), which often improves accuracy on noisy multi-line subtitles.
     #[clap(long)]
-    pub dump: bool,
+    pub line_mode: bool,
 
-    /// Dump raw subtitle images into the working directory as PNG files.
+    /// Don't recognize the subtitles with a single text line with the page segmentation
+    /// mode for a single line (7).
+    ///
+    /// By default, this mode is used for one-liners as it is often more accurate,
+    /// unless another mode is set with `--config tessedit_pageseg_mode=<psm>`.
     #[clap(long)]
-    pub dump_raw: bool,
+    pub no_single_line_psm: bool,
+
+    /// Don't retry the OCR of the images inverted when nothing was recognized.
+    ///
+    /// By default, the subtitles with many text pixels but no recognized text are
+    /// recognized again with inverted colors, which rescues some subtitles with a
+    /// wrong polarity after binarization.
+    #[clap(long)]
+    pub no_invert_retry: bool,
+
+    /// Separator written between the columns of a line, like `\t` or ` | `.
+    ///
+    /// Columns are parts of a line separated by gaps much wider than spaces, as in
+    /// tabular content (karaoke timers, scoreboards). They are recognized separately,
+    /// instead of being merged as words of the same line.
+    #[clap(long, value_parser = parse_separator)]
+    pub column_separator: Option<String>,
+
+    /// Maximum number of Tesseract instances; if not present, one per thread,
+    /// or one per subtitle if there are fewer subtitles than threads.
+    ///
+    /// Each instance loads the language data, so limiting them reduces memory use
+    /// on machines with many cores. Image conversion still uses all threads.
+    #[clap(long)]
+    pub max_tesseract_instances: Option<usize>,
+
+    /// Number of threads each Tesseract instance can use internally.
+    ///
+    /// By default one, unless there are fewer subtitles than threads, in which case
+    /// the idle threads are shared between Tesseract instances.
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub tesseract_threads: Option<u32>,
+
+    /// Maximum time in seconds of the OCR of a subtitle image.
+    ///
+    /// Rare pathological images can make Tesseract spin for minutes: after this
+    /// time, the subtitle is skipped and reported, and the OCR goes on with the others.
+    /// The abandoned OCR keeps running with its Tesseract instance until it ends: once
+    /// 8 of them are still running, the OCR of the next subtitles fails.
+    #[clap(long)]
+    pub ocr_timeout: Option<u64>,
+
+    /// Abort the whole process if the OCR panics on a subtitle, instead of skipping this subtitle.
+    #[clap(long)]
+    pub abort_on_panic: bool,
+
+    /// Keep the control characters of the Tesseract text, like the form feed ending each page.
+    ///
+    /// By default, they are removed from the text, except the line feeds and tabs.
+    #[clap(long)]
+    pub keep_control_chars: bool,
+
+    /// Flag the subtitles with a mean Tesseract word confidence below this percentage for checking.
+    ///
+    /// Short subtitles with only dictionary words (e.g. `Yes.`, `No!`) aren't flagged,
+    /// as Tesseract gives them a low confidence.
+    #[clap(long, value_parser = clap::value_parser!(u32).range(0..=100))]
+    pub min_confidence: Option<u32>,
+
+    /// What to do with the subtitles flagged by `--min-confidence`.
+    #[clap(long, value_enum, default_value_t, requires = "min_confidence")]
+    pub low_confidence: LowConfidencePolicy,
+
+    /// Words file, one per line, extending the dictionary of the `--min-confidence` check.
+    ///
+    /// The dictionary has the common short answers of the `eng`, `fra`, `deu` and `spa` languages.
+    #[clap(long, requires = "min_confidence", value_hint = ValueHint::FilePath)]
+    pub dictionary: Option<PathBuf>,
 }
 
-// https://github.com/clap-rs/clap_derive/blob/master/examples/keyvalue.rs
-fn parse_key_val(s: &str) -> Result<(Variable, String), Error> {
-    let pos = s.find('=').ok_or_else(|| Error::ParseKeyValuePair {
-        value: s.to_owned(),
-    })?;
-    Ok((
-        parse_tesseract_variable(&s[..pos])?,
-        s[pos + 1..].to_owned(),
-    ))
-}
-
-fn parse_tesseract_variable(s: impl AsRef<str>) -> Result<Variable, Error> {
-    Ok(match s.as_ref() {
-        "classify_num_cp_levels" => Variable::ClassifyNumCpLevels,
-        "textord_dotmatrix_gap" => Variable::TextordDotmatrixGap,
-        "textord_debug_block" => Variable::TextordDebugBlock,
-        "textord_pitch_range" => Variable::TextordPitchRange,
-        "textord_words_veto_power" => Variable::TextordWordsVetoPower,
-        "textord_tabfind_show_strokewidths" => Variable::TextordTabfindShowStrokewidths,
-        "pitsync_linear_version" => Variable::PitsyncLinearVersion,
-        "pitsync_fake_depth" => Variable::PitsyncFakeDepth,
-        "oldbl_holed_losscount" => Variable::OldblHoledLosscount,
-        "textord_skewsmooth_offset" => Variable::TextordSkewsmoothOffset,
-        "textord_skewsmooth_offset2" => Variable::TextordSkewsmoothOffset2,
-        "textord_test_x" => Variable::TextordTestX,
-        "textord_test_y" => Variable::TextordTestY,
-        "textord_min_blobs_in_row" => Variable::TextordMinBlobsInRow,
-        "textord_spline_minblobs" => Variable::TextordSplineMinblobs,
-        "textord_spline_medianwin" => Variable::TextordSplineMedianwin,
-        "textord_max_blob_overlaps" => Variable::TextordMaxBlobOverlaps,
-        "textord_min_xheight" => Variable::TextordMinXheight,
-        "textord_lms_line_trials" => Variable::TextordLmsLineTrials,
-        "textord_tabfind_show_images" => Variable::TextordTabfindShowImages,
-        "textord_fp_chop_error" => Variable::TextordFpChopError,
-        "edges_max_children_per_outline" => Variable::EdgesMaxChildrenPerOutline,
-        "edges_max_children_layers" => Variable::EdgesMaxChildrenLayers,
-        "edges_children_per_grandchild" => Variable::EdgesChildrenPerGrandchild,
-        "edges_children_count_limit" => Variable::EdgesChildrenCountLimit,
-        "edges_min_nonhole" => Variable::EdgesMinNonhole,
-        "edges_patharea_ratio" => Variable::EdgesPathareaRatio,
-        "devanagari_split_debuglevel" => Variable::DevanagariSplitDebuglevel,
-        "textord_tabfind_show_partitions" => Variable::TextordTabfindShowPartitions,
-        "textord_debug_tabfind" => Variable::TextordDebugTabfind,
-        "textord_debug_bugs" => Variable::TextordDebugBugs,
-        "textord_testregion_left" => Variable::TextordTestregionLeft,
-        "textord_testregion_top" => Variable::TextordTestregionTop,
-        "textord_testregion_right" => Variable::TextordTestregionRight,
-        "textord_testregion_bottom" => Variable::TextordTestregionBottom,
-        "editor_image_xpos" => Variable::EditorImageXpos,
-        "editor_image_ypos" => Variable::EditorImageYpos,
-        "editor_image_menuheight" => Variable::EditorImageMenuheight,
-        "editor_image_word_bb_color" => Variable::EditorImageWordBbColor,
-        "editor_image_blob_bb_color" => Variable::EditorImageBlobBbColor,
-        "editor_image_text_color" => Variable::EditorImageTextColor,
-        "editor_dbwin_xpos" => Variable::EditorDbwinXpos,
-        "editor_dbwin_ypos" => Variable::EditorDbwinYpos,
-        "editor_dbwin_height" => Variable::EditorDbwinHeight,
-        "editor_dbwin_width" => Variable::EditorDbwinWidth,
-        "editor_word_xpos" => Variable::EditorWordXpos,
-        "editor_word_ypos" => Variable::EditorWordYpos,
-        "editor_word_height" => Variable::EditorWordHeight,
-        "editor_word_width" => Variable::EditorWordWidth,
-        "wordrec_display_splits" => Variable::WordrecDisplaySplits,
-        "poly_debug" => Variable::PolyDebug,
-        "poly_wide_objects_better" => Variable::PolyWideObjectsBetter,
-        "wordrec_display_all_blobs" => Variable::WordrecDisplayAllBlobs,
-        "wordrec_blob_pause" => Variable::WordrecBlobPause,
-        "textord_fp_chopping" => Variable::TextordFpChopping,
-        "textord_force_make_prop_words" => Variable::TextordForceMakePropWords,
-        "textord_chopper_test" => Variable::TextordChopperTest,
-        "textord_restore_underlines" => Variable::TextordRestoreUnderlines,
-        "textord_show_initial_words" => Variable::TextordShowInitialWords,
-        "textord_show_new_words" => Variable::TextordShowNewWords,
-        "textord_show_fixed_words" => Variable::TextordShowFixedWords,
-        "textord_blocksall_fixed" => Variable::TextordBlocksallFixed,
-        "textord_blocksall_prop" => Variable::TextordBlocksallProp,
-        "textord_blocksall_testing" => Variable::TextordBlocksallTesting,
-        "textord_test_mode" => Variable::TextordTestMode,
-        "textord_pitch_scalebigwords" => Variable::TextordPitchScalebigwords,
-        "textord_all_prop" => Variable::TextordAllProp,
-        "textord_debug_pitch_test" => Variable::TextordDebugPitchTest,
-        "textord_disable_pitch_test" => Variable::TextordDisablePitchTest,
-        "textord_fast_pitch_test" => Variable::TextordFastPitchTest,
-        "textord_debug_pitch_metric" => Variable::TextordDebugPitchMetric,
-        "textord_show_row_cuts" => Variable::TextordShowRowCuts,
-        "textord_show_page_cuts" => Variable::TextordShowPageCuts,
-        "textord_pitch_cheat" => Variable::TextordPitchCheat,
-        "textord_blockndoc_fixed" => Variable::TextordBlockndocFixed,
-        "textord_show_tables" => Variable::TextordShowTables,
-        "textord_tablefind_show_mark" => Variable::TextordTablefindShowMark,
-        "textord_tablefind_show_stats" => Variable::TextordTablefindShowStats,
-        "textord_tablefind_recognize_tables" => Variable::TextordTablefindRecognizeTables,
-        "textord_tabfind_show_initialtabs" => Variable::TextordTabfindShowInitialtabs,
-        "textord_tabfind_show_finaltabs" => Variable::TextordTabfindShowFinaltabs,
-        "textord_tabfind_only_strokewidths" => Variable::TextordTabfindOnlyStrokewidths,
-        "textord_really_old_xheight" => Variable::TextordReallyOldXheight,
-        "textord_oldbl_debug" => Variable::TextordOldblDebug,
-        "textord_debug_baselines" => Variable::TextordDebugBaselines,
-        "textord_oldbl_paradef" => Variable::TextordOldblParadef,
-        "textord_oldbl_split_splines" => Variable::TextordOldblSplitSplines,
-        "textord_oldbl_merge_parts" => Variable::TextordOldblMergeParts,
-        "oldbl_corrfix" => Variable::OldblCorrfix,
-        "oldbl_xhfix" => Variable::OldblXhfix,
-        "textord_ocropus_mode" => Variable::TextordOcropusMode,
-        "textord_heavy_nr" => Variable::TextordHeavyNr,
-        "textord_show_initial_rows" => Variable::TextordShowInitialRows,
-        "textord_show_parallel_rows" => Variable::TextordShowParallelRows,
-        "textord_show_expanded_rows" => Variable::TextordShowExpandedRows,
-        "textord_show_final_rows" => Variable::TextordShowFinalRows,
-        "textord_show_final_blobs" => Variable::TextordShowFinalBlobs,
-        "textord_test_landscape" => Variable::TextordTestLandscape,
-        "textord_parallel_baselines" => Variable::TextordParallelBaselines,
-        "textord_straight_baselines" => Variable::TextordStraightBaselines,
-        "textord_old_baselines" => Variable::TextordOldBaselines,
-        "textord_old_xheight" => Variable::TextordOldXheight,
-        "textord_fix_xheight_bug" => Variable::TextordFixXheightBug,
-        "textord_fix_makerow_bug" => Variable::TextordFixMakerowBug,
-        "textord_debug_xheights" => Variable::TextordDebugXheights,
-        "textord_biased_skewcalc" => Variable::TextordBiasedSkewcalc,
-        "textord_interpolating_skew" => Variable::TextordInterpolatingSkew,
-        "textord_new_initial_xheight" => Variable::TextordNewInitialXheight,
-        "textord_debug_blob" => Variable::TextordDebugBlob,
-        "gapmap_debug" => Variable::GapmapDebug,
-        "gapmap_use_ends" => Variable::GapmapUseEnds,
-        "gapmap_no_isolated_quanta" => Variable::GapmapNoIsolatedQuanta,
-        "edges_use_new_outline_complexity" => Variable::EdgesUseNewOutlineComplexity,
-        "edges_debug" => Variable::EdgesDebug,
-        "edges_children_fix" => Variable::EdgesChildrenFix,
-        "textord_show_fixed_cuts" => Variable::TextordShowFixedCuts,
-        "devanagari_split_debugimage" => Variable::DevanagariSplitDebugimage,
-        "textord_tabfind_show_initial_partitions" => Variable::TextordTabfindShowInitialPartitions,
-        "textord_tabfind_show_reject_blobs" => Variable::TextordTabfindShowRejectBlobs,
-        "textord_tabfind_show_columns" => Variable::TextordTabfindShowColumns,
-        "textord_tabfind_show_blocks" => Variable::TextordTabfindShowBlocks,
-        "textord_tabfind_find_tables" => Variable::TextordTabfindFindTables,
-        "textord_space_size_is_variable" => Variable::TextordSpaceSizeIsVariable,
-        "textord_debug_printable" => Variable::TextordDebugPrintable,
-        "equationdetect_save_bi_image" => Variable::EquationdetectSaveBiImage,
-        "equationdetect_save_spt_image" => Variable::EquationdetectSaveSptImage,
-        "equationdetect_save_seed_image" => Variable::EquationdetectSaveSeedImage,
-        "equationdetect_save_merged_image" => Variable::EquationdetectSaveMergedImage,
-        "stream_filelist" => Variable::StreamFilelist,
-        "debug_file" => Variable::DebugFile,
-        "dotproduct" => Variable::Dotproduct,
-        "classify_font_name" => Variable::ClassifyFontName,
-        "fx_debugfile" => Variable::FxDebugfile,
-        "editor_image_win_name" => Variable::EditorImageWinName,
-        "editor_dbwin_name" => Variable::EditorDbwinName,
-        "editor_word_name" => Variable::EditorWordName,
-        "document_title" => Variable::DocumentTitle,
-        "classify_pico_feature_length" => Variable::ClassifyPicoFeatureLength,
-        "classify_norm_adj_midpoint" => Variable::ClassifyNormAdjMidpoint,
-        "classify_norm_adj_curl" => Variable::ClassifyNormAdjCurl,
-        "classify_min_slope" => Variable::ClassifyMinSlope,
-        "classify_max_slope" => Variable::ClassifyMaxSlope,
-        "classify_cp_angle_pad_loose" => Variable::ClassifyCpAnglePadLoose,
-        "classify_cp_angle_pad_medium" => Variable::ClassifyCpAnglePadMedium,
-        "classify_cp_angle_pad_tight" => Variable::ClassifyCpAnglePadTight,
-        "classify_cp_end_pad_loose" => Variable::ClassifyCpEndPadLoose,
-        "classify_cp_end_pad_medium" => Variable::ClassifyCpEndPadMedium,
-        "classify_cp_end_pad_tight" => Variable::ClassifyCpEndPadTight,
-        "classify_cp_side_pad_loose" => Variable::ClassifyCpSidePadLoose,
-        "classify_cp_side_pad_medium" => Variable::ClassifyCpSidePadMedium,
-        "classify_cp_side_pad_tight" => Variable::ClassifyCpSidePadTight,
-        "classify_pp_angle_pad" => Variable::ClassifyPpAnglePad,
-        "classify_pp_end_pad" => Variable::ClassifyPpEndPad,
-        "classify_pp_side_pad" => Variable::ClassifyPpSidePad,
-        "textord_underline_offset" => Variable::TextordUnderlineOffset,
-        "textord_wordstats_smooth_factor" => Variable::TextordWordstatsSmoothFactor,
-        "textord_width_smooth_factor" => Variable::TextordWidthSmoothFactor,
-        "textord_words_width_ile" => Variable::TextordWordsWidthIle,
-        "textord_words_maxspace" => Variable::TextordWordsMaxspace,
-        "textord_words_default_maxspace" => Variable::TextordWordsDefaultMaxspace,
-        "textord_words_default_minspace" => Variable::TextordWordsDefaultMinspace,
-        "textord_words_min_minspace" => Variable::TextordWordsMinMinspace,
-        "textord_words_default_nonspace" => Variable::TextordWordsDefaultNonspace,
-        "textord_words_initial_lower" => Variable::TextordWordsInitialLower,
-        "textord_words_initial_upper" => Variable::TextordWordsInitialUpper,
-        "textord_words_minlarge" => Variable::TextordWordsMinlarge,
-        "textord_words_pitchsd_threshold" => Variable::TextordWordsPitchsdThreshold,
-        "textord_words_def_fixed" => Variable::TextordWordsDefFixed,
-        "textord_words_def_prop" => Variable::TextordWordsDefProp,
-        "textord_pitch_rowsimilarity" => Variable::TextordPitchRowsimilarity,
-        "words_initial_lower" => Variable::WordsInitialLower,
-        "words_initial_upper" => Variable::WordsInitialUpper,
-        "words_default_prop_nonspace" => Variable::WordsDefaultPropNonspace,
-        "words_default_fixed_space" => Variable::WordsDefaultFixedSpace,
-        "words_default_fixed_limit" => Variable::WordsDefaultFixedLimit,
-        "textord_words_definite_spread" => Variable::TextordWordsDefiniteSpread,
-        "textord_spacesize_ratiofp" => Variable::TextordSpacesizeRatiofp,
-        "textord_spacesize_ratioprop" => Variable::TextordSpacesizeRatioprop,
-        "textord_fpiqr_ratio" => Variable::TextordFpiqrRatio,
-        "textord_max_pitch_iqr" => Variable::TextordMaxPitchIqr,
-        "textord_fp_min_width" => Variable::TextordFpMinWidth,
-        "textord_projection_scale" => Variable::TextordProjectionScale,
-        "textord_balance_factor" => Variable::TextordBalanceFactor,
-        "textord_tabvector_vertical_gap_fraction" => Variable::TextordTabvectorVerticalGapFraction,
-        "textord_tabvector_vertical_box_ratio" => Variable::TextordTabvectorVerticalBoxRatio,
-        "pitsync_joined_edge" => Variable::PitsyncJoinedEdge,
-        "pitsync_offset_freecut_fraction" => Variable::PitsyncOffsetFreecutFraction,
-        "oldbl_xhfract" => Variable::OldblXhfract,
-        "oldbl_dot_error_size" => Variable::OldblDotErrorSize,
-        "textord_oldbl_jumplimit" => Variable::TextordOldblJumplimit,
-        "textord_spline_shift_fraction" => Variable::TextordSplineShiftFraction,
-        "textord_spline_outlier_fraction" => Variable::TextordSplineOutlierFraction,
-        "textord_skew_ile" => Variable::TextordSkewIle,
-        "textord_skew_lag" => Variable::TextordSkewLag,
-        "textord_linespace_iqrlimit" => Variable::TextordLinespaceIqrlimit,
-        "textord_width_limit" => Variable::TextordWidthLimit,
-        "textord_chop_width" => Variable::TextordChopWidth,
-        "textord_expansion_factor" => Variable::TextordExpansionFactor,
-        "textord_overlap_x" => Variable::TextordOverlapX,
-        "textord_minxh" => Variable::TextordMinxh,
-        "textord_min_linesize" => Variable::TextordMinLinesize,
-        "textord_excess_blobsize" => Variable::TextordExcessBlobsize,
-        "textord_occupancy_threshold" => Variable::TextordOccupancyThreshold,
-        "textord_underline_width" => Variable::TextordUnderlineWidth,
-        "textord_min_blob_height_fraction" => Variable::TextordMinBlobHeightFraction,
-        "textord_xheight_mode_fraction" => Variable::TextordXheightModeFraction,
-        "textord_ascheight_mode_fraction" => Variable::TextordAscheightModeFraction,
-        "textord_descheight_mode_fraction" => Variable::TextordDescheightModeFraction,
-        "textord_ascx_ratio_min" => Variable::TextordAscxRatioMin,
-        "textord_ascx_ratio_max" => Variable::TextordAscxRatioMax,
-        "textord_descx_ratio_min" => Variable::TextordDescxRatioMin,
-        "textord_descx_ratio_max" => Variable::TextordDescxRatioMax,
-        "textord_xheight_error_margin" => Variable::TextordXheightErrorMargin,
-        "gapmap_big_gaps" => Variable::GapmapBigGaps,
-        "textord_fp_chop_snap" => Variable::TextordFpChopSnap,
-        "edges_childarea" => Variable::EdgesChildarea,
-        "edges_boxarea" => Variable::EdgesBoxarea,
-        "textord_underline_threshold" => Variable::TextordUnderlineThreshold,
-        "ambigs_debug_level" => Variable::AmbigsDebugLevel,
-        "classify_debug_level" => Variable::ClassifyDebugLevel,
-        "classify_norm_method" => Variable::ClassifyNormMethod,
-        "matcher_debug_level" => Variable::MatcherDebugLevel,
-        "matcher_debug_flags" => Variable::MatcherDebugFlags,
-        "classify_learning_debug_level" => Variable::ClassifyLearningDebugLevel,
-        "matcher_permanent_classes_min" => Variable::MatcherPermanentClassesMin,
-        "matcher_min_examples_for_prototyping" => Variable::MatcherMinExamplesForPrototyping,
-        "matcher_sufficient_examples_for_prototyping" => {
-            Variable::MatcherSufficientExamplesForPrototyping
+/// Options of the subtitles times.
+#[derive(Args, Clone, Debug)]
+#[clap(next_help_heading = "Timing")]
+pub struct TimingArgs {
+    /// Shift all subtitles by this many milliseconds (can be negative).
+    ///
+    /// For `VobSub`, this replaces the `time offset:` and `delay:` directives of the idx file.
+    #[clap(long, allow_negative_numbers = true)]
+    pub time_offset: Option<i64>,
+
+    /// Time the `VobSub` subtitles with their start and stop commands only.
+    ///
+    /// By default, the subtitles are restricted to the interval where they are visible,
+    /// following the fades and display area changes of the control sequences of the sub file.
+    #[clap(long)]
+    pub no_control_timing: bool,
+
+    /// Correct a progressive drift with a time of the input and the time it must have.
+    ///
+    /// Given as `src=HH:MM:SS.mmm,dst=HH:MM:SS.mmm`, and can be repeated: the times
+    /// are corrected piecewise-linearly between the anchors, after `--time-offset`.
+    /// A single anchor shifts all the times. The anchors can't go backwards: a later `src`
+    /// must have a later `dst`. The times corrected before 0 start at 0.
+    #[clap(long, value_parser = parse_anchor, conflicts_with = "extract_only")]
+    pub anchor: Vec<Anchor>,
+
+    /// What to do with the subtitles ending before or when they start.
+    ///
+    /// Applied to the decoded subtitles, and to the final cues with `--validate fix`.
+    #[clap(long, value_enum, default_value_t)]
+    pub invalid_times: InvalidTimePolicy,
+
+    /// Duration in milliseconds of the subtitles repaired with `--invalid-times repair`.
+    #[clap(long, default_value = "1000")]
+    pub min_duration: u32,
+
+    /// How to write subtitles displayed at the same time (e.g. two speakers, top and bottom).
+    ///
+    /// The subtitles overlapping in the same region of the display are always kept as they are.
+    /// The regions of DVB subtitles, and of the runs with `--decode-cache` or `--max-memory`, are unknown:
+    /// all their overlapping subtitles are merged or split.
+    #[clap(long, value_enum, default_value_t = OverlapPolicy::Keep)]
+    pub overlap: OverlapPolicy,
+
+    /// Write the subtitles in the order of the input, even if some start before a previous one.
+    ///
+    /// By default, subtitles are sorted by start time, as out of order cues are rejected by players.
+    /// `--validate` then only reports the cues out of order, but `--overlap merge` and `split`
+    /// still sort the subtitles.
+    #[clap(long)]
+    pub keep_order: bool,
+
+    /// Flag the subtitles read faster than this number of characters per second (e.g. 25).
+    ///
+    /// The line breaks are not counted. The mean reading speed is logged too.
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_cps: Option<u32>,
+
+    /// Extend the subtitles read faster than `--max-cps`, without getting closer than two frames to the next one.
+    #[clap(long, requires = "max_cps")]
+    pub extend_fast_cues: bool,
+}
+
+/// Options of the post-processing of the OCR text.
+#[derive(Args, Clone, Debug)]
+#[clap(next_help_heading = "Text")]
+pub struct TextArgs {
+    /// Unicode normalization of the OCR output.
+    ///
+    /// With `nfc-folded`, the ligatures (e.g. `ﬁ`) and full-width forms sometimes emitted by
+    /// Tesseract for Latin scripts are also replaced by their plain characters.
+    #[clap(long, value_enum, default_value_t)]
+    pub normalize: Normalization,
+
+    /// Convention for the dashes starting the dialogue lines, often mangled by OCR.
+    ///
+    /// `hyphen` writes `- `, `hyphen-tight` writes `-` and `en-dash` writes `– `
+    /// (French cinema style) before the text of each speaker.
+    #[clap(long, value_enum, default_value_t)]
+    pub dialogue_dashes: DialogueDashes,
+
+    /// Don't correct the usual Tesseract mistakes in the OCR output.
+    ///
+    /// By default, broken ellipses (`. . .`), backticks used as apostrophes and `|` or `l`
+    /// recognized instead of an `I` are fixed: `|` next to a letter, except with
+    /// `--column-separator`, `l` in uppercase words, and as the English pronoun, alone
+    /// between spaces or in a contraction like `l'm`.
+    #[clap(long)]
+    pub no_postprocess: bool,
+
+    /// File of substitutions applied on the OCR output, one per line.
+    ///
+    /// Each line has a regular expression and its replacement separated by a tab,
+    /// where `$1` is the first group of the expression. They are applied in order,
+    /// after the built-in corrections.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub replace_file: Option<PathBuf>,
+
+    /// Correction model applied on the OCR output, learned with `--learn-corrections`.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub corrections: Option<PathBuf>,
+
+    /// Remove the subtitles which look like ripper credits, instead of only reporting them.
+    #[clap(long)]
+    pub strip_credits: bool,
+
+    /// File of additional regular expressions detecting credits subtitles, one per line.
+    ///
+    /// They extend the patterns shipped with subtile-ocr, and are matched case-insensitively.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub credits_patterns: Option<PathBuf>,
+
+    /// What to do with the sentences split across consecutive subtitles.
+    ///
+    /// A subtitle ending without terminal punctuation, followed by one starting with a lowercase
+    /// letter, continues its sentence. `merge` is useful to produce transcripts rather than timed subtitles.
+    #[clap(long, value_enum, default_value_t)]
+    pub split_sentences: SentencePolicy,
+
+    /// What to do with the cues breaking the SRT constraints before writing them.
+    ///
+    /// Cues must start in order, last, have a text and respect `--max-lines`.
+    /// Each fixed or invalid cue is reported.
+    #[clap(long, value_enum, default_value_t)]
+    pub validate: ValidationPolicy,
+
+    /// Maximum number of lines of a cue, checked by `--validate`.
+    #[clap(long)]
+    pub max_lines: Option<NonZeroUsize>,
+
+    /// Detect the italic lines from the slant of their letters, and wrap them in `<i>` tags.
+    ///
+    /// The tags are written as `{\i1}` and `{\i0}` in the `ASS` output.
+    #[clap(long)]
+    pub detect_italics: bool,
+}
+
+/// Options of the written files.
+#[derive(Args, Clone, Debug)]
+#[clap(next_help_heading = "Output")]
+pub struct OutputArgs {
+    /// Output subtitle file; stdout if not present.
+    ///
+    /// Can be repeated to write several files from the same OCR, each one in the
+    /// `--format` at the same position, or guessed from its extension.
+    /// Missing parent directories are created, like for the other written files.
+    #[clap(short = 'o', long, value_parser, value_hint = ValueHint::FilePath)]
+    pub output: Vec<PathBuf>,
+
+    /// Format of the output subtitle file(s).
+    ///
+    /// Can be repeated to write several formats from the same OCR, with an
+    /// `--output` file for each one or an `--output-template` using `{format}`.
+    /// The `hocr` format has the Tesseract results of each subtitle image, with the boxes
+    /// of the lines and words, before the text post-processing. It is got in addition
+    /// to the text of the other formats, which is recognized as without it.
+    #[clap(long, value_enum)]
+    pub format: Vec<OutputFormat>,
+
+    /// Template of the output subtitle file path, instead of `--output`.
+    ///
+    /// Available placeholders are `{dir}` and `{stem}` of the input file, `{lang}`,
+    /// `{track}` (the id of the track of a Matroska input) and `{format}`,
+    /// e.g. `{dir}/{stem}.{lang}.{format}`.
+    #[clap(long, conflicts_with = "output")]
+    pub output_template: Option<String>,
+
+    /// Font of the default style of the `ASS` output.
+    #[clap(long, default_value = "Arial")]
+    pub ass_font: String,
+
+    /// Font size of the default style of the `ASS` output.
+    #[clap(long, default_value = "20")]
+    pub ass_font_size: u32,
+
+    /// Outline width of the default style of the `ASS` output.
+    #[clap(long, default_value = "2")]
+    pub ass_outline: u32,
+
+    /// Vertical margin, from the bottom of the video, of the default style of the `ASS` output.
+    #[clap(long, default_value = "10")]
+    pub ass_margin_v: u32,
+
+    /// Also write the OCR result of each subtitle in this format, with the boxes and confidences of the words.
+    ///
+    /// The results are of the whole images, got in addition to the text of the subtitles.
+    /// The `tsv` results can't be written with the `hocr` output format.
+    #[clap(long, value_enum)]
+    pub ocr_output: Option<OcrOutput>,
+
+    /// Directory of the `--ocr-output` files, one per subtitle.
+    ///
+    /// By default, the directory is next to the output file, e.g. `movie.ocr` for `movie.srt`.
+    #[clap(long, requires = "ocr_output", value_hint = ValueHint::DirPath)]
+    pub ocr_output_dir: Option<PathBuf>,
+
+    /// Write in this file where each subtitle comes from in the input, for debugging.
+    ///
+    /// Origins are the packet file positions from the idx file for `VobSub`,
+    /// and the number of the display set in the stream for PGS.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub trace: Option<PathBuf>,
+
+    /// Write the subtitles flagged for checking (skipped images, suspicious OCR results) in this file.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub qa_report: Option<PathBuf>,
+
+    /// Write an index of the subtitles times in this file.
+    ///
+    /// The index is written as JSON if the file extension is `.json`, as tab-separated text otherwise.
+    /// The JSON entries have the `lang` used for the OCR of the subtitle.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub cue_index: Option<PathBuf>,
+
+    /// Only list the first subtitle of each interval of this many seconds in the cue index.
+    #[clap(long, requires = "cue_index")]
+    pub cue_index_interval: Option<u32>,
+
+    /// Write the subtitles inferred as forced in this file, for a track without forced flags.
+    ///
+    /// The subtitles scattered in the track (e.g. translations of foreign dialogues or signs)
+    /// are proposed for review, from the density of the subtitles around them and their duration.
+    /// The format is chosen from the file extension, like `--output`.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub infer_forced: Option<PathBuf>,
+
+    /// Write the subtitles flagged by `--min-confidence` in this file, to proofread only them.
+    ///
+    /// The format is chosen from the file extension, like `--output`.
+    #[clap(long, requires = "min_confidence", value_hint = ValueHint::FilePath)]
+    pub confidence_review: Option<PathBuf>,
+}
+
+impl Opt {
+    /// Check that the Tesseract language is given if the run needs the OCR,
+    /// exiting with a usage error like for the other missing arguments otherwise.
+    pub fn check_lang(&self) {
+        let runs_ocr = self.learn_corrections.is_none()
+            && self.palette_from_frame.is_none()
+            && self.fix_idx.is_none()
+            && self.extract_only.is_none()
+            && self.contact_sheet.is_none()
+            && !self.no_ocr;
+        if runs_ocr && self.ocr.lang.is_none() {
+            Self::command()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "the Tesseract language `--lang <LANG>` is required to run the OCR",
+                )
+                .exit();
+        }
+    }
+}
+
+impl From<Opt> for Config {
+    fn from(opt: Opt) -> Self {
+        Self::default()
+            .with_input(opt.input.into())
+            .with_image(opt.image.into())
+            .with_ocr(opt.ocr.into())
+            .with_timing(opt.timing.into())
+            .with_text(opt.text.into())
+            .with_output(opt.output.into())
+    }
+}
+
+impl From<InputArgs> for InputConfig {
+    fn from(args: InputArgs) -> Self {
+        let mut offsets = args.concat_offset.into_iter();
+        let concat = args
+            .concat
+            .into_iter()
+            .map(|path| (path, offsets.next()))
+            .collect();
+        let config = Self::default()
+            .with_track(args.track)
+            .with_concat(concat)
+            .with_stdin_format(args.input_format.map(Into::into))
+            .with_stdin_idx(args.idx)
+            .with_palette(args.palette)
+            .with_recover(args.recover)
+            .with_max_image_size(args.max_image_size)
+            .with_parse_stall_timeout(
+                (args.parse_stall_timeout != 0)
+                    .then(|| Duration::from_secs(args.parse_stall_timeout)),
+            )
+            .with_decode_cache(args.decode_cache)
+            .with_max_memory(args.max_memory);
+        match args.input {
+            Some(path) => config.with_path(path),
+            None => config,
+        }
+    }
+}
+
+impl From<ImageArgs> for ImageConfig {
+    fn from(args: ImageArgs) -> Self {
+        Self::default()
+            .with_threshold(args.threshold)
+            .with_alpha_threshold(args.alpha_threshold)
+            .with_luma_threshold(args.luma_threshold)
+            .with_convert(args.convert.into())
+            .with_ink_tolerance(args.ink_tolerance)
+            .with_border(args.border)
+            .with_target_line_height(args.target_line_height)
+            .with_crop(args.crop)
+            .with_fill_hollow(args.fill_hollow)
+            .with_split_groups(args.split_groups)
+            .with_min_ink_pixels(args.min_ink_pixels)
+            .with_duplicate_max_duration(args.duplicate_max_duration)
+            .with_filter(args.filter)
+    }
+}
+
+impl From<OcrArgs> for OcrConfig {
+    fn from(args: OcrArgs) -> Self {
+        let config = Self::default()
+            .with_tessdata_dir(args.tessdata_dir)
+            .with_variables(args.config)
+            .with_dpi(args.dpi)
+            .with_line_mode(args.line_mode)
+            .with_single_line_psm(!args.no_single_line_psm)
+            .with_invert_retry(!args.no_invert_retry)
+            .with_column_separator(args.column_separator)
+            .with_max_instances(args.max_tesseract_instances)
+            .with_tesseract_threads(args.tesseract_threads)
+            // The binary owns its process, so it can limit the threads of each Tesseract instance.
+            .with_thread_limit_env(true)
+            .with_timeout(args.ocr_timeout.map(Duration::from_secs))
+            .with_panic_isolation(!args.abort_on_panic)
+            .with_scrub_control_chars(!args.keep_control_chars)
+            .with_min_confidence(args.min_confidence)
+            .with_low_confidence(args.low_confidence.into())
+            .with_dictionary(args.dictionary);
+        // Without language, the runs without OCR keep the default one.
+        match args.lang {
+            Some(lang) => config.with_lang(lang),
+            None => config,
+        }
+    }
+}
+
+impl From<TimingArgs> for TimingConfig {
+    fn from(args: TimingArgs) -> Self {
+        Self::default()
+            .with_offset(args.time_offset)
+            .with_control_timing(!args.no_control_timing)
+            .with_anchors(args.anchor)
+            .with_invalid_times(args.invalid_times.into())
+            .with_min_duration(args.min_duration)
+            .with_overlap(args.overlap.into())
+            .with_keep_order(args.keep_order)
+            .with_max_cps(args.max_cps)
+            .with_extend_fast_cues(args.extend_fast_cues)
+    }
+}
+
+impl From<TextArgs> for TextConfig {
+    fn from(args: TextArgs) -> Self {
+        Self::default()
+            .with_normalize(args.normalize.into())
+            .with_dialogue_dashes(args.dialogue_dashes.into())
+            .with_postprocess(!args.no_postprocess)
+            .with_replace_file(args.replace_file)
+            .with_corrections(args.corrections)
+            .with_strip_credits(args.strip_credits)
+            .with_credits_patterns(args.credits_patterns)
+            .with_split_sentences(args.split_sentences.into())
+            .with_validate(args.validate.into())
+            .with_max_lines(args.max_lines)
+            .with_detect_italics(args.detect_italics)
+    }
+}
+
+impl From<OutputArgs> for OutputConfig {
+    fn from(args: OutputArgs) -> Self {
+        Self::default()
+            .with_files(args.output)
+            .with_formats(args.format.into_iter().map(Into::into).collect())
+            .with_template(args.output_template)
+            .with_ass_style(AssStyle {
+                font: args.ass_font,
+                font_size: args.ass_font_size,
+                outline: args.ass_outline,
+                margin_v: args.ass_margin_v,
+            })
+            .with_ocr_output(args.ocr_output.map(Into::into))
+            .with_ocr_output_dir(args.ocr_output_dir)
+            .with_trace(args.trace)
+            .with_qa_report(args.qa_report)
+            .with_cue_index(args.cue_index)
+            .with_cue_index_interval(args.cue_index_interval)
+            .with_infer_forced(args.infer_forced)
+            .with_confidence_review(args.confidence_review)
+    }
+}
+fn parse_separator(s: &str) -> Result<String, Infallible> {
+    Ok(s.replace("\\t", "\t"))
+}
+
+/// Format of the output subtitle files.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// `SubRip` text.
+    #[default]
+    Srt,
+    /// Advanced `SubStation` Alpha.
+    Ass,
+    /// `hOCR` document of the Tesseract results, with the boxes of the lines and words.
+    Hocr,
+}
+
+impl From<OutputFormat> for subtile_ocr_core::OutputFormat {
+    fn from(output_format: OutputFormat) -> Self {
+        match output_format {
+            OutputFormat::Srt => Self::Srt,
+            OutputFormat::Ass => Self::Ass,
+            OutputFormat::Hocr => Self::Hocr,
+        }
+    }
+}
+
+/// What to do with the subtitles flagged by `--min-confidence`.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum LowConfidencePolicy {
+    /// Only flag the subtitles in the report of the subtitles to check.
+    #[default]
+    Flag,
+    /// Also start the text of the subtitles with a `[low OCR confidence: NN%]` marker.
+    Marker,
+    /// Fail, after the report of the subtitles to check.
+    Fail,
+}
+
+impl From<LowConfidencePolicy> for subtile_ocr_core::LowConfidencePolicy {
+    fn from(low_confidence_policy: LowConfidencePolicy) -> Self {
+        match low_confidence_policy {
+            LowConfidencePolicy::Flag => Self::Flag,
+            LowConfidencePolicy::Marker => Self::Marker,
+            LowConfidencePolicy::Fail => Self::Fail,
+        }
+    }
+}
+
+/// Format of the subtitles read from stdin.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum InputFormat {
+    /// `PGS` stream, as in a `.sup` file.
+    Sup,
+    /// `VobSub` stream, as in a `.sub` file, with its idx file given separately.
+    Vobsub,
+    /// `MPEG` transport stream with `DVB` subtitles, as in a `.ts` file.
+    Ts,
+}
+
+impl From<InputFormat> for subtile_ocr_core::InputFormat {
+    fn from(input_format: InputFormat) -> Self {
+        match input_format {
+            InputFormat::Sup => Self::Sup,
+            InputFormat::Vobsub => Self::Vobsub,
+            InputFormat::Ts => Self::Ts,
+        }
+    }
+}
+
+/// Strategy used to convert `VobSub` images for `OCR`.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum ConvertStrategy {
+    /// Binarize the luminance of the palette colors.
+    #[default]
+    PaletteLuma,
+    /// Cluster the colors of the RGBA render, using alpha as mask, and keep the text fill.
+    RgbaCluster,
+    /// Binarize the luminance weighted by alpha with the threshold adapted to each image (Otsu),
+    /// also for the `PGS` and `DVB` images.
+    Otsu,
+}
+
+impl From<ConvertStrategy> for subtile_ocr_core::ConvertStrategy {
+    fn from(convert_strategy: ConvertStrategy) -> Self {
+        match convert_strategy {
+            ConvertStrategy::PaletteLuma => Self::PaletteLuma,
+            ConvertStrategy::RgbaCluster => Self::RgbaCluster,
+            ConvertStrategy::Otsu => Self::Otsu,
         }
-        "classify_adapt_proto_threshold" => Variable::ClassifyAdaptProtoThreshold,
-        "classify_adapt_feature_threshold" => Variable::ClassifyAdaptFeatureThreshold,
-        "classify_class_pruner_threshold" => Variable::ClassifyClassPrunerThreshold,
-        "classify_class_pruner_multiplier" => Variable::ClassifyClassPrunerMultiplier,
-        "classify_cp_cutoff_strength" => Variable::ClassifyCpCutoffStrength,
-        "classify_integer_matcher_multiplier" => Variable::ClassifyIntegerMatcherMultiplier,
-        "dawg_debug_level" => Variable::DawgDebugLevel,
-        "hyphen_debug_level" => Variable::HyphenDebugLevel,
-        "stopper_smallword_size" => Variable::StopperSmallwordSize,
-        "stopper_debug_level" => Variable::StopperDebugLevel,
-        "tessedit_truncate_wordchoice_log" => Variable::TesseditTruncateWordchoiceLog,
-        "max_permuter_attempts" => Variable::MaxPermuterAttempts,
-        "repair_unchopped_blobs" => Variable::RepairUnchoppedBlobs,
-        "chop_debug" => Variable::ChopDebug,
-        "chop_split_length" => Variable::ChopSplitLength,
-        "chop_same_distance" => Variable::ChopSameDistance,
-        "chop_min_outline_points" => Variable::ChopMinOutlinePoints,
-        "chop_seam_pile_size" => Variable::ChopSeamPileSize,
-        "chop_inside_angle" => Variable::ChopInsideAngle,
-        "chop_min_outline_area" => Variable::ChopMinOutlineArea,
-        "chop_centered_maxwidth" => Variable::ChopCenteredMaxwidth,
-        "chop_x_y_weight" => Variable::ChopXyWeight,
-        "wordrec_debug_level" => Variable::WordrecDebugLevel,
-        "wordrec_max_join_chunks" => Variable::WordrecMaxJoinChunks,
-        "segsearch_debug_level" => Variable::SegsearchDebugLevel,
-        "segsearch_max_pain_points" => Variable::SegsearchMaxPainPoints,
-        "segsearch_max_futile_classifications" => Variable::SegsearchMaxFutileClassifications,
-        "language_model_debug_level" => Variable::LanguageModelDebugLevel,
-        "language_model_ngram_order" => Variable::LanguageModelNgramOrder,
-        "language_model_viterbi_list_max_num_prunable" => {
-            Variable::LanguageModelViterbiListMaxNumPrunable
+    }
+}
+
+/// Format of the `OCR` results written for each subtitle, with the boxes of the words.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum OcrOutput {
+    /// `hOCR` page of Tesseract.
+    Hocr,
+    /// Tab-separated table of Tesseract, with a row for each block, paragraph, line and word.
+    Tsv,
+}
+
+impl From<OcrOutput> for subtile_ocr_core::OcrOutput {
+    fn from(ocr_output: OcrOutput) -> Self {
+        match ocr_output {
+            OcrOutput::Hocr => Self::Hocr,
+            OcrOutput::Tsv => Self::Tsv,
         }
-        "language_model_viterbi_list_max_size" => Variable::LanguageModelViterbiListMaxSize,
-        "language_model_min_compound_length" => Variable::LanguageModelMinCompoundLength,
-        "wordrec_display_segmentations" => Variable::WordrecDisplaySegmentations,
-        "tessedit_pageseg_mode" => Variable::TesseditPagesegMode,
-        "tessedit_ocr_engine_mode" => Variable::TesseditOcrEngineMode,
-        "pageseg_devanagari_split_strategy" => Variable::PagesegDevanagariSplitStrategy,
-        "ocr_devanagari_split_strategy" => Variable::OcrDevanagariSplitStrategy,
-        "bidi_debug" => Variable::BidiDebug,
-        "applybox_debug" => Variable::ApplyboxDebug,
-        "applybox_page" => Variable::ApplyboxPage,
-        "tessedit_bigram_debug" => Variable::TesseditBigramDebug,
-        "debug_noise_removal" => Variable::DebugNoiseRemoval,
-        "noise_maxperblob" => Variable::NoiseMaxperblob,
-        "noise_maxperword" => Variable::NoiseMaxperword,
-        "debug_x_ht_level" => Variable::DebugXHtLevel,
-        "quality_min_initial_alphas_reqd" => Variable::QualityMinInitialAlphasReqd,
-        "tessedit_tess_adaption_mode" => Variable::TesseditTessAdaptionMode,
-        "multilang_debug_level" => Variable::MultilangDebugLevel,
-        "paragraph_debug_level" => Variable::ParagraphDebugLevel,
-        "tessedit_preserve_min_wd_len" => Variable::TesseditPreserveMinWdLen,
-        "crunch_rating_max" => Variable::CrunchRatingMax,
-        "crunch_pot_indicators" => Variable::CrunchPotIndicators,
-        "crunch_leave_lc_strings" => Variable::CrunchLeaveLcStrings,
-        "crunch_leave_uc_strings" => Variable::CrunchLeaveUcStrings,
-        "crunch_long_repetitions" => Variable::CrunchLongRepetitions,
-        "crunch_debug" => Variable::CrunchDebug,
-        "fixsp_non_noise_limit" => Variable::FixspNonNoiseLimit,
-        "fixsp_done_mode" => Variable::FixspDoneMode,
-        "debug_fix_space_level" => Variable::DebugFixSpaceLevel,
-        "x_ht_acceptance_tolerance" => Variable::XHtAcceptanceTolerance,
-        "x_ht_min_change" => Variable::XHtMinChange,
-        "superscript_debug" => Variable::SuperscriptDebug,
-        "jpg_quality" => Variable::JpgQuality,
-        "user_defined_dpi" => Variable::UserDefinedDpi,
-        "min_characters_to_try" => Variable::MinCharactersToTry,
-        "suspect_level" => Variable::SuspectLevel,
-        "suspect_short_words" => Variable::SuspectShortWords,
-        "tessedit_reject_mode" => Variable::TesseditRejectMode,
-        "tessedit_image_border" => Variable::TesseditImageBorder,
-        "min_sane_x_ht_pixels" => Variable::MinSaneXHtPixels,
-        "tessedit_page_number" => Variable::TesseditPageNumber,
-        "tessedit_parallelize" => Variable::TesseditParallelize,
-        "lstm_choice_mode" => Variable::LstmChoiceMode,
-        "tosp_debug_level" => Variable::TospDebugLevel,
-        "tosp_enough_space_samples_for_median" => Variable::TospEnoughSpaceSamplesForMedian,
-        "tosp_redo_kern_limit" => Variable::TospRedoKernLimit,
-        "tosp_few_samples" => Variable::TospFewSamples,
-        "tosp_short_row" => Variable::TospShortRow,
-        "tosp_sanity_method" => Variable::TospSanityMethod,
-        "textord_max_noise_size" => Variable::TextordMaxNoiseSize,
-        "textord_baseline_debug" => Variable::TextordBaselineDebug,
-        "textord_noise_sizefraction" => Variable::TextordNoiseSizefraction,
-        "textord_noise_translimit" => Variable::TextordNoiseTranslimit,
-        "textord_noise_sncount" => Variable::TextordNoiseSncount,
-        "use_ambigs_for_adaption" => Variable::UseAmbigsForAdaption,
-        "allow_blob_division" => Variable::AllowBlobDivision,
-        "prioritize_division" => Variable::PrioritizeDivision,
-        "classify_enable_learning" => Variable::ClassifyEnableLearning,
-        "tess_cn_matching" => Variable::TessCnMatching,
-        "tess_bn_matching" => Variable::TessBnMatching,
-        "classify_enable_adaptive_matcher" => Variable::ClassifyEnableAdaptiveMatcher,
-        "classify_use_pre_adapted_templates" => Variable::ClassifyUsePreAdaptedTemplates,
-        "classify_save_adapted_templates" => Variable::ClassifySaveAdaptedTemplates,
-        "classify_enable_adaptive_debugger" => Variable::ClassifyEnableAdaptiveDebugger,
-        "classify_nonlinear_norm" => Variable::ClassifyNonlinearNorm,
-        "disable_character_fragments" => Variable::DisableCharacterFragments,
-        "classify_debug_character_fragments" => Variable::ClassifyDebugCharacterFragments,
-        "matcher_debug_separate_windows" => Variable::MatcherDebugSeparateWindows,
-        "classify_bln_numeric_mode" => Variable::ClassifyBlnNumericMode,
-        "load_system_dawg" => Variable::LoadSystemDawg,
-        "load_freq_dawg" => Variable::LoadFreqDawg,
-        "load_unambig_dawg" => Variable::LoadUnambigDawg,
-        "load_punc_dawg" => Variable::LoadPuncDawg,
-        "load_number_dawg" => Variable::LoadNumberDawg,
-        "load_bigram_dawg" => Variable::LoadBigramDawg,
-        "use_only_first_uft8_step" => Variable::UseOnlyFirstUft8Step,
-        "stopper_no_acceptable_choices" => Variable::StopperNoAcceptableChoices,
-        "segment_nonalphabetic_script" => Variable::SegmentNonalphabeticScript,
-        "save_doc_words" => Variable::SaveDocWords,
-        "merge_fragments_in_matrix" => Variable::MergeFragmentsInMatrix,
-        "wordrec_enable_assoc" => Variable::WordrecEnableAssoc,
-        "force_word_assoc" => Variable::ForceWordAssoc,
-        "chop_enable" => Variable::ChopEnable,
-        "chop_vertical_creep" => Variable::ChopVerticalCreep,
-        "chop_new_seam_pile" => Variable::ChopNewSeamPile,
-        "assume_fixed_pitch_char_segment" => Variable::AssumeFixedPitchCharSegment,
-        "wordrec_skip_no_truth_words" => Variable::WordrecSkipNoTruthWords,
-        "wordrec_debug_blamer" => Variable::WordrecDebugBlamer,
-        "wordrec_run_blamer" => Variable::WordrecRunBlamer,
-        "save_alt_choices" => Variable::SaveAltChoices,
-        "language_model_ngram_on" => Variable::LanguageModelNgramOn,
-        "language_model_ngram_use_only_first_uft8_step" => {
-            Variable::LanguageModelNgramUseOnlyFirstUft8Step
+    }
+}
+
+/// What to do with the subtitles ending before or when they start, rejected by several players.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum InvalidTimePolicy {
+    /// Extend the subtitles to the minimum duration.
+    #[default]
+    Repair,
+    /// Remove the subtitles.
+    Drop,
+}
+
+impl From<InvalidTimePolicy> for subtile_ocr_core::InvalidTimePolicy {
+    fn from(invalid_time_policy: InvalidTimePolicy) -> Self {
+        match invalid_time_policy {
+            InvalidTimePolicy::Repair => Self::Repair,
+            InvalidTimePolicy::Drop => Self::Drop,
         }
-        "language_model_ngram_space_delimited_language" => {
-            Variable::LanguageModelNgramSpaceDelimitedLanguage
+    }
+}
+
+/// Unicode normalization applied on the `OCR` output.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum Normalization {
+    /// Canonical composition.
+    #[default]
+    Nfc,
+    /// Canonical composition, with the Latin ligatures and full-width forms replaced by their
+    /// plain characters, for Latin scripts. The full-width punctuation of `CJK` scripts is lost.
+    NfcFolded,
+    /// Compatibility composition, which also replaces other compatibility characters (e.g. `½`, `²`).
+    Nfkc,
+    /// Keep the text as recognized.
+    Off,
+}
+
+impl From<Normalization> for subtile_ocr_core::Normalization {
+    fn from(normalization: Normalization) -> Self {
+        match normalization {
+            Normalization::Nfc => Self::Nfc,
+            Normalization::NfcFolded => Self::NfcFolded,
+            Normalization::Nfkc => Self::Nfkc,
+            Normalization::Off => Self::Off,
         }
-        "language_model_use_sigmoidal_certainty" => Variable::LanguageModelUseSigmoidalCertainty,
-        "tessedit_resegment_from_boxes" => Variable::TesseditResegmentFromBoxes,
-        "tessedit_resegment_from_line_boxes" => Variable::TesseditResegmentFromLineBoxes,
-        "tessedit_train_from_boxes" => Variable::TesseditTrainFromBoxes,
-        "tessedit_make_boxes_from_boxes" => Variable::TesseditMakeBoxesFromBoxes,
-        "tessedit_train_line_recognizer" => Variable::TesseditTrainLineRecognizer,
-        "tessedit_dump_pageseg_images" => Variable::TesseditDumpPagesegImages,
-        "tessedit_do_invert" => Variable::TesseditDoInvert,
-        "tessedit_ambigs_training" => Variable::TesseditAmbigsTraining,
-        "tessedit_adaption_debug" => Variable::TesseditAdaptionDebug,
-        "applybox_learn_chars_and_char_frags_mode" => Variable::ApplyboxLearnCharsAndCharFragsMode,
-        "applybox_learn_ngrams_mode" => Variable::ApplyboxLearnNgramsMode,
-        "tessedit_display_outwords" => Variable::TesseditDisplayOutwords,
-        "tessedit_dump_choices" => Variable::TesseditDumpChoices,
-        "tessedit_timing_debug" => Variable::TesseditTimingDebug,
-        "tessedit_fix_fuzzy_spaces" => Variable::TesseditFixFuzzySpaces,
-        "tessedit_unrej_any_wd" => Variable::TesseditUnrejAnyWd,
-        "tessedit_fix_hyphens" => Variable::TesseditFixHyphens,
-        "tessedit_enable_doc_dict" => Variable::TesseditEnableDocDict,
-        "tessedit_debug_fonts" => Variable::TesseditDebugFonts,
-        "tessedit_debug_block_rejection" => Variable::TesseditDebugBlockRejection,
-        "tessedit_enable_bigram_correction" => Variable::TesseditEnableBigramCorrection,
-        "tessedit_enable_dict_correction" => Variable::TesseditEnableDictCorrection,
-        "enable_noise_removal" => Variable::EnableNoiseRemoval,
-        "tessedit_minimal_rej_pass1" => Variable::TesseditMinimalRejPass1,
-        "tessedit_test_adaption" => Variable::TesseditTestAdaption,
-        "test_pt" => Variable::TestPt,
-        "paragraph_text_based" => Variable::ParagraphTextBased,
-        "lstm_use_matrix" => Variable::LstmUseMatrix,
-        "tessedit_good_quality_unrej" => Variable::TesseditGoodQualityUnrej,
-        "tessedit_use_reject_spaces" => Variable::TesseditUseRejectSpaces,
-        "tessedit_preserve_blk_rej_perfect_wds" => Variable::TesseditPreserveBlkRejPerfectWds,
-        "tessedit_preserve_row_rej_perfect_wds" => Variable::TesseditPreserveRowRejPerfectWds,
-        "tessedit_dont_blkrej_good_wds" => Variable::TesseditDontBlkrejGoodWds,
-        "tessedit_dont_rowrej_good_wds" => Variable::TesseditDontRowrejGoodWds,
-        "tessedit_row_rej_good_docs" => Variable::TesseditRowRejGoodDocs,
-        "tessedit_reject_bad_qual_wds" => Variable::TesseditRejectBadQualWds,
-        "tessedit_debug_doc_rejection" => Variable::TesseditDebugDocRejection,
-        "tessedit_debug_quality_metrics" => Variable::TesseditDebugQualityMetrics,
-        "bland_unrej" => Variable::BlandUnrej,
-        "unlv_tilde_crunching" => Variable::UnlvTildeCrunching,
-        "hocr_font_info" => Variable::HocrFontInfo,
-        "hocr_char_boxes" => Variable::HocrCharBoxes,
-        "crunch_early_merge_tess_fails" => Variable::CrunchEarlyMergeTessFails,
-        "crunch_early_convert_bad_unlv_chs" => Variable::CrunchEarlyConvertBadUnlvChs,
-        "crunch_terrible_garbage" => Variable::CrunchTerribleGarbage,
-        "crunch_leave_ok_strings" => Variable::CrunchLeaveOkStrings,
-        "crunch_accept_ok" => Variable::CrunchAcceptOk,
-        "crunch_leave_accept_strings" => Variable::CrunchLeaveAcceptStrings,
-        "crunch_include_numerals" => Variable::CrunchIncludeNumerals,
-        "tessedit_prefer_joined_punct" => Variable::TesseditPreferJoinedPunct,
-        "tessedit_write_block_separators" => Variable::TesseditWriteBlockSeparators,
-        "tessedit_write_rep_codes" => Variable::TesseditWriteRepCodes,
-        "tessedit_write_unlv" => Variable::TesseditWriteUnlv,
-        "tessedit_create_txt" => Variable::TesseditCreateTxt,
-        "tessedit_create_hocr" => Variable::TesseditCreateHocr,
-        "tessedit_create_alto" => Variable::TesseditCreateAlto,
-        "tessedit_create_lstmbox" => Variable::TesseditCreateLstmbox,
-        "tessedit_create_tsv" => Variable::TesseditCreateTsv,
-        "tessedit_create_wordstrbox" => Variable::TesseditCreateWordstrbox,
-        "tessedit_create_pdf" => Variable::TesseditCreatePdf,
-        "textonly_pdf" => Variable::TextonlyPdf,
-        "suspect_constrain_1Il" => Variable::SuspectConstrain1Il,
-        "tessedit_minimal_rejection" => Variable::TesseditMinimalRejection,
-        "tessedit_zero_rejection" => Variable::TesseditZeroRejection,
-        "tessedit_word_for_word" => Variable::TesseditWordForWord,
-        "tessedit_zero_kelvin_rejection" => Variable::TesseditZeroKelvinRejection,
-        "tessedit_rejection_debug" => Variable::TesseditRejectionDebug,
-        "tessedit_flip_0O" => Variable::TesseditFlip0O,
-        "rej_trust_doc_dawg" => Variable::RejTrustDocDawg,
-        "rej_1Il_use_dict_word" => Variable::Rej1IlUseDictWord,
-        "rej_1Il_trust_permuter_type" => Variable::Rej1IlTrustPermuterType,
-        "rej_use_tess_accepted" => Variable::RejUseTessAccepted,
-        "rej_use_tess_blanks" => Variable::RejUseTessBlanks,
-        "rej_use_good_perm" => Variable::RejUseGoodPerm,
-        "rej_use_sensible_wd" => Variable::RejUseSensibleWd,
-        "rej_alphas_in_number_perm" => Variable::RejAlphasInNumberPerm,
-        "tessedit_create_boxfile" => Variable::TesseditCreateBoxfile,
-        "tessedit_write_images" => Variable::TesseditWriteImages,
-        "interactive_display_mode" => Variable::InteractiveDisplayMode,
-        "tessedit_override_permuter" => Variable::TesseditOverridePermuter,
-        "tessedit_use_primary_params_model" => Variable::TesseditUsePrimaryParamsModel,
-        "textord_tabfind_show_vlines" => Variable::TextordTabfindShowVlines,
-        "textord_use_cjk_fp_model" => Variable::TextordUseCjkFpModel,
-        "poly_allow_detailed_fx" => Variable::PolyAllowDetailedFx,
-        "tessedit_init_config_only" => Variable::TesseditInitConfigOnly,
-        "textord_equation_detect" => Variable::TextordEquationDetect,
-        "textord_tabfind_vertical_text" => Variable::TextordTabfindVerticalText,
-        "textord_tabfind_force_vertical_text" => Variable::TextordTabfindForceVerticalText,
-        "preserve_interword_spaces" => Variable::PreserveInterwordSpaces,
-        "pageseg_apply_music_mask" => Variable::PagesegApplyMusicMask,
-        "textord_single_height_mode" => Variable::TextordSingleHeightMode,
-        "tosp_old_to_method" => Variable::TospOldToMethod,
-        "tosp_old_to_constrain_sp_kn" => Variable::TospOldToConstrainSpKn,
-        "tosp_only_use_prop_rows" => Variable::TospOnlyUsePropRows,
-        "tosp_force_wordbreak_on_punct" => Variable::TospForceWordbreakOnPunct,
-        "tosp_use_pre_chopping" => Variable::TospUsePreChopping,
-        "tosp_old_to_bug_fix" => Variable::TospOldToBugFix,
-        "tosp_block_use_cert_spaces" => Variable::TospBlockUseCertSpaces,
-        "tosp_row_use_cert_spaces" => Variable::TospRowUseCertSpaces,
-        "tosp_narrow_blobs_not_cert" => Variable::TospNarrowBlobsNotCert,
-        "tosp_row_use_cert_spaces1" => Variable::TospRowUseCertSpaces1,
-        "tosp_recovery_isolated_row_stats" => Variable::TospRecoveryIsolatedRowStats,
-        "tosp_only_small_gaps_for_kern" => Variable::TospOnlySmallGapsForKern,
-        "tosp_all_flips_fuzzy" => Variable::TospAllFlipsFuzzy,
-        "tosp_fuzzy_limit_all" => Variable::TospFuzzyLimitAll,
-        "tosp_stats_use_xht_gaps" => Variable::TospStatsUseXhtGaps,
-        "tosp_use_xht_gaps" => Variable::TospUseXhtGaps,
-        "tosp_only_use_xht_gaps" => Variable::TospOnlyUseXhtGaps,
-        "tosp_rule_9_test_punct" => Variable::TospRule9TestPunct,
-        "tosp_flip_fuzz_kn_to_sp" => Variable::TospFlipFuzzKnToSp,
-        "tosp_flip_fuzz_sp_to_kn" => Variable::TospFlipFuzzSpToKn,
-        "tosp_improve_thresh" => Variable::TospImproveThresh,
-        "textord_no_rejects" => Variable::TextordNoRejects,
-        "textord_show_blobs" => Variable::TextordShowBlobs,
-        "textord_show_boxes" => Variable::TextordShowBoxes,
-        "textord_noise_rejwords" => Variable::TextordNoiseRejwords,
-        "textord_noise_rejrows" => Variable::TextordNoiseRejrows,
-        "textord_noise_debug" => Variable::TextordNoiseDebug,
-        "classify_learn_debug_str" => Variable::ClassifyLearnDebugStr,
-        "user_words_file" => Variable::UserWordsFile,
-        "user_words_suffix" => Variable::UserWordsSuffix,
-        "user_patterns_file" => Variable::UserPatternsFile,
-        "user_patterns_suffix" => Variable::UserPatternsSuffix,
-        "output_ambig_words_file" => Variable::OutputAmbigWordsFile,
-        "word_to_debug" => Variable::WordToDebug,
-        "tessedit_char_blacklist" => Variable::TesseditCharBlacklist,
-        "tessedit_char_whitelist" => Variable::TesseditCharWhitelist,
-        "tessedit_char_unblacklist" => Variable::TesseditCharUnblacklist,
-        "tessedit_write_params_to_file" => Variable::TesseditWriteParamsToFile,
-        "applybox_exposure_pattern" => Variable::ApplyboxExposurePattern,
-        "chs_leading_punct" => Variable::ChsLeadingPunct,
-        "chs_trailing_punct1" => Variable::ChsTrailingPunct1,
-        "chs_trailing_punct2" => Variable::ChsTrailingPunct2,
-        "outlines_odd" => Variable::OutlinesOdd,
-        "outlines_2" => Variable::Outlines2,
-        "numeric_punctuation" => Variable::NumericPunctuation,
-        "unrecognised_char" => Variable::UnrecognisedChar,
-        "ok_repeated_ch_non_alphanum_wds" => Variable::OkRepeatedChNonAlphanumWds,
-        "conflict_set_I_l_1" => Variable::ConflictSetIl1,
-        "file_type" => Variable::FileType,
-        "tessedit_load_sublangs" => Variable::TesseditLoadSublangs,
-        "page_separator" => Variable::PageSeparator,
-        "classify_char_norm_range" => Variable::ClassifyCharNormRange,
-        "classify_max_rating_ratio" => Variable::ClassifyMaxRatingRatio,
-        "classify_max_certainty_margin" => Variable::ClassifyMaxCertaintyMargin,
-        "matcher_good_threshold" => Variable::MatcherGoodThreshold,
-        "matcher_reliable_adaptive_result" => Variable::MatcherReliableAdaptiveResult,
-        "matcher_perfect_threshold" => Variable::MatcherPerfectThreshold,
-        "matcher_bad_match_pad" => Variable::MatcherBadMatchPad,
-        "matcher_rating_margin" => Variable::MatcherRatingMargin,
-        "matcher_avg_noise_size" => Variable::MatcherAvgNoiseSize,
-        "matcher_clustering_max_angle_delta" => Variable::MatcherClusteringMaxAngleDelta,
-        "classify_misfit_junk_penalty" => Variable::ClassifyMisfitJunkPenalty,
-        "rating_scale" => Variable::RatingScale,
-        "certainty_scale" => Variable::CertaintyScale,
-        "tessedit_class_miss_scale" => Variable::TesseditClassMissScale,
-        "classify_adapted_pruning_factor" => Variable::ClassifyAdaptedPruningFactor,
-        "classify_adapted_pruning_threshold" => Variable::ClassifyAdaptedPruningThreshold,
-        "classify_character_fragments_garbage_certainty_threshold" => {
-            Variable::ClassifyCharacterFragmentsGarbageCertaintyThreshold
+    }
+}
+
+/// Convention for the dashes starting the lines of a dialogue, at each change of speaker.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum DialogueDashes {
+    /// Keep the dashes as recognized.
+    #[default]
+    Keep,
+    /// Hyphen followed by a space (`- Yes.`), the usual English convention.
+    Hyphen,
+    /// Hyphen directly followed by the text (`-Yes.`).
+    HyphenTight,
+    /// En dash followed by a space (`– Oui.`), the French cinema convention.
+    EnDash,
+}
+
+impl From<DialogueDashes> for subtile_ocr_core::DialogueDashes {
+    fn from(dialogue_dashes: DialogueDashes) -> Self {
+        match dialogue_dashes {
+            DialogueDashes::Keep => Self::Keep,
+            DialogueDashes::Hyphen => Self::Hyphen,
+            DialogueDashes::HyphenTight => Self::HyphenTight,
+            DialogueDashes::EnDash => Self::EnDash,
         }
-        "speckle_large_max_size" => Variable::SpeckleLargeMaxSize,
-        "speckle_rating_penalty" => Variable::SpeckleRatingPenalty,
-        "xheight_penalty_subscripts" => Variable::XheightPenaltySubscripts,
-        "xheight_penalty_inconsistent" => Variable::XheightPenaltyInconsistent,
-        "segment_penalty_dict_frequent_word" => Variable::SegmentPenaltyDictFrequentWord,
-        "segment_penalty_dict_case_ok" => Variable::SegmentPenaltyDictCaseOk,
-        "segment_penalty_dict_case_bad" => Variable::SegmentPenaltyDictCaseBad,
-        "segment_penalty_dict_nonword" => Variable::SegmentPenaltyDictNonword,
-        "segment_penalty_garbage" => Variable::SegmentPenaltyGarbage,
-        "stopper_nondict_certainty_base" => Variable::StopperNondictCertaintyBase,
-        "stopper_phase2_certainty_rejection_offset" => {
-            Variable::StopperPhase2CertaintyRejectionOffset
+    }
+}
+
+/// How to write subtitles displayed at the same time (e.g. two speakers, top and bottom).
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum OverlapPolicy {
+    /// Write overlapping subtitles as they are.
+    #[default]
+    Keep,
+    /// Merge overlapping subtitles of distinct regions into one cue covering all of them.
+    Merge,
+    /// Split overlapping subtitles of distinct regions into successive cues, each with all the texts visible at that time.
+    Split,
+}
+
+impl From<OverlapPolicy> for subtile_ocr_core::OverlapPolicy {
+    fn from(overlap_policy: OverlapPolicy) -> Self {
+        match overlap_policy {
+            OverlapPolicy::Keep => Self::Keep,
+            OverlapPolicy::Merge => Self::Merge,
+            OverlapPolicy::Split => Self::Split,
         }
-        "stopper_certainty_per_char" => Variable::StopperCertaintyPerChar,
-        "stopper_allowable_character_badness" => Variable::StopperAllowableCharacterBadness,
-        "doc_dict_pending_threshold" => Variable::DocDictPendingThreshold,
-        "doc_dict_certainty_threshold" => Variable::DocDictCertaintyThreshold,
-        "tessedit_certainty_threshold" => Variable::TesseditCertaintyThreshold,
-        "chop_split_dist_knob" => Variable::ChopSplitDistKnob,
-        "chop_overlap_knob" => Variable::ChopOverlapKnob,
-        "chop_center_knob" => Variable::ChopCenterKnob,
-        "chop_sharpness_knob" => Variable::ChopSharpnessKnob,
-        "chop_width_change_knob" => Variable::ChopWidthChangeKnob,
-        "chop_ok_split" => Variable::ChopOkSplit,
-        "chop_good_split" => Variable::ChopGoodSplit,
-        "segsearch_max_char_wh_ratio" => Variable::SegsearchMaxCharWhRatio,
-        "language_model_ngram_small_prob" => Variable::LanguageModelNgramSmallProb,
-        "language_model_ngram_nonmatch_score" => Variable::LanguageModelNgramNonmatchScore,
-        "language_model_ngram_scale_factor" => Variable::LanguageModelNgramScaleFactor,
-        "language_model_ngram_rating_factor" => Variable::LanguageModelNgramRatingFactor,
-        "language_model_penalty_non_freq_dict_word" => {
-            Variable::LanguageModelPenaltyNonFreqDictWord
+    }
+}
+
+/// What to do with the sentences split across consecutive subtitles.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum SentencePolicy {
+    /// Write the subtitles as they are.
+    #[default]
+    Keep,
+    /// Mark the split with `...` at the end of the subtitle and the start of the next one.
+    Annotate,
+    /// Merge the subtitles of a sentence into one covering all of them, e.g. for transcripts.
+    Merge,
+}
+
+impl From<SentencePolicy> for subtile_ocr_core::SentencePolicy {
+    fn from(sentence_policy: SentencePolicy) -> Self {
+        match sentence_policy {
+            SentencePolicy::Keep => Self::Keep,
+            SentencePolicy::Annotate => Self::Annotate,
+            SentencePolicy::Merge => Self::Merge,
         }
-        "language_model_penalty_non_dict_word" => Variable::LanguageModelPenaltyNonDictWord,
-        "language_model_penalty_punc" => Variable::LanguageModelPenaltyPunc,
-        "language_model_penalty_case" => Variable::LanguageModelPenaltyCase,
-        "language_model_penalty_script" => Variable::LanguageModelPenaltyScript,
-        "language_model_penalty_chartype" => Variable::LanguageModelPenaltyChartype,
-        "language_model_penalty_font" => Variable::LanguageModelPenaltyFont,
-        "language_model_penalty_spacing" => Variable::LanguageModelPenaltySpacing,
-        "language_model_penalty_increment" => Variable::LanguageModelPenaltyIncrement,
-        "noise_cert_basechar" => Variable::NoiseCertBasechar,
-        "noise_cert_disjoint" => Variable::NoiseCertDisjoint,
-        "noise_cert_punc" => Variable::NoiseCertPunc,
-        "noise_cert_factor" => Variable::NoiseCertFactor,
-        "quality_rej_pc" => Variable::QualityRejPc,
-        "quality_blob_pc" => Variable::QualityBlobPc,
-        "quality_outline_pc" => Variable::QualityOutlinePc,
-        "quality_char_pc" => Variable::QualityCharPc,
-        "test_pt_x" => Variable::TestPtX,
-        "test_pt_y" => Variable::TestPtY,
-        "tessedit_reject_doc_percent" => Variable::TesseditRejectDocPercent,
-        "tessedit_reject_block_percent" => Variable::TesseditRejectBlockPercent,
-        "tessedit_reject_row_percent" => Variable::TesseditRejectRowPercent,
-        "tessedit_whole_wd_rej_row_percent" => Variable::TesseditWholeWdRejRowPercent,
-        "tessedit_good_doc_still_rowrej_wd" => Variable::TesseditGoodDocStillRowrejWd,
-        "quality_rowrej_pc" => Variable::QualityRowrejPc,
-        "crunch_terrible_rating" => Variable::CrunchTerribleRating,
-        "crunch_poor_garbage_cert" => Variable::CrunchPoorGarbageCert,
-        "crunch_poor_garbage_rate" => Variable::CrunchPoorGarbageRate,
-        "crunch_pot_poor_rate" => Variable::CrunchPotPoorRate,
-        "crunch_pot_poor_cert" => Variable::CrunchPotPoorCert,
-        "crunch_del_rating" => Variable::CrunchDelRating,
-        "crunch_del_cert" => Variable::CrunchDelCert,
-        "crunch_del_min_ht" => Variable::CrunchDelMinHt,
-        "crunch_del_max_ht" => Variable::CrunchDelMaxHt,
-        "crunch_del_min_width" => Variable::CrunchDelMinWidth,
-        "crunch_del_high_word" => Variable::CrunchDelHighWord,
-        "crunch_del_low_word" => Variable::CrunchDelLowWord,
-        "crunch_small_outlines_size" => Variable::CrunchSmallOutlinesSize,
-        "fixsp_small_outlines_size" => Variable::FixspSmallOutlinesSize,
-        "superscript_worse_certainty" => Variable::SuperscriptWorseCertainty,
-        "superscript_bettered_certainty" => Variable::SuperscriptBetteredCertainty,
-        "superscript_scaledown_ratio" => Variable::SuperscriptScaledownRatio,
-        "subscript_max_y_top" => Variable::SubscriptMaxYTop,
-        "superscript_min_y_bottom" => Variable::SuperscriptMinYBottom,
-        "suspect_rating_per_ch" => Variable::SuspectRatingPerCh,
-        "suspect_accept_rating" => Variable::SuspectAcceptRating,
-        "tessedit_lower_flip_hyphen" => Variable::TesseditLowerFlipHyphen,
-        "tessedit_upper_flip_hyphen" => Variable::TesseditUpperFlipHyphen,
-        "rej_whole_of_mostly_reject_word_fract" => Variable::RejWholeOfMostlyRejectWordFract,
-        "min_orientation_margin" => Variable::MinOrientationMargin,
-        "textord_tabfind_vertical_text_ratio" => Variable::TextordTabfindVerticalTextRatio,
-        "textord_tabfind_aligned_gap_fraction" => Variable::TextordTabfindAlignedGapFraction,
-        "tosp_old_sp_kn_th_factor" => Variable::TospOldSpKnThFactor,
-        "tosp_threshold_bias1" => Variable::TospThresholdBias1,
-        "tosp_threshold_bias2" => Variable::TospThresholdBias2,
-        "tosp_narrow_fraction" => Variable::TospNarrowFraction,
-        "tosp_narrow_aspect_ratio" => Variable::TospNarrowAspectRatio,
-        "tosp_wide_fraction" => Variable::TospWideFraction,
-        "tosp_wide_aspect_ratio" => Variable::TospWideAspectRatio,
-        "tosp_fuzzy_space_factor" => Variable::TospFuzzySpaceFactor,
-        "tosp_fuzzy_space_factor1" => Variable::TospFuzzySpaceFactor1,
-        "tosp_fuzzy_space_factor2" => Variable::TospFuzzySpaceFactor2,
-        "tosp_gap_factor" => Variable::TospGapFactor,
-        "tosp_kern_gap_factor1" => Variable::TospKernGapFactor1,
-        "tosp_kern_gap_factor2" => Variable::TospKernGapFactor2,
-        "tosp_kern_gap_factor3" => Variable::TospKernGapFactor3,
-        "tosp_ignore_big_gaps" => Variable::TospIgnoreBigGaps,
-        "tosp_ignore_very_big_gaps" => Variable::TospIgnoreVeryBigGaps,
-        "tosp_rep_space" => Variable::TospRepSpace,
-        "tosp_enough_small_gaps" => Variable::TospEnoughSmallGaps,
-        "tosp_table_kn_sp_ratio" => Variable::TospTableKnSpRatio,
-        "tosp_table_xht_sp_ratio" => Variable::TospTableXhtSpRatio,
-        "tosp_table_fuzzy_kn_sp_ratio" => Variable::TospTableFuzzyKnSpRatio,
-        "tosp_fuzzy_kn_fraction" => Variable::TospFuzzyKnFraction,
-        "tosp_fuzzy_sp_fraction" => Variable::TospFuzzySpFraction,
-        "tosp_min_sane_kn_sp" => Variable::TospMinSaneKnSp,
-        "tosp_init_guess_kn_mult" => Variable::TospInitGuessKnMult,
-        "tosp_init_guess_xht_mult" => Variable::TospInitGuessXhtMult,
-        "tosp_max_sane_kn_thresh" => Variable::TospMaxSaneKnThresh,
-        "tosp_flip_caution" => Variable::TospFlipCaution,
-        "tosp_large_kerning" => Variable::TospLargeKerning,
-        "tosp_dont_fool_with_small_kerns" => Variable::TospDontFoolWithSmallKerns,
-        "tosp_near_lh_edge" => Variable::TospNearLhEdge,
-        "tosp_silly_kn_sp_gap" => Variable::TospSillyKnSpGap,
-        "tosp_pass_wide_fuzz_sp_to_context" => Variable::TospPassWideFuzzSpToContext,
-        "textord_noise_area_ratio" => Variable::TextordNoiseAreaRatio,
-        "textord_initialx_ile" => Variable::TextordInitialxIle,
-        "textord_initialasc_ile" => Variable::TextordInitialascIle,
-        "textord_noise_sizelimit" => Variable::TextordNoiseSizelimit,
-        "textord_noise_normratio" => Variable::TextordNoiseNormratio,
-        "textord_noise_syfract" => Variable::TextordNoiseSyfract,
-        "textord_noise_sxfract" => Variable::TextordNoiseSxfract,
-        "textord_noise_hfract" => Variable::TextordNoiseHfract,
-        "textord_noise_rowratio" => Variable::TextordNoiseRowratio,
-        "textord_blshift_maxshift" => Variable::TextordBlshiftMaxshift,
-        "textord_blshift_xfraction" => Variable::TextordBlshiftXfraction,
-        _ => {
-            return Err(Error::TesseractVariableName {
-                value: s.as_ref().to_owned(),
-            })
+    }
+}
+
+/// What to do with the cues breaking the `SRT` constraints before writing them.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum ValidationPolicy {
+    /// Report each invalid cue, and write the cues as they are.
+    #[default]
+    Report,
+    /// Fix the invalid cues, reporting each adjustment.
+    Fix,
+    /// Fail, reporting each invalid cue.
+    Error,
+    /// Write the cues as they are.
+    Off,
+}
+
+impl From<ValidationPolicy> for subtile_ocr_core::ValidationPolicy {
+    fn from(validation_policy: ValidationPolicy) -> Self {
+        match validation_policy {
+            ValidationPolicy::Report => Self::Report,
+            ValidationPolicy::Fix => Self::Fix,
+            ValidationPolicy::Error => Self::Error,
+            ValidationPolicy::Off => Self::Off,
         }
-    })
+    }
 }
@@ -1,22 +0,0 @@
-use subtile::vobsub;
-
-/// Convert an sRGB palette to a luminance palette.
-#[must_use]
-pub fn rgb_palette_to_luminance(palette: &vobsub::Palette) -> [f32; 16] {
-    palette.map(|x| {
-        let r = srgb_to_linear(x[0]);
-        let g = srgb_to_linear(x[1]);
-        let b = srgb_to_linear(x[2]);
-        0.2126 * r + 0.7152 * g + 0.0722 * b
-    })
-}
-
-/// Convert an sRGB color space channel to linear.
-fn srgb_to_linear(channel: u8) -> f32 {
-    let value = f32::from(channel) / 255.0;
-    if value <= 0.04045 {
-        value / 12.92
-    } else {
-        ((value + 0.055) / 1.055).powf(2.4)
-    }
-}
@@ -0,0 +1,75 @@
+use std::{ops::ControlFlow, path::PathBuf};
+
+use image::{DynamicImage, GrayImage};
+use subtile_ocr_core::{InspectError, Inspector, TimeSpan};
+
+use crate::{contact_sheet, dump, opt::Opt};
+
+/// Tools of the cli working on the subtitle images of a run: the image dumps,
+/// the contact sheets and the run without `OCR`.
+#[derive(Debug)]
+pub struct Tools {
+    dump: bool,
+    dump_raw: bool,
+    dump_name: String,
+    contact_sheet: Option<PathBuf>,
+    contact_sheet_step: usize,
+    no_ocr: bool,
+}
+
+impl From<&Opt> for Tools {
+    fn from(opt: &Opt) -> Self {
+        Self {
+            dump: opt.dump,
+            dump_raw: opt.dump_raw,
+            dump_name: opt.dump_name.clone(),
+            contact_sheet: opt.contact_sheet.clone(),
+            contact_sheet_step: opt.contact_sheet_step,
+            no_ocr: opt.no_ocr,
+        }
+    }
+}
+
+impl Inspector for Tools {
+    fn wants_raw_images(&self) -> bool {
+        self.dump_raw
+    }
+
+    fn raw_images(
+        &mut self,
+        times: &[TimeSpan],
+        images: Vec<DynamicImage>,
+    ) -> Result<(), InspectError> {
+        dump::dump_images("dumps_raw", &self.dump_name, times, images)?;
+        Ok(())
+    }
+
+    // Only write contact sheets of the decoded images if requested, without OCR.
+    fn converted_images(
+        &mut self,
+        times: &[TimeSpan],
+        images: &[GrayImage],
+    ) -> Result<ControlFlow<()>, InspectError> {
+        let Some(dir) = &self.contact_sheet else {
+            return Ok(ControlFlow::Continue(()));
+        };
+        contact_sheet::write_contact_sheets(dir, times, images, self.contact_sheet_step)?;
+        Ok(ControlFlow::Break(()))
+    }
+
+    // Stop before the OCR if requested, to time the decoding alone.
+    fn prepared_images(
+        &mut self,
+        times: &[TimeSpan],
+        images: &[GrayImage],
+    ) -> Result<ControlFlow<()>, InspectError> {
+        if self.dump {
+            dump::dump_images("dumps", &self.dump_name, times, images.iter().cloned())?;
+        }
+        Ok(if self.no_ocr {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        })
+    }
+}
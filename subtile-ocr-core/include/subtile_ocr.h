@@ -0,0 +1,47 @@
+/* C API of subtile-ocr, built with the `ffi` feature. */
+
+#ifndef SUBTILE_OCR_H
+#define SUBTILE_OCR_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define SUBTILE_OCR_OK 0
+#define SUBTILE_OCR_ERROR_ARGUMENT 1
+#define SUBTILE_OCR_ERROR_SETUP 2
+#define SUBTILE_OCR_ERROR_INPUT 3
+#define SUBTILE_OCR_ERROR_PARSE 4
+#define SUBTILE_OCR_ERROR_OCR 5
+#define SUBTILE_OCR_ERROR_OUTPUT 6
+#define SUBTILE_OCR_ERROR_INTERNAL 7
+
+/* Negative numbers, null pointers and false keep the default value of the option. */
+typedef struct SubtileOcrOptions {
+    const char *lang;         /* Tesseract language(s), like "eng" or "eng+fra". Required. */
+    const char *tessdata_dir; /* Path to Tesseract's tessdata directory. */
+    int32_t dpi;              /* Dots per inch of the subtitles images. */
+    int32_t border;           /* Border in pixels to surround each subtitle image. */
+    float threshold;          /* Threshold for image binarization, between 0.0 and 1.0. */
+    bool thread_limit_env;    /* Set OMP_THREAD_LIMIT in the process environment, not thread-safe. */
+} SubtileOcrOptions;
+
+/* Convert the subtitles of the .idx or .sup file at `input` to SRT.
+ * The `input` path is used as is on Unix, and must be valid UTF-8 elsewhere.
+ * On success, `*srt_out` is set to a string to release with `subtile_ocr_free_string`. */
+int32_t subtile_ocr_process_file(const char *input, const SubtileOcrOptions *options, char **srt_out);
+
+/* Release a string returned by `subtile_ocr_process_file`. */
+void subtile_ocr_free_string(char *string);
+
+/* Message of the last error of the calling thread, or NULL. */
+const char *subtile_ocr_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SUBTILE_OCR_H */
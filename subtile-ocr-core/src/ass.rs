@@ -0,0 +1,95 @@
+use std::io::{self, Write};
+
+use subtile::time::{TimePoint, TimeSpan};
+
+/// Header of the written `ASS` files, before the styles.
+const SCRIPT_INFO: &str = "[Script Info]
+ScriptType: v4.00+
+WrapStyle: 0
+ScaledBorderAndShadow: yes
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+";
+
+/// Header of the events, after the styles.
+const EVENTS: &str = "
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+";
+
+/// Default style of the written `ASS` files, for bottom centered subtitles.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssStyle {
+    /// Name of the font.
+    pub font: String,
+    /// Size of the font.
+    pub font_size: u32,
+    /// Width of the outline of the text.
+    pub outline: u32,
+    /// Margin between the subtitles and the bottom of the video.
+    pub margin_v: u32,
+}
+
+impl Default for AssStyle {
+    fn default() -> Self {
+        Self {
+            font: "Arial".into(),
+            font_size: 20,
+            outline: 2,
+            margin_v: 10,
+        }
+    }
+}
+
+/// Write `subtitles` in the `ASS` format, with `style` as the default style.
+///
+/// # Errors
+///
+/// Will return an error if writing fails.
+pub fn write_ass(
+    writer: &mut impl Write,
+    subtitles: &[(TimeSpan, String)],
+    style: &AssStyle,
+) -> io::Result<()> {
+    writer.write_all(SCRIPT_INFO.as_bytes())?;
+    // The fields are separated by commas, which can't be escaped in a font name.
+    writeln!(
+        writer,
+        "Style: Default,{},{},&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,{},1,2,10,10,{},1",
+        style.font.replace(',', " "),
+        style.font_size,
+        style.outline,
+        style.margin_v
+    )?;
+    writer.write_all(EVENTS.as_bytes())?;
+    for (time, text) in subtitles {
+        // The braces of the text would start override blocks.
+        let text = text
+            .trim_end()
+            .replace('{', "\\{")
+            .replace('}', "\\}")
+            .replace('\n', "\\N")
+            .replace("<i>", "{\\i1}")
+            .replace("</i>", "{\\i0}");
+        writeln!(
+            writer,
+            "Dialogue: 0,{},{},Default,,0,0,0,,{text}",
+            format_time(time.start),
+            format_time(time.end)
+        )?;
+    }
+    writer.flush()
+}
+
+/// Format `time` as `H:MM:SS.cc`, the `ASS` time format in centiseconds.
+fn format_time(time: TimePoint) -> String {
+    let centisecs = time.msecs().max(0) / 10;
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        centisecs / 360_000,
+        centisecs / 6000 % 60,
+        centisecs / 100 % 60,
+        centisecs % 100
+    )
+}
@@ -0,0 +1,133 @@
+use std::{
+    mem,
+    path::Path,
+    sync::mpsc::{self, SyncSender},
+    thread,
+};
+
+use image::GrayImage;
+use log::info;
+use subtile::time::TimeSpan;
+
+use crate::{
+    detected_italics, filter_images, prepare_images, qa::QaReport, sanity,
+    stream::SubtitlePipeline, timing, Config, Error, OcrSession, OcrSettings, Recognized,
+};
+
+/// Bytes in a mebibyte.
+const BYTES_PER_MIB: u64 = 1024 * 1024;
+
+/// Times and images prepared for `OCR` of consecutive subtitles.
+type Batch = (Vec<TimeSpan>, Vec<GrayImage>);
+
+/// Decode, filter and recognize the subtitles of `input` in batches, holding at most
+/// `max_memory` MiB of images at once.
+///
+/// The next batch is decoded while the `OCR` of the current one runs, so each batch holds
+/// half of `max_memory`. The Tesseract instances are kept from one batch to the next,
+/// and the first subtitle of a batch is compared to the last frame of the previous one
+/// for the duplicates.
+///
+/// The images are converted for `OCR` in the decoding thread, one at a time as they are decoded,
+/// and the positions of `PGS` subtitles are read alongside them. A conversion stage of its own
+/// would hold each batch twice, decoded and converted, while the `OCR` takes most of the time.
+/// With a single producer and consumer, the bounded channel of the standard library
+/// is enough, without a crossbeam dependency.
+///
+/// # Errors
+///
+/// Will forward the errors of the decoding and of the `OCR`.
+#[profiling::function]
+pub fn recognize_in_batches(
+    input: &Path,
+    opt: &Config,
+    max_memory: u64,
+    ocr_settings: &OcrSettings,
+    qa: &mut QaReport,
+) -> Result<Recognized, Error> {
+    let max_bytes =
+        usize::try_from(max_memory.saturating_mul(BYTES_PER_MIB) / 2).unwrap_or(usize::MAX);
+    let ocr_opt = ocr_settings.ocr_opt(opt, None);
+    let mut session = OcrSession::new(&ocr_opt);
+    let mut recognized = Recognized::default();
+    let mut last_frame = None;
+    let mut decoded = 0;
+    let mut batch_count = 0;
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::sync_channel(0);
+        scope.spawn(move || {
+            if let Err(err) = decode_batches(input, opt, max_bytes, &sender) {
+                let _ = sender.send(Err(err));
+            }
+        });
+        // Leaving the loop on an error drops the receiver, which stops the decoding.
+        for batch in receiver {
+            let (times, images) = batch?;
+            let first_index = decoded;
+            decoded += times.len();
+            batch_count += 1;
+            let times = match opt.timing.offset {
+                Some(offset) => times
+                    .into_iter()
+                    .map(|time| timing::shift_span(time, offset))
+                    .collect(),
+                None => times,
+            };
+            // The images of the batches are decoded without their display region.
+            let regions = vec![None; times.len()];
+            let (times, images, regions) = filter_images(
+                times,
+                images,
+                regions,
+                opt,
+                first_index,
+                &mut last_frame,
+                qa,
+            );
+            let (times, images, regions) = prepare_images(times, images, regions, opt, qa);
+            let widths = images.iter().map(GrayImage::width).collect();
+            let italics = detected_italics(&images, opt);
+            let recognitions = session.process(images)?;
+            recognized.extend(times, widths, regions, italics, recognitions);
+        }
+        Ok::<_, Error>(())
+    })?;
+    recognized.metrics = session.metrics();
+    sanity::check_decoded_count(input, decoded);
+    info!("{decoded} subtitles decoded and recognized in {batch_count} batch(es)");
+    Ok(recognized)
+}
+
+/// Decode the subtitles of `input`, sending them to `sender` in batches of images
+/// of about `max_bytes`.
+///
+/// The decoding stops without error if the receiver is gone.
+///
+/// # Errors
+///
+/// Will return the errors of the decoding, to be sent to the receiver.
+fn decode_batches(
+    input: &Path,
+    opt: &Config,
+    max_bytes: usize,
+    sender: &SyncSender<Result<Batch, Error>>,
+) -> Result<(), Error> {
+    let mut batch: Batch = (Vec::new(), Vec::new());
+    let mut bytes = 0;
+    for subtitle in SubtitlePipeline::new(opt).with_input(input).images()? {
+        let (time, image) = subtitle?;
+        bytes += image.as_raw().len();
+        batch.0.push(time);
+        batch.1.push(image);
+        if bytes >= max_bytes {
+            bytes = 0;
+            if sender.send(Ok(mem::take(&mut batch))).is_err() {
+                return Ok(());
+            }
+        }
+    }
+    if !batch.0.is_empty() {
+        let _ = sender.send(Ok(batch));
+    }
+    Ok(())
+}
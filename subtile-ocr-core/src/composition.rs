@@ -0,0 +1,435 @@
+use std::{
+    collections::HashMap,
+    io::{self, Read},
+};
+
+use image::{imageops, GrayAlphaImage, LumaA};
+use log::debug;
+
+/// Size of the header of the `PGS` segments.
+const PGS_HEADER_SIZE: usize = 13;
+
+/// Segment types of the `PGS` streams.
+const PALETTE_DEFINITION: u8 = 0x14;
+const OBJECT_DEFINITION: u8 = 0x15;
+const PRESENTATION_COMPOSITION: u8 = 0x16;
+const END_OF_DISPLAY_SET: u8 = 0x80;
+
+/// Flags of the fragments of an object definition, and of the cropped composition objects.
+const FIRST_FRAGMENT: u8 = 0x80;
+const CROPPED_OBJECT: u8 = 0x40;
+
+/// Composition of a `PGS` display set showing at least one object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgsComposition {
+    /// Display size of the stream.
+    pub display: (u32, u32),
+    /// Position on the display of the first object, or of the composited image.
+    pub origin: (u32, u32),
+    /// Image of all the objects at their positions, if the display set shows several objects.
+    pub composited: Option<GrayAlphaImage>,
+}
+
+/// Reader of the compositions of a `PGS` stream showing at least one object, one segment at a time.
+///
+/// The compositions are in the order of the decoded subtitles. The images of `subtile` only show
+/// one object, so the display sets of several objects (e.g. two text regions) are composited here,
+/// from the objects and palettes defined in the stream. The reading ends at the first incomplete
+/// or unknown segment, like the end of a truncated stream.
+pub struct PgsCompositions<R> {
+    reader: R,
+    ended: bool,
+    /// Composition of the display set being read.
+    pending: Option<Composition>,
+    /// Colors of the palettes, by palette id.
+    palettes: HashMap<u8, Box<[LumaA<u8>; 256]>>,
+    /// Objects by object id, kept for the later display sets showing them again.
+    objects: HashMap<u16, Object>,
+}
+
+/// Composition segment of a display set.
+struct Composition {
+    display: (u32, u32),
+    palette_id: u8,
+    objects: Vec<CompositionObject>,
+}
+
+/// Object shown by a composition, at `x`, `y` of the display.
+struct CompositionObject {
+    id: u16,
+    x: u32,
+    y: u32,
+    /// Part of the object shown, as `x`, `y`, `width`, `height` in the object.
+    crop: Option<(u32, u32, u32, u32)>,
+}
+
+/// Object of a `PGS` stream, with its run-length encoded pixels.
+struct Object {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+impl<R: Read> PgsCompositions<R> {
+    /// Read the compositions of the `PGS` stream of `reader`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            ended: false,
+            pending: None,
+            palettes: HashMap::new(),
+            objects: HashMap::new(),
+        }
+    }
+
+    /// Read the next segment, with its type and payload.
+    fn read_segment(&mut self) -> io::Result<Option<(u8, Vec<u8>)>> {
+        let mut header = [0; PGS_HEADER_SIZE];
+        match self.reader.read_exact(&mut header) {
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            result => result?,
+        }
+        if &header[..2] != b"PG" {
+            return Ok(None);
+        }
+        let kind = header[10];
+        let size = u64::from(u16::from_be_bytes([header[11], header[12]]));
+        let mut payload = Vec::new();
+        let read = (&mut self.reader).take(size).read_to_end(&mut payload)?;
+        Ok((read as u64 == size).then_some((kind, payload)))
+    }
+
+    /// Get the composition of the display set read, if it shows at least one object.
+    fn finish_display_set(&mut self) -> Option<PgsComposition> {
+        let composition = self.pending.take()?;
+        let first = composition.objects.first()?;
+        let (origin, composited) = match composition.objects[..] {
+            [_] => ((first.x, first.y), None),
+            _ => match self.composite(&composition) {
+                Some((origin, image)) => (origin, Some(image)),
+                None => {
+                    debug!("A PGS display set shows undefined objects, only the first one is kept");
+                    ((first.x, first.y), None)
+                }
+            },
+        };
+        Some(PgsComposition {
+            display: composition.display,
+            origin,
+            composited,
+        })
+    }
+
+    /// Draw the objects of `composition` in one image, with the display position of the image.
+    ///
+    /// Returns `None` if an object or the palette isn't defined.
+    fn composite(&self, composition: &Composition) -> Option<((u32, u32), GrayAlphaImage)> {
+        let palette = self.palettes.get(&composition.palette_id)?;
+        let images = composition
+            .objects
+            .iter()
+            .map(|shown| {
+                let image = decode_object(self.objects.get(&shown.id)?, palette);
+                let image = match shown.crop {
+                    Some((x, y, width, height)) => {
+                        imageops::crop_imm(&image, x, y, width, height).to_image()
+                    }
+                    None => image,
+                };
+                Some(((shown.x, shown.y), image))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let left = images.iter().map(|((x, _), _)| *x).min()?;
+        let top = images.iter().map(|((_, y), _)| *y).min()?;
+        let right = images
+            .iter()
+            .map(|((x, _), image)| x + image.width())
+            .max()?;
+        let bottom = images
+            .iter()
+            .map(|((_, y), image)| y + image.height())
+            .max()?;
+        let mut composited = GrayAlphaImage::new(right - left, bottom - top);
+        for ((x, y), image) in &images {
+            imageops::replace(
+                &mut composited,
+                image,
+                i64::from(x - left),
+                i64::from(y - top),
+            );
+        }
+        Some(((left, top), composited))
+    }
+
+    /// Handle the segment of `kind` with `payload`.
+    fn handle_segment(&mut self, kind: u8, payload: &[u8]) {
+        match kind {
+            PRESENTATION_COMPOSITION => self.pending = parse_composition(payload),
+            PALETTE_DEFINITION => {
+                if let Some(&id) = payload.first() {
+                    let palette = self
+                        .palettes
+                        .entry(id)
+                        .or_insert_with(|| Box::new([LumaA([0, 0]); 256]));
+                    // The entries follow the palette id and version: id, Y, Cr, Cb and alpha.
+                    for entry in payload.get(2..).unwrap_or_default().chunks_exact(5) {
+                        palette[usize::from(entry[0])] = LumaA([entry[1], entry[4]]);
+                    }
+                }
+            }
+            OBJECT_DEFINITION => self.add_object_fragment(payload),
+            _ => {}
+        }
+    }
+
+    /// Add the fragment `payload` of an object definition to its object.
+    fn add_object_fragment(&mut self, payload: &[u8]) {
+        let Some(header) = payload.get(..4) else {
+            return;
+        };
+        let id = u16::from_be_bytes([header[0], header[1]]);
+        if header[3] & FIRST_FRAGMENT != 0 {
+            // The first fragment has the data length on 3 bytes, then the object size.
+            let Some(size) = payload.get(7..11) else {
+                return;
+            };
+            let object = Object {
+                width: u32::from(u16::from_be_bytes([size[0], size[1]])),
+                height: u32::from(u16::from_be_bytes([size[2], size[3]])),
+                data: payload[11..].to_vec(),
+            };
+            self.objects.insert(id, object);
+        } else if let Some(object) = self.objects.get_mut(&id) {
+            object.data.extend_from_slice(&payload[4..]);
+        }
+    }
+}
+
+impl<R: Read> Iterator for PgsCompositions<R> {
+    type Item = io::Result<PgsComposition>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.ended {
+            let (kind, payload) = match self.read_segment() {
+                Ok(Some(segment)) => segment,
+                Ok(None) => break,
+                Err(err) => {
+                    self.ended = true;
+                    return Some(Err(err));
+                }
+            };
+            // A display set ends with its end segment, or at the composition of the next one.
+            let composition = match kind {
+                END_OF_DISPLAY_SET | PRESENTATION_COMPOSITION => self.finish_display_set(),
+                _ => None,
+            };
+            self.handle_segment(kind, &payload);
+            if let Some(composition) = composition {
+                return Some(Ok(composition));
+            }
+        }
+        self.ended = true;
+        self.finish_display_set().map(Ok)
+    }
+}
+
+/// Parse the composition segment `payload`.
+fn parse_composition(payload: &[u8]) -> Option<Composition> {
+    let word = |pos: usize| {
+        payload
+            .get(pos..pos + 2)
+            .map(|bytes| u32::from(u16::from_be_bytes([bytes[0], bytes[1]])))
+    };
+    let display = (word(0)?, word(2)?);
+    let palette_id = *payload.get(9)?;
+    let count = *payload.get(10)?;
+    // The composition objects follow the 11 bytes of the composition header.
+    let mut pos = 11;
+    let mut objects = Vec::with_capacity(usize::from(count));
+    for _ in 0..count {
+        let id = u16::try_from(word(pos)?).ok()?;
+        let cropped = payload.get(pos + 3)? & CROPPED_OBJECT != 0;
+        let (x, y) = (word(pos + 4)?, word(pos + 6)?);
+        let crop = if cropped {
+            Some((
+                word(pos + 8)?,
+                word(pos + 10)?,
+                word(pos + 12)?,
+                word(pos + 14)?,
+            ))
+        } else {
+            None
+        };
+        pos += if cropped { 16 } else { 8 };
+        objects.push(CompositionObject { id, x, y, crop });
+    }
+    Some(Composition {
+        display,
+        palette_id,
+        objects,
+    })
+}
+
+/// Decode the run-length encoded pixels of `object` with the colors of `palette`.
+///
+/// The lines shorter than the object are padded with transparent pixels.
+fn decode_object(object: &Object, palette: &[LumaA<u8>; 256]) -> GrayAlphaImage {
+    let mut image = GrayAlphaImage::new(object.width, object.height);
+    let (mut x, mut y) = (0, 0);
+    let mut bytes = object.data.iter().copied();
+    let mut put = |x: &mut u32, y: u32, count: u32, color: u8| {
+        for _ in 0..count {
+            if *x < object.width && y < object.height {
+                image.put_pixel(*x, y, palette[usize::from(color)]);
+            }
+            *x += 1;
+        }
+    };
+    while let Some(byte) = bytes.next() {
+        if byte != 0 {
+            put(&mut x, y, 1, byte);
+            continue;
+        }
+        let Some(flags) = bytes.next() else {
+            break;
+        };
+        if flags == 0 {
+            (x, y) = (0, y + 1);
+            continue;
+        }
+        let mut count = u32::from(flags & 0x3F);
+        if flags & 0x40 != 0 {
+            count = (count << 8) | u32::from(bytes.next().unwrap_or_default());
+        }
+        let color = if flags & 0x80 != 0 {
+            bytes.next().unwrap_or_default()
+        } else {
+            0
+        };
+        put(&mut x, y, count, color);
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use image::LumaA;
+
+    use super::{PgsComposition, PgsCompositions, END_OF_DISPLAY_SET, PRESENTATION_COMPOSITION};
+
+    /// Stream of a display set with a line at the top and one at the bottom of the display,
+    /// then of a display set with one line, each followed by an empty display set.
+    const TWO_OBJECTS: &[u8] = include_bytes!("../tests/fixtures/two_objects.sup");
+
+    /// Build a `PGS` segment of `kind` with `payload`.
+    fn segment(kind: u8, payload: &[u8]) -> Vec<u8> {
+        let mut segment = b"PG".to_vec();
+        segment.extend_from_slice(&[0; 8]);
+        segment.push(kind);
+        segment.extend_from_slice(&u16::try_from(payload.len()).unwrap().to_be_bytes());
+        segment.extend_from_slice(payload);
+        segment
+    }
+
+    /// Build a composition of a 1920x1080 display with objects at `positions`.
+    fn composition(positions: &[(u16, u16)]) -> Vec<u8> {
+        let mut payload = [1920_u16.to_be_bytes(), 1080_u16.to_be_bytes()].concat();
+        payload.extend_from_slice(&[0x10, 0, 1, 0x80, 0, 0]);
+        payload.push(u8::try_from(positions.len()).unwrap());
+        for (id, (x, y)) in (0_u16..).zip(positions) {
+            payload.extend_from_slice(&id.to_be_bytes());
+            payload.extend_from_slice(&[0, 0]);
+            payload.extend_from_slice(&x.to_be_bytes());
+            payload.extend_from_slice(&y.to_be_bytes());
+        }
+        segment(PRESENTATION_COMPOSITION, &payload)
+    }
+
+    fn read(stream: &[u8]) -> Vec<PgsComposition> {
+        PgsCompositions::new(stream)
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn read_positions() {
+        let stream = [
+            composition(&[(100, 900)]),
+            segment(0x15, &[0; 300]),
+            segment(END_OF_DISPLAY_SET, &[]),
+            composition(&[]),
+            composition(&[(200, 50)]),
+        ]
+        .concat();
+        let positions = read(&stream)
+            .into_iter()
+            .map(|composition| (composition.display, composition.origin))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            positions,
+            [((1920, 1080), (100, 900)), ((1920, 1080), (200, 50))]
+        );
+    }
+
+    #[test]
+    fn composite_two_objects() {
+        let compositions = read(TWO_OBJECTS);
+        assert_eq!(compositions.len(), 2);
+
+        let two = &compositions[0];
+        assert_eq!(two.display, (1920, 1080));
+        assert_eq!(two.origin, (100, 50));
+        let image = two.composited.as_ref().unwrap();
+        assert_eq!(image.dimensions(), (40, 930));
+        // The top object is 20x10 of ink, the bottom one 40x30 with a transparent left half.
+        let ink = LumaA([235, 255]);
+        let transparent = LumaA([0, 0]);
+        assert_eq!(*image.get_pixel(0, 0), ink);
+        assert_eq!(*image.get_pixel(19, 9), ink);
+        assert_eq!(*image.get_pixel(20, 0), transparent);
+        assert_eq!(*image.get_pixel(0, 10), transparent);
+        assert_eq!(*image.get_pixel(19, 929), transparent);
+        assert_eq!(*image.get_pixel(20, 900), ink);
+        assert_eq!(*image.get_pixel(39, 929), ink);
+
+        let one = &compositions[1];
+        assert_eq!(one.origin, (800, 950));
+        assert_eq!(one.composited, None);
+    }
+
+    #[test]
+    fn undefined_objects() {
+        let compositions = read(&composition(&[(10, 20), (30, 40)]));
+        assert_eq!(compositions.len(), 1);
+        assert_eq!(compositions[0].origin, (10, 20));
+        assert_eq!(compositions[0].composited, None);
+    }
+
+    #[test]
+    fn truncated_stream() {
+        let mut stream = [composition(&[(100, 900)]), composition(&[(200, 50)])].concat();
+        stream.truncate(stream.len() - 1);
+        let positions = read(&stream)
+            .into_iter()
+            .map(|composition| composition.origin)
+            .collect::<Vec<_>>();
+        assert_eq!(positions, [(100, 900)]);
+    }
+
+    #[test]
+    fn read_error() {
+        struct Failing;
+        impl io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("broken"))
+            }
+        }
+        let mut compositions = PgsCompositions::new(Failing);
+        assert!(compositions
+            .next()
+            .is_some_and(|composition| composition.is_err()));
+        assert!(compositions.next().is_none());
+    }
+}
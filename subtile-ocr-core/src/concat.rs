@@ -0,0 +1,56 @@
+use image::GrayImage;
+use log::{info, warn};
+use subtile::time::TimeSpan;
+
+use crate::{cue::Region, decode_with_regions, sanity, timing, Config, Decoded, Error};
+
+/// Append the subtitles of the concatenated inputs of `opt` to the `times`, `images`
+/// and display `regions` of the main input, in one timeline.
+///
+/// Each part starts at its start time, or at the end of the last subtitle of the
+/// previous parts if it has none. The parts starting before the end of the previous
+/// ones are reported, their overlapping subtitles are handled by the overlap policy.
+///
+/// # Errors
+///
+/// Will forward the errors of the decoding of the appended inputs.
+#[profiling::function]
+pub fn append_inputs(
+    mut times: Vec<TimeSpan>,
+    mut images: Vec<GrayImage>,
+    mut regions: Vec<Option<Region>>,
+    opt: &Config,
+) -> Result<Decoded, Error> {
+    for (path, start) in &opt.input.concat {
+        let (part_times, part_images, part_regions) = decode_with_regions(path, opt)?;
+        sanity::check_decoded_count(path, part_times.len());
+        let end = times.iter().map(|time| time.end.msecs()).max();
+        let offset = start.unwrap_or_else(|| end.unwrap_or_default());
+        let part_times = part_times
+            .into_iter()
+            .map(|time| timing::shift_span(time, offset))
+            .collect::<Vec<_>>();
+        let overlapping = end.map_or(0, |end| {
+            part_times
+                .iter()
+                .filter(|time| time.start.msecs() < end)
+                .count()
+        });
+        if overlapping > 0 {
+            warn!(
+                "{overlapping} subtitle(s) of {} start before the end of the previous parts, \
+                 with an offset of {offset} ms",
+                path.display()
+            );
+        }
+        info!(
+            "{} subtitle(s) of {} appended with an offset of {offset} ms",
+            part_times.len(),
+            path.display()
+        );
+        times.extend(part_times);
+        images.extend(part_images);
+        regions.extend(part_regions);
+    }
+    Ok((times, images, regions))
+}
@@ -0,0 +1,1654 @@
+use image::Rgb;
+use leptess::Variable;
+use std::{num::NonZeroUsize, path::PathBuf, str::FromStr, time::Duration};
+use subtile::vobsub;
+use thiserror::Error;
+
+use crate::{
+    ass::AssStyle,
+    crop::Crop,
+    cue_filter::CueFilter,
+    ocr_output::OcrOutput,
+    preprocessor::ConvertStrategy,
+    qa::LowConfidencePolicy,
+    sentences::SentencePolicy,
+    stdin::InputFormat,
+    text::{DialogueDashes, Normalization},
+    timing::{Anchor, InvalidTimePolicy, OverlapPolicy},
+    validate::ValidationPolicy,
+    OutputFormat,
+};
+
+/// Error of the parsing of a configuration value given as text.
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("No `=` in key-value pair {value}")]
+    ParseKeyValuePair { value: String },
+
+    #[error("Invalid tesseract variable name: {value}")]
+    TesseractVariableName { value: String },
+
+    #[error("Invalid palette, expected 16 hexadecimal RGB colors separated by commas: {value}")]
+    Palette { value: String },
+
+    #[error("Invalid anchor, expected `src=HH:MM:SS.mmm,dst=HH:MM:SS.mmm`: {value}")]
+    Anchor { value: String },
+
+    #[error("Invalid crop region, expected `x,y,width,height` with a non-zero size: {value}")]
+    Crop { value: String },
+}
+
+/// Configuration of a run, with the settings of each step of the pipeline in a group.
+///
+/// Built from [`Config::default`] with the groups built from their own `default` and
+/// `with_*` setters, e.g.
+/// `Config::default().with_input(InputConfig::default().with_path("movie.sup"))`.
+/// New settings can be added in minor versions, with a default keeping the previous behavior.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct Config {
+    pub(crate) input: InputConfig,
+    pub(crate) image: ImageConfig,
+    pub(crate) ocr: OcrConfig,
+    pub(crate) timing: TimingConfig,
+    pub(crate) text: TextConfig,
+    pub(crate) output: OutputConfig,
+}
+
+impl Config {
+    /// Set the input of the run.
+    #[must_use]
+    pub fn with_input(mut self, input: InputConfig) -> Self {
+        self.input = input;
+        self
+    }
+
+    /// Set the conversion of the subtitle images for `OCR`.
+    #[must_use]
+    pub fn with_image(mut self, image: ImageConfig) -> Self {
+        self.image = image;
+        self
+    }
+
+    /// Set the text recognition.
+    #[must_use]
+    pub fn with_ocr(mut self, ocr: OcrConfig) -> Self {
+        self.ocr = ocr;
+        self
+    }
+
+    /// Set the correction of the times.
+    #[must_use]
+    pub fn with_timing(mut self, timing: TimingConfig) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Set the post-processing of the text.
+    #[must_use]
+    pub fn with_text(mut self, text: TextConfig) -> Self {
+        self.text = text;
+        self
+    }
+
+    /// Set the written files.
+    #[must_use]
+    pub fn with_output(mut self, output: OutputConfig) -> Self {
+        self.output = output;
+        self
+    }
+}
+
+/// Input of a run: the subtitle file and how to decode it.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct InputConfig {
+    pub(crate) path: Option<PathBuf>,
+    pub(crate) track: Option<usize>,
+    pub(crate) concat: Vec<(PathBuf, Option<i64>)>,
+    pub(crate) stdin_format: Option<InputFormat>,
+    pub(crate) stdin_idx: Option<PathBuf>,
+    pub(crate) palette: Option<vobsub::Palette>,
+    pub(crate) recover: bool,
+    pub(crate) max_image_size: u32,
+    pub(crate) parse_stall_timeout: Option<Duration>,
+    pub(crate) decode_cache: Option<PathBuf>,
+    pub(crate) max_memory: Option<u64>,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            track: None,
+            concat: Vec::new(),
+            stdin_format: None,
+            stdin_idx: None,
+            palette: None,
+            recover: false,
+            max_image_size: 4096,
+            parse_stall_timeout: Some(Duration::from_secs(30)),
+            decode_cache: None,
+            max_memory: None,
+        }
+    }
+}
+
+impl InputConfig {
+    /// Path of the file to process, or `-` to read the subtitles from stdin.
+    #[must_use]
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Subtitle track of a Matroska input, with its id as listed by `mkvmerge --identify`.
+    ///
+    /// The first `VobSub` or `PGS` track is used if not set.
+    #[must_use]
+    pub fn with_track(mut self, track: Option<usize>) -> Self {
+        self.track = track;
+        self
+    }
+
+    /// Other inputs appended to the timeline of the main input, e.g. the second part of a disc,
+    /// with the start time in milliseconds of each one.
+    ///
+    /// An input without start time starts at the end of the last subtitle of the previous ones.
+    #[must_use]
+    pub fn with_concat(mut self, concat: Vec<(PathBuf, Option<i64>)>) -> Self {
+        self.concat = concat;
+        self
+    }
+
+    /// Format of the subtitles read from stdin, with `-` as input path.
+    #[must_use]
+    pub fn with_stdin_format(mut self, stdin_format: Option<InputFormat>) -> Self {
+        self.stdin_format = stdin_format;
+        self
+    }
+
+    /// Idx file of the `VobSub` stream read from stdin.
+    #[must_use]
+    pub fn with_stdin_idx(mut self, stdin_idx: Option<PathBuf>) -> Self {
+        self.stdin_idx = stdin_idx;
+        self
+    }
+
+    /// Palette of the `VobSub` subtitles, instead of the one of the idx file.
+    #[must_use]
+    pub fn with_palette(mut self, palette: Option<vobsub::Palette>) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Salvage the complete subtitles of a truncated input, and report where its data ends.
+    #[must_use]
+    pub fn with_recover(mut self, recover: bool) -> Self {
+        self.recover = recover;
+        self
+    }
+
+    /// Maximum width and height in pixels of the decoded subtitle images, larger ones are skipped.
+    ///
+    /// `4096` by default.
+    #[must_use]
+    pub fn with_max_image_size(mut self, max_image_size: u32) -> Self {
+        self.max_image_size = max_image_size;
+        self
+    }
+
+    /// Maximum time without progress of the parsing of `PGS` or `VobSub` subtitles, `None` to wait forever.
+    ///
+    /// 30 seconds by default.
+    #[must_use]
+    pub fn with_parse_stall_timeout(mut self, parse_stall_timeout: Option<Duration>) -> Self {
+        self.parse_stall_timeout = parse_stall_timeout;
+        self
+    }
+
+    /// Cache the decoded images in this directory, to skip the decoding in the next runs.
+    #[must_use]
+    pub fn with_decode_cache(mut self, decode_cache: Option<PathBuf>) -> Self {
+        self.decode_cache = decode_cache;
+        self
+    }
+
+    /// Maximum memory of the subtitle images held at once, in MiB, to decode and recognize them in batches.
+    #[must_use]
+    pub fn with_max_memory(mut self, max_memory: Option<u64>) -> Self {
+        self.max_memory = max_memory;
+        self
+    }
+}
+
+/// Conversion of the decoded subtitle images for `OCR`, and their selection.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ImageConfig {
+    pub(crate) threshold: f32,
+    pub(crate) alpha_threshold: u8,
+    pub(crate) luma_threshold: u8,
+    pub(crate) convert: ConvertStrategy,
+    pub(crate) ink_tolerance: f32,
+    pub(crate) border: u32,
+    pub(crate) target_line_height: Option<u32>,
+    pub(crate) crop: Option<Crop>,
+    pub(crate) fill_hollow: bool,
+    pub(crate) split_groups: bool,
+    pub(crate) min_ink_pixels: usize,
+    pub(crate) duplicate_max_duration: u32,
+    pub(crate) filter: Option<CueFilter>,
+}
+
+impl Default for ImageConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 0.6,
+            alpha_threshold: 100,
+            luma_threshold: 100,
+            convert: ConvertStrategy::default(),
+            ink_tolerance: 0.0,
+            border: 10,
+            target_line_height: None,
+            crop: None,
+            fill_hollow: false,
+            split_groups: false,
+            min_ink_pixels: 20,
+            duplicate_max_duration: 100,
+            filter: None,
+        }
+    }
+}
+
+impl ImageConfig {
+    /// Threshold for the binarization of the `VobSub` images, between 0.0 and 1.0.
+    ///
+    /// `0.6` by default.
+    #[must_use]
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Minimum alpha of the text pixels of the `PGS` and `DVB` subtitle images.
+    ///
+    /// `100` by default.
+    #[must_use]
+    pub fn with_alpha_threshold(mut self, alpha_threshold: u8) -> Self {
+        self.alpha_threshold = alpha_threshold;
+        self
+    }
+
+    /// Minimum luminance of the text pixels of the `PGS` and `DVB` subtitle images.
+    ///
+    /// `100` by default.
+    #[must_use]
+    pub fn with_luma_threshold(mut self, luma_threshold: u8) -> Self {
+        self.luma_threshold = luma_threshold;
+        self
+    }
+
+    /// Strategy used to convert the `VobSub` images.
+    #[must_use]
+    pub fn with_convert(mut self, convert: ConvertStrategy) -> Self {
+        self.convert = convert;
+        self
+    }
+
+    /// Luminance tolerance around the threshold for the anti-aliased edges of `VobSub` glyphs.
+    ///
+    /// `0.0`, disabled by default.
+    #[must_use]
+    pub fn with_ink_tolerance(mut self, ink_tolerance: f32) -> Self {
+        self.ink_tolerance = ink_tolerance;
+        self
+    }
+
+    /// Border in pixels surrounding each subtitle image.
+    ///
+    /// `10` by default.
+    #[must_use]
+    pub fn with_border(mut self, border: u32) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Height in pixels of the text lines, to which bigger subtitles are downscaled.
+    #[must_use]
+    pub fn with_target_line_height(mut self, target_line_height: Option<u32>) -> Self {
+        self.target_line_height = target_line_height;
+        self
+    }
+
+    /// Only recognize the text in this region of the display.
+    #[must_use]
+    pub fn with_crop(mut self, crop: Option<Crop>) -> Self {
+        self.crop = crop;
+        self
+    }
+
+    /// Fill the glyphs of subtitles rendered as outlines only.
+    #[must_use]
+    pub fn with_fill_hollow(mut self, fill_hollow: bool) -> Self {
+        self.fill_hollow = fill_hollow;
+        self
+    }
+
+    /// Split the subtitle images with groups of lines far apart into separate subtitles.
+    #[must_use]
+    pub fn with_split_groups(mut self, split_groups: bool) -> Self {
+        self.split_groups = split_groups;
+        self
+    }
+
+    /// Minimum number of text pixels of a subtitle image to be recognized, `0` to recognize all of them.
+    ///
+    /// `20` by default.
+    #[must_use]
+    pub fn with_min_ink_pixels(mut self, min_ink_pixels: usize) -> Self {
+        self.min_ink_pixels = min_ink_pixels;
+        self
+    }
+
+    /// Maximum duration in milliseconds of a subtitle repeating the previous image to be removed,
+    /// `0` to keep them.
+    ///
+    /// `100` by default.
+    #[must_use]
+    pub fn with_duplicate_max_duration(mut self, duplicate_max_duration: u32) -> Self {
+        self.duplicate_max_duration = duplicate_max_duration;
+        self
+    }
+
+    /// Only keep the subtitles matching this expression.
+    #[must_use]
+    pub fn with_filter(mut self, filter: Option<CueFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
+}
+
+/// Text recognition of the subtitle images by Tesseract.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct OcrConfig {
+    pub(crate) tessdata_dir: Option<String>,
+    pub(crate) lang: String,
+    pub(crate) variables: Vec<TesseractVariable>,
+    pub(crate) dpi: i32,
+    pub(crate) line_mode: bool,
+    pub(crate) single_line_psm: bool,
+    pub(crate) invert_retry: bool,
+    pub(crate) column_separator: Option<String>,
+    pub(crate) max_instances: Option<usize>,
+    pub(crate) tesseract_threads: Option<u32>,
+    pub(crate) thread_limit_env: bool,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) panic_isolation: bool,
+    pub(crate) scrub_control_chars: bool,
+    pub(crate) min_confidence: Option<u32>,
+    pub(crate) low_confidence: LowConfidencePolicy,
+    pub(crate) dictionary: Option<PathBuf>,
+}
+
+impl Default for OcrConfig {
+    fn default() -> Self {
+        Self {
+            tessdata_dir: None,
+            lang: "eng".to_owned(),
+            variables: Vec::new(),
+            dpi: 150,
+            line_mode: false,
+            single_line_psm: true,
+            invert_retry: true,
+            column_separator: None,
+            max_instances: None,
+            tesseract_threads: None,
+            thread_limit_env: false,
+            timeout: None,
+            panic_isolation: true,
+            scrub_control_chars: true,
+            min_confidence: None,
+            low_confidence: LowConfidencePolicy::default(),
+            dictionary: None,
+        }
+    }
+}
+
+impl OcrConfig {
+    /// Path to Tesseract's tessdata directory.
+    #[must_use]
+    pub fn with_tessdata_dir(mut self, tessdata_dir: Option<String>) -> Self {
+        self.tessdata_dir = tessdata_dir;
+        self
+    }
+
+    /// The Tesseract language(s) to use.
+    ///
+    /// `eng` by default.
+    #[must_use]
+    pub fn with_lang(mut self, lang: impl Into<String>) -> Self {
+        self.lang = lang.into();
+        self
+    }
+
+    /// Values of Tesseract config variables.
+    #[must_use]
+    pub fn with_variables(mut self, variables: Vec<TesseractVariable>) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// DPI of subtitle images.
+    ///
+    /// `150` by default.
+    #[must_use]
+    pub fn with_dpi(mut self, dpi: i32) -> Self {
+        self.dpi = dpi;
+        self
+    }
+
+    /// Send each text line of a subtitle image separately to Tesseract.
+    #[must_use]
+    pub fn with_line_mode(mut self, line_mode: bool) -> Self {
+        self.line_mode = line_mode;
+        self
+    }
+
+    /// Recognize the subtitles with a single text line with the page segmentation
+    /// mode for a single line (7), unless another mode is set in the variables.
+    ///
+    /// On by default.
+    #[must_use]
+    pub fn with_single_line_psm(mut self, single_line_psm: bool) -> Self {
+        self.single_line_psm = single_line_psm;
+        self
+    }
+
+    /// Retry the recognition of the images inverted when nothing was recognized.
+    ///
+    /// On by default.
+    #[must_use]
+    pub fn with_invert_retry(mut self, invert_retry: bool) -> Self {
+        self.invert_retry = invert_retry;
+        self
+    }
+
+    /// Separator written between the columns of a line, like `\t` or ` | `.
+    #[must_use]
+    pub fn with_column_separator(mut self, column_separator: Option<String>) -> Self {
+        self.column_separator = column_separator;
+        self
+    }
+
+    /// Maximum number of Tesseract instances, one per thread by default.
+    #[must_use]
+    pub fn with_max_instances(mut self, max_instances: Option<usize>) -> Self {
+        self.max_instances = max_instances;
+        self
+    }
+
+    /// Number of threads each Tesseract instance can use internally.
+    #[must_use]
+    pub fn with_tesseract_threads(mut self, tesseract_threads: Option<u32>) -> Self {
+        self.tesseract_threads = tesseract_threads;
+        self
+    }
+
+    /// Set the `OMP_THREAD_LIMIT` environment variable of the process to the number of threads
+    /// of each Tesseract instance.
+    ///
+    /// Off, as the environment is shared by the whole process by default.
+    #[must_use]
+    pub fn with_thread_limit_env(mut self, thread_limit_env: bool) -> Self {
+        self.thread_limit_env = thread_limit_env;
+        self
+    }
+
+    /// Maximum time of the recognition of a subtitle image.
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Only skip the subtitle if Tesseract panics on it, instead of aborting the process.
+    ///
+    /// On by default.
+    #[must_use]
+    pub fn with_panic_isolation(mut self, panic_isolation: bool) -> Self {
+        self.panic_isolation = panic_isolation;
+        self
+    }
+
+    /// Remove the control characters of the text, like the form feed ending each page,
+    /// except the line feeds and tabs.
+    ///
+    /// On by default.
+    #[must_use]
+    pub fn with_scrub_control_chars(mut self, scrub_control_chars: bool) -> Self {
+        self.scrub_control_chars = scrub_control_chars;
+        self
+    }
+
+    /// Flag the subtitles with a mean word confidence below this percentage for checking.
+    #[must_use]
+    pub fn with_min_confidence(mut self, min_confidence: Option<u32>) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// What to do with the subtitles flagged by [`OcrConfig::with_min_confidence`].
+    #[must_use]
+    pub fn with_low_confidence(mut self, low_confidence: LowConfidencePolicy) -> Self {
+        self.low_confidence = low_confidence;
+        self
+    }
+
+    /// Words file, one per line, extending the dictionary of the [`OcrConfig::with_min_confidence`] check.
+    #[must_use]
+    pub fn with_dictionary(mut self, dictionary: Option<PathBuf>) -> Self {
+        self.dictionary = dictionary;
+        self
+    }
+}
+
+/// Correction of the times of the subtitles.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct TimingConfig {
+    pub(crate) offset: Option<i64>,
+    pub(crate) control_timing: bool,
+    pub(crate) anchors: Vec<Anchor>,
+    pub(crate) invalid_times: InvalidTimePolicy,
+    pub(crate) min_duration: u32,
+    pub(crate) overlap: OverlapPolicy,
+    pub(crate) keep_order: bool,
+    pub(crate) max_cps: Option<u32>,
+    pub(crate) extend_fast_cues: bool,
+}
+
+impl Default for TimingConfig {
+    fn default() -> Self {
+        Self {
+            offset: None,
+            control_timing: true,
+            anchors: Vec::new(),
+            invalid_times: InvalidTimePolicy::default(),
+            min_duration: 1000,
+            overlap: OverlapPolicy::Keep,
+            keep_order: false,
+            max_cps: None,
+            extend_fast_cues: false,
+        }
+    }
+}
+
+impl TimingConfig {
+    /// Shift all subtitles by this many milliseconds, replacing the offset of an idx file.
+    #[must_use]
+    pub fn with_offset(mut self, offset: Option<i64>) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Restrict the `VobSub` subtitles to the interval where their control sequences display them,
+    /// instead of their start and stop commands only.
+    ///
+    /// On by default.
+    #[must_use]
+    pub fn with_control_timing(mut self, control_timing: bool) -> Self {
+        self.control_timing = control_timing;
+        self
+    }
+
+    /// Correct a progressive drift piecewise-linearly between these anchors, after the offset.
+    #[must_use]
+    pub fn with_anchors(mut self, anchors: Vec<Anchor>) -> Self {
+        self.anchors = anchors;
+        self
+    }
+
+    /// What to do with the subtitles ending before or when they start.
+    #[must_use]
+    pub fn with_invalid_times(mut self, invalid_times: InvalidTimePolicy) -> Self {
+        self.invalid_times = invalid_times;
+        self
+    }
+
+    /// Duration in milliseconds of the subtitles repaired with [`InvalidTimePolicy::Repair`].
+    ///
+    /// `1000` by default.
+    #[must_use]
+    pub fn with_min_duration(mut self, min_duration: u32) -> Self {
+        self.min_duration = min_duration;
+        self
+    }
+
+    /// How to write the subtitles displayed at the same time in distinct regions.
+    #[must_use]
+    pub fn with_overlap(mut self, overlap: OverlapPolicy) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// Write the subtitles in the order of the input, instead of sorting them by start time.
+    #[must_use]
+    pub fn with_keep_order(mut self, keep_order: bool) -> Self {
+        self.keep_order = keep_order;
+        self
+    }
+
+    /// Flag the subtitles read faster than this number of characters per second.
+    #[must_use]
+    pub fn with_max_cps(mut self, max_cps: Option<u32>) -> Self {
+        self.max_cps = max_cps;
+        self
+    }
+
+    /// Extend the subtitles read faster than [`TimingConfig::with_max_cps`], without getting closer
+    /// than two frames to the next one.
+    #[must_use]
+    pub fn with_extend_fast_cues(mut self, extend_fast_cues: bool) -> Self {
+        self.extend_fast_cues = extend_fast_cues;
+        self
+    }
+}
+
+/// Post-processing of the recognized text.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct TextConfig {
+    pub(crate) normalize: Normalization,
+    pub(crate) dialogue_dashes: DialogueDashes,
+    pub(crate) postprocess: bool,
+    pub(crate) replace_file: Option<PathBuf>,
+    pub(crate) corrections: Option<PathBuf>,
+    pub(crate) strip_credits: bool,
+    pub(crate) credits_patterns: Option<PathBuf>,
+    pub(crate) split_sentences: SentencePolicy,
+    pub(crate) validate: ValidationPolicy,
+    pub(crate) max_lines: Option<NonZeroUsize>,
+    pub(crate) detect_italics: bool,
+}
+
+impl Default for TextConfig {
+    fn default() -> Self {
+        Self {
+            normalize: Normalization::default(),
+            dialogue_dashes: DialogueDashes::default(),
+            postprocess: true,
+            replace_file: None,
+            corrections: None,
+            strip_credits: false,
+            credits_patterns: None,
+            split_sentences: SentencePolicy::default(),
+            validate: ValidationPolicy::default(),
+            max_lines: None,
+            detect_italics: false,
+        }
+    }
+}
+
+impl TextConfig {
+    /// Unicode normalization of the text.
+    #[must_use]
+    pub fn with_normalize(mut self, normalize: Normalization) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Convention for the dashes starting the dialogue lines.
+    #[must_use]
+    pub fn with_dialogue_dashes(mut self, dialogue_dashes: DialogueDashes) -> Self {
+        self.dialogue_dashes = dialogue_dashes;
+        self
+    }
+
+    /// Correct the usual Tesseract mistakes, like broken ellipses or `l` instead of `I`.
+    ///
+    /// On by default.
+    #[must_use]
+    pub fn with_postprocess(mut self, postprocess: bool) -> Self {
+        self.postprocess = postprocess;
+        self
+    }
+
+    /// File of substitutions applied on the text, one per line.
+    #[must_use]
+    pub fn with_replace_file(mut self, replace_file: Option<PathBuf>) -> Self {
+        self.replace_file = replace_file;
+        self
+    }
+
+    /// Correction model applied on the text, learned with `subtile-ocr --learn-corrections`.
+    #[must_use]
+    pub fn with_corrections(mut self, corrections: Option<PathBuf>) -> Self {
+        self.corrections = corrections;
+        self
+    }
+
+    /// Remove the subtitles which look like ripper credits, instead of only reporting them.
+    #[must_use]
+    pub fn with_strip_credits(mut self, strip_credits: bool) -> Self {
+        self.strip_credits = strip_credits;
+        self
+    }
+
+    /// File of additional regular expressions detecting credits subtitles, one per line.
+    #[must_use]
+    pub fn with_credits_patterns(mut self, credits_patterns: Option<PathBuf>) -> Self {
+        self.credits_patterns = credits_patterns;
+        self
+    }
+
+    /// What to do with the sentences split across consecutive subtitles.
+    #[must_use]
+    pub fn with_split_sentences(mut self, split_sentences: SentencePolicy) -> Self {
+        self.split_sentences = split_sentences;
+        self
+    }
+
+    /// What to do with the cues breaking the `SRT` constraints.
+    #[must_use]
+    pub fn with_validate(mut self, validate: ValidationPolicy) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    /// Maximum number of lines of a cue, checked by [`TextConfig::with_validate`].
+    #[must_use]
+    pub fn with_max_lines(mut self, max_lines: Option<NonZeroUsize>) -> Self {
+        self.max_lines = max_lines;
+        self
+    }
+
+    /// Detect the italic lines from the slant of their letters, and wrap them in `<i>` tags.
+    #[must_use]
+    pub fn with_detect_italics(mut self, detect_italics: bool) -> Self {
+        self.detect_italics = detect_italics;
+        self
+    }
+}
+
+/// Files written by a run.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct OutputConfig {
+    pub(crate) files: Vec<PathBuf>,
+    pub(crate) formats: Vec<OutputFormat>,
+    pub(crate) template: Option<String>,
+    pub(crate) ass_style: AssStyle,
+    pub(crate) ocr_output: Option<OcrOutput>,
+    pub(crate) ocr_output_dir: Option<PathBuf>,
+    pub(crate) trace: Option<PathBuf>,
+    pub(crate) qa_report: Option<PathBuf>,
+    pub(crate) cue_index: Option<PathBuf>,
+    pub(crate) cue_index_interval: Option<u32>,
+    pub(crate) infer_forced: Option<PathBuf>,
+    pub(crate) confidence_review: Option<PathBuf>,
+}
+
+impl OutputConfig {
+    /// Output subtitle files, each one in the format at the same position in
+    /// [`OutputConfig::with_formats`] or guessed from its extension; stdout if empty.
+    #[must_use]
+    pub fn with_files(mut self, files: Vec<PathBuf>) -> Self {
+        self.files = files;
+        self
+    }
+
+    /// Formats of the output subtitle files.
+    #[must_use]
+    pub fn with_formats(mut self, formats: Vec<OutputFormat>) -> Self {
+        self.formats = formats;
+        self
+    }
+
+    /// Template of the output subtitle file path, instead of [`OutputConfig::with_files`].
+    #[must_use]
+    pub fn with_template(mut self, template: Option<String>) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Default style of the `ASS` output.
+    #[must_use]
+    pub fn with_ass_style(mut self, ass_style: AssStyle) -> Self {
+        self.ass_style = ass_style;
+        self
+    }
+
+    /// Also write the `OCR` result of each subtitle in this format.
+    #[must_use]
+    pub fn with_ocr_output(mut self, ocr_output: Option<OcrOutput>) -> Self {
+        self.ocr_output = ocr_output;
+        self
+    }
+
+    /// Directory of the [`OutputConfig::with_ocr_output`] files, next to the output file by default.
+    #[must_use]
+    pub fn with_ocr_output_dir(mut self, ocr_output_dir: Option<PathBuf>) -> Self {
+        self.ocr_output_dir = ocr_output_dir;
+        self
+    }
+
+    /// Write in this file where each subtitle comes from in the input.
+    #[must_use]
+    pub fn with_trace(mut self, trace: Option<PathBuf>) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Write the subtitles flagged for checking in this file.
+    #[must_use]
+    pub fn with_qa_report(mut self, qa_report: Option<PathBuf>) -> Self {
+        self.qa_report = qa_report;
+        self
+    }
+
+    /// Write an index of the subtitles times in this file.
+    #[must_use]
+    pub fn with_cue_index(mut self, cue_index: Option<PathBuf>) -> Self {
+        self.cue_index = cue_index;
+        self
+    }
+
+    /// Only list the first subtitle of each interval of this many seconds in the cue index.
+    #[must_use]
+    pub fn with_cue_index_interval(mut self, cue_index_interval: Option<u32>) -> Self {
+        self.cue_index_interval = cue_index_interval;
+        self
+    }
+
+    /// Write the subtitles inferred as forced in this file.
+    #[must_use]
+    pub fn with_infer_forced(mut self, infer_forced: Option<PathBuf>) -> Self {
+        self.infer_forced = infer_forced;
+        self
+    }
+
+    /// Write the subtitles flagged by [`OcrConfig::with_min_confidence`] in this file.
+    #[must_use]
+    pub fn with_confidence_review(mut self, confidence_review: Option<PathBuf>) -> Self {
+        self.confidence_review = confidence_review;
+        self
+    }
+}
+
+/// Value of a Tesseract config variable, like the `-c name=value` argument of `tesseract`.
+///
+/// Parsed from `name=value` with [`str::parse`].
+#[derive(Clone, Debug)]
+pub struct TesseractVariable {
+    pub(crate) variable: Variable,
+    pub(crate) value: String,
+}
+
+impl TesseractVariable {
+    /// Create the `value` of the variable `name`.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::TesseractVariableName`] if the variable name is unknown.
+    pub fn new(name: &str, value: impl Into<String>) -> Result<Self, Error> {
+        Ok(Self {
+            variable: parse_tesseract_variable(name)?,
+            value: value.into(),
+        })
+    }
+}
+
+impl FromStr for TesseractVariable {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = s.split_once('=').ok_or_else(|| Error::ParseKeyValuePair {
+            value: s.to_owned(),
+        })?;
+        Self::new(name, value)
+    }
+}
+
+/// Parse a palette given as 16 hexadecimal RGB colors separated by commas, like an idx `palette:` line.
+///
+/// # Errors
+///
+/// Will return [`Error::Palette`] if the palette is invalid.
+pub fn parse_palette(s: &str) -> Result<vobsub::Palette, Error> {
+    let error = || Error::Palette {
+        value: s.to_owned(),
+    };
+    let colors = s
+        .trim_start_matches("palette:")
+        .split(',')
+        .map(|color| {
+            let color = color.trim();
+            let rgb = (color.len() == 6)
+                .then(|| u32::from_str_radix(color, 16).ok())
+                .flatten()
+                .ok_or_else(error)?;
+            let [_, r, g, b] = rgb.to_be_bytes();
+            Ok(Rgb([r, g, b]))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    colors.try_into().map_err(|_| error())
+}
+
+/// Parse an anchor given as `src=HH:MM:SS.mmm,dst=HH:MM:SS.mmm`.
+///
+/// # Errors
+///
+/// Will return [`Error::Anchor`] if the anchor is invalid.
+pub fn parse_anchor(s: &str) -> Result<Anchor, Error> {
+    let error = || Error::Anchor {
+        value: s.to_owned(),
+    };
+    let (src, dst) = s.split_once(',').ok_or_else(error)?;
+    let src = src.trim().strip_prefix("src=").ok_or_else(error)?;
+    let dst = dst.trim().strip_prefix("dst=").ok_or_else(error)?;
+    Ok(Anchor {
+        src: parse_clock(src).ok_or_else(error)?,
+        dst: parse_clock(dst).ok_or_else(error)?,
+    })
+}
+
+/// Parse a crop region given as `x,y,width,height`.
+///
+/// # Errors
+///
+/// Will return [`Error::Crop`] if the region is invalid or empty.
+pub fn parse_crop(s: &str) -> Result<Crop, Error> {
+    let error = || Error::Crop {
+        value: s.to_owned(),
+    };
+    let values = s
+        .split(',')
+        .map(|value| value.trim().parse::<u32>().map_err(|_| error()))
+        .collect::<Result<Vec<_>, _>>()?;
+    match values[..] {
+        [x, y, width, height] if width > 0 && height > 0 => Ok(Crop {
+            x,
+            y,
+            width,
+            height,
+        }),
+        _ => Err(error()),
+    }
+}
+
+/// Parse a `HH:MM:SS.mmm` time in milliseconds, the milliseconds being optional.
+fn parse_clock(s: &str) -> Option<i64> {
+    let (clock, msecs) = s.split_once(['.', ',']).unwrap_or((s, "0"));
+    let msecs = format!("{msecs:0<3}").get(..3)?.parse::<i64>().ok()?;
+    let parts = clock
+        .split(':')
+        .map(|part| part.parse::<i64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let seconds = match parts[..] {
+        [hours, minutes, seconds] => (hours * 60 + minutes) * 60 + seconds,
+        [minutes, seconds] => minutes * 60 + seconds,
+        _ => return None,
+    };
+    Some(seconds * 1000 + msecs)
+}
+
+fn parse_tesseract_variable(s: impl AsRef<str>) -> Result<Variable, Error> {
+    Ok(match s.as_ref() {
+        "classify_num_cp_levels" => Variable::ClassifyNumCpLevels,
+        "textord_dotmatrix_gap" => Variable::TextordDotmatrixGap,
+        "textord_debug_block" => Variable::TextordDebugBlock,
+        "textord_pitch_range" => Variable::TextordPitchRange,
+        "textord_words_veto_power" => Variable::TextordWordsVetoPower,
+        "textord_tabfind_show_strokewidths" => Variable::TextordTabfindShowStrokewidths,
+        "pitsync_linear_version" => Variable::PitsyncLinearVersion,
+        "pitsync_fake_depth" => Variable::PitsyncFakeDepth,
+        "oldbl_holed_losscount" => Variable::OldblHoledLosscount,
+        "textord_skewsmooth_offset" => Variable::TextordSkewsmoothOffset,
+        "textord_skewsmooth_offset2" => Variable::TextordSkewsmoothOffset2,
+        "textord_test_x" => Variable::TextordTestX,
+        "textord_test_y" => Variable::TextordTestY,
+        "textord_min_blobs_in_row" => Variable::TextordMinBlobsInRow,
+        "textord_spline_minblobs" => Variable::TextordSplineMinblobs,
+        "textord_spline_medianwin" => Variable::TextordSplineMedianwin,
+        "textord_max_blob_overlaps" => Variable::TextordMaxBlobOverlaps,
+        "textord_min_xheight" => Variable::TextordMinXheight,
+        "textord_lms_line_trials" => Variable::TextordLmsLineTrials,
+        "textord_tabfind_show_images" => Variable::TextordTabfindShowImages,
+        "textord_fp_chop_error" => Variable::TextordFpChopError,
+        "edges_max_children_per_outline" => Variable::EdgesMaxChildrenPerOutline,
+        "edges_max_children_layers" => Variable::EdgesMaxChildrenLayers,
+        "edges_children_per_grandchild" => Variable::EdgesChildrenPerGrandchild,
+        "edges_children_count_limit" => Variable::EdgesChildrenCountLimit,
+        "edges_min_nonhole" => Variable::EdgesMinNonhole,
+        "edges_patharea_ratio" => Variable::EdgesPathareaRatio,
+        "devanagari_split_debuglevel" => Variable::DevanagariSplitDebuglevel,
+        "textord_tabfind_show_partitions" => Variable::TextordTabfindShowPartitions,
+        "textord_debug_tabfind" => Variable::TextordDebugTabfind,
+        "textord_debug_bugs" => Variable::TextordDebugBugs,
+        "textord_testregion_left" => Variable::TextordTestregionLeft,
+        "textord_testregion_top" => Variable::TextordTestregionTop,
+        "textord_testregion_right" => Variable::TextordTestregionRight,
+        "textord_testregion_bottom" => Variable::TextordTestregionBottom,
+        "editor_image_xpos" => Variable::EditorImageXpos,
+        "editor_image_ypos" => Variable::EditorImageYpos,
+        "editor_image_menuheight" => Variable::EditorImageMenuheight,
+        "editor_image_word_bb_color" => Variable::EditorImageWordBbColor,
+        "editor_image_blob_bb_color" => Variable::EditorImageBlobBbColor,
+        "editor_image_text_color" => Variable::EditorImageTextColor,
+        "editor_dbwin_xpos" => Variable::EditorDbwinXpos,
+        "editor_dbwin_ypos" => Variable::EditorDbwinYpos,
+        "editor_dbwin_height" => Variable::EditorDbwinHeight,
+        "editor_dbwin_width" => Variable::EditorDbwinWidth,
+        "editor_word_xpos" => Variable::EditorWordXpos,
+        "editor_word_ypos" => Variable::EditorWordYpos,
+        "editor_word_height" => Variable::EditorWordHeight,
+        "editor_word_width" => Variable::EditorWordWidth,
+        "wordrec_display_splits" => Variable::WordrecDisplaySplits,
+        "poly_debug" => Variable::PolyDebug,
+        "poly_wide_objects_better" => Variable::PolyWideObjectsBetter,
+        "wordrec_display_all_blobs" => Variable::WordrecDisplayAllBlobs,
+        "wordrec_blob_pause" => Variable::WordrecBlobPause,
+        "textord_fp_chopping" => Variable::TextordFpChopping,
+        "textord_force_make_prop_words" => Variable::TextordForceMakePropWords,
+        "textord_chopper_test" => Variable::TextordChopperTest,
+        "textord_restore_underlines" => Variable::TextordRestoreUnderlines,
+        "textord_show_initial_words" => Variable::TextordShowInitialWords,
+        "textord_show_new_words" => Variable::TextordShowNewWords,
+        "textord_show_fixed_words" => Variable::TextordShowFixedWords,
+        "textord_blocksall_fixed" => Variable::TextordBlocksallFixed,
+        "textord_blocksall_prop" => Variable::TextordBlocksallProp,
+        "textord_blocksall_testing" => Variable::TextordBlocksallTesting,
+        "textord_test_mode" => Variable::TextordTestMode,
+        "textord_pitch_scalebigwords" => Variable::TextordPitchScalebigwords,
+        "textord_all_prop" => Variable::TextordAllProp,
+        "textord_debug_pitch_test" => Variable::TextordDebugPitchTest,
+        "textord_disable_pitch_test" => Variable::TextordDisablePitchTest,
+        "textord_fast_pitch_test" => Variable::TextordFastPitchTest,
+        "textord_debug_pitch_metric" => Variable::TextordDebugPitchMetric,
+        "textord_show_row_cuts" => Variable::TextordShowRowCuts,
+        "textord_show_page_cuts" => Variable::TextordShowPageCuts,
+        "textord_pitch_cheat" => Variable::TextordPitchCheat,
+        "textord_blockndoc_fixed" => Variable::TextordBlockndocFixed,
+        "textord_show_tables" => Variable::TextordShowTables,
+        "textord_tablefind_show_mark" => Variable::TextordTablefindShowMark,
+        "textord_tablefind_show_stats" => Variable::TextordTablefindShowStats,
+        "textord_tablefind_recognize_tables" => Variable::TextordTablefindRecognizeTables,
+        "textord_tabfind_show_initialtabs" => Variable::TextordTabfindShowInitialtabs,
+        "textord_tabfind_show_finaltabs" => Variable::TextordTabfindShowFinaltabs,
+        "textord_tabfind_only_strokewidths" => Variable::TextordTabfindOnlyStrokewidths,
+        "textord_really_old_xheight" => Variable::TextordReallyOldXheight,
+        "textord_oldbl_debug" => Variable::TextordOldblDebug,
+        "textord_debug_baselines" => Variable::TextordDebugBaselines,
+        "textord_oldbl_paradef" => Variable::TextordOldblParadef,
+        "textord_oldbl_split_splines" => Variable::TextordOldblSplitSplines,
+        "textord_oldbl_merge_parts" => Variable::TextordOldblMergeParts,
+        "oldbl_corrfix" => Variable::OldblCorrfix,
+        "oldbl_xhfix" => Variable::OldblXhfix,
+        "textord_ocropus_mode" => Variable::TextordOcropusMode,
+        "textord_heavy_nr" => Variable::TextordHeavyNr,
+        "textord_show_initial_rows" => Variable::TextordShowInitialRows,
+        "textord_show_parallel_rows" => Variable::TextordShowParallelRows,
+        "textord_show_expanded_rows" => Variable::TextordShowExpandedRows,
+        "textord_show_final_rows" => Variable::TextordShowFinalRows,
+        "textord_show_final_blobs" => Variable::TextordShowFinalBlobs,
+        "textord_test_landscape" => Variable::TextordTestLandscape,
+        "textord_parallel_baselines" => Variable::TextordParallelBaselines,
+        "textord_straight_baselines" => Variable::TextordStraightBaselines,
+        "textord_old_baselines" => Variable::TextordOldBaselines,
+        "textord_old_xheight" => Variable::TextordOldXheight,
+        "textord_fix_xheight_bug" => Variable::TextordFixXheightBug,
+        "textord_fix_makerow_bug" => Variable::TextordFixMakerowBug,
+        "textord_debug_xheights" => Variable::TextordDebugXheights,
+        "textord_biased_skewcalc" => Variable::TextordBiasedSkewcalc,
+        "textord_interpolating_skew" => Variable::TextordInterpolatingSkew,
+        "textord_new_initial_xheight" => Variable::TextordNewInitialXheight,
+        "textord_debug_blob" => Variable::TextordDebugBlob,
+        "gapmap_debug" => Variable::GapmapDebug,
+        "gapmap_use_ends" => Variable::GapmapUseEnds,
+        "gapmap_no_isolated_quanta" => Variable::GapmapNoIsolatedQuanta,
+        "edges_use_new_outline_complexity" => Variable::EdgesUseNewOutlineComplexity,
+        "edges_debug" => Variable::EdgesDebug,
+        "edges_children_fix" => Variable::EdgesChildrenFix,
+        "textord_show_fixed_cuts" => Variable::TextordShowFixedCuts,
+        "devanagari_split_debugimage" => Variable::DevanagariSplitDebugimage,
+        "textord_tabfind_show_initial_partitions" => Variable::TextordTabfindShowInitialPartitions,
+        "textord_tabfind_show_reject_blobs" => Variable::TextordTabfindShowRejectBlobs,
+        "textord_tabfind_show_columns" => Variable::TextordTabfindShowColumns,
+        "textord_tabfind_show_blocks" => Variable::TextordTabfindShowBlocks,
+        "textord_tabfind_find_tables" => Variable::TextordTabfindFindTables,
+        "textord_space_size_is_variable" => Variable::TextordSpaceSizeIsVariable,
+        "textord_debug_printable" => Variable::TextordDebugPrintable,
+        "equationdetect_save_bi_image" => Variable::EquationdetectSaveBiImage,
+        "equationdetect_save_spt_image" => Variable::EquationdetectSaveSptImage,
+        "equationdetect_save_seed_image" => Variable::EquationdetectSaveSeedImage,
+        "equationdetect_save_merged_image" => Variable::EquationdetectSaveMergedImage,
+        "stream_filelist" => Variable::StreamFilelist,
+        "debug_file" => Variable::DebugFile,
+        "dotproduct" => Variable::Dotproduct,
+        "classify_font_name" => Variable::ClassifyFontName,
+        "fx_debugfile" => Variable::FxDebugfile,
+        "editor_image_win_name" => Variable::EditorImageWinName,
+        "editor_dbwin_name" => Variable::EditorDbwinName,
+        "editor_word_name" => Variable::EditorWordName,
+        "document_title" => Variable::DocumentTitle,
+        "classify_pico_feature_length" => Variable::ClassifyPicoFeatureLength,
+        "classify_norm_adj_midpoint" => Variable::ClassifyNormAdjMidpoint,
+        "classify_norm_adj_curl" => Variable::ClassifyNormAdjCurl,
+        "classify_min_slope" => Variable::ClassifyMinSlope,
+        "classify_max_slope" => Variable::ClassifyMaxSlope,
+        "classify_cp_angle_pad_loose" => Variable::ClassifyCpAnglePadLoose,
+        "classify_cp_angle_pad_medium" => Variable::ClassifyCpAnglePadMedium,
+        "classify_cp_angle_pad_tight" => Variable::ClassifyCpAnglePadTight,
+        "classify_cp_end_pad_loose" => Variable::ClassifyCpEndPadLoose,
+        "classify_cp_end_pad_medium" => Variable::ClassifyCpEndPadMedium,
+        "classify_cp_end_pad_tight" => Variable::ClassifyCpEndPadTight,
+        "classify_cp_side_pad_loose" => Variable::ClassifyCpSidePadLoose,
+        "classify_cp_side_pad_medium" => Variable::ClassifyCpSidePadMedium,
+        "classify_cp_side_pad_tight" => Variable::ClassifyCpSidePadTight,
+        "classify_pp_angle_pad" => Variable::ClassifyPpAnglePad,
+        "classify_pp_end_pad" => Variable::ClassifyPpEndPad,
+        "classify_pp_side_pad" => Variable::ClassifyPpSidePad,
+        "textord_underline_offset" => Variable::TextordUnderlineOffset,
+        "textord_wordstats_smooth_factor" => Variable::TextordWordstatsSmoothFactor,
+        "textord_width_smooth_factor" => Variable::TextordWidthSmoothFactor,
+        "textord_words_width_ile" => Variable::TextordWordsWidthIle,
+        "textord_words_maxspace" => Variable::TextordWordsMaxspace,
+        "textord_words_default_maxspace" => Variable::TextordWordsDefaultMaxspace,
+        "textord_words_default_minspace" => Variable::TextordWordsDefaultMinspace,
+        "textord_words_min_minspace" => Variable::TextordWordsMinMinspace,
+        "textord_words_default_nonspace" => Variable::TextordWordsDefaultNonspace,
+        "textord_words_initial_lower" => Variable::TextordWordsInitialLower,
+        "textord_words_initial_upper" => Variable::TextordWordsInitialUpper,
+        "textord_words_minlarge" => Variable::TextordWordsMinlarge,
+        "textord_words_pitchsd_threshold" => Variable::TextordWordsPitchsdThreshold,
+        "textord_words_def_fixed" => Variable::TextordWordsDefFixed,
+        "textord_words_def_prop" => Variable::TextordWordsDefProp,
+        "textord_pitch_rowsimilarity" => Variable::TextordPitchRowsimilarity,
+        "words_initial_lower" => Variable::WordsInitialLower,
+        "words_initial_upper" => Variable::WordsInitialUpper,
+        "words_default_prop_nonspace" => Variable::WordsDefaultPropNonspace,
+        "words_default_fixed_space" => Variable::WordsDefaultFixedSpace,
+        "words_default_fixed_limit" => Variable::WordsDefaultFixedLimit,
+        "textord_words_definite_spread" => Variable::TextordWordsDefiniteSpread,
+        "textord_spacesize_ratiofp" => Variable::TextordSpacesizeRatiofp,
+        "textord_spacesize_ratioprop" => Variable::TextordSpacesizeRatioprop,
+        "textord_fpiqr_ratio" => Variable::TextordFpiqrRatio,
+        "textord_max_pitch_iqr" => Variable::TextordMaxPitchIqr,
+        "textord_fp_min_width" => Variable::TextordFpMinWidth,
+        "textord_projection_scale" => Variable::TextordProjectionScale,
+        "textord_balance_factor" => Variable::TextordBalanceFactor,
+        "textord_tabvector_vertical_gap_fraction" => Variable::TextordTabvectorVerticalGapFraction,
+        "textord_tabvector_vertical_box_ratio" => Variable::TextordTabvectorVerticalBoxRatio,
+        "pitsync_joined_edge" => Variable::PitsyncJoinedEdge,
+        "pitsync_offset_freecut_fraction" => Variable::PitsyncOffsetFreecutFraction,
+        "oldbl_xhfract" => Variable::OldblXhfract,
+        "oldbl_dot_error_size" => Variable::OldblDotErrorSize,
+        "textord_oldbl_jumplimit" => Variable::TextordOldblJumplimit,
+        "textord_spline_shift_fraction" => Variable::TextordSplineShiftFraction,
+        "textord_spline_outlier_fraction" => Variable::TextordSplineOutlierFraction,
+        "textord_skew_ile" => Variable::TextordSkewIle,
+        "textord_skew_lag" => Variable::TextordSkewLag,
+        "textord_linespace_iqrlimit" => Variable::TextordLinespaceIqrlimit,
+        "textord_width_limit" => Variable::TextordWidthLimit,
+        "textord_chop_width" => Variable::TextordChopWidth,
+        "textord_expansion_factor" => Variable::TextordExpansionFactor,
+        "textord_overlap_x" => Variable::TextordOverlapX,
+        "textord_minxh" => Variable::TextordMinxh,
+        "textord_min_linesize" => Variable::TextordMinLinesize,
+        "textord_excess_blobsize" => Variable::TextordExcessBlobsize,
+        "textord_occupancy_threshold" => Variable::TextordOccupancyThreshold,
+        "textord_underline_width" => Variable::TextordUnderlineWidth,
+        "textord_min_blob_height_fraction" => Variable::TextordMinBlobHeightFraction,
+        "textord_xheight_mode_fraction" => Variable::TextordXheightModeFraction,
+        "textord_ascheight_mode_fraction" => Variable::TextordAscheightModeFraction,
+        "textord_descheight_mode_fraction" => Variable::TextordDescheightModeFraction,
+        "textord_ascx_ratio_min" => Variable::TextordAscxRatioMin,
+        "textord_ascx_ratio_max" => Variable::TextordAscxRatioMax,
+        "textord_descx_ratio_min" => Variable::TextordDescxRatioMin,
+        "textord_descx_ratio_max" => Variable::TextordDescxRatioMax,
+        "textord_xheight_error_margin" => Variable::TextordXheightErrorMargin,
+        "gapmap_big_gaps" => Variable::GapmapBigGaps,
+        "textord_fp_chop_snap" => Variable::TextordFpChopSnap,
+        "edges_childarea" => Variable::EdgesChildarea,
+        "edges_boxarea" => Variable::EdgesBoxarea,
+        "textord_underline_threshold" => Variable::TextordUnderlineThreshold,
+        "ambigs_debug_level" => Variable::AmbigsDebugLevel,
+        "classify_debug_level" => Variable::ClassifyDebugLevel,
+        "classify_norm_method" => Variable::ClassifyNormMethod,
+        "matcher_debug_level" => Variable::MatcherDebugLevel,
+        "matcher_debug_flags" => Variable::MatcherDebugFlags,
+        "classify_learning_debug_level" => Variable::ClassifyLearningDebugLevel,
+        "matcher_permanent_classes_min" => Variable::MatcherPermanentClassesMin,
+        "matcher_min_examples_for_prototyping" => Variable::MatcherMinExamplesForPrototyping,
+        "matcher_sufficient_examples_for_prototyping" => {
+            Variable::MatcherSufficientExamplesForPrototyping
+        }
+        "classify_adapt_proto_threshold" => Variable::ClassifyAdaptProtoThreshold,
+        "classify_adapt_feature_threshold" => Variable::ClassifyAdaptFeatureThreshold,
+        "classify_class_pruner_threshold" => Variable::ClassifyClassPrunerThreshold,
+        "classify_class_pruner_multiplier" => Variable::ClassifyClassPrunerMultiplier,
+        "classify_cp_cutoff_strength" => Variable::ClassifyCpCutoffStrength,
+        "classify_integer_matcher_multiplier" => Variable::ClassifyIntegerMatcherMultiplier,
+        "dawg_debug_level" => Variable::DawgDebugLevel,
+        "hyphen_debug_level" => Variable::HyphenDebugLevel,
+        "stopper_smallword_size" => Variable::StopperSmallwordSize,
+        "stopper_debug_level" => Variable::StopperDebugLevel,
+        "tessedit_truncate_wordchoice_log" => Variable::TesseditTruncateWordchoiceLog,
+        "max_permuter_attempts" => Variable::MaxPermuterAttempts,
+        "repair_unchopped_blobs" => Variable::RepairUnchoppedBlobs,
+        "chop_debug" => Variable::ChopDebug,
+        "chop_split_length" => Variable::ChopSplitLength,
+        "chop_same_distance" => Variable::ChopSameDistance,
+        "chop_min_outline_points" => Variable::ChopMinOutlinePoints,
+        "chop_seam_pile_size" => Variable::ChopSeamPileSize,
+        "chop_inside_angle" => Variable::ChopInsideAngle,
+        "chop_min_outline_area" => Variable::ChopMinOutlineArea,
+        "chop_centered_maxwidth" => Variable::ChopCenteredMaxwidth,
+        "chop_x_y_weight" => Variable::ChopXyWeight,
+        "wordrec_debug_level" => Variable::WordrecDebugLevel,
+        "wordrec_max_join_chunks" => Variable::WordrecMaxJoinChunks,
+        "segsearch_debug_level" => Variable::SegsearchDebugLevel,
+        "segsearch_max_pain_points" => Variable::SegsearchMaxPainPoints,
+        "segsearch_max_futile_classifications" => Variable::SegsearchMaxFutileClassifications,
+        "language_model_debug_level" => Variable::LanguageModelDebugLevel,
+        "language_model_ngram_order" => Variable::LanguageModelNgramOrder,
+        "language_model_viterbi_list_max_num_prunable" => {
+            Variable::LanguageModelViterbiListMaxNumPrunable
+        }
+        "language_model_viterbi_list_max_size" => Variable::LanguageModelViterbiListMaxSize,
+        "language_model_min_compound_length" => Variable::LanguageModelMinCompoundLength,
+        "wordrec_display_segmentations" => Variable::WordrecDisplaySegmentations,
+        "tessedit_pageseg_mode" => Variable::TesseditPagesegMode,
+        "tessedit_ocr_engine_mode" => Variable::TesseditOcrEngineMode,
+        "pageseg_devanagari_split_strategy" => Variable::PagesegDevanagariSplitStrategy,
+        "ocr_devanagari_split_strategy" => Variable::OcrDevanagariSplitStrategy,
+        "bidi_debug" => Variable::BidiDebug,
+        "applybox_debug" => Variable::ApplyboxDebug,
+        "applybox_page" => Variable::ApplyboxPage,
+        "tessedit_bigram_debug" => Variable::TesseditBigramDebug,
+        "debug_noise_removal" => Variable::DebugNoiseRemoval,
+        "noise_maxperblob" => Variable::NoiseMaxperblob,
+        "noise_maxperword" => Variable::NoiseMaxperword,
+        "debug_x_ht_level" => Variable::DebugXHtLevel,
+        "quality_min_initial_alphas_reqd" => Variable::QualityMinInitialAlphasReqd,
+        "tessedit_tess_adaption_mode" => Variable::TesseditTessAdaptionMode,
+        "multilang_debug_level" => Variable::MultilangDebugLevel,
+        "paragraph_debug_level" => Variable::ParagraphDebugLevel,
+        "tessedit_preserve_min_wd_len" => Variable::TesseditPreserveMinWdLen,
+        "crunch_rating_max" => Variable::CrunchRatingMax,
+        "crunch_pot_indicators" => Variable::CrunchPotIndicators,
+        "crunch_leave_lc_strings" => Variable::CrunchLeaveLcStrings,
+        "crunch_leave_uc_strings" => Variable::CrunchLeaveUcStrings,
+        "crunch_long_repetitions" => Variable::CrunchLongRepetitions,
+        "crunch_debug" => Variable::CrunchDebug,
+        "fixsp_non_noise_limit" => Variable::FixspNonNoiseLimit,
+        "fixsp_done_mode" => Variable::FixspDoneMode,
+        "debug_fix_space_level" => Variable::DebugFixSpaceLevel,
+        "x_ht_acceptance_tolerance" => Variable::XHtAcceptanceTolerance,
+        "x_ht_min_change" => Variable::XHtMinChange,
+        "superscript_debug" => Variable::SuperscriptDebug,
+        "jpg_quality" => Variable::JpgQuality,
+        "user_defined_dpi" => Variable::UserDefinedDpi,
+        "min_characters_to_try" => Variable::MinCharactersToTry,
+        "suspect_level" => Variable::SuspectLevel,
+        "suspect_short_words" => Variable::SuspectShortWords,
+        "tessedit_reject_mode" => Variable::TesseditRejectMode,
+        "tessedit_image_border" => Variable::TesseditImageBorder,
+        "min_sane_x_ht_pixels" => Variable::MinSaneXHtPixels,
+        "tessedit_page_number" => Variable::TesseditPageNumber,
+        "tessedit_parallelize" => Variable::TesseditParallelize,
+        "lstm_choice_mode" => Variable::LstmChoiceMode,
+        "tosp_debug_level" => Variable::TospDebugLevel,
+        "tosp_enough_space_samples_for_median" => Variable::TospEnoughSpaceSamplesForMedian,
+        "tosp_redo_kern_limit" => Variable::TospRedoKernLimit,
+        "tosp_few_samples" => Variable::TospFewSamples,
+        "tosp_short_row" => Variable::TospShortRow,
+        "tosp_sanity_method" => Variable::TospSanityMethod,
+        "textord_max_noise_size" => Variable::TextordMaxNoiseSize,
+        "textord_baseline_debug" => Variable::TextordBaselineDebug,
+        "textord_noise_sizefraction" => Variable::TextordNoiseSizefraction,
+        "textord_noise_translimit" => Variable::TextordNoiseTranslimit,
+        "textord_noise_sncount" => Variable::TextordNoiseSncount,
+        "use_ambigs_for_adaption" => Variable::UseAmbigsForAdaption,
+        "allow_blob_division" => Variable::AllowBlobDivision,
+        "prioritize_division" => Variable::PrioritizeDivision,
+        "classify_enable_learning" => Variable::ClassifyEnableLearning,
+        "tess_cn_matching" => Variable::TessCnMatching,
+        "tess_bn_matching" => Variable::TessBnMatching,
+        "classify_enable_adaptive_matcher" => Variable::ClassifyEnableAdaptiveMatcher,
+        "classify_use_pre_adapted_templates" => Variable::ClassifyUsePreAdaptedTemplates,
+        "classify_save_adapted_templates" => Variable::ClassifySaveAdaptedTemplates,
+        "classify_enable_adaptive_debugger" => Variable::ClassifyEnableAdaptiveDebugger,
+        "classify_nonlinear_norm" => Variable::ClassifyNonlinearNorm,
+        "disable_character_fragments" => Variable::DisableCharacterFragments,
+        "classify_debug_character_fragments" => Variable::ClassifyDebugCharacterFragments,
+        "matcher_debug_separate_windows" => Variable::MatcherDebugSeparateWindows,
+        "classify_bln_numeric_mode" => Variable::ClassifyBlnNumericMode,
+        "load_system_dawg" => Variable::LoadSystemDawg,
+        "load_freq_dawg" => Variable::LoadFreqDawg,
+        "load_unambig_dawg" => Variable::LoadUnambigDawg,
+        "load_punc_dawg" => Variable::LoadPuncDawg,
+        "load_number_dawg" => Variable::LoadNumberDawg,
+        "load_bigram_dawg" => Variable::LoadBigramDawg,
+        "use_only_first_uft8_step" => Variable::UseOnlyFirstUft8Step,
+        "stopper_no_acceptable_choices" => Variable::StopperNoAcceptableChoices,
+        "segment_nonalphabetic_script" => Variable::SegmentNonalphabeticScript,
+        "save_doc_words" => Variable::SaveDocWords,
+        "merge_fragments_in_matrix" => Variable::MergeFragmentsInMatrix,
+        "wordrec_enable_assoc" => Variable::WordrecEnableAssoc,
+        "force_word_assoc" => Variable::ForceWordAssoc,
+        "chop_enable" => Variable::ChopEnable,
+        "chop_vertical_creep" => Variable::ChopVerticalCreep,
+        "chop_new_seam_pile" => Variable::ChopNewSeamPile,
+        "assume_fixed_pitch_char_segment" => Variable::AssumeFixedPitchCharSegment,
+        "wordrec_skip_no_truth_words" => Variable::WordrecSkipNoTruthWords,
+        "wordrec_debug_blamer" => Variable::WordrecDebugBlamer,
+        "wordrec_run_blamer" => Variable::WordrecRunBlamer,
+        "save_alt_choices" => Variable::SaveAltChoices,
+        "language_model_ngram_on" => Variable::LanguageModelNgramOn,
+        "language_model_ngram_use_only_first_uft8_step" => {
+            Variable::LanguageModelNgramUseOnlyFirstUft8Step
+        }
+        "language_model_ngram_space_delimited_language" => {
+            Variable::LanguageModelNgramSpaceDelimitedLanguage
+        }
+        "language_model_use_sigmoidal_certainty" => Variable::LanguageModelUseSigmoidalCertainty,
+        "tessedit_resegment_from_boxes" => Variable::TesseditResegmentFromBoxes,
+        "tessedit_resegment_from_line_boxes" => Variable::TesseditResegmentFromLineBoxes,
+        "tessedit_train_from_boxes" => Variable::TesseditTrainFromBoxes,
+        "tessedit_make_boxes_from_boxes" => Variable::TesseditMakeBoxesFromBoxes,
+        "tessedit_train_line_recognizer" => Variable::TesseditTrainLineRecognizer,
+        "tessedit_dump_pageseg_images" => Variable::TesseditDumpPagesegImages,
+        "tessedit_do_invert" => Variable::TesseditDoInvert,
+        "tessedit_ambigs_training" => Variable::TesseditAmbigsTraining,
+        "tessedit_adaption_debug" => Variable::TesseditAdaptionDebug,
+        "applybox_learn_chars_and_char_frags_mode" => Variable::ApplyboxLearnCharsAndCharFragsMode,
+        "applybox_learn_ngrams_mode" => Variable::ApplyboxLearnNgramsMode,
+        "tessedit_display_outwords" => Variable::TesseditDisplayOutwords,
+        "tessedit_dump_choices" => Variable::TesseditDumpChoices,
+        "tessedit_timing_debug" => Variable::TesseditTimingDebug,
+        "tessedit_fix_fuzzy_spaces" => Variable::TesseditFixFuzzySpaces,
+        "tessedit_unrej_any_wd" => Variable::TesseditUnrejAnyWd,
+        "tessedit_fix_hyphens" => Variable::TesseditFixHyphens,
+        "tessedit_enable_doc_dict" => Variable::TesseditEnableDocDict,
+        "tessedit_debug_fonts" => Variable::TesseditDebugFonts,
+        "tessedit_debug_block_rejection" => Variable::TesseditDebugBlockRejection,
+        "tessedit_enable_bigram_correction" => Variable::TesseditEnableBigramCorrection,
+        "tessedit_enable_dict_correction" => Variable::TesseditEnableDictCorrection,
+        "enable_noise_removal" => Variable::EnableNoiseRemoval,
+        "tessedit_minimal_rej_pass1" => Variable::TesseditMinimalRejPass1,
+        "tessedit_test_adaption" => Variable::TesseditTestAdaption,
+        "test_pt" => Variable::TestPt,
+        "paragraph_text_based" => Variable::ParagraphTextBased,
+        "lstm_use_matrix" => Variable::LstmUseMatrix,
+        "tessedit_good_quality_unrej" => Variable::TesseditGoodQualityUnrej,
+        "tessedit_use_reject_spaces" => Variable::TesseditUseRejectSpaces,
+        "tessedit_preserve_blk_rej_perfect_wds" => Variable::TesseditPreserveBlkRejPerfectWds,
+        "tessedit_preserve_row_rej_perfect_wds" => Variable::TesseditPreserveRowRejPerfectWds,
+        "tessedit_dont_blkrej_good_wds" => Variable::TesseditDontBlkrejGoodWds,
+        "tessedit_dont_rowrej_good_wds" => Variable::TesseditDontRowrejGoodWds,
+        "tessedit_row_rej_good_docs" => Variable::TesseditRowRejGoodDocs,
+        "tessedit_reject_bad_qual_wds" => Variable::TesseditRejectBadQualWds,
+        "tessedit_debug_doc_rejection" => Variable::TesseditDebugDocRejection,
+        "tessedit_debug_quality_metrics" => Variable::TesseditDebugQualityMetrics,
+        "bland_unrej" => Variable::BlandUnrej,
+        "unlv_tilde_crunching" => Variable::UnlvTildeCrunching,
+        "hocr_font_info" => Variable::HocrFontInfo,
+        "hocr_char_boxes" => Variable::HocrCharBoxes,
+        "crunch_early_merge_tess_fails" => Variable::CrunchEarlyMergeTessFails,
+        "crunch_early_convert_bad_unlv_chs" => Variable::CrunchEarlyConvertBadUnlvChs,
+        "crunch_terrible_garbage" => Variable::CrunchTerribleGarbage,
+        "crunch_leave_ok_strings" => Variable::CrunchLeaveOkStrings,
+        "crunch_accept_ok" => Variable::CrunchAcceptOk,
+        "crunch_leave_accept_strings" => Variable::CrunchLeaveAcceptStrings,
+        "crunch_include_numerals" => Variable::CrunchIncludeNumerals,
+        "tessedit_prefer_joined_punct" => Variable::TesseditPreferJoinedPunct,
+        "tessedit_write_block_separators" => Variable::TesseditWriteBlockSeparators,
+        "tessedit_write_rep_codes" => Variable::TesseditWriteRepCodes,
+        "tessedit_write_unlv" => Variable::TesseditWriteUnlv,
+        "tessedit_create_txt" => Variable::TesseditCreateTxt,
+        "tessedit_create_hocr" => Variable::TesseditCreateHocr,
+        "tessedit_create_alto" => Variable::TesseditCreateAlto,
+        "tessedit_create_lstmbox" => Variable::TesseditCreateLstmbox,
+        "tessedit_create_tsv" => Variable::TesseditCreateTsv,
+        "tessedit_create_wordstrbox" => Variable::TesseditCreateWordstrbox,
+        "tessedit_create_pdf" => Variable::TesseditCreatePdf,
+        "textonly_pdf" => Variable::TextonlyPdf,
+        "suspect_constrain_1Il" => Variable::SuspectConstrain1Il,
+        "tessedit_minimal_rejection" => Variable::TesseditMinimalRejection,
+        "tessedit_zero_rejection" => Variable::TesseditZeroRejection,
+        "tessedit_word_for_word" => Variable::TesseditWordForWord,
+        "tessedit_zero_kelvin_rejection" => Variable::TesseditZeroKelvinRejection,
+        "tessedit_rejection_debug" => Variable::TesseditRejectionDebug,
+        "tessedit_flip_0O" => Variable::TesseditFlip0O,
+        "rej_trust_doc_dawg" => Variable::RejTrustDocDawg,
+        "rej_1Il_use_dict_word" => Variable::Rej1IlUseDictWord,
+        "rej_1Il_trust_permuter_type" => Variable::Rej1IlTrustPermuterType,
+        "rej_use_tess_accepted" => Variable::RejUseTessAccepted,
+        "rej_use_tess_blanks" => Variable::RejUseTessBlanks,
+        "rej_use_good_perm" => Variable::RejUseGoodPerm,
+        "rej_use_sensible_wd" => Variable::RejUseSensibleWd,
+        "rej_alphas_in_number_perm" => Variable::RejAlphasInNumberPerm,
+        "tessedit_create_boxfile" => Variable::TesseditCreateBoxfile,
+        "tessedit_write_images" => Variable::TesseditWriteImages,
+        "interactive_display_mode" => Variable::InteractiveDisplayMode,
+        "tessedit_override_permuter" => Variable::TesseditOverridePermuter,
+        "tessedit_use_primary_params_model" => Variable::TesseditUsePrimaryParamsModel,
+        "textord_tabfind_show_vlines" => Variable::TextordTabfindShowVlines,
+        "textord_use_cjk_fp_model" => Variable::TextordUseCjkFpModel,
+        "poly_allow_detailed_fx" => Variable::PolyAllowDetailedFx,
+        "tessedit_init_config_only" => Variable::TesseditInitConfigOnly,
+        "textord_equation_detect" => Variable::TextordEquationDetect,
+        "textord_tabfind_vertical_text" => Variable::TextordTabfindVerticalText,
+        "textord_tabfind_force_vertical_text" => Variable::TextordTabfindForceVerticalText,
+        "preserve_interword_spaces" => Variable::PreserveInterwordSpaces,
+        "pageseg_apply_music_mask" => Variable::PagesegApplyMusicMask,
+        "textord_single_height_mode" => Variable::TextordSingleHeightMode,
+        "tosp_old_to_method" => Variable::TospOldToMethod,
+        "tosp_old_to_constrain_sp_kn" => Variable::TospOldToConstrainSpKn,
+        "tosp_only_use_prop_rows" => Variable::TospOnlyUsePropRows,
+        "tosp_force_wordbreak_on_punct" => Variable::TospForceWordbreakOnPunct,
+        "tosp_use_pre_chopping" => Variable::TospUsePreChopping,
+        "tosp_old_to_bug_fix" => Variable::TospOldToBugFix,
+        "tosp_block_use_cert_spaces" => Variable::TospBlockUseCertSpaces,
+        "tosp_row_use_cert_spaces" => Variable::TospRowUseCertSpaces,
+        "tosp_narrow_blobs_not_cert" => Variable::TospNarrowBlobsNotCert,
+        "tosp_row_use_cert_spaces1" => Variable::TospRowUseCertSpaces1,
+        "tosp_recovery_isolated_row_stats" => Variable::TospRecoveryIsolatedRowStats,
+        "tosp_only_small_gaps_for_kern" => Variable::TospOnlySmallGapsForKern,
+        "tosp_all_flips_fuzzy" => Variable::TospAllFlipsFuzzy,
+        "tosp_fuzzy_limit_all" => Variable::TospFuzzyLimitAll,
+        "tosp_stats_use_xht_gaps" => Variable::TospStatsUseXhtGaps,
+        "tosp_use_xht_gaps" => Variable::TospUseXhtGaps,
+        "tosp_only_use_xht_gaps" => Variable::TospOnlyUseXhtGaps,
+        "tosp_rule_9_test_punct" => Variable::TospRule9TestPunct,
+        "tosp_flip_fuzz_kn_to_sp" => Variable::TospFlipFuzzKnToSp,
+        "tosp_flip_fuzz_sp_to_kn" => Variable::TospFlipFuzzSpToKn,
+        "tosp_improve_thresh" => Variable::TospImproveThresh,
+        "textord_no_rejects" => Variable::TextordNoRejects,
+        "textord_show_blobs" => Variable::TextordShowBlobs,
+        "textord_show_boxes" => Variable::TextordShowBoxes,
+        "textord_noise_rejwords" => Variable::TextordNoiseRejwords,
+        "textord_noise_rejrows" => Variable::TextordNoiseRejrows,
+        "textord_noise_debug" => Variable::TextordNoiseDebug,
+        "classify_learn_debug_str" => Variable::ClassifyLearnDebugStr,
+        "user_words_file" => Variable::UserWordsFile,
+        "user_words_suffix" => Variable::UserWordsSuffix,
+        "user_patterns_file" => Variable::UserPatternsFile,
+        "user_patterns_suffix" => Variable::UserPatternsSuffix,
+        "output_ambig_words_file" => Variable::OutputAmbigWordsFile,
+        "word_to_debug" => Variable::WordToDebug,
+        "tessedit_char_blacklist" => Variable::TesseditCharBlacklist,
+        "tessedit_char_whitelist" => Variable::TesseditCharWhitelist,
+        "tessedit_char_unblacklist" => Variable::TesseditCharUnblacklist,
+        "tessedit_write_params_to_file" => Variable::TesseditWriteParamsToFile,
+        "applybox_exposure_pattern" => Variable::ApplyboxExposurePattern,
+        "chs_leading_punct" => Variable::ChsLeadingPunct,
+        "chs_trailing_punct1" => Variable::ChsTrailingPunct1,
+        "chs_trailing_punct2" => Variable::ChsTrailingPunct2,
+        "outlines_odd" => Variable::OutlinesOdd,
+        "outlines_2" => Variable::Outlines2,
+        "numeric_punctuation" => Variable::NumericPunctuation,
+        "unrecognised_char" => Variable::UnrecognisedChar,
+        "ok_repeated_ch_non_alphanum_wds" => Variable::OkRepeatedChNonAlphanumWds,
+        "conflict_set_I_l_1" => Variable::ConflictSetIl1,
+        "file_type" => Variable::FileType,
+        "tessedit_load_sublangs" => Variable::TesseditLoadSublangs,
+        "page_separator" => Variable::PageSeparator,
+        "classify_char_norm_range" => Variable::ClassifyCharNormRange,
+        "classify_max_rating_ratio" => Variable::ClassifyMaxRatingRatio,
+        "classify_max_certainty_margin" => Variable::ClassifyMaxCertaintyMargin,
+        "matcher_good_threshold" => Variable::MatcherGoodThreshold,
+        "matcher_reliable_adaptive_result" => Variable::MatcherReliableAdaptiveResult,
+        "matcher_perfect_threshold" => Variable::MatcherPerfectThreshold,
+        "matcher_bad_match_pad" => Variable::MatcherBadMatchPad,
+        "matcher_rating_margin" => Variable::MatcherRatingMargin,
+        "matcher_avg_noise_size" => Variable::MatcherAvgNoiseSize,
+        "matcher_clustering_max_angle_delta" => Variable::MatcherClusteringMaxAngleDelta,
+        "classify_misfit_junk_penalty" => Variable::ClassifyMisfitJunkPenalty,
+        "rating_scale" => Variable::RatingScale,
+        "certainty_scale" => Variable::CertaintyScale,
+        "tessedit_class_miss_scale" => Variable::TesseditClassMissScale,
+        "classify_adapted_pruning_factor" => Variable::ClassifyAdaptedPruningFactor,
+        "classify_adapted_pruning_threshold" => Variable::ClassifyAdaptedPruningThreshold,
+        "classify_character_fragments_garbage_certainty_threshold" => {
+            Variable::ClassifyCharacterFragmentsGarbageCertaintyThreshold
+        }
+        "speckle_large_max_size" => Variable::SpeckleLargeMaxSize,
+        "speckle_rating_penalty" => Variable::SpeckleRatingPenalty,
+        "xheight_penalty_subscripts" => Variable::XheightPenaltySubscripts,
+        "xheight_penalty_inconsistent" => Variable::XheightPenaltyInconsistent,
+        "segment_penalty_dict_frequent_word" => Variable::SegmentPenaltyDictFrequentWord,
+        "segment_penalty_dict_case_ok" => Variable::SegmentPenaltyDictCaseOk,
+        "segment_penalty_dict_case_bad" => Variable::SegmentPenaltyDictCaseBad,
+        "segment_penalty_dict_nonword" => Variable::SegmentPenaltyDictNonword,
+        "segment_penalty_garbage" => Variable::SegmentPenaltyGarbage,
+        "stopper_nondict_certainty_base" => Variable::StopperNondictCertaintyBase,
+        "stopper_phase2_certainty_rejection_offset" => {
+            Variable::StopperPhase2CertaintyRejectionOffset
+        }
+        "stopper_certainty_per_char" => Variable::StopperCertaintyPerChar,
+        "stopper_allowable_character_badness" => Variable::StopperAllowableCharacterBadness,
+        "doc_dict_pending_threshold" => Variable::DocDictPendingThreshold,
+        "doc_dict_certainty_threshold" => Variable::DocDictCertaintyThreshold,
+        "tessedit_certainty_threshold" => Variable::TesseditCertaintyThreshold,
+        "chop_split_dist_knob" => Variable::ChopSplitDistKnob,
+        "chop_overlap_knob" => Variable::ChopOverlapKnob,
+        "chop_center_knob" => Variable::ChopCenterKnob,
+        "chop_sharpness_knob" => Variable::ChopSharpnessKnob,
+        "chop_width_change_knob" => Variable::ChopWidthChangeKnob,
+        "chop_ok_split" => Variable::ChopOkSplit,
+        "chop_good_split" => Variable::ChopGoodSplit,
+        "segsearch_max_char_wh_ratio" => Variable::SegsearchMaxCharWhRatio,
+        "language_model_ngram_small_prob" => Variable::LanguageModelNgramSmallProb,
+        "language_model_ngram_nonmatch_score" => Variable::LanguageModelNgramNonmatchScore,
+        "language_model_ngram_scale_factor" => Variable::LanguageModelNgramScaleFactor,
+        "language_model_ngram_rating_factor" => Variable::LanguageModelNgramRatingFactor,
+        "language_model_penalty_non_freq_dict_word" => {
+            Variable::LanguageModelPenaltyNonFreqDictWord
+        }
+        "language_model_penalty_non_dict_word" => Variable::LanguageModelPenaltyNonDictWord,
+        "language_model_penalty_punc" => Variable::LanguageModelPenaltyPunc,
+        "language_model_penalty_case" => Variable::LanguageModelPenaltyCase,
+        "language_model_penalty_script" => Variable::LanguageModelPenaltyScript,
+        "language_model_penalty_chartype" => Variable::LanguageModelPenaltyChartype,
+        "language_model_penalty_font" => Variable::LanguageModelPenaltyFont,
+        "language_model_penalty_spacing" => Variable::LanguageModelPenaltySpacing,
+        "language_model_penalty_increment" => Variable::LanguageModelPenaltyIncrement,
+        "noise_cert_basechar" => Variable::NoiseCertBasechar,
+        "noise_cert_disjoint" => Variable::NoiseCertDisjoint,
+        "noise_cert_punc" => Variable::NoiseCertPunc,
+        "noise_cert_factor" => Variable::NoiseCertFactor,
+        "quality_rej_pc" => Variable::QualityRejPc,
+        "quality_blob_pc" => Variable::QualityBlobPc,
+        "quality_outline_pc" => Variable::QualityOutlinePc,
+        "quality_char_pc" => Variable::QualityCharPc,
+        "test_pt_x" => Variable::TestPtX,
+        "test_pt_y" => Variable::TestPtY,
+        "tessedit_reject_doc_percent" => Variable::TesseditRejectDocPercent,
+        "tessedit_reject_block_percent" => Variable::TesseditRejectBlockPercent,
+        "tessedit_reject_row_percent" => Variable::TesseditRejectRowPercent,
+        "tessedit_whole_wd_rej_row_percent" => Variable::TesseditWholeWdRejRowPercent,
+        "tessedit_good_doc_still_rowrej_wd" => Variable::TesseditGoodDocStillRowrejWd,
+        "quality_rowrej_pc" => Variable::QualityRowrejPc,
+        "crunch_terrible_rating" => Variable::CrunchTerribleRating,
+        "crunch_poor_garbage_cert" => Variable::CrunchPoorGarbageCert,
+        "crunch_poor_garbage_rate" => Variable::CrunchPoorGarbageRate,
+        "crunch_pot_poor_rate" => Variable::CrunchPotPoorRate,
+        "crunch_pot_poor_cert" => Variable::CrunchPotPoorCert,
+        "crunch_del_rating" => Variable::CrunchDelRating,
+        "crunch_del_cert" => Variable::CrunchDelCert,
+        "crunch_del_min_ht" => Variable::CrunchDelMinHt,
+        "crunch_del_max_ht" => Variable::CrunchDelMaxHt,
+        "crunch_del_min_width" => Variable::CrunchDelMinWidth,
+        "crunch_del_high_word" => Variable::CrunchDelHighWord,
+        "crunch_del_low_word" => Variable::CrunchDelLowWord,
+        "crunch_small_outlines_size" => Variable::CrunchSmallOutlinesSize,
+        "fixsp_small_outlines_size" => Variable::FixspSmallOutlinesSize,
+        "superscript_worse_certainty" => Variable::SuperscriptWorseCertainty,
+        "superscript_bettered_certainty" => Variable::SuperscriptBetteredCertainty,
+        "superscript_scaledown_ratio" => Variable::SuperscriptScaledownRatio,
+        "subscript_max_y_top" => Variable::SubscriptMaxYTop,
+        "superscript_min_y_bottom" => Variable::SuperscriptMinYBottom,
+        "suspect_rating_per_ch" => Variable::SuspectRatingPerCh,
+        "suspect_accept_rating" => Variable::SuspectAcceptRating,
+        "tessedit_lower_flip_hyphen" => Variable::TesseditLowerFlipHyphen,
+        "tessedit_upper_flip_hyphen" => Variable::TesseditUpperFlipHyphen,
+        "rej_whole_of_mostly_reject_word_fract" => Variable::RejWholeOfMostlyRejectWordFract,
+        "min_orientation_margin" => Variable::MinOrientationMargin,
+        "textord_tabfind_vertical_text_ratio" => Variable::TextordTabfindVerticalTextRatio,
+        "textord_tabfind_aligned_gap_fraction" => Variable::TextordTabfindAlignedGapFraction,
+        "tosp_old_sp_kn_th_factor" => Variable::TospOldSpKnThFactor,
+        "tosp_threshold_bias1" => Variable::TospThresholdBias1,
+        "tosp_threshold_bias2" => Variable::TospThresholdBias2,
+        "tosp_narrow_fraction" => Variable::TospNarrowFraction,
+        "tosp_narrow_aspect_ratio" => Variable::TospNarrowAspectRatio,
+        "tosp_wide_fraction" => Variable::TospWideFraction,
+        "tosp_wide_aspect_ratio" => Variable::TospWideAspectRatio,
+        "tosp_fuzzy_space_factor" => Variable::TospFuzzySpaceFactor,
+        "tosp_fuzzy_space_factor1" => Variable::TospFuzzySpaceFactor1,
+        "tosp_fuzzy_space_factor2" => Variable::TospFuzzySpaceFactor2,
+        "tosp_gap_factor" => Variable::TospGapFactor,
+        "tosp_kern_gap_factor1" => Variable::TospKernGapFactor1,
+        "tosp_kern_gap_factor2" => Variable::TospKernGapFactor2,
+        "tosp_kern_gap_factor3" => Variable::TospKernGapFactor3,
+        "tosp_ignore_big_gaps" => Variable::TospIgnoreBigGaps,
+        "tosp_ignore_very_big_gaps" => Variable::TospIgnoreVeryBigGaps,
+        "tosp_rep_space" => Variable::TospRepSpace,
+        "tosp_enough_small_gaps" => Variable::TospEnoughSmallGaps,
+        "tosp_table_kn_sp_ratio" => Variable::TospTableKnSpRatio,
+        "tosp_table_xht_sp_ratio" => Variable::TospTableXhtSpRatio,
+        "tosp_table_fuzzy_kn_sp_ratio" => Variable::TospTableFuzzyKnSpRatio,
+        "tosp_fuzzy_kn_fraction" => Variable::TospFuzzyKnFraction,
+        "tosp_fuzzy_sp_fraction" => Variable::TospFuzzySpFraction,
+        "tosp_min_sane_kn_sp" => Variable::TospMinSaneKnSp,
+        "tosp_init_guess_kn_mult" => Variable::TospInitGuessKnMult,
+        "tosp_init_guess_xht_mult" => Variable::TospInitGuessXhtMult,
+        "tosp_max_sane_kn_thresh" => Variable::TospMaxSaneKnThresh,
+        "tosp_flip_caution" => Variable::TospFlipCaution,
+        "tosp_large_kerning" => Variable::TospLargeKerning,
+        "tosp_dont_fool_with_small_kerns" => Variable::TospDontFoolWithSmallKerns,
+        "tosp_near_lh_edge" => Variable::TospNearLhEdge,
+        "tosp_silly_kn_sp_gap" => Variable::TospSillyKnSpGap,
+        "tosp_pass_wide_fuzz_sp_to_context" => Variable::TospPassWideFuzzSpToContext,
+        "textord_noise_area_ratio" => Variable::TextordNoiseAreaRatio,
+        "textord_initialx_ile" => Variable::TextordInitialxIle,
+        "textord_initialasc_ile" => Variable::TextordInitialascIle,
+        "textord_noise_sizelimit" => Variable::TextordNoiseSizelimit,
+        "textord_noise_normratio" => Variable::TextordNoiseNormratio,
+        "textord_noise_syfract" => Variable::TextordNoiseSyfract,
+        "textord_noise_sxfract" => Variable::TextordNoiseSxfract,
+        "textord_noise_hfract" => Variable::TextordNoiseHfract,
+        "textord_noise_rowratio" => Variable::TextordNoiseRowratio,
+        "textord_blshift_maxshift" => Variable::TextordBlshiftMaxshift,
+        "textord_blshift_xfraction" => Variable::TextordBlshiftXfraction,
+        _ => {
+            return Err(Error::TesseractVariableName {
+                value: s.as_ref().to_owned(),
+            })
+        }
+    })
+}
@@ -0,0 +1,270 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write as _,
+    fs, io,
+    path::Path,
+};
+
+use log::info;
+
+use crate::Error;
+
+/// Minimum number of occurrences of a correction in the training pair to be learned.
+const MIN_RULE_COUNT: usize = 2;
+
+/// Maximum number of characters of a corrected segment, longer edits are rewrites, not `OCR` confusions.
+const MAX_SEGMENT_LEN: usize = 4;
+
+/// Character-level substitutions learned from a manually corrected `OCR` output.
+///
+/// Each rule replaces a segment with its surrounding characters, so a confusion is
+/// only corrected in the context where it was seen (e.g. `l` to `I` at the start of a line).
+#[derive(Debug, Default)]
+pub struct CorrectionModel {
+    /// Replaced text, replacement and number of occurrences, most frequent first.
+    rules: Vec<(String, String, usize)>,
+}
+
+impl CorrectionModel {
+    /// Learn the corrections from pairs of `OCR` output and corrected text of the same subtitles.
+    #[must_use]
+    pub fn learn<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut counts = HashMap::<(String, String), usize>::new();
+        for (ocr, corrected) in pairs {
+            let ocr = with_boundaries(ocr);
+            let corrected = with_boundaries(corrected);
+            for edit in edits(&ocr, &corrected) {
+                *counts.entry(edit).or_default() += 1;
+            }
+        }
+
+        // Drop the ambiguous segments, corrected in different ways.
+        let mut by_source = BTreeMap::<&str, Vec<(&str, usize)>>::new();
+        for ((from, to), count) in &counts {
+            by_source.entry(from).or_default().push((to, *count));
+        }
+        let mut rules = by_source
+            .into_iter()
+            .filter_map(|(from, targets)| match targets[..] {
+                [(to, count)] if count >= MIN_RULE_COUNT => {
+                    Some((from.to_owned(), to.to_owned(), count))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        rules.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+        Self { rules }
+    }
+
+    /// Read a model written with [`CorrectionModel::write`].
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the file can't be read or a line is invalid.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let rules = fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let mut fields = line.split('\t');
+                match (fields.next(), fields.next(), fields.next(), fields.next()) {
+                    (Some(from), Some(to), Some(count), None) => count
+                        .parse()
+                        .ok()
+                        .map(|count| (unescape(from), unescape(to), count)),
+                    _ => None,
+                }
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid correction rule `{line}`"),
+                    )
+                })
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Write the model in the file at `path`, one tab-separated rule per line.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the file can't be written.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut content = String::new();
+        for (from, to, count) in &self.rules {
+            let _ = writeln!(content, "{}\t{}\t{count}", escape(from), escape(to));
+        }
+        crate::create_parent_dirs(path)?;
+        fs::write(path, content)
+    }
+
+    /// Number of rules of the model.
+    #[must_use]
+    pub fn rule_count(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Apply the corrections on `text`.
+    #[must_use]
+    pub fn apply(&self, text: &str) -> String {
+        // The boundaries are kept by the rules, as they are only part of their context.
+        let mut text = format!("\n{text}\n");
+        for (from, to, _) in &self.rules {
+            text = text.replace(from.as_str(), to);
+        }
+        text[1..text.len() - 1].to_owned()
+    }
+}
+
+/// Learn a correction model from the `OCR` output in `ocr_path` and its corrected version
+/// in `corrected_path`, and write it to `model_path`.
+///
+/// Subtitles of the two files are paired by their timing line, so the corrected file must keep the times.
+///
+/// # Errors
+///
+/// Will return [`Error::ReadCorrections`] if a subtitle file can't be read.
+/// Will return [`Error::WriteCorrections`] if the model file can't be written.
+pub fn learn_corrections(
+    ocr_path: &Path,
+    corrected_path: &Path,
+    model_path: &Path,
+) -> Result<(), Error> {
+    let read = |path: &Path| {
+        fs::read_to_string(path).map_err(|source| Error::ReadCorrections {
+            path: path.to_path_buf(),
+            source,
+        })
+    };
+    let ocr = read(ocr_path)?;
+    let corrected = read(corrected_path)?;
+    let corrected_cues = srt_cues(&corrected).collect::<HashMap<_, _>>();
+    let pairs = srt_cues(&ocr)
+        .filter_map(|(time, text)| Some((text, corrected_cues.get(time)?.as_str())))
+        .collect::<Vec<_>>();
+
+    let model = CorrectionModel::learn(
+        pairs
+            .iter()
+            .map(|(ocr, corrected)| (ocr.as_str(), *corrected)),
+    );
+    model
+        .write(model_path)
+        .map_err(|source| Error::WriteCorrections {
+            path: crate::absolute_path(model_path),
+            source,
+        })?;
+    info!(
+        "{} correction rules learned from {} paired subtitles, written to {}",
+        model.rule_count(),
+        pairs.len(),
+        model_path.display()
+    );
+    Ok(())
+}
+
+/// Get the timing line and the text of the cues of `SRT` content.
+fn srt_cues(content: &str) -> impl Iterator<Item = (&str, String)> {
+    content
+        .split("\n\n")
+        .flat_map(|block| block.split("\r\n\r\n"))
+        .filter_map(|block| {
+            let mut lines = block.trim_matches(['\r', '\n']).lines();
+            let _index = lines.next()?;
+            let time = lines.next()?.trim();
+            let text = lines.map(str::trim_end).collect::<Vec<_>>().join("\n");
+            Some((time, text))
+        })
+}
+
+/// Surround `text` with line breaks, so the rules can match its start and end.
+fn with_boundaries(text: &str) -> String {
+    format!("\n{}\n", text.trim())
+}
+
+/// Get the edits to go from `source` to `target`, each one with a character of context on both sides.
+fn edits(source: &str, target: &str) -> Vec<(String, String)> {
+    let source = source.chars().collect::<Vec<_>>();
+    let target = target.chars().collect::<Vec<_>>();
+
+    // Levenshtein distances between the suffixes of both texts.
+    let width = target.len() + 1;
+    let mut dist = vec![0_usize; (source.len() + 1) * width];
+    for i in (0..=source.len()).rev() {
+        for j in (0..=target.len()).rev() {
+            dist[i * width + j] = if i == source.len() {
+                target.len() - j
+            } else if j == target.len() {
+                source.len() - i
+            } else if source[i] == target[j] {
+                dist[(i + 1) * width + j + 1]
+            } else {
+                1 + dist[(i + 1) * width + j + 1]
+                    .min(dist[(i + 1) * width + j])
+                    .min(dist[i * width + j + 1])
+            };
+        }
+    }
+
+    // Follow the alignment, grouping successive differences.
+    let mut edits = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let mut edit_start: Option<(usize, usize)> = None;
+    while i < source.len() || j < target.len() {
+        let is_match = i < source.len() && j < target.len() && source[i] == target[j];
+        if is_match && dist[i * width + j] == dist[(i + 1) * width + j + 1] {
+            if let Some((start_i, start_j)) = edit_start.take() {
+                let is_short = i - start_i <= MAX_SEGMENT_LEN && j - start_j <= MAX_SEGMENT_LEN;
+                if is_short && start_i > 0 && start_j > 0 {
+                    let from = source[start_i - 1..=i].iter().collect();
+                    let to = target[start_j - 1..=j].iter().collect();
+                    edits.push((from, to));
+                }
+            }
+            i += 1;
+            j += 1;
+            continue;
+        }
+        edit_start.get_or_insert((i, j));
+        if i < source.len()
+            && j < target.len()
+            && dist[i * width + j] == 1 + dist[(i + 1) * width + j + 1]
+        {
+            i += 1;
+            j += 1;
+        } else if i < source.len() && dist[i * width + j] == 1 + dist[(i + 1) * width + j] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    edits
+}
+
+/// Escape the tabs, line breaks and backslashes of a rule segment.
+fn escape(segment: &str) -> String {
+    segment
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+/// Reverse [`escape`].
+fn unescape(segment: &str) -> String {
+    let mut unescaped = String::with_capacity(segment.len());
+    let mut chars = segment.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        unescaped.push(match chars.next() {
+            Some('t') => '\t',
+            Some('n') => '\n',
+            Some(other) => other,
+            None => '\\',
+        });
+    }
+    unescaped
+}
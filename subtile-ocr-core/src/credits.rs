@@ -0,0 +1,88 @@
+use std::{fs, io, path::Path};
+
+use log::info;
+use regex::RegexSet;
+
+use crate::{cue::Cue, qa::QaReport};
+
+/// Patterns of the common credits added by rippers, matched case-insensitively.
+const DEFAULT_PATTERNS: &[&str] = &[
+    r"\bsub(title)?s?\s+(ripped|synced|resynced|corrected|created|translated|edited)\b",
+    r"\b(ripped|synced|resynced|sync|corrected|encoded|translated)\s+(and\s+\w+\s+)?by\b",
+    r"\bdownloaded\s+from\b",
+    r"\b(opensubtitles|addic7ed|subscene|podnapisi|yify|yts|tvsubtitles)\b",
+    r"(\bwww\.|https?://)\S+",
+];
+
+/// Detector of the credits subtitles, added by rippers.
+#[derive(Debug)]
+pub struct CreditsDetector {
+    patterns: RegexSet,
+}
+
+impl CreditsDetector {
+    /// Create a detector with the default patterns, and the additional ones of the file at `path`, if any.
+    ///
+    /// The file has one regular expression per line, empty lines and lines starting with `#` are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the file can't be read, or if a pattern is invalid.
+    pub fn new(path: Option<&Path>) -> io::Result<Self> {
+        let content = path.map(fs::read_to_string).transpose()?;
+        let custom = content
+            .iter()
+            .flat_map(|content| content.lines())
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        let patterns = DEFAULT_PATTERNS
+            .iter()
+            .copied()
+            .chain(custom)
+            .map(|pattern| format!("(?i){pattern}"));
+        let patterns = RegexSet::new(patterns)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok(Self { patterns })
+    }
+
+    /// Check if `text` looks like credits.
+    #[must_use]
+    pub fn is_credits(&self, text: &str) -> bool {
+        self.patterns.is_match(text)
+    }
+
+    /// Flag the credits subtitles in `qa`, and remove them if `strip` is set.
+    #[must_use]
+    #[profiling::function]
+    pub fn handle(&self, subtitles: Vec<Cue>, strip: bool, qa: &mut QaReport) -> Vec<Cue> {
+        let mut removed = Vec::new();
+        let subtitles = subtitles
+            .into_iter()
+            .filter(|cue| {
+                if !self.is_credits(&cue.text) {
+                    return true;
+                }
+                let text = one_line(&cue.text);
+                if strip {
+                    removed.push(text);
+                } else {
+                    qa.add(cue.time, format!("looks like credits: `{text}`"));
+                }
+                !strip
+            })
+            .collect();
+        if !removed.is_empty() {
+            info!(
+                "{} credits subtitles removed:\n\t{}",
+                removed.len(),
+                removed.join("\n\t")
+            );
+        }
+        subtitles
+    }
+}
+
+/// Join the lines of a subtitle `text`, to log it on one line.
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
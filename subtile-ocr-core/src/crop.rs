@@ -0,0 +1,52 @@
+use std::fmt;
+
+use image::{GrayImage, Luma};
+
+/// Region of interest of the display, out of which the subtitle pixels are ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Crop {
+    /// Left of the region, in display pixels.
+    pub x: u32,
+    /// Top of the region, in display pixels.
+    pub y: u32,
+    /// Width of the region.
+    pub width: u32,
+    /// Height of the region.
+    pub height: u32,
+}
+
+impl Crop {
+    /// Check if the display pixel at `x`, `y` is in the region.
+    #[must_use]
+    pub const fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && y >= self.y && x - self.x < self.width && y - self.y < self.height
+    }
+
+    /// Check if the region is inside a display of `width` x `height` pixels.
+    #[must_use]
+    pub const fn fits(&self, width: u32, height: u32) -> bool {
+        self.x.saturating_add(self.width) <= width && self.y.saturating_add(self.height) <= height
+    }
+
+    /// Blank the pixels of the `OCR` image `image` out of the region.
+    ///
+    /// The image shows the display from `left`, `top`, surrounded by `border` pixels.
+    pub fn mask(&self, image: &mut GrayImage, left: u32, top: u32, border: u32) {
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            let display_x = (left + x).checked_sub(border);
+            let display_y = (top + y).checked_sub(border);
+            let inside = display_x
+                .zip(display_y)
+                .is_some_and(|(x, y)| self.contains(x, y));
+            if !inside {
+                *pixel = Luma([255]);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Crop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{},{}", self.x, self.y, self.width, self.height)
+    }
+}
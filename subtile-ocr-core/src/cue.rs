@@ -0,0 +1,125 @@
+use std::ops::Range;
+
+use subtile::time::TimeSpan;
+
+/// Rows of the display covered by the image of a subtitle,
+/// to tell apart the subtitles shown at the same time (e.g. a sign at the top and the dialogue at the bottom).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    /// First row of the image on the display.
+    pub top: u32,
+    /// Row after the last row of the image on the display.
+    pub bottom: u32,
+}
+
+impl Region {
+    /// Create the region of an image of `height` rows displayed from row `top`.
+    #[must_use]
+    pub const fn new(top: u32, height: u32) -> Self {
+        Self {
+            top,
+            bottom: top + height,
+        }
+    }
+
+    /// Check if the region shares rows of the display with `other`.
+    #[must_use]
+    pub const fn intersects(self, other: Self) -> bool {
+        self.top < other.bottom && other.top < self.bottom
+    }
+
+    /// Get the region of the `rows` of an image of `height` rows covering this region,
+    /// in proportion if the image has been scaled or bordered.
+    #[must_use]
+    pub fn part(self, rows: &Range<u32>, height: u32) -> Self {
+        let row = |row: u32| {
+            let offset = u64::from(row.min(height)) * u64::from(self.bottom - self.top)
+                / u64::from(height.max(1));
+            self.top + u32::try_from(offset).unwrap_or(u32::MAX)
+        };
+        Self {
+            top: row(rows.start),
+            bottom: row(rows.end),
+        }
+    }
+
+    /// Get the region covering this region and `other`.
+    #[must_use]
+    fn union(self, other: Self) -> Self {
+        Self {
+            top: self.top.min(other.top),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+}
+
+/// Subtitle through the post-processing, with what is known of its recognition.
+///
+/// The post-processing steps merging or splitting subtitles keep these with the text,
+/// instead of matching them again by time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cue {
+    /// Time of the subtitle.
+    pub time: TimeSpan,
+    /// Recognized text, corrected by the post-processing.
+    pub text: String,
+    /// Italic flag of each line of the subtitle image, from the top.
+    pub italics: Vec<bool>,
+    /// Mean confidence in percent of the words of the `OCR` result, `None` without word.
+    pub confidence: Option<u32>,
+    /// Confidence in percent of the `OCR` result, if flagged as too low.
+    pub low_confidence: Option<u32>,
+    /// Rows of the display covered by the subtitle image, `None` if unknown.
+    pub region: Option<Region>,
+}
+
+impl Cue {
+    /// Create the cue of `text` at `time` from several `cues`, in their order,
+    /// like a merge of their texts.
+    ///
+    /// The merged cue has the lowest of the confidences, and of the flagged confidences, of the `cues`,
+    /// and covers their regions if they are all known.
+    #[must_use]
+    pub fn merged<'a>(
+        time: TimeSpan,
+        text: String,
+        cues: impl IntoIterator<Item = &'a Self>,
+    ) -> Self {
+        let mut italics = Vec::new();
+        let mut confidence = None;
+        let mut low_confidence = None;
+        let mut region = None;
+        for (idx, cue) in cues.into_iter().enumerate() {
+            italics.extend_from_slice(&cue.italics);
+            confidence = lowest(confidence, cue.confidence);
+            low_confidence = lowest(low_confidence, cue.low_confidence);
+            region = match (idx, region, cue.region) {
+                (0, _, region) => region,
+                (_, Some(merged), Some(other)) => Some(Region::union(merged, other)),
+                _ => None,
+            };
+        }
+        Self {
+            time,
+            text,
+            italics,
+            confidence,
+            low_confidence,
+            region,
+        }
+    }
+
+    /// Get the time and text of the cue, to write it.
+    #[must_use]
+    pub fn into_subtitle(self) -> (TimeSpan, String) {
+        (self.time, self.text)
+    }
+}
+
+/// Get the lowest of the `merged` and `other` confidences, ignoring the missing ones.
+fn lowest(merged: Option<u32>, other: Option<u32>) -> Option<u32> {
+    match (merged, other) {
+        (Some(merged), Some(other)) => Some(u32::min(merged, other)),
+        (merged, other) => merged.or(other),
+    }
+}
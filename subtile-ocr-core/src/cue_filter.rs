@@ -0,0 +1,643 @@
+use std::{iter::Peekable, str::CharIndices};
+
+use image::GrayImage;
+use log::info;
+use rayon::prelude::*;
+use subtile::time::TimeSpan;
+use thiserror::Error;
+
+use crate::filter::ink_pixels;
+
+/// Error of cue filter expression parsing.
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Unexpected end of filter expression, expected {expected}")]
+    UnexpectedEnd { expected: &'static str },
+
+    #[error("Unexpected `{token}` at position {pos} of filter expression, expected {expected}")]
+    UnexpectedToken {
+        token: String,
+        pos: usize,
+        expected: &'static str,
+    },
+
+    #[error("Unknown attribute `{name}` in filter expression, expected one of: duration, start, end, index, width, height, ink, repeat")]
+    UnknownAttribute { name: String },
+
+    #[error("Attribute `{name}` isn't available in filter expressions, the decoded images have no position on the display or forced flag")]
+    UnavailableAttribute { name: String },
+
+    #[error("Filter expression nested too deeply at position {pos}, the limit is {MAX_DEPTH} levels of `!` and parentheses")]
+    TooDeep { pos: usize },
+
+    #[error("Invalid value `{value}` for `{attribute}` in filter expression")]
+    InvalidValue { attribute: String, value: String },
+}
+
+/// Maximum nesting of `!` and parentheses in a filter expression, to bound the recursion of the parser.
+const MAX_DEPTH: usize = 64;
+
+/// Attribute of a decoded subtitle, usable in a filter expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Attribute {
+    /// Display duration, in milliseconds.
+    Duration,
+    /// Start time, in milliseconds.
+    Start,
+    /// End time, in milliseconds.
+    End,
+    /// Number of the subtitle in the input, from 1.
+    Index,
+    /// Width of the image, in pixels.
+    Width,
+    /// Height of the image, in pixels.
+    Height,
+    /// Number of text pixels of the image.
+    Ink,
+    /// 1 if the image repeats the image of the previous subtitle of the input, else 0.
+    Repeat,
+}
+
+impl Attribute {
+    /// Get the attribute named `name`.
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "duration" => Self::Duration,
+            "start" => Self::Start,
+            "end" => Self::End,
+            "index" => Self::Index,
+            "width" => Self::Width,
+            "height" => Self::Height,
+            "ink" => Self::Ink,
+            "repeat" => Self::Repeat,
+            _ => return None,
+        })
+    }
+
+    /// Check if the attribute is a flag, usable alone as a condition, like `repeat` for `repeat != 0`.
+    const fn is_flag(self) -> bool {
+        matches!(self, Self::Repeat)
+    }
+
+    /// Check if the attribute is a time, which values accept a `ms` or `s` unit.
+    const fn is_time(self) -> bool {
+        matches!(self, Self::Duration | Self::Start | Self::End)
+    }
+
+    /// Get the value of the attribute for the subtitle `index` (from 0) shown at `time`,
+    /// after the subtitle with the `previous` image if any.
+    fn value(
+        self,
+        index: usize,
+        time: TimeSpan,
+        image: &GrayImage,
+        previous: Option<&GrayImage>,
+    ) -> f64 {
+        match self {
+            Self::Duration => (time.end.msecs() - time.start.msecs()) as f64,
+            Self::Start => time.start.msecs() as f64,
+            Self::End => time.end.msecs() as f64,
+            Self::Index => (index + 1) as f64,
+            Self::Width => f64::from(image.width()),
+            Self::Height => f64::from(image.height()),
+            Self::Ink => ink_pixels(image) as f64,
+            Self::Repeat => f64::from(u8::from(previous == Some(image))),
+        }
+    }
+}
+
+/// Comparison operator of a filter expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparison {
+    /// `==`
+    Equal,
+    /// `!=`
+    NotEqual,
+    /// `<`
+    Less,
+    /// `<=`
+    LessOrEqual,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    /// Get the operator of the comparison.
+    const fn symbol(self) -> &'static str {
+        match self {
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::Less => "<",
+            Self::LessOrEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterOrEqual => ">=",
+        }
+    }
+}
+
+/// Node of a parsed filter expression.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    /// Both expressions are true.
+    And(Box<Expr>, Box<Expr>),
+    /// At least one of the expressions is true.
+    Or(Box<Expr>, Box<Expr>),
+    /// The expression is false.
+    Not(Box<Expr>),
+    /// An attribute compared to a value.
+    Compare(Attribute, Comparison, f64),
+}
+
+impl Expr {
+    /// Evaluate the expression for the subtitle `index` (from 0) shown at `time`,
+    /// after the subtitle with the `previous` image if any.
+    #[must_use]
+    pub fn eval(
+        &self,
+        index: usize,
+        time: TimeSpan,
+        image: &GrayImage,
+        previous: Option<&GrayImage>,
+    ) -> bool {
+        match self {
+            Self::And(lhs, rhs) => {
+                lhs.eval(index, time, image, previous) && rhs.eval(index, time, image, previous)
+            }
+            Self::Or(lhs, rhs) => {
+                lhs.eval(index, time, image, previous) || rhs.eval(index, time, image, previous)
+            }
+            Self::Not(expr) => !expr.eval(index, time, image, previous),
+            Self::Compare(attribute, comparison, value) => {
+                let actual = attribute.value(index, time, image, previous);
+                match comparison {
+                    Comparison::Equal => actual == *value,
+                    Comparison::NotEqual => actual != *value,
+                    Comparison::Less => actual < *value,
+                    Comparison::LessOrEqual => actual <= *value,
+                    Comparison::Greater => actual > *value,
+                    Comparison::GreaterOrEqual => actual >= *value,
+                }
+            }
+        }
+    }
+}
+
+/// Filter of the decoded subtitles, kept only if its expression is true.
+///
+/// Expressions compare attributes of the subtitles to values, combined with `&&`, `||`, `!`
+/// and parentheses, e.g. `duration > 300ms && (height < 200 || ink >= 50)`. The `repeat` flag
+/// can be used alone, e.g. `!repeat`.
+/// The `index` is the number of the subtitle in the decoded input, before any filter.
+///
+/// The position of the subtitles on the display and their forced flag aren't known when the
+/// expression is evaluated, so `position` and `forced` are rejected.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CueFilter {
+    expr: Expr,
+}
+
+impl CueFilter {
+    /// Parse a filter `expression`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an [`Error`] if the expression is invalid.
+    pub fn parse(expression: &str) -> Result<Self, Error> {
+        let mut parser = Parser {
+            tokens: tokenize(expression)?.into_iter().peekable(),
+            depth: 0,
+        };
+        let expr = parser.or()?;
+        match parser.tokens.next() {
+            Some((pos, token)) => Err(Error::UnexpectedToken {
+                token: token.to_string(),
+                pos,
+                expected: "`&&`, `||` or the end",
+            }),
+            None => Ok(Self { expr }),
+        }
+    }
+
+    /// Create the filter of the images with at least `min_ink_pixels` text pixels,
+    /// and not repeating the previous image for less than `duplicate_max_duration` ms.
+    ///
+    /// This is the expression `ink >= min_ink_pixels && !(repeat == 1 && duration < duplicate_max_duration)`,
+    /// without the parts with a value of 0. `None` if both values are 0.
+    #[must_use]
+    pub fn from_limits(min_ink_pixels: usize, duplicate_max_duration: u32) -> Option<Self> {
+        let min_ink = (min_ink_pixels > 0).then_some(Expr::Compare(
+            Attribute::Ink,
+            Comparison::GreaterOrEqual,
+            min_ink_pixels as f64,
+        ));
+        let not_duplicate = (duplicate_max_duration > 0).then(|| {
+            Expr::Not(Box::new(Expr::And(
+                Box::new(Expr::Compare(Attribute::Repeat, Comparison::Equal, 1.0)),
+                Box::new(Expr::Compare(
+                    Attribute::Duration,
+                    Comparison::Less,
+                    f64::from(duplicate_max_duration),
+                )),
+            )))
+        });
+        let expr = match (min_ink, not_duplicate) {
+            (Some(lhs), Some(rhs)) => Expr::And(Box::new(lhs), Box::new(rhs)),
+            (expr, None) | (None, expr) => expr?,
+        };
+        Some(Self { expr })
+    }
+
+    /// Combine with the `other` filter, keeping the subtitles kept by both.
+    #[must_use]
+    pub fn and(self, other: Self) -> Self {
+        Self {
+            expr: Expr::And(Box::new(self.expr), Box::new(other.expr)),
+        }
+    }
+
+    /// Get the parsed expression.
+    #[must_use]
+    pub const fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    /// Keep only the subtitles for which the expression is true.
+    #[must_use]
+    pub fn apply(
+        &self,
+        times: Vec<TimeSpan>,
+        images: Vec<GrayImage>,
+    ) -> (Vec<TimeSpan>, Vec<GrayImage>) {
+        self.apply_from(0, None, times, images, |_, _, _| {})
+    }
+
+    /// Keep only the subtitles for which the expression is true, the first subtitle
+    /// being at index `first_index` of the input after the `previous` image, e.g. for
+    /// a batch of subtitles.
+    ///
+    /// Each removed subtitle is given to `on_removed`, with its index in the input.
+    #[profiling::function]
+    pub fn apply_from(
+        &self,
+        first_index: usize,
+        previous: Option<&GrayImage>,
+        times: Vec<TimeSpan>,
+        images: Vec<GrayImage>,
+        mut on_removed: impl FnMut(usize, TimeSpan, &GrayImage),
+    ) -> (Vec<TimeSpan>, Vec<GrayImage>) {
+        let count = times.len();
+        let kept = (0..count)
+            .into_par_iter()
+            .map(|idx| {
+                let previous = idx.checked_sub(1).map(|idx| &images[idx]).or(previous);
+                self.expr
+                    .eval(first_index + idx, times[idx], &images[idx], previous)
+            })
+            .collect::<Vec<_>>();
+        let (times, images): (Vec<_>, Vec<_>) = times
+            .into_iter()
+            .zip(images)
+            .zip(kept)
+            .enumerate()
+            .filter_map(|(idx, ((time, image), kept))| {
+                if !kept {
+                    on_removed(first_index + idx, time, &image);
+                }
+                kept.then_some((time, image))
+            })
+            .unzip();
+        if times.len() < count {
+            info!(
+                "{} subtitles have been removed by the filter expression",
+                count - times.len()
+            );
+        }
+        (times, images)
+    }
+}
+
+/// Token of a filter expression.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Value(String),
+    Comparison(Comparison),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ident(text) | Self::Value(text) => f.write_str(text),
+            Self::Comparison(comparison) => f.write_str(comparison.symbol()),
+            Self::And => f.write_str("&&"),
+            Self::Or => f.write_str("||"),
+            Self::Not => f.write_str("!"),
+            Self::Open => f.write_str("("),
+            Self::Close => f.write_str(")"),
+        }
+    }
+}
+
+/// Split a filter `expression` into tokens, with their position.
+fn tokenize(expression: &str) -> Result<Vec<(usize, Token)>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.char_indices().peekable();
+    while let Some((pos, c)) = chars.next() {
+        let next = chars.peek().map(|(_, c)| *c);
+        let token = match (c, next) {
+            (c, _) if c.is_whitespace() => continue,
+            ('&', Some('&')) => skip_then(&mut chars, Token::And),
+            ('|', Some('|')) => skip_then(&mut chars, Token::Or),
+            ('=', Some('=')) => skip_then(&mut chars, Token::Comparison(Comparison::Equal)),
+            ('!', Some('=')) => skip_then(&mut chars, Token::Comparison(Comparison::NotEqual)),
+            ('<', Some('=')) => skip_then(&mut chars, Token::Comparison(Comparison::LessOrEqual)),
+            ('>', Some('=')) => {
+                skip_then(&mut chars, Token::Comparison(Comparison::GreaterOrEqual))
+            }
+            ('<', _) => Token::Comparison(Comparison::Less),
+            ('>', _) => Token::Comparison(Comparison::Greater),
+            ('!', _) => Token::Not,
+            ('(', _) => Token::Open,
+            (')', _) => Token::Close,
+            (c, _) if c.is_ascii_alphanumeric() || c == '.' || c == '_' => {
+                let mut word = String::from(c);
+                while let Some((_, c)) =
+                    chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '.' || *c == '_')
+                {
+                    word.push(c);
+                }
+                if c.is_ascii_digit() || c == '.' {
+                    Token::Value(word)
+                } else {
+                    Token::Ident(word)
+                }
+            }
+            (c, _) => {
+                return Err(Error::UnexpectedToken {
+                    token: c.to_string(),
+                    pos,
+                    expected: "an attribute, a value, an operator or a parenthesis",
+                })
+            }
+        };
+        tokens.push((pos, token));
+    }
+    Ok(tokens)
+}
+
+/// Skip the next char of a two chars operator, and return its `token`.
+fn skip_then(chars: &mut Peekable<CharIndices>, token: Token) -> Token {
+    chars.next();
+    token
+}
+
+/// Recursive descent parser of filter expressions.
+struct Parser<I: Iterator<Item = (usize, Token)>> {
+    tokens: Peekable<I>,
+    /// Nesting of the `!` and parentheses being parsed.
+    depth: usize,
+}
+
+impl<I: Iterator<Item = (usize, Token)>> Parser<I> {
+    /// Parse `and ( || and )*`.
+    fn or(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.and()?;
+        while self
+            .tokens
+            .next_if(|(_, token)| *token == Token::Or)
+            .is_some()
+        {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    /// Parse `unary ( && unary )*`.
+    fn and(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.unary()?;
+        while self
+            .tokens
+            .next_if(|(_, token)| *token == Token::And)
+            .is_some()
+        {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    /// Parse `!unary`, `( or )`, `attribute comparison value` or `flag`.
+    fn unary(&mut self) -> Result<Expr, Error> {
+        const EXPECTED: &str = "an attribute, `!` or `(`";
+        match self.next(EXPECTED)? {
+            (pos, Token::Not) => {
+                let expr = self.nested(pos, Self::unary)?;
+                Ok(Expr::Not(Box::new(expr)))
+            }
+            (pos, Token::Open) => {
+                let expr = self.nested(pos, Self::or)?;
+                match self.next("`)`")? {
+                    (_, Token::Close) => Ok(expr),
+                    (pos, token) => Err(unexpected(pos, &token, "`)`")),
+                }
+            }
+            (_, Token::Ident(name)) => {
+                let attribute = Attribute::from_name(&name).ok_or_else(|| match name.as_str() {
+                    "position" | "forced" => Error::UnavailableAttribute { name: name.clone() },
+                    _ => Error::UnknownAttribute { name: name.clone() },
+                })?;
+                let compared = matches!(self.tokens.peek(), Some((_, Token::Comparison(_))));
+                if attribute.is_flag() && !compared {
+                    return Ok(Expr::Compare(attribute, Comparison::NotEqual, 0.0));
+                }
+                let comparison = match self.next("a comparison operator")? {
+                    (_, Token::Comparison(comparison)) => comparison,
+                    (pos, token) => return Err(unexpected(pos, &token, "a comparison operator")),
+                };
+                let value = match self.next("a value")? {
+                    (_, Token::Value(value)) => parse_value(&name, attribute, &value)?,
+                    (pos, token) => return Err(unexpected(pos, &token, "a value")),
+                };
+                Ok(Expr::Compare(attribute, comparison, value))
+            }
+            (pos, token) => Err(unexpected(pos, &token, EXPECTED)),
+        }
+    }
+
+    /// Parse with `parse` the expression nested in the `!` or the parenthesis at `pos`.
+    fn nested(
+        &mut self,
+        pos: usize,
+        parse: impl FnOnce(&mut Self) -> Result<Expr, Error>,
+    ) -> Result<Expr, Error> {
+        if self.depth == MAX_DEPTH {
+            return Err(Error::TooDeep { pos });
+        }
+        self.depth += 1;
+        let expr = parse(self);
+        self.depth -= 1;
+        expr
+    }
+
+    /// Get the next token, or an error if there is no more tokens.
+    fn next(&mut self, expected: &'static str) -> Result<(usize, Token), Error> {
+        self.tokens.next().ok_or(Error::UnexpectedEnd { expected })
+    }
+}
+
+/// Create an [`Error::UnexpectedToken`].
+fn unexpected(pos: usize, token: &Token, expected: &'static str) -> Error {
+    Error::UnexpectedToken {
+        token: token.to_string(),
+        pos,
+        expected,
+    }
+}
+
+/// Parse the `value` compared to `attribute`; times accept a `ms` (default) or `s` unit.
+fn parse_value(name: &str, attribute: Attribute, value: &str) -> Result<f64, Error> {
+    let (number, factor) = match (value.strip_suffix("ms"), value.strip_suffix('s')) {
+        (Some(number), _) if attribute.is_time() => (number, 1.0),
+        (None, Some(number)) if attribute.is_time() => (number, 1000.0),
+        _ => (value, 1.0),
+    };
+    number
+        .parse::<f64>()
+        .map(|number| number * factor)
+        .map_err(|_| Error::InvalidValue {
+            attribute: name.into(),
+            value: value.into(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Attribute, Comparison, CueFilter, Error, Expr, MAX_DEPTH};
+
+    /// Parse `expression`, which must be valid.
+    fn parse(expression: &str) -> Expr {
+        CueFilter::parse(expression).unwrap().expr().clone()
+    }
+
+    /// Comparison of `attribute` to `value`.
+    fn compare(attribute: Attribute, comparison: Comparison, value: f64) -> Box<Expr> {
+        Box::new(Expr::Compare(attribute, comparison, value))
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let width = compare(Attribute::Width, Comparison::Less, 10.0);
+        let height = compare(Attribute::Height, Comparison::Equal, 20.0);
+        let ink = compare(Attribute::Ink, Comparison::GreaterOrEqual, 30.0);
+        assert_eq!(
+            parse("width < 10 || height == 20 && ink >= 30"),
+            Expr::Or(
+                width.clone(),
+                Box::new(Expr::And(height.clone(), ink.clone()))
+            )
+        );
+        assert_eq!(
+            parse("(width < 10 || height == 20) && ink >= 30"),
+            Expr::And(Box::new(Expr::Or(width, height)), ink)
+        );
+    }
+
+    #[test]
+    fn not_applies_to_the_next_operand() {
+        let repeat = compare(Attribute::Repeat, Comparison::NotEqual, 0.0);
+        let index = compare(Attribute::Index, Comparison::Greater, 1.0);
+        assert_eq!(
+            parse("!repeat && index > 1"),
+            Expr::And(Box::new(Expr::Not(repeat.clone())), index.clone())
+        );
+        assert_eq!(
+            parse("!(repeat && index > 1)"),
+            Expr::Not(Box::new(Expr::And(repeat, index)))
+        );
+    }
+
+    #[test]
+    fn time_units() {
+        for (expression, value) in [
+            ("duration > 300", 300.0),
+            ("duration > 300ms", 300.0),
+            ("duration > 1.5s", 1500.0),
+        ] {
+            assert_eq!(
+                parse(expression),
+                *compare(Attribute::Duration, Comparison::Greater, value)
+            );
+        }
+        assert!(matches!(
+            CueFilter::parse("height > 2s"),
+            Err(Error::InvalidValue { attribute, value }) if attribute == "height" && value == "2s"
+        ));
+    }
+
+    #[test]
+    fn flags_only_alone() {
+        assert_eq!(
+            parse("repeat == 1"),
+            *compare(Attribute::Repeat, Comparison::Equal, 1.0)
+        );
+        assert!(matches!(
+            CueFilter::parse("ink && repeat"),
+            Err(Error::UnexpectedToken { pos: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn error_positions() {
+        assert!(matches!(
+            CueFilter::parse("width < 10 height"),
+            Err(Error::UnexpectedToken { token, pos: 11, .. }) if token == "height"
+        ));
+        assert!(matches!(
+            CueFilter::parse("(width < 10"),
+            Err(Error::UnexpectedEnd { expected: "`)`" })
+        ));
+        assert!(matches!(
+            CueFilter::parse("width < 10 # 2"),
+            Err(Error::UnexpectedToken { token, pos: 11, .. }) if token == "#"
+        ));
+        assert!(matches!(
+            CueFilter::parse("width <"),
+            Err(Error::UnexpectedEnd {
+                expected: "a value"
+            })
+        ));
+    }
+
+    #[test]
+    fn unknown_and_unavailable_attributes() {
+        assert!(matches!(
+            CueFilter::parse("size > 2"),
+            Err(Error::UnknownAttribute { name }) if name == "size"
+        ));
+        assert!(matches!(
+            CueFilter::parse("duration > 300ms && !forced"),
+            Err(Error::UnavailableAttribute { name }) if name == "forced"
+        ));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let nested = |depth| format!("{}repeat{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(CueFilter::parse(&nested(MAX_DEPTH)).is_ok());
+        assert!(matches!(
+            CueFilter::parse(&nested(MAX_DEPTH + 1)),
+            Err(Error::TooDeep { pos }) if pos == MAX_DEPTH
+        ));
+        assert!(matches!(
+            CueFilter::parse(&"!".repeat(100_000)),
+            Err(Error::TooDeep { pos: MAX_DEPTH })
+        ));
+    }
+}
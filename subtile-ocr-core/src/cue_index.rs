@@ -0,0 +1,73 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use subtile::time::TimeSpan;
+
+use crate::{diagnostics, timing::format_timestamp};
+
+/// Write an index of the subtitles times in `path`.
+///
+/// The index is written as `JSON` if the extension of `path` is `json`, as tab-separated text otherwise.
+/// With an `interval` in seconds, only the first subtitle starting in each interval is listed.
+/// The `JSON` entries are tagged with the Tesseract language(s) `lang` used for their `OCR`,
+/// for the tools setting the track language when muxing.
+///
+/// # Errors
+///
+/// Will return an error if the file can't be created or written.
+#[profiling::function]
+pub fn write_cue_index(
+    path: &Path,
+    subtitles: &[(TimeSpan, String)],
+    interval: Option<u32>,
+    lang: &str,
+) -> io::Result<()> {
+    let mut last_bucket = None;
+    let cues = subtitles
+        .iter()
+        .enumerate()
+        .filter(|(_, (time, _))| {
+            let Some(interval) = interval.filter(|interval| *interval > 0) else {
+                return true;
+            };
+            let bucket = time.start.msecs().div_euclid(i64::from(interval) * 1000);
+            let is_first = last_bucket != Some(bucket);
+            last_bucket = Some(bucket);
+            is_first
+        })
+        .map(|(idx, (time, _))| (idx + 1, time));
+
+    crate::create_parent_dirs(path)?;
+    let mut out = BufWriter::new(File::create(path)?);
+    if path.extension().is_some_and(|ext| ext == "json") {
+        let lang = diagnostics::escape(lang);
+        writeln!(out, "[")?;
+        for (count, (number, time)) in cues.enumerate() {
+            if count > 0 {
+                writeln!(out, ",")?;
+            }
+            write!(
+                out,
+                "  {{\"number\": {number}, \"start\": \"{}\", \"end\": \"{}\", \"start_ms\": {}, \"end_ms\": {}, \"lang\": \"{lang}\"}}",
+                format_timestamp(time.start, '.'),
+                format_timestamp(time.end, '.'),
+                time.start.msecs(),
+                time.end.msecs()
+            )?;
+        }
+        writeln!(out, "\n]")?;
+    } else {
+        for (number, time) in cues {
+            writeln!(
+                out,
+                "{number}\t{}\t{}",
+                format_timestamp(time.start, '.'),
+                format_timestamp(time.end, '.')
+            )?;
+        }
+    }
+    out.flush()
+}
@@ -0,0 +1,302 @@
+use std::{
+    fs::{self, File},
+    hash::{DefaultHasher, Hasher},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use image::GrayImage;
+use log::{info, warn};
+use subtile::time::{TimePoint, TimeSpan};
+
+use crate::{decode, Config, Error};
+
+/// First bytes of a cache file, with the version of the format.
+const MAGIC: &[u8] = b"subtile-ocr decode cache 1\n";
+
+/// Size of the chunks of the input read to compute its hash.
+const HASH_CHUNK_SIZE: usize = 1 << 16;
+
+/// Decode the subtitles of `input` like [`decode`], with the decoded images cached in `dir`.
+///
+/// The cache is keyed by the hash of the content of `input` and of the options of `opt` used
+/// by the decoding and the conversion of the images, so the runs changing only the `OCR`
+/// options reuse it. An unreadable cache is decoded again, and a cache which can't be
+/// written is reported without failing the run.
+///
+/// # Errors
+///
+/// Will forward the errors of [`decode`].
+#[profiling::function]
+pub fn decode_cached(
+    dir: &Path,
+    input: &Path,
+    opt: &Config,
+) -> Result<(Vec<TimeSpan>, Vec<GrayImage>), Error> {
+    let path = match cache_key(input, opt) {
+        Ok(key) => dir.join(format!("{key:016x}.cache")),
+        Err(err) => {
+            warn!(
+                "Unable to hash '{}' for the decode cache: {err}",
+                input.display()
+            );
+            return decode(input, opt);
+        }
+    };
+    if path.exists() {
+        match read_cache(&path) {
+            Ok(decoded) => {
+                info!(
+                    "{} decoded subtitles read from the cache {}",
+                    decoded.0.len(),
+                    path.display()
+                );
+                return Ok(decoded);
+            }
+            Err(err) => warn!(
+                "Unable to read the decode cache {}, decoding again: {err}",
+                path.display()
+            ),
+        }
+    }
+    let (times, images) = decode(input, opt)?;
+    match write_cache(&path, &times, &images) {
+        Ok(()) => info!("Decoded subtitles cached in {}", path.display()),
+        Err(err) => warn!("Unable to write the decode cache {}: {err}", path.display()),
+    }
+    Ok((times, images))
+}
+
+/// Hash the content of `input`, with its sub file for `VobSub`, and the decoding options of `opt`.
+fn cache_key(input: &Path, opt: &Config) -> io::Result<u64> {
+    let mut hasher = DefaultHasher::new();
+    let sub = input.with_extension("sub");
+    let is_vobsub = input.extension().is_some_and(|ext| ext == "idx");
+    let mut files = vec![input];
+    if is_vobsub {
+        files.push(&sub);
+    }
+    let mut buffer = vec![0; HASH_CHUNK_SIZE];
+    for path in files {
+        let mut file = File::open(path)?;
+        loop {
+            let size = file.read(&mut buffer)?;
+            if size == 0 {
+                break;
+            }
+            hasher.write(&buffer[..size]);
+        }
+    }
+    // The idx timing directives are only applied without `--time-offset`.
+    let options = format!(
+        "{:?}",
+        (
+            opt.image.threshold,
+            opt.image.ink_tolerance,
+            opt.image.convert,
+            opt.image.border,
+            opt.image.target_line_height,
+            opt.image.alpha_threshold,
+            opt.image.luma_threshold,
+            opt.input.palette,
+            opt.image.crop,
+            opt.input.max_image_size,
+            opt.input.recover,
+            opt.timing.offset.is_some(),
+        )
+    );
+    hasher.write(options.as_bytes());
+    Ok(hasher.finish())
+}
+
+/// Read the times and images of the cache file `path`.
+fn read_cache(path: &Path) -> io::Result<(Vec<TimeSpan>, Vec<GrayImage>)> {
+    let mut reader = GzDecoder::new(BufReader::new(File::open(path)?));
+    let mut magic = vec![0; MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a decode cache of this version",
+        ));
+    }
+    let count = read_u64(&mut reader)?;
+    let mut times = Vec::new();
+    let mut images = Vec::new();
+    for _ in 0..count {
+        let start = TimePoint::from_msecs(read_i64(&mut reader)?);
+        let end = TimePoint::from_msecs(read_i64(&mut reader)?);
+        let width = read_u32(&mut reader)?;
+        let height = read_u32(&mut reader)?;
+        let size = u64::from(width) * u64::from(height);
+        let mut pixels = Vec::new();
+        (&mut reader).take(size).read_to_end(&mut pixels)?;
+        let image = GrayImage::from_raw(width, height, pixels)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "truncated image"))?;
+        times.push(TimeSpan::new(start, end));
+        images.push(image);
+    }
+    // Reading the end of the stream checks its checksum, for a cache truncated or corrupted.
+    if reader.read(&mut [0])? != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "data after the subtitles",
+        ));
+    }
+    Ok((times, images))
+}
+
+/// Write the `times` and `images` in the cache file `path`, replaced once complete.
+fn write_cache(path: &Path, times: &[TimeSpan], images: &[GrayImage]) -> io::Result<()> {
+    crate::create_parent_dirs(path)?;
+    let partial = path.with_extension("cache.partial");
+    let mut writer = GzEncoder::new(BufWriter::new(File::create(&partial)?), Compression::fast());
+    writer.write_all(MAGIC)?;
+    writer.write_all(&(times.len() as u64).to_le_bytes())?;
+    for (time, image) in times.iter().zip(images) {
+        writer.write_all(&time.start.msecs().to_le_bytes())?;
+        writer.write_all(&time.end.msecs().to_le_bytes())?;
+        writer.write_all(&image.width().to_le_bytes())?;
+        writer.write_all(&image.height().to_le_bytes())?;
+        writer.write_all(image.as_raw())?;
+    }
+    writer.finish()?.flush()?;
+    fs::rename(&partial, path)
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_i64(reader: &mut impl Read) -> io::Result<i64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(i64::from_le_bytes(bytes))
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::{self, Write},
+        path::PathBuf,
+    };
+
+    use flate2::{write::GzEncoder, Compression};
+    use image::{GrayImage, Luma};
+    use subtile::time::{TimePoint, TimeSpan};
+
+    use super::{read_cache, write_cache, MAGIC};
+
+    /// Path of the cache file `name` of a test, in a directory of this process.
+    fn cache_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("subtile-ocr-test-{}", std::process::id()))
+            .join(name)
+    }
+
+    /// Write `content` compressed in the cache file `name`, and return its path.
+    fn write_compressed(name: &str, content: &[u8]) -> PathBuf {
+        let path = cache_path(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut writer = GzEncoder::new(Vec::new(), Compression::fast());
+        writer.write_all(content).unwrap();
+        fs::write(&path, writer.finish().unwrap()).unwrap();
+        path
+    }
+
+    /// Cache content with a header for `count` subtitles, and a subtitle of `width` x `height`.
+    fn header(count: u64, width: u32, height: u32) -> Vec<u8> {
+        let mut content = MAGIC.to_vec();
+        content.extend_from_slice(&count.to_le_bytes());
+        content.extend_from_slice(&1000_i64.to_le_bytes());
+        content.extend_from_slice(&2000_i64.to_le_bytes());
+        content.extend_from_slice(&width.to_le_bytes());
+        content.extend_from_slice(&height.to_le_bytes());
+        content
+    }
+
+    fn span(start: i64, end: i64) -> TimeSpan {
+        TimeSpan::new(TimePoint::from_msecs(start), TimePoint::from_msecs(end))
+    }
+
+    #[test]
+    fn write_and_read_cache() {
+        let path = cache_path("sample.cache");
+        let times = vec![span(1000, 2000), span(-40, 3500)];
+        let images = vec![
+            GrayImage::from_fn(3, 2, |x, y| Luma([(x * 10 + y) as u8])),
+            GrayImage::from_pixel(1, 4, Luma([255])),
+        ];
+        write_cache(&path, &times, &images).unwrap();
+        assert!(!path.with_extension("cache.partial").exists());
+
+        let (read_times, read_images) = read_cache(&path).unwrap();
+        assert_eq!(read_times, times);
+        assert_eq!(read_images, images);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn empty_cache() {
+        let path = cache_path("empty.cache");
+        write_cache(&path, &[], &[]).unwrap();
+        let (times, images) = read_cache(&path).unwrap();
+        assert!(times.is_empty() && images.is_empty());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn truncated_cache() {
+        let path = cache_path("truncated.cache");
+        let image = GrayImage::from_pixel(20, 10, Luma([128]));
+        write_cache(&path, &[span(0, 1000)], &[image]).unwrap();
+        let data = fs::read(&path).unwrap();
+        for size in [0, 10, data.len() / 2, data.len() - 1] {
+            fs::write(&path, &data[..size]).unwrap();
+            assert!(read_cache(&path).is_err(), "{size} bytes of {}", data.len());
+        }
+        fs::remove_file(path).unwrap();
+
+        // Compressed data ending inside the pixels of the image.
+        let mut content = header(1, 20, 10);
+        content.extend_from_slice(&[0; 150]);
+        let path = write_compressed("short_image.cache", &content);
+        let err = read_cache(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn malformed_cache() {
+        // Another version of the format.
+        let mut content = b"subtile-ocr decode cache 0\n".to_vec();
+        content.extend_from_slice(&0_u64.to_le_bytes());
+        let path = write_compressed("version.cache", &content);
+        let err = read_cache(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(path).unwrap();
+
+        // Not compressed.
+        let path = cache_path("raw.cache");
+        fs::write(&path, header(0, 0, 0)).unwrap();
+        assert!(read_cache(&path).is_err());
+        fs::remove_file(path).unwrap();
+
+        // Counts and sizes much larger than the content, not allocated ahead.
+        for (count, width, height) in [(u64::MAX, 1, 1), (1, u32::MAX, u32::MAX)] {
+            let path = write_compressed("sizes.cache", &header(count, width, height));
+            assert!(read_cache(&path).is_err());
+            fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::{
+    fs::File,
+    io::{self, LineWriter, Write},
+    path::Path,
+    sync::{Mutex, PoisonError},
+};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::summary::SUMMARY_TARGET;
+
+/// Logger recording the warnings and errors as `JSON` lines in a file, for automated checks.
+///
+/// The summary of the recognized streams is recorded too. Records are also forwarded
+/// to an inner logger, which displays them for the user.
+pub struct DiagnosticsLogger {
+    inner: Box<dyn Log>,
+    file: Mutex<LineWriter<File>>,
+}
+
+impl DiagnosticsLogger {
+    /// Install as global logger, forwarding to `inner` with its `inner_level`, and recording in the file at `path`.
+    ///
+    /// The returned logger can record the final error of the process with [`DiagnosticsLogger::record`].
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the file can't be created or if a logger is already installed.
+    pub fn install(
+        inner: Box<dyn Log>,
+        inner_level: LevelFilter,
+        path: &Path,
+    ) -> io::Result<&'static Self> {
+        crate::create_parent_dirs(path)?;
+        let file = Mutex::new(LineWriter::new(File::create(path)?));
+        let logger: &'static Self = Box::leak(Box::new(Self { inner, file }));
+        log::set_logger(logger).map_err(|err| io::Error::other(err.to_string()))?;
+        // The summary is logged at the info level.
+        log::set_max_level(inner_level.max(LevelFilter::Info));
+        Ok(logger)
+    }
+
+    /// Record an event in the diagnostics file, without displaying it.
+    pub fn record(&self, level: Level, target: &str, message: &str) {
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        // There is no place to report a failure to write the diagnostics.
+        let _ = writeln!(
+            file,
+            "{{\"level\": \"{}\", \"target\": \"{}\", \"message\": \"{}\"}}",
+            level.as_str().to_lowercase(),
+            escape(target),
+            escape(message)
+        );
+    }
+}
+
+impl Log for DiagnosticsLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        is_recorded(metadata) || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if is_recorded(record.metadata()) {
+            self.record(record.level(), record.target(), &record.args().to_string());
+        }
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        let _ = self
+            .file
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .flush();
+        self.inner.flush();
+    }
+}
+
+/// Check if the events of `metadata` are recorded: the warnings, errors and stream summary.
+fn is_recorded(metadata: &Metadata) -> bool {
+    metadata.level() <= Level::Warn || metadata.target() == SUMMARY_TARGET
+}
+
+/// Escape `text` for a `JSON` string.
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
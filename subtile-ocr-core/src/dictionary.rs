@@ -0,0 +1,87 @@
+use std::{collections::HashSet, fs, io, path::Path};
+
+/// Maximum number of words of the short subtitles checked against the dictionary.
+const MAX_SHORT_WORDS: usize = 2;
+
+/// Common short answers and interjections of dialogues, by Tesseract language.
+const DEFAULT_WORDS: &[(&str, &[&str])] = &[
+    (
+        "eng",
+        &[
+            "ah", "all", "bye", "come", "fine", "go", "good", "great", "hello", "help", "hey",
+            "hi", "how", "huh", "no", "now", "oh", "ok", "okay", "please", "really", "right",
+            "run", "see", "sir", "sorry", "stop", "sure", "thanks", "uh", "um", "wait", "well",
+            "what", "when", "where", "who", "why", "wow", "yeah", "yes", "you",
+        ],
+    ),
+    (
+        "fra",
+        &[
+            "ah", "allez", "allô", "au", "bien", "bon", "bonjour", "comment", "d'accord", "eh",
+            "hé", "merci", "moi", "non", "oh", "oui", "pardon", "pourquoi", "quoi", "qui",
+            "revoir", "salut", "si", "stop", "toi", "viens", "vite", "voilà",
+        ],
+    ),
+    (
+        "deu",
+        &[
+            "ach", "danke", "doch", "gut", "hallo", "he", "hey", "ja", "komm", "los", "nein",
+            "nicht", "oh", "okay", "stopp", "tschüss", "warte", "warum", "was", "wer", "wie", "wo",
+        ],
+    ),
+    (
+        "spa",
+        &[
+            "adiós", "ah", "bien", "bueno", "cómo", "dónde", "eh", "gracias", "hola", "no", "oh",
+            "oye", "perdón", "por", "qué", "quién", "sí", "vale", "vamos", "ven", "ya",
+        ],
+    ),
+];
+
+/// Words recognized with confidence in short subtitles, even if Tesseract doesn't.
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    /// Create a dictionary with the default words of the Tesseract languages `lang` (e.g. `eng+fra`),
+    /// and the words of the file at `path`, if any.
+    ///
+    /// The file has one word per line, empty lines and lines starting with `#` are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the file can't be read.
+    pub fn new(lang: &str, path: Option<&Path>) -> io::Result<Self> {
+        let content = path.map(fs::read_to_string).transpose()?;
+        let custom = content
+            .iter()
+            .flat_map(|content| content.lines())
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        let defaults = lang.split('+').flat_map(|lang| {
+            DEFAULT_WORDS
+                .iter()
+                .filter(move |(default_lang, _)| *default_lang == lang)
+                .flat_map(|(_, words)| words.iter().copied())
+        });
+        let words = defaults.chain(custom).map(str::to_lowercase).collect();
+        Ok(Self { words })
+    }
+
+    /// Check if `text` is a short subtitle (e.g. `Yes.`, `No!`) with only words of the dictionary.
+    #[must_use]
+    pub fn knows_short_text(&self, text: &str) -> bool {
+        let words = text
+            .split(|c: char| !c.is_alphabetic() && c != '\'')
+            .map(|word| word.trim_matches('\''))
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>();
+        !words.is_empty()
+            && words.len() <= MAX_SHORT_WORDS
+            && words
+                .iter()
+                .all(|word| self.words.contains(&word.to_lowercase()))
+    }
+}
@@ -0,0 +1,1162 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::{self, Read},
+};
+
+use image::{GrayAlphaImage, GrayImage, Luma, LumaA};
+use log::{debug, info, warn};
+use subtile::time::{TimePoint, TimeSpan};
+use thiserror::Error;
+
+use crate::crop::Crop;
+
+/// Size of an `MPEG` transport stream packet.
+const TS_PACKET_SIZE: usize = 188;
+
+/// First byte of each transport stream packet.
+const TS_SYNC_BYTE: u8 = 0x47;
+
+/// Stream id of the `PES` packets of private data, which carry the `DVB` subtitles.
+const PRIVATE_STREAM_1: u8 = 0xBD;
+
+/// First bytes of the `PES` payload of `DVB` subtitles: the data identifier and the subtitle stream id.
+const DVB_SUBTITLE_HEADER: [u8; 2] = [0x20, 0x00];
+
+/// Byte starting each subtitling segment.
+const SEGMENT_SYNC_BYTE: u8 = 0x0F;
+
+/// Size of the header of a subtitling segment: sync byte, type, page id and length.
+const SEGMENT_HEADER_SIZE: usize = 6;
+
+/// Segment types of the `DVB` subtitles, from ETSI EN 300 743.
+const PAGE_COMPOSITION: u8 = 0x10;
+const REGION_COMPOSITION: u8 = 0x11;
+const CLUT_DEFINITION: u8 = 0x12;
+const OBJECT_DATA: u8 = 0x13;
+const DISPLAY_DEFINITION: u8 = 0x14;
+const END_OF_DISPLAY_SET: u8 = 0x80;
+
+/// Modulo of the 33 bits `PES` timestamps.
+const PTS_MODULO: i64 = 1 << 33;
+
+/// Ticks of the `PES` timestamps per millisecond.
+const PTS_TICKS_PER_MS: i64 = 90;
+
+/// Display size of the subtitles without display definition segment.
+const DEFAULT_DISPLAY_SIZE: (u32, u32) = (720, 576);
+
+/// Error of `DVB` subtitles parsing.
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Not an MPEG transport stream: no sync byte at the start of the file")]
+    NotTransportStream,
+
+    #[error("Failed to read the transport stream")]
+    Read(#[source] io::Error),
+
+    #[error("No DVB subtitles stream found in the transport stream")]
+    NoSubtitleStream,
+
+    #[error("The crop region {crop} doesn't fit in the display size {width}x{height} of the DVB subtitles")]
+    CropOutsideDisplay { crop: Crop, width: u32, height: u32 },
+}
+
+/// Decode the `DVB` subtitles of the `MPEG` transport stream read from `reader`.
+///
+/// The packets are read one at a time, only the `PES` packets of private data are reassembled.
+/// The first page of the first stream of `DVB` subtitles is decoded, the others are reported.
+/// The subtitles times are relative to the first timestamp of the recording,
+/// and the regions wider or higher than `max_size` are skipped.
+/// With a `crop` region, the pixels out of it are ignored.
+///
+/// # Errors
+///
+/// Will return [`Error::Read`] if the transport stream can't be read.
+/// Will return [`Error::NotTransportStream`] if `reader` doesn't start like a transport stream.
+/// Will return [`Error::NoSubtitleStream`] if no `DVB` subtitles are found.
+/// Will return [`Error::CropOutsideDisplay`] if `crop` doesn't fit in a display size of the subtitles.
+#[profiling::function]
+pub fn parse(
+    reader: impl Read,
+    max_size: u32,
+    crop: Option<Crop>,
+) -> Result<Vec<(TimeSpan, GrayAlphaImage)>, Error> {
+    let demuxed = demux(reader)?;
+    let mut decoder = Decoder::new(max_size, crop);
+    for (pts, segments) in &demuxed.packets {
+        let time =
+            pts.map(|pts| TimePoint::from_msecs(pts_diff(pts, demuxed.start) / PTS_TICKS_PER_MS));
+        decoder.push_segments(time, segments);
+    }
+    if let Some(crop) = crop {
+        let (width, height) = decoder.display_size.unwrap_or(DEFAULT_DISPLAY_SIZE);
+        if !crop.fits(width, height) {
+            return Err(Error::CropOutsideDisplay {
+                crop,
+                width,
+                height,
+            });
+        }
+    }
+    Ok(decoder.finish())
+}
+
+/// Convert a decoded `image` for `OCR`: black text on white, surrounded by `border` pixels.
+///
+/// The text pixels have an alpha above `alpha_threshold` and a luminance above `luma_threshold`.
+#[must_use]
+pub fn to_ocr_image(
+    image: &GrayAlphaImage,
+    alpha_threshold: u8,
+    luma_threshold: u8,
+    border: u32,
+) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let mut converted = GrayImage::from_pixel(width + border * 2, height + border * 2, Luma([255]));
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let LumaA([luma, alpha]) = *pixel;
+        if alpha > alpha_threshold && luma > luma_threshold {
+            converted.put_pixel(x + border, y + border, Luma([0]));
+        }
+    }
+    converted
+}
+
+/// `PES` packets of the subtitles stream of a transport stream.
+struct Demuxed {
+    /// Segments of each packet, with the packet timestamp.
+    packets: Vec<(Option<i64>, Vec<u8>)>,
+    /// First timestamp of the recording, in any stream.
+    start: i64,
+}
+
+/// Extract the `PES` packets of the first `DVB` subtitles stream of the transport stream of `reader`.
+///
+/// Only the timestamp is read from the `PES` packets of the other streams, like audio or video.
+fn demux(mut reader: impl Read) -> Result<Demuxed, Error> {
+    let mut packet = [0; TS_PACKET_SIZE];
+    let mut first = true;
+    let mut buffers: HashMap<u16, Vec<u8>> = HashMap::new();
+    let mut subtitle_pid = None;
+    let mut other_pids = BTreeSet::new();
+    let mut start = None;
+    let mut packets = Vec::new();
+    let mut lost_sync_count = 0;
+
+    let mut complete = |pid: u16, pes: &[u8]| {
+        let Some(header) = PesHeader::parse(pes) else {
+            return;
+        };
+        if let Some(pts) = header.pts {
+            start = Some(start.map_or(pts, |start: i64| start.min(pts)));
+        }
+        let Some(segments) = header.dvb_segments(pes) else {
+            return;
+        };
+        match subtitle_pid {
+            None => {
+                info!("DVB subtitles found in the stream with PID {pid:#x}");
+                subtitle_pid = Some(pid);
+            }
+            Some(subtitle_pid) if subtitle_pid != pid => {
+                other_pids.insert(pid);
+                return;
+            }
+            Some(_) => {}
+        }
+        packets.push((header.pts, segments.to_vec()));
+    };
+
+    loop {
+        let size = read_packet(&mut reader, &mut packet).map_err(Error::Read)?;
+        if first && (size == 0 || packet[0] != TS_SYNC_BYTE) {
+            return Err(Error::NotTransportStream);
+        }
+        first = false;
+        if size < TS_PACKET_SIZE {
+            if size > 0 {
+                debug!("Incomplete transport stream packet of {size} bytes at the end");
+            }
+            break;
+        }
+        if packet[0] != TS_SYNC_BYTE {
+            lost_sync_count += 1;
+            continue;
+        }
+        let unit_start = packet[1] & 0x40 != 0;
+        let pid = u16::from_be_bytes([packet[1] & 0x1F, packet[2]]);
+        let adaptation = packet[3] & 0x20 != 0;
+        let has_payload = packet[3] & 0x10 != 0;
+        let payload_start = if adaptation {
+            5 + usize::from(packet[4])
+        } else {
+            4
+        };
+        let Some(payload) = packet.get(payload_start..).filter(|_| has_payload) else {
+            continue;
+        };
+        if unit_start {
+            let private = payload.get(3) == Some(&PRIVATE_STREAM_1);
+            let previous = if private {
+                buffers.insert(pid, payload.to_vec())
+            } else {
+                buffers.remove(&pid)
+            };
+            if let Some(pes) = previous {
+                complete(pid, &pes);
+            }
+            if !private {
+                // The other streams are only needed for their timestamp, in their first packet.
+                complete(pid, payload);
+            }
+        } else if let Some(buffer) = buffers.get_mut(&pid) {
+            buffer.extend_from_slice(payload);
+        }
+    }
+    let mut remaining = buffers.into_iter().collect::<Vec<_>>();
+    remaining.sort_unstable_by_key(|(pid, _)| *pid);
+    for (pid, pes) in remaining {
+        complete(pid, &pes);
+    }
+
+    if lost_sync_count > 0 {
+        warn!("{lost_sync_count} transport stream packets without sync byte have been skipped");
+    }
+    if !other_pids.is_empty() {
+        warn!(
+            "Only the first DVB subtitles stream is decoded, the streams with PID {} are ignored",
+            other_pids
+                .iter()
+                .map(|pid| format!("{pid:#x}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    if subtitle_pid.is_none() {
+        return Err(Error::NoSubtitleStream);
+    }
+    Ok(Demuxed {
+        packets,
+        start: start.unwrap_or(0),
+    })
+}
+
+/// Fill `packet` from `reader`, and return the number of bytes read, less than its size at the end.
+fn read_packet(reader: &mut impl Read, packet: &mut [u8; TS_PACKET_SIZE]) -> io::Result<usize> {
+    let mut size = 0;
+    while size < packet.len() {
+        match reader.read(&mut packet[size..]) {
+            Ok(0) => break,
+            Ok(count) => size += count,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(size)
+}
+
+/// Header of a `PES` packet.
+struct PesHeader {
+    stream_id: u8,
+    /// Presentation timestamp, in 90 kHz ticks.
+    pts: Option<i64>,
+    /// Offset of the payload in the packet.
+    payload_start: usize,
+    /// End of the packet, if its length is declared.
+    end: Option<usize>,
+}
+
+impl PesHeader {
+    /// Parse the header of `pes`, `None` if it's not a `PES` packet with an optional header.
+    fn parse(pes: &[u8]) -> Option<Self> {
+        if pes.len() < 9 || pes[..3] != [0, 0, 1] {
+            return None;
+        }
+        let length = usize::from(u16::from_be_bytes([pes[4], pes[5]]));
+        let pts = if pes[7] & 0x80 == 0 {
+            None
+        } else {
+            pes.get(9..14).map(|pts| {
+                (i64::from(pts[0] >> 1) & 0x07) << 30
+                    | i64::from(pts[1]) << 22
+                    | i64::from(pts[2] >> 1) << 15
+                    | i64::from(pts[3]) << 7
+                    | i64::from(pts[4] >> 1)
+            })
+        };
+        Some(Self {
+            stream_id: pes[3],
+            pts,
+            payload_start: 9 + usize::from(pes[8]),
+            end: (length > 0).then_some(6 + length),
+        })
+    }
+
+    /// Get the subtitling segments of `pes`, if it carries `DVB` subtitles.
+    fn dvb_segments<'a>(&self, pes: &'a [u8]) -> Option<&'a [u8]> {
+        if self.stream_id != PRIVATE_STREAM_1 {
+            return None;
+        }
+        let end = self.end.unwrap_or(pes.len());
+        if end > pes.len() {
+            debug!(
+                "Truncated PES packet of {} bytes instead of {end}",
+                pes.len()
+            );
+        }
+        pes.get(self.payload_start..end.min(pes.len()))?
+            .strip_prefix(&DVB_SUBTITLE_HEADER)
+    }
+}
+
+/// Get the time from timestamp `start` to `pts`, across a timestamps wrap-around.
+const fn pts_diff(pts: i64, start: i64) -> i64 {
+    (pts - start).rem_euclid(PTS_MODULO)
+}
+
+/// Region of a page, with its pixel codes.
+struct Region {
+    width: u32,
+    height: u32,
+    /// Bits of the pixel codes: 2, 4 or 8.
+    depth: u8,
+    clut_id: u8,
+    pixels: Vec<u8>,
+    /// Objects shown in the region, with their position in it.
+    objects: Vec<(u16, u32, u32)>,
+}
+
+/// Luminance and opacity of the entries of a color lookup table, for each pixel depth.
+#[derive(Clone)]
+struct Clut {
+    entries2: [LumaA<u8>; 4],
+    entries4: [LumaA<u8>; 16],
+    entries8: [LumaA<u8>; 256],
+}
+
+impl Default for Clut {
+    /// Approximation of the default tables of the standard, as the streams usually define theirs:
+    /// entry 0 is transparent, the bits of the others select the full or half red, green and blue.
+    fn default() -> Self {
+        let entry = |code: u8| {
+            if code == 0 {
+                return LumaA([0, 0]);
+            }
+            let level = if code & 0x08 == 0 { 255.0 } else { 127.0 };
+            let channel = |bit: u8| if code & bit == 0 { 0.0 } else { level };
+            let luma = 0.299 * channel(0x01) + 0.587 * channel(0x02) + 0.114 * channel(0x04);
+            LumaA([luma as u8, 255])
+        };
+        Self {
+            entries2: [
+                LumaA([0, 0]),
+                LumaA([255, 255]),
+                LumaA([0, 255]),
+                LumaA([127, 255]),
+            ],
+            entries4: std::array::from_fn(|code| entry(code as u8)),
+            entries8: std::array::from_fn(|code| entry(code as u8 & 0x0F)),
+        }
+    }
+}
+
+impl Clut {
+    /// Get the luminance and opacity of pixel `code` of `depth` bits.
+    fn get(&self, depth: u8, code: u8) -> LumaA<u8> {
+        match depth {
+            2 => self.entries2[usize::from(code & 0x03)],
+            4 => self.entries4[usize::from(code & 0x0F)],
+            _ => self.entries8[usize::from(code)],
+        }
+    }
+}
+
+/// Subtitle shown by the decoder, waiting for its end.
+struct Shown {
+    start: TimePoint,
+    /// Maximum time the page is shown, in seconds.
+    time_out: u8,
+    image: GrayAlphaImage,
+}
+
+/// State of the decoding of the subtitling segments.
+struct Decoder {
+    max_size: u32,
+    crop: Option<Crop>,
+    /// Composition page decoded, the first one of the stream.
+    page_id: Option<u16>,
+    /// Other composition pages of the stream, ignored.
+    other_pages: BTreeSet<u16>,
+    /// Smallest display size of the display definitions.
+    display_size: Option<(u32, u32)>,
+    /// Regions of the current page, with their position.
+    page_regions: Vec<(u8, u32, u32)>,
+    page_time_out: u8,
+    page_time: Option<TimePoint>,
+    /// The page has been updated since it was rendered.
+    page_dirty: bool,
+    regions: HashMap<u8, Region>,
+    cluts: HashMap<u8, Clut>,
+    shown: Option<Shown>,
+    subtitles: Vec<(TimeSpan, GrayAlphaImage)>,
+}
+
+impl Decoder {
+    fn new(max_size: u32, crop: Option<Crop>) -> Self {
+        Self {
+            max_size,
+            crop,
+            page_id: None,
+            other_pages: BTreeSet::new(),
+            display_size: None,
+            page_regions: Vec::new(),
+            page_time_out: 0,
+            page_time: None,
+            page_dirty: false,
+            regions: HashMap::new(),
+            cluts: HashMap::new(),
+            shown: None,
+            subtitles: Vec::new(),
+        }
+    }
+
+    /// Decode the subtitling `segments` of a packet presented at `time`.
+    fn push_segments(&mut self, time: Option<TimePoint>, segments: &[u8]) {
+        let mut pos = 0;
+        while pos + SEGMENT_HEADER_SIZE <= segments.len() && segments[pos] == SEGMENT_SYNC_BYTE {
+            let kind = segments[pos + 1];
+            let page_id = u16::from_be_bytes([segments[pos + 2], segments[pos + 3]]);
+            let length = usize::from(u16::from_be_bytes([segments[pos + 4], segments[pos + 5]]));
+            let start = pos + SEGMENT_HEADER_SIZE;
+            let Some(data) = segments.get(start..start + length) else {
+                debug!("Truncated subtitling segment of type {kind:#x}");
+                break;
+            };
+            pos = start + length;
+            if !self.select_page(kind, page_id) {
+                continue;
+            }
+            match kind {
+                PAGE_COMPOSITION => self.page_composition(time, data),
+                REGION_COMPOSITION => self.region_composition(data),
+                CLUT_DEFINITION => self.clut_definition(data),
+                OBJECT_DATA => self.object_data(data),
+                END_OF_DISPLAY_SET => self.render(),
+                DISPLAY_DEFINITION => self.display_definition(data),
+                kind => debug!("Subtitling segment of type {kind:#x} ignored"),
+            }
+        }
+    }
+
+    /// Check if a segment of `kind` for `page_id` belongs to the decoded page.
+    ///
+    /// The first page with a page composition is decoded. The segments of the pages without
+    /// page composition are kept, as they are the ancillary pages shared by the composition pages.
+    fn select_page(&mut self, kind: u8, page_id: u16) -> bool {
+        if self.page_id == Some(page_id) {
+            return true;
+        }
+        if kind == PAGE_COMPOSITION {
+            if self.page_id.is_none() {
+                info!("DVB subtitles page {page_id} selected");
+                self.page_id = Some(page_id);
+                return true;
+            }
+            self.other_pages.insert(page_id);
+            return false;
+        }
+        !self.other_pages.contains(&page_id)
+    }
+
+    /// Record the display size, only needed to check the crop region.
+    fn display_definition(&mut self, data: &[u8]) {
+        let [_, width_hi, width_lo, height_hi, height_lo, ..] = *data else {
+            return;
+        };
+        // The sizes are coded minus one.
+        let width = u32::from(u16::from_be_bytes([width_hi, width_lo])) + 1;
+        let height = u32::from(u16::from_be_bytes([height_hi, height_lo])) + 1;
+        self.display_size = Some(match self.display_size {
+            Some((min_width, min_height)) => (min_width.min(width), min_height.min(height)),
+            None => (width, height),
+        });
+    }
+
+    /// Start a new display set, ending the subtitle shown.
+    fn page_composition(&mut self, time: Option<TimePoint>, data: &[u8]) {
+        let [time_out, state, ..] = *data else {
+            return;
+        };
+        // The display set without end segment is complete when the next one starts.
+        self.render();
+        if let Some(time) = time {
+            self.end_shown(time);
+        }
+        // An acquisition point or a mode change refreshes the whole page.
+        if (state >> 2) & 0x03 != 0 {
+            self.regions.clear();
+            self.cluts.clear();
+        }
+        self.page_time_out = time_out;
+        self.page_time = time;
+        self.page_regions = data[2..]
+            .chunks_exact(6)
+            .map(|region| {
+                (
+                    region[0],
+                    u32::from(u16::from_be_bytes([region[2], region[3]])),
+                    u32::from(u16::from_be_bytes([region[4], region[5]])),
+                )
+            })
+            .collect();
+        self.page_dirty = true;
+    }
+
+    /// Create or update a region of the page.
+    fn region_composition(&mut self, data: &[u8]) {
+        if data.len() < 10 {
+            return;
+        }
+        let id = data[0];
+        let fill = data[1] & 0x08 != 0;
+        let width = u32::from(u16::from_be_bytes([data[2], data[3]]));
+        let height = u32::from(u16::from_be_bytes([data[4], data[5]]));
+        let depth = match (data[6] >> 2) & 0x07 {
+            1 => 2,
+            2 => 4,
+            _ => 8,
+        };
+        if width > self.max_size || height > self.max_size {
+            warn!(
+                "DVB region of {width}x{height} skipped, over the maximum of {0}x{0}, the segment is likely corrupt.",
+                self.max_size
+            );
+            self.regions.remove(&id);
+            return;
+        }
+        let background = match depth {
+            2 => (data[9] >> 2) & 0x03,
+            4 => data[9] >> 4,
+            _ => data[8],
+        };
+        let mut objects = Vec::new();
+        let mut pos = 10;
+        while pos + 6 <= data.len() {
+            let object_id = u16::from_be_bytes([data[pos], data[pos + 1]]);
+            let object_type = data[pos + 2] >> 6;
+            let x = u32::from(u16::from_be_bytes([data[pos + 2], data[pos + 3]]) & 0x0FFF);
+            let y = u32::from(u16::from_be_bytes([data[pos + 4], data[pos + 5]]) & 0x0FFF);
+            objects.push((object_id, x, y));
+            // Character objects have their foreground and background codes.
+            pos += if object_type == 1 || object_type == 2 {
+                8
+            } else {
+                6
+            };
+        }
+
+        let region = self.regions.entry(id).or_insert_with(|| Region {
+            width: 0,
+            height: 0,
+            depth,
+            clut_id: data[7],
+            pixels: Vec::new(),
+            objects: Vec::new(),
+        });
+        if region.width != width || region.height != height || region.depth != depth {
+            region.width = width;
+            region.height = height;
+            region.depth = depth;
+            region.pixels = vec![background; width as usize * height as usize];
+        } else if fill {
+            region.pixels.fill(background);
+        }
+        region.clut_id = data[7];
+        region.objects = objects;
+        self.page_dirty = true;
+    }
+
+    /// Define the entries of a color lookup table.
+    fn clut_definition(&mut self, data: &[u8]) {
+        let Some(&id) = data.first() else {
+            return;
+        };
+        let clut = self.cluts.entry(id).or_default();
+        let mut pos = 2;
+        while pos + 2 <= data.len() {
+            let code = data[pos];
+            let flags = data[pos + 1];
+            let (luma, transparency, size) = if flags & 0x01 == 0 {
+                let Some(bytes) = data.get(pos + 2..pos + 4) else {
+                    break;
+                };
+                let value = u16::from_be_bytes([bytes[0], bytes[1]]);
+                (((value >> 10) as u8) << 2, ((value & 0x03) as u8) << 6, 4)
+            } else {
+                let Some(bytes) = data.get(pos + 2..pos + 6) else {
+                    break;
+                };
+                (bytes[0], bytes[3], 6)
+            };
+            // A luminance of 0 is a fully transparent entry.
+            let alpha = if luma == 0 { 0 } else { 255 - transparency };
+            let entry = LumaA([luma, alpha]);
+            if flags & 0x80 != 0 {
+                clut.entries2[usize::from(code & 0x03)] = entry;
+            }
+            if flags & 0x40 != 0 {
+                clut.entries4[usize::from(code & 0x0F)] = entry;
+            }
+            if flags & 0x20 != 0 {
+                clut.entries8[usize::from(code)] = entry;
+            }
+            pos += size;
+        }
+        self.page_dirty = true;
+    }
+
+    /// Draw an object in the regions showing it.
+    fn object_data(&mut self, data: &[u8]) {
+        if data.len() < 7 {
+            return;
+        }
+        let object_id = u16::from_be_bytes([data[0], data[1]]);
+        let coding_method = (data[2] >> 2) & 0x03;
+        let non_modifying = data[2] & 0x02 != 0;
+        if coding_method != 0 {
+            debug!("DVB object {object_id} coded as characters is ignored");
+            return;
+        }
+        let top_length = usize::from(u16::from_be_bytes([data[3], data[4]]));
+        let bottom_length = usize::from(u16::from_be_bytes([data[5], data[6]]));
+        let Some(top) = data.get(7..7 + top_length) else {
+            return;
+        };
+        // Without data for the bottom field, the lines of the top field are repeated.
+        let bottom = match data.get(7 + top_length..7 + top_length + bottom_length) {
+            Some(bottom) if bottom_length > 0 => bottom,
+            _ => top,
+        };
+        for region in self.regions.values_mut() {
+            let positions = region
+                .objects
+                .iter()
+                .filter(|(id, _, _)| *id == object_id)
+                .map(|(_, x, y)| (*x, *y))
+                .collect::<Vec<_>>();
+            for (x, y) in positions {
+                draw_field(region, top, x, y, non_modifying);
+                draw_field(region, bottom, x, y + 1, non_modifying);
+            }
+        }
+        self.page_dirty = true;
+    }
+
+    /// Render the page updated since its last rendering, as the subtitle shown.
+    fn render(&mut self) {
+        if !self.page_dirty {
+            return;
+        }
+        self.page_dirty = false;
+        let Some(start) = self.page_time else {
+            return;
+        };
+        let default_clut = Clut::default();
+        let mut pixels = Vec::new();
+        for (region_id, region_x, region_y) in &self.page_regions {
+            let Some(region) = self.regions.get(region_id) else {
+                continue;
+            };
+            let clut = self.cluts.get(&region.clut_id).unwrap_or(&default_clut);
+            for (idx, code) in region.pixels.iter().enumerate() {
+                let pixel = clut.get(region.depth, *code);
+                let idx = idx as u32;
+                let (x, y) = (region_x + idx % region.width, region_y + idx / region.width);
+                let cropped = self.crop.is_some_and(|crop| !crop.contains(x, y));
+                if pixel.0[1] > 0 && !cropped {
+                    pixels.push((x, y, pixel));
+                }
+            }
+        }
+        let Some(bounds) = bounds(&pixels) else {
+            return;
+        };
+        let (min_x, min_y, max_x, max_y) = bounds;
+        let mut image = GrayAlphaImage::new(max_x - min_x + 1, max_y - min_y + 1);
+        for (x, y, pixel) in pixels {
+            image.put_pixel(x - min_x, y - min_y, pixel);
+        }
+        self.shown = Some(Shown {
+            start,
+            time_out: self.page_time_out,
+            image,
+        });
+    }
+
+    /// End the subtitle shown at `time`, or at its time-out if earlier.
+    fn end_shown(&mut self, time: TimePoint) {
+        let Some(shown) = self.shown.take() else {
+            return;
+        };
+        let time_out = shown.start.msecs() + i64::from(shown.time_out) * 1000;
+        let end = if shown.time_out > 0 {
+            time.msecs().min(time_out)
+        } else {
+            time.msecs()
+        };
+        if end > shown.start.msecs() {
+            self.subtitles.push((
+                TimeSpan::new(shown.start, TimePoint::from_msecs(end)),
+                shown.image,
+            ));
+        }
+    }
+
+    /// Get the decoded subtitles, with the last one ended by its time-out.
+    fn finish(mut self) -> Vec<(TimeSpan, GrayAlphaImage)> {
+        if !self.other_pages.is_empty() {
+            warn!(
+                "Only the first DVB subtitles page is decoded, the pages {} are ignored",
+                self.other_pages
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        self.render();
+        let end = self
+            .shown
+            .as_ref()
+            .map(|shown| shown.start.msecs() + i64::from(shown.time_out.max(1)) * 1000);
+        if let Some(end) = end {
+            self.end_shown(TimePoint::from_msecs(end));
+        }
+        self.subtitles
+    }
+}
+
+/// Get the bounds `(min_x, min_y, max_x, max_y)` of the `pixels`, `None` if there is none.
+fn bounds(pixels: &[(u32, u32, LumaA<u8>)]) -> Option<(u32, u32, u32, u32)> {
+    pixels.iter().fold(None, |bounds, (x, y, _)| {
+        Some(match bounds {
+            None => (*x, *y, *x, *y),
+            Some((min_x, min_y, max_x, max_y)) => {
+                (min_x.min(*x), min_y.min(*y), max_x.max(*x), max_y.max(*y))
+            }
+        })
+    })
+}
+
+/// Tables mapping the pixel codes of a string to the depth of its region.
+struct MapTables {
+    two_to_four: [u8; 4],
+    two_to_eight: [u8; 4],
+    four_to_eight: [u8; 16],
+}
+
+impl Default for MapTables {
+    fn default() -> Self {
+        Self {
+            two_to_four: [0x0, 0x7, 0x8, 0xF],
+            two_to_eight: [0x00, 0x77, 0x88, 0xFF],
+            four_to_eight: std::array::from_fn(|code| code as u8 * 0x11),
+        }
+    }
+}
+
+impl MapTables {
+    /// Map `code` of a string of `depth` bits to a pixel code of `region_depth` bits.
+    fn map(&self, depth: u8, region_depth: u8, code: u8) -> u8 {
+        match (depth, region_depth) {
+            (2, 4) => self.two_to_four[usize::from(code)],
+            (2, 8) => self.two_to_eight[usize::from(code)],
+            (4, 8) => self.four_to_eight[usize::from(code)],
+            // Strings deeper than their region keep the most significant bits.
+            (depth, region_depth) => code >> depth.saturating_sub(region_depth),
+        }
+    }
+}
+
+/// Draw the pixel data of a field of an object at `x`, `y` in `region`.
+///
+/// The lines of a field are every other line of the object, from its first line `y`.
+/// With `non_modifying`, the pixels of code 1 are not drawn.
+fn draw_field(region: &mut Region, data: &[u8], x: u32, y: u32, non_modifying: bool) {
+    let mut maps = MapTables::default();
+    let (mut line_x, mut line_y) = (x, y);
+    let mut pos = 0;
+    while let Some(&data_type) = data.get(pos) {
+        pos += 1;
+        let depth = match data_type {
+            0x10 => 2,
+            0x11 => 4,
+            0x12 => 8,
+            0x20..=0x22 => {
+                let rest = data.get(pos..).unwrap_or_default();
+                pos += read_map_table(&mut maps, data_type, rest);
+                continue;
+            }
+            0xF0 => {
+                line_x = x;
+                line_y += 2;
+                continue;
+            }
+            data_type => {
+                debug!(
+                    "Unknown DVB pixel data type {data_type:#x}, the rest of the object is ignored"
+                );
+                return;
+            }
+        };
+        let mut reader = BitReader::new(&data[pos..]);
+        while let Some((run, code)) = reader.pixel_run(depth) {
+            let code = maps.map(depth, region.depth, code);
+            if !(non_modifying && code == 1) && line_y < region.height {
+                let row = line_y as usize * region.width as usize;
+                let first = line_x.min(region.width);
+                let last = (line_x + run).min(region.width);
+                region.pixels[row + first as usize..row + last as usize].fill(code);
+            }
+            line_x += run;
+        }
+        pos += reader.consumed();
+    }
+}
+
+/// Read a map table of `data_type` from `data` into `maps`, and return its size.
+fn read_map_table(maps: &mut MapTables, data_type: u8, data: &[u8]) -> usize {
+    let mut reader = BitReader::new(data);
+    match data_type {
+        0x20 => maps
+            .two_to_four
+            .iter_mut()
+            .for_each(|code| *code = reader.read(4)),
+        0x21 => maps
+            .two_to_eight
+            .iter_mut()
+            .for_each(|code| *code = reader.read(8)),
+        _ => maps
+            .four_to_eight
+            .iter_mut()
+            .for_each(|code| *code = reader.read(8)),
+    }
+    reader.consumed()
+}
+
+/// Reader of the bits of the pixel data, most significant first.
+struct BitReader<'a> {
+    data: &'a [u8],
+    bit: usize,
+}
+
+impl<'a> BitReader<'a> {
+    const fn new(data: &'a [u8]) -> Self {
+        Self { data, bit: 0 }
+    }
+
+    /// Read `count` bits, up to 8, as 0 after the end of the data.
+    fn read(&mut self, count: usize) -> u8 {
+        let mut value = 0;
+        for _ in 0..count {
+            let byte = self.data.get(self.bit / 8).copied().unwrap_or(0);
+            value = (value << 1) | ((byte >> (7 - self.bit % 8)) & 1);
+            self.bit += 1;
+        }
+        value
+    }
+
+    /// Number of bytes read, the last one included even if partially read.
+    const fn consumed(&self) -> usize {
+        self.bit.div_ceil(8)
+    }
+
+    /// Read the next run of pixels of a string of `depth` bits, as its length and code.
+    ///
+    /// Return `None` at the end of the string, or the end of the data.
+    fn pixel_run(&mut self, depth: u8) -> Option<(u32, u8)> {
+        if self.bit >= self.data.len() * 8 {
+            return None;
+        }
+        match depth {
+            2 => self.pixel_run_2(),
+            4 => self.pixel_run_4(),
+            _ => self.pixel_run_8(),
+        }
+    }
+
+    /// Read a run of a 2-bit/pixel code string.
+    fn pixel_run_2(&mut self) -> Option<(u32, u8)> {
+        let code = self.read(2);
+        if code != 0 {
+            return Some((1, code));
+        }
+        if self.read(1) == 1 {
+            let run = u32::from(self.read(3)) + 3;
+            return Some((run, self.read(2)));
+        }
+        if self.read(1) == 1 {
+            return Some((1, 0));
+        }
+        match self.read(2) {
+            0 => None,
+            1 => Some((2, 0)),
+            2 => {
+                let run = u32::from(self.read(4)) + 12;
+                Some((run, self.read(2)))
+            }
+            _ => {
+                let run = u32::from(self.read(8)) + 29;
+                Some((run, self.read(2)))
+            }
+        }
+    }
+
+    /// Read a run of a 4-bit/pixel code string.
+    fn pixel_run_4(&mut self) -> Option<(u32, u8)> {
+        let code = self.read(4);
+        if code != 0 {
+            return Some((1, code));
+        }
+        if self.read(1) == 0 {
+            let run = self.read(3);
+            return (run != 0).then_some((u32::from(run) + 2, 0));
+        }
+        if self.read(1) == 0 {
+            let run = u32::from(self.read(2)) + 4;
+            return Some((run, self.read(4)));
+        }
+        match self.read(2) {
+            0 => Some((1, 0)),
+            1 => Some((2, 0)),
+            2 => {
+                let run = u32::from(self.read(4)) + 9;
+                Some((run, self.read(4)))
+            }
+            _ => {
+                let run = u32::from(self.read(8)) + 25;
+                Some((run, self.read(4)))
+            }
+        }
+    }
+
+    /// Read a run of an 8-bit/pixel code string.
+    fn pixel_run_8(&mut self) -> Option<(u32, u8)> {
+        let code = self.read(8);
+        if code != 0 {
+            return Some((1, code));
+        }
+        if self.read(1) == 0 {
+            let run = self.read(7);
+            return (run != 0).then_some((u32::from(run), 0));
+        }
+        let run = u32::from(self.read(7));
+        Some((run, self.read(8)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+
+    use image::LumaA;
+
+    use super::{parse, Error, PAGE_COMPOSITION, TS_PACKET_SIZE};
+
+    const PID: u16 = 0x0100;
+    const PAGE: u16 = 1;
+
+    /// Build a subtitling segment of `kind` for `page_id`.
+    fn segment(kind: u8, page_id: u16, data: &[u8]) -> Vec<u8> {
+        let mut segment = vec![0x0F, kind];
+        segment.extend_from_slice(&page_id.to_be_bytes());
+        segment.extend_from_slice(&u16::try_from(data.len()).unwrap().to_be_bytes());
+        segment.extend_from_slice(data);
+        segment
+    }
+
+    /// Build a `PES` packet of private data presented at `msecs`.
+    fn pes(stream_id: u8, msecs: i64, payload: &[u8]) -> Vec<u8> {
+        let pts = msecs * 90;
+        let header = [
+            0x80,
+            0x80,
+            5,
+            0x21 | ((pts >> 29) & 0x0E) as u8,
+            (pts >> 22) as u8,
+            ((pts >> 14) & 0xFE) as u8 | 1,
+            (pts >> 7) as u8,
+            ((pts << 1) & 0xFE) as u8 | 1,
+        ];
+        let length = u16::try_from(header.len() + payload.len()).unwrap();
+        let mut pes = vec![0, 0, 1, stream_id];
+        pes.extend_from_slice(&length.to_be_bytes());
+        pes.extend_from_slice(&header);
+        pes.extend_from_slice(payload);
+        pes
+    }
+
+    /// Build a `PES` packet of `DVB` subtitles with `segments`, presented at `msecs`.
+    fn subtitle_pes(msecs: i64, segments: &[Vec<u8>]) -> Vec<u8> {
+        let mut payload = vec![0x20, 0x00];
+        payload.extend(segments.concat());
+        payload.push(0xFF);
+        pes(0xBD, msecs, &payload)
+    }
+
+    /// Split `pes` in transport stream packets of `pid`, padded at the end.
+    fn packets(pid: u16, pes: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for (counter, chunk) in pes.chunks(TS_PACKET_SIZE - 4).enumerate() {
+            let unit_start = if counter == 0 { 0x40 } else { 0 };
+            let [pid_hi, pid_lo] = pid.to_be_bytes();
+            data.extend_from_slice(&[
+                0x47,
+                unit_start | pid_hi,
+                pid_lo,
+                0x10 | (counter as u8 & 0x0F),
+            ]);
+            data.extend_from_slice(chunk);
+            data.resize(data.len() + TS_PACKET_SIZE - 4 - chunk.len(), 0xFF);
+        }
+        data
+    }
+
+    /// Segments of a display set of `page_id`: a 4x2 white region at 10,20.
+    fn display_set(page_id: u16) -> Vec<Vec<u8>> {
+        vec![
+            // Time-out of 10s, acquisition point, region 0 at 10,20.
+            segment(PAGE_COMPOSITION, page_id, &[10, 0x08, 0, 0, 0, 10, 0, 20]),
+            // Region 0 of 4x2 pixels of 4 bits, filled with code 0, showing object 1 at 0,0.
+            segment(
+                0x11,
+                page_id,
+                &[0, 0x08, 0, 4, 0, 2, 0x08, 0, 0, 0, 0, 1, 0, 0, 0, 0],
+            ),
+            // Code 1 of the 4 bits entries of CLUT 0 is opaque white.
+            segment(0x12, page_id, &[0, 0, 1, 0x41, 255, 128, 128, 0]),
+            // Object 1 coded as pixels, a line of 4 pixels of code 1, repeated in the bottom field.
+            segment(
+                0x13,
+                page_id,
+                &[0, 1, 0, 0, 5, 0, 0, 0x11, 0x11, 0x11, 0x00, 0xF0],
+            ),
+            segment(0x80, page_id, &[]),
+        ]
+    }
+
+    /// Transport stream showing the display set of `page_id` from 1s to 3s.
+    fn sample(page_id: u16) -> Vec<u8> {
+        let mut data = packets(PID, &subtitle_pes(1000, &display_set(page_id)));
+        let clear = segment(PAGE_COMPOSITION, page_id, &[10, 0]);
+        data.extend(packets(PID, &subtitle_pes(3000, &[clear])));
+        data
+    }
+
+    #[test]
+    fn decode_sample() {
+        let subtitles = parse(sample(PAGE).as_slice(), 1024, None).unwrap();
+        assert_eq!(subtitles.len(), 1);
+        let (time, image) = &subtitles[0];
+        assert_eq!((time.start.msecs(), time.end.msecs()), (0, 2000));
+        assert_eq!(image.dimensions(), (4, 2));
+        assert!(image.pixels().all(|pixel| *pixel == LumaA([255, 255])));
+    }
+
+    #[test]
+    fn ignore_other_pages() {
+        let mut data = packets(PID, &subtitle_pes(0, &display_set(PAGE)));
+        data.extend(packets(PID, &subtitle_pes(500, &display_set(PAGE + 1))));
+        let clear = segment(PAGE_COMPOSITION, PAGE, &[10, 0]);
+        data.extend(packets(PID, &subtitle_pes(2000, &[clear])));
+        let subtitles = parse(data.as_slice(), 1024, None).unwrap();
+        assert_eq!(subtitles.len(), 1);
+        let (time, _) = &subtitles[0];
+        assert_eq!((time.start.msecs(), time.end.msecs()), (0, 2000));
+    }
+
+    #[test]
+    fn time_from_first_timestamp_of_any_stream() {
+        let mut data = packets(0x0200, &pes(0xE0, 500, &[0; 400]));
+        data.extend(sample(PAGE));
+        let subtitles = parse(data.as_slice(), 1024, None).unwrap();
+        let (time, _) = &subtitles[0];
+        assert_eq!((time.start.msecs(), time.end.msecs()), (500, 2500));
+    }
+
+    #[test]
+    fn reject_empty_and_not_transport_stream() {
+        assert!(matches!(
+            parse(io::empty(), 1024, None),
+            Err(Error::NotTransportStream)
+        ));
+        assert!(matches!(
+            parse([0u8; TS_PACKET_SIZE * 2].as_slice(), 1024, None),
+            Err(Error::NotTransportStream)
+        ));
+    }
+
+    #[test]
+    fn reject_stream_without_subtitles() {
+        let data = packets(0x0200, &pes(0xE0, 0, &[0; 100]));
+        assert!(matches!(
+            parse(data.as_slice(), 1024, None),
+            Err(Error::NoSubtitleStream)
+        ));
+    }
+
+    #[test]
+    fn forward_read_errors() {
+        struct Failing;
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("broken"))
+            }
+        }
+        assert!(matches!(parse(Failing, 1024, None), Err(Error::Read(_))));
+    }
+
+    #[test]
+    fn truncated_stream() {
+        let data = sample(PAGE);
+        for size in (1..data.len()).step_by(7) {
+            // A truncated stream may have no subtitles, but never panics.
+            let _ = parse(&data[..size], 1024, None);
+        }
+        let subtitles = parse(&data[..TS_PACKET_SIZE + 100], 1024, None).unwrap();
+        assert_eq!(subtitles.len(), 1);
+    }
+
+    #[test]
+    fn malformed_segments() {
+        let mut segments = display_set(PAGE);
+        // Object data with field lengths beyond the segment, and unknown pixel data types.
+        segments.insert(3, segment(0x13, PAGE, &[0, 1, 0, 0xFF, 0xFF, 0, 0, 0x11]));
+        segments.insert(
+            3,
+            segment(0x13, PAGE, &[0, 1, 0, 0, 3, 0, 0, 0x42, 0x11, 0x11]),
+        );
+        // Region larger than the maximum size, and a segment length beyond the packet.
+        segments.push(segment(
+            0x11,
+            PAGE,
+            &[1, 0x08, 0xFF, 0xFF, 0xFF, 0xFF, 0x08, 0, 0, 0],
+        ));
+        let mut truncated = segment(0x13, PAGE, &[0, 1]);
+        truncated[5] = 0xFF;
+        segments.push(truncated);
+        let data = packets(PID, &subtitle_pes(0, &segments));
+        let subtitles = parse(data.as_slice(), 1024, None).unwrap();
+        assert_eq!(subtitles.len(), 1);
+
+        for byte in 0..=u8::MAX {
+            let garbage = [byte; 64];
+            let mut payload = vec![0x20, 0x00];
+            payload.extend_from_slice(&segment(byte, PAGE, &garbage));
+            payload.extend_from_slice(&garbage);
+            let data = packets(PID, &pes(0xBD, 0, &payload));
+            let _ = parse(data.as_slice(), 1024, None);
+        }
+    }
+}
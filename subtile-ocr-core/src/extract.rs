@@ -0,0 +1,189 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use log::info;
+use subtile::time::TimeSpan;
+
+use crate::idx::{self, IdxEntry, IdxTiming};
+
+/// Size of a `PGS` segment header.
+const PGS_HEADER_SIZE: usize = 13;
+
+/// Tolerance in milliseconds between a packet timestamp and the decoded subtitle time.
+const TOLERANCE_MS: i64 = 20;
+
+/// Write the subtitles of `input` displayed at `times` in `output`, without re-encoding them.
+///
+/// `times` are compared with the packets timestamps shifted by `time_offset` from cli if present,
+/// or by the idx timing directives for `VobSub`. For `VobSub` the `.sub` file is written next to `output`.
+///
+/// # Errors
+///
+/// Will return an error if the input files can't be read, are invalid, or if the output can't be written.
+/// The `DVB` subtitles of a transport stream can't be extracted, and `output` can't be `input`.
+#[profiling::function]
+pub fn extract(
+    input: &Path,
+    output: &Path,
+    times: &[TimeSpan],
+    time_offset: Option<i64>,
+) -> io::Result<()> {
+    if input.extension().is_some_and(|ext| ext == "ts") {
+        return Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "the DVB subtitles of a transport stream can't be extracted",
+        ));
+    }
+    let is_vobsub = input.extension().is_some_and(|ext| ext == "idx");
+    let is_input = is_same_file(input, output)
+        || (is_vobsub && is_same_file(&input.with_extension("sub"), &output.with_extension("sub")));
+    if is_input {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "the output file is the input file",
+        ));
+    }
+    crate::create_parent_dirs(output)?;
+    if is_vobsub {
+        extract_vobsub(input, output, times, time_offset)
+    } else {
+        extract_pgs(input, output, times, time_offset.unwrap_or(0))
+    }
+}
+
+/// Copy the display sets of a `PGS` stream which are shown during `times`.
+fn extract_pgs(
+    input: &Path,
+    output: &Path,
+    times: &[TimeSpan],
+    time_offset: i64,
+) -> io::Result<()> {
+    let data = fs::read(input)?;
+    let mut extracted = Vec::new();
+    let mut kept_count = 0;
+    let mut display_set_start = None;
+    let mut pos = 0;
+    while pos + PGS_HEADER_SIZE <= data.len() {
+        let header = &data[pos..pos + PGS_HEADER_SIZE];
+        if &header[..2] != b"PG" {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid PGS segment header at offset {pos}"),
+            ));
+        }
+        let pts = u32::from_be_bytes([header[2], header[3], header[4], header[5]]);
+        let kind = header[10];
+        let size = usize::from(u16::from_be_bytes([header[11], header[12]]));
+        let end = pos + PGS_HEADER_SIZE + size;
+        if end > data.len() {
+            break; // truncated segment
+        }
+
+        let (start, start_pts) = *display_set_start.get_or_insert((pos, pts));
+        // The end segment closes the display set.
+        if kind == 0x80 {
+            if is_displayed(times, i64::from(start_pts) / 90 + time_offset) {
+                extracted.extend_from_slice(&data[start..end]);
+                kept_count += 1;
+            }
+            display_set_start = None;
+        }
+        pos = end;
+    }
+
+    fs::write(output, extracted)?;
+    info!(
+        "{kept_count} display sets extracted to {}",
+        output.display()
+    );
+    Ok(())
+}
+
+/// Copy the packets of a `VobSub` stream which are shown during `times`, with an updated idx file.
+fn extract_vobsub(
+    input: &Path,
+    output: &Path,
+    times: &[TimeSpan],
+    time_offset: Option<i64>,
+) -> io::Result<()> {
+    let content = idx::read_content(input)?;
+    let sub = fs::read(input.with_extension("sub"))?;
+    let idx_timing = IdxTiming::parse(&content);
+
+    // A subtitle packet ends where the next one starts, whatever its stream.
+    let mut positions = idx::parse_entries(&content)
+        .into_iter()
+        .map(|entry| entry.filepos)
+        .collect::<Vec<_>>();
+    positions.sort_unstable();
+
+    let mut extracted_idx = String::with_capacity(content.len());
+    let mut extracted_sub = Vec::new();
+    let mut kept_count = 0;
+    for line in content.lines() {
+        let Some(entry) = IdxEntry::parse(line) else {
+            extracted_idx.push_str(line);
+            extracted_idx.push('\n');
+            continue;
+        };
+        let timestamp = match time_offset {
+            Some(offset) => entry.timestamp + offset,
+            None => idx_timing.apply_msecs(entry.timestamp),
+        };
+        if !is_displayed(times, timestamp) {
+            continue;
+        }
+
+        let start = usize::try_from(entry.filepos)
+            .ok()
+            .filter(|start| *start <= sub.len())
+            .ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "filepos {:x} is after the end of the sub file",
+                        entry.filepos
+                    ),
+                )
+            })?;
+        let end = positions
+            .iter()
+            .find(|pos| **pos > entry.filepos)
+            .and_then(|pos| usize::try_from(*pos).ok())
+            .map_or(sub.len(), |end| end.min(sub.len()));
+        let timestamp_part = line
+            .split_once(',')
+            .map_or(line, |(timestamp, _)| timestamp);
+        let _ = writeln!(
+            extracted_idx,
+            "{timestamp_part}, filepos: {:09x}",
+            extracted_sub.len()
+        );
+        extracted_sub.extend_from_slice(&sub[start..end]);
+        kept_count += 1;
+    }
+
+    fs::write(output, extracted_idx)?;
+    fs::write(output.with_extension("sub"), extracted_sub)?;
+    info!("{kept_count} subtitles extracted to {}", output.display());
+    Ok(())
+}
+
+/// Check if `output` is the existing file `input`, through links or relative paths.
+fn is_same_file(input: &Path, output: &Path) -> bool {
+    match (fs::canonicalize(input), fs::canonicalize(output)) {
+        (Ok(input), Ok(output)) => input == output,
+        _ => false,
+    }
+}
+
+/// Check if a packet with a timestamp of `msecs` is shown during one of `times`.
+fn is_displayed(times: &[TimeSpan], msecs: i64) -> bool {
+    times
+        .iter()
+        .any(|time| time.start.msecs() - TOLERANCE_MS <= msecs && msecs <= time.end.msecs())
+}
@@ -0,0 +1,280 @@
+//! C API, to embed `subtile-ocr` in tools written in other languages.
+//!
+//! Build it as a shared library with
+//! `cargo rustc -p subtile-ocr-core --lib --release --features ffi --crate-type cdylib`,
+//! the declarations are in `include/subtile_ocr.h`.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString, OsString},
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    ptr,
+};
+
+use subtile::srt;
+
+use crate::{run_to_subtitles, Config, ErrorKind, ImageConfig, InputConfig, OcrConfig};
+
+/// The subtitles have been converted.
+pub const SUBTILE_OCR_OK: i32 = 0;
+/// An argument is invalid (null pointer, invalid UTF-8 or invalid option value).
+pub const SUBTILE_OCR_ERROR_ARGUMENT: i32 = 1;
+/// The process can't be set up.
+pub const SUBTILE_OCR_ERROR_SETUP: i32 = 2;
+/// The input file can't be identified or opened.
+pub const SUBTILE_OCR_ERROR_INPUT: i32 = 3;
+/// The subtitles can't be decoded from the input.
+pub const SUBTILE_OCR_ERROR_PARSE: i32 = 4;
+/// The `OCR` of subtitles images failed.
+pub const SUBTILE_OCR_ERROR_OCR: i32 = 5;
+/// The results can't be written.
+pub const SUBTILE_OCR_ERROR_OUTPUT: i32 = 6;
+/// An unexpected error happened, like a panic.
+pub const SUBTILE_OCR_ERROR_INTERNAL: i32 = 7;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Options of [`subtile_ocr_process_file`].
+///
+/// Negative numbers, null pointers and `false` keep the default value of the option.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SubtileOcrOptions {
+    /// Tesseract language(s) to use, like `eng` or `eng+fra`. Required.
+    pub lang: *const c_char,
+    /// Path to Tesseract's tessdata directory.
+    pub tessdata_dir: *const c_char,
+    /// Dots per inch of the subtitles images.
+    pub dpi: i32,
+    /// Border in pixels to surround each subtitle image.
+    pub border: i32,
+    /// Threshold for subtitle image binarization, between 0.0 and 1.0.
+    pub threshold: f32,
+    /// Set `OMP_THREAD_LIMIT` in the environment of the process to limit the threads of each
+    /// Tesseract instance, which oversubscribe the thread pool of the `OCR` otherwise.
+    ///
+    /// Only set it if the process doesn't read its environment from other threads,
+    /// as changing it isn't thread-safe.
+    pub thread_limit_env: bool,
+}
+
+/// Convert the subtitles of the `.idx` or `.sup` file at `input` to `SRT`.
+///
+/// On success, `*srt_out` is set to a nul-terminated `SRT` string, to release with
+/// [`subtile_ocr_free_string`]. On failure, an error code is returned and the message
+/// is available with [`subtile_ocr_last_error`].
+///
+/// # Safety
+///
+/// `input` and the strings of `options` must be null or valid nul-terminated strings,
+/// `options` must be null or point to a valid [`SubtileOcrOptions`], and `srt_out`
+/// must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn subtile_ocr_process_file(
+    input: *const c_char,
+    options: *const SubtileOcrOptions,
+    srt_out: *mut *mut c_char,
+) -> i32 {
+    LAST_ERROR.set(None);
+    if srt_out.is_null() {
+        return set_error(SUBTILE_OCR_ERROR_ARGUMENT, "`srt_out` is null");
+    }
+    *srt_out = ptr::null_mut();
+    let (Some(input), Some(options)) = (path_arg(input), options.as_ref()) else {
+        return set_error(
+            SUBTILE_OCR_ERROR_ARGUMENT,
+            "`input` or `options` is invalid",
+        );
+    };
+    let Some(lang) = str_arg(options.lang) else {
+        return set_error(SUBTILE_OCR_ERROR_ARGUMENT, "`options.lang` is invalid");
+    };
+
+    let mut ocr = OcrConfig::default()
+        .with_lang(lang)
+        .with_thread_limit_env(options.thread_limit_env);
+    if let Some(tessdata_dir) = str_arg(options.tessdata_dir) {
+        ocr = ocr.with_tessdata_dir(Some(tessdata_dir.to_owned()));
+    }
+    if options.dpi >= 0 {
+        ocr = ocr.with_dpi(options.dpi);
+    }
+    let mut image = ImageConfig::default();
+    if let Ok(border) = u32::try_from(options.border) {
+        image = image.with_border(border);
+    }
+    match options.threshold {
+        threshold if threshold < 0.0 => {}
+        threshold if threshold <= 1.0 => image = image.with_threshold(threshold),
+        // Also for NaN.
+        _ => {
+            return set_error(
+                SUBTILE_OCR_ERROR_ARGUMENT,
+                "`options.threshold` must be between 0.0 and 1.0",
+            )
+        }
+    }
+    let opt = Config::default()
+        .with_input(InputConfig::default().with_path(PathBuf::from(input)))
+        .with_image(image)
+        .with_ocr(ocr);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| run_to_subtitles(&opt)));
+    let subtitles = match result {
+        Ok(Ok(subtitles)) => subtitles,
+        Ok(Err(err)) => {
+            let code = match err.kind() {
+                ErrorKind::Input => SUBTILE_OCR_ERROR_INPUT,
+                ErrorKind::Parse => SUBTILE_OCR_ERROR_PARSE,
+                ErrorKind::Ocr => SUBTILE_OCR_ERROR_OCR,
+                ErrorKind::Output => SUBTILE_OCR_ERROR_OUTPUT,
+                _ => SUBTILE_OCR_ERROR_SETUP,
+            };
+            return set_error(code, &format!("{:#}", anyhow::Error::new(err)));
+        }
+        Err(_) => return set_error(SUBTILE_OCR_ERROR_INTERNAL, "subtile-ocr panicked"),
+    };
+
+    let mut content = Vec::new();
+    if let Err(err) = srt::write_srt(&mut content, &subtitles) {
+        return set_error(SUBTILE_OCR_ERROR_OUTPUT, &err.to_string());
+    }
+    match CString::new(content) {
+        Ok(content) => {
+            *srt_out = content.into_raw();
+            SUBTILE_OCR_OK
+        }
+        Err(_) => set_error(SUBTILE_OCR_ERROR_OUTPUT, "the subtitles contain a nul byte"),
+    }
+}
+
+/// Release a string returned by [`subtile_ocr_process_file`].
+///
+/// # Safety
+///
+/// `string` must be null or a string returned by [`subtile_ocr_process_file`], not already released.
+#[no_mangle]
+pub unsafe extern "C" fn subtile_ocr_free_string(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Get the message of the last error of the calling thread, or null if there is none.
+///
+/// The message is valid until the next call of [`subtile_ocr_process_file`] on the same thread.
+#[no_mangle]
+pub extern "C" fn subtile_ocr_last_error() -> *const c_char {
+    LAST_ERROR.with_borrow(|message| {
+        message
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Read a string argument, `None` if null or not valid UTF-8.
+///
+/// # Safety
+///
+/// `arg` must be null or a valid nul-terminated string.
+unsafe fn str_arg<'a>(arg: *const c_char) -> Option<&'a str> {
+    if arg.is_null() {
+        None
+    } else {
+        CStr::from_ptr(arg).to_str().ok()
+    }
+}
+
+/// Read a path argument, `None` if null.
+///
+/// The bytes of the path are kept as is on Unix, where paths don't have to be valid UTF-8.
+/// Elsewhere, the path must be valid UTF-8.
+///
+/// # Safety
+///
+/// `arg` must be null or a valid nul-terminated string.
+unsafe fn path_arg(arg: *const c_char) -> Option<OsString> {
+    if arg.is_null() {
+        return None;
+    }
+    let arg = CStr::from_ptr(arg);
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Some(std::ffi::OsStr::from_bytes(arg.to_bytes()).to_owned())
+    }
+    #[cfg(not(unix))]
+    {
+        arg.to_str().ok().map(OsString::from)
+    }
+}
+
+/// Store `message` as the last error of the thread, and return `code`.
+fn set_error(code: i32, message: &str) -> i32 {
+    let message = CString::new(message.replace('\0', " ")).ok();
+    LAST_ERROR.set(message);
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Options with the default values, for the file `input.sup`.
+    fn default_options(lang: &CStr) -> SubtileOcrOptions {
+        SubtileOcrOptions {
+            lang: lang.as_ptr(),
+            tessdata_dir: ptr::null(),
+            dpi: -1,
+            border: -1,
+            threshold: -1.0,
+            thread_limit_env: false,
+        }
+    }
+
+    /// Run [`subtile_ocr_process_file`] on `input.sup`, with the message of its error.
+    fn process(options: &SubtileOcrOptions) -> (i32, Option<String>) {
+        let mut srt = ptr::null_mut();
+        let code = unsafe { subtile_ocr_process_file(c"input.sup".as_ptr(), options, &mut srt) };
+        assert!(srt.is_null());
+        let message = unsafe { str_arg(subtile_ocr_last_error()) }.map(str::to_owned);
+        (code, message)
+    }
+
+    #[test]
+    fn reject_threshold_out_of_range() {
+        for threshold in [1.5, f32::NAN] {
+            let options = SubtileOcrOptions {
+                threshold,
+                ..default_options(c"eng")
+            };
+            let (code, message) = process(&options);
+            assert_eq!(code, SUBTILE_OCR_ERROR_ARGUMENT);
+            assert_eq!(
+                message.as_deref(),
+                Some("`options.threshold` must be between 0.0 and 1.0")
+            );
+        }
+    }
+
+    #[test]
+    fn reject_null_arguments() {
+        let options = SubtileOcrOptions {
+            lang: ptr::null(),
+            ..default_options(c"eng")
+        };
+        assert_eq!(process(&options).0, SUBTILE_OCR_ERROR_ARGUMENT);
+
+        let code = unsafe {
+            subtile_ocr_process_file(
+                c"input.sup".as_ptr(),
+                &default_options(c"eng"),
+                ptr::null_mut(),
+            )
+        };
+        assert_eq!(code, SUBTILE_OCR_ERROR_ARGUMENT);
+    }
+}
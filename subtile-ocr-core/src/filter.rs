@@ -0,0 +1,178 @@
+use image::{imageops, GrayImage, Luma};
+use log::info;
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+use subtile::time::TimeSpan;
+
+use crate::{cue::Region, qa::QaReport, split};
+
+/// Split the subtitles whose image has groups of lines far apart into one subtitle per group.
+///
+/// Some discs render unrelated texts (e.g. a sign at the top and the dialogue at the bottom)
+/// in the same image. Each group is cropped with a `border`, and keeps the time of the subtitle,
+/// with the part of its display region covered by the group.
+#[must_use]
+#[profiling::function]
+pub fn split_separated_groups(
+    times: Vec<TimeSpan>,
+    images: Vec<GrayImage>,
+    regions: Vec<Option<Region>>,
+    border: u32,
+) -> (Vec<TimeSpan>, Vec<GrayImage>, Vec<Option<Region>>) {
+    let mut split_times = Vec::with_capacity(times.len());
+    let mut split_images = Vec::with_capacity(images.len());
+    let mut split_regions = Vec::with_capacity(regions.len());
+    let mut split_count = 0;
+    for ((time, image), region) in times.into_iter().zip(images).zip(regions) {
+        let groups = split::find_groups(&image);
+        if groups.len() < 2 {
+            split_times.push(time);
+            split_images.push(image);
+            split_regions.push(region);
+            continue;
+        }
+        split_count += 1;
+        for group in &groups {
+            split_times.push(time);
+            split_images.push(split::crop_rows(&image, group, border));
+            split_regions.push(region.map(|region| region.part(group, image.height())));
+        }
+    }
+
+    if split_count > 0 {
+        info!("{split_count} subtitles with separated groups of lines have been split");
+    }
+    (split_times, split_images, split_regions)
+}
+
+/// Count the text pixels of an image prepared for `OCR`, with dark text on a light background.
+#[must_use]
+pub fn ink_pixels(image: &GrayImage) -> usize {
+    image.pixels().filter(|pixel| pixel.0[0] < 128).count()
+}
+
+/// Border in pixels added around the images with text touching their edges.
+const EDGE_BORDER: u32 = 20;
+
+/// Expand the border of the images with text touching the edges of the decoded image (e.g. cropped renders).
+///
+/// `border` is the border already added around the decoded images, so the text touching
+/// their edges is at `border` pixels from the edges. Expanded subtitles are flagged in `qa`.
+#[profiling::function]
+pub fn expand_edge_borders(
+    times: &[TimeSpan],
+    images: &mut [GrayImage],
+    border: u32,
+    qa: &mut QaReport,
+) {
+    let expanded = images
+        .par_iter_mut()
+        .map(|image| {
+            let touches_edge = touches_edge(image, border);
+            if touches_edge {
+                let (width, height) = image.dimensions();
+                let mut bordered = GrayImage::from_pixel(
+                    width + EDGE_BORDER * 2,
+                    height + EDGE_BORDER * 2,
+                    Luma([255]),
+                );
+                imageops::replace(&mut bordered, image, EDGE_BORDER.into(), EDGE_BORDER.into());
+                *image = bordered;
+            }
+            touches_edge
+        })
+        .collect::<Vec<_>>();
+
+    let mut expanded_count = 0;
+    for (idx, time) in expanded
+        .iter()
+        .zip(times)
+        .enumerate()
+        .filter_map(|(idx, (expanded, time))| expanded.then_some((idx, time)))
+    {
+        qa.add(
+            *time,
+            format!(
+                "subtitle {}: text touching the image edge, border expanded",
+                idx + 1
+            ),
+        );
+        expanded_count += 1;
+    }
+    if expanded_count > 0 {
+        info!("{expanded_count} subtitles with text touching the image edges have a larger border");
+    }
+}
+
+/// Check if `image` has text pixels at `border` pixels or less from its edges.
+fn touches_edge(image: &GrayImage, border: u32) -> bool {
+    let (width, height) = image.dimensions();
+    image.enumerate_pixels().any(|(x, y, pixel)| {
+        let near_edge =
+            x <= border || y <= border || x + border + 1 >= width || y + border + 1 >= height;
+        near_edge && split::is_ink(*pixel)
+    })
+}
+
+/// Minimum ratio of enclosed background to text pixels for an image to be considered
+/// as made of hollow glyphs. Regular text (with counters like in `o` or `e`) stays well below.
+const HOLLOW_MIN_RATIO: f32 = 0.6;
+
+/// Fill the glyphs of the images rendered as outlines only (dark outline, light fill).
+///
+/// The background enclosed by text pixels is painted as text in the images where it
+/// is large relative to the text, which happens with heavy outlined subtitle fonts.
+#[profiling::function]
+pub fn fill_hollow_glyphs(images: &mut [GrayImage]) {
+    let filled_count = images
+        .par_iter_mut()
+        .map(|image| usize::from(fill_hollow_image(image)))
+        .sum::<usize>();
+    if filled_count > 0 {
+        info!("{filled_count} subtitles with hollow glyphs have been filled");
+    }
+}
+
+/// Fill the background enclosed by text pixels of `image` if it looks like hollow glyphs.
+///
+/// Returns `true` if the image has been modified.
+fn fill_hollow_image(image: &mut GrayImage) -> bool {
+    let (width, height) = image.dimensions();
+    let is_ink = |x: u32, y: u32| image.get_pixel(x, y).0[0] < 128;
+
+    // Flood the background from the image borders, the remaining background is enclosed.
+    let mut outside = vec![false; width as usize * height as usize];
+    let mut stack = (0..width)
+        .flat_map(|x| [(x, 0), (x, height.saturating_sub(1))])
+        .chain((0..height).flat_map(|y| [(0, y), (width.saturating_sub(1), y)]))
+        .collect::<Vec<_>>();
+    while let Some((x, y)) = stack.pop() {
+        if x >= width || y >= height {
+            continue;
+        }
+        let idx = (y * width + x) as usize;
+        if outside[idx] || is_ink(x, y) {
+            continue;
+        }
+        outside[idx] = true;
+        stack.push((x + 1, y));
+        stack.push((x, y + 1));
+        if let Some(x) = x.checked_sub(1) {
+            stack.push((x, y));
+        }
+        if let Some(y) = y.checked_sub(1) {
+            stack.push((x, y));
+        }
+    }
+
+    let ink = ink_pixels(image);
+    let enclosed = outside.iter().filter(|outside| !**outside).count() - ink;
+    if ink == 0 || (enclosed as f32) < ink as f32 * HOLLOW_MIN_RATIO {
+        return false;
+    }
+    for (outside, pixel) in outside.iter().zip(image.pixels_mut()) {
+        if !outside {
+            pixel.0[0] = 0;
+        }
+    }
+    true
+}
@@ -0,0 +1,74 @@
+use log::info;
+use subtile::time::TimeSpan;
+
+/// Half of the window around each subtitle in which the subtitles are counted, in milliseconds.
+const DENSITY_HALF_WINDOW: i64 = 60_000;
+
+/// A subtitle is sparse if the density around it is at most this fraction of the median density.
+const SPARSE_DIVISOR: usize = 4;
+
+/// Median density under which the track already looks like a forced only track.
+const FORCED_TRACK_DENSITY: usize = 6;
+
+/// Shortest duration of a forced subtitle, in milliseconds; shorter ones are mostly glitches.
+const MIN_FORCED_DURATION: i64 = 700;
+
+/// Longest duration of a forced subtitle, in milliseconds; longer ones are mostly lyrics or captions.
+const MAX_FORCED_DURATION: i64 = 8_000;
+
+/// Propose the forced subtitles of a full track, without forced flags.
+///
+/// The forced subtitles (e.g. the translation of dialogues in a foreign language, or of signs)
+/// are scattered in a track of the whole dialogues: the subtitles with few others in the
+/// two minutes around them, compared to the median of the track, are proposed if their
+/// duration is the one of a dialogue line. A track which is sparse everywhere already
+/// looks like forced only, so nothing is proposed.
+///
+/// The proposal is a heuristic, to be reviewed before use.
+#[must_use]
+#[profiling::function]
+pub fn infer_forced(subtitles: &[(TimeSpan, String)]) -> Vec<(TimeSpan, String)> {
+    let mut starts = subtitles
+        .iter()
+        .map(|(time, _)| time.start.msecs())
+        .collect::<Vec<_>>();
+    starts.sort_unstable();
+    let densities = subtitles
+        .iter()
+        .map(|(time, _)| {
+            let start = time.start.msecs();
+            let first = starts.partition_point(|other| *other < start - DENSITY_HALF_WINDOW);
+            let last = starts.partition_point(|other| *other <= start + DENSITY_HALF_WINDOW);
+            last - first
+        })
+        .collect::<Vec<_>>();
+    let mut sorted = densities.clone();
+    sorted.sort_unstable();
+    let Some(median) = sorted.get(sorted.len() / 2).copied() else {
+        return Vec::new();
+    };
+    if median < FORCED_TRACK_DENSITY {
+        info!(
+            "No forced subtitles inferred: the track already looks like a forced only track, \
+             with a median of {median} subtitle(s) in two minutes"
+        );
+        return Vec::new();
+    }
+
+    let forced = subtitles
+        .iter()
+        .zip(densities)
+        .filter(|((time, _), density)| {
+            let duration = time.end.msecs() - time.start.msecs();
+            density * SPARSE_DIVISOR <= median
+                && (MIN_FORCED_DURATION..=MAX_FORCED_DURATION).contains(&duration)
+        })
+        .map(|(subtitle, _)| subtitle.clone())
+        .collect::<Vec<_>>();
+    info!(
+        "{} subtitle(s) of {} inferred as forced, with a median of {median} subtitle(s) in two minutes",
+        forced.len(),
+        subtitles.len()
+    );
+    forced
+}
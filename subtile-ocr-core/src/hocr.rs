@@ -0,0 +1,55 @@
+use std::{
+    io::{self, Write},
+    sync::LazyLock,
+};
+
+use regex::Regex;
+use subtile::time::TimeSpan;
+
+use crate::timing::format_timestamp;
+
+/// Header of the written `hOCR` documents.
+const HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.0 Transitional//EN" "http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd">
+<html xmlns="http://www.w3.org/1999/xhtml" xml:lang="en" lang="en">
+ <head>
+  <title></title>
+  <meta http-equiv="Content-Type" content="text/html;charset=utf-8"/>
+  <meta name="ocr-system" content="subtile-ocr with tesseract"/>
+  <meta name="ocr-capabilities" content="ocr_page ocr_carea ocr_par ocr_line ocrx_word"/>
+ </head>
+ <body>
+"#;
+
+/// Footer of the written `hOCR` documents.
+const FOOTER: &str = " </body>\n</html>\n";
+
+/// Identifiers of the `hOCR` elements.
+static ID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"id='([^']*)'").unwrap_or_else(|_| unreachable!()));
+
+/// Write the Tesseract `hOCR` `pages` of the subtitles in one document.
+///
+/// Each page is in a `div` with the times of the subtitle in `data-start` and `data-end`,
+/// and its element identifiers are prefixed with the subtitle number to keep them unique.
+///
+/// # Errors
+///
+/// Will return an error if writing fails.
+pub fn write_hocr(writer: &mut impl Write, pages: &[(TimeSpan, String)]) -> io::Result<()> {
+    writer.write_all(HEADER.as_bytes())?;
+    for (idx, (time, page)) in pages.iter().enumerate() {
+        let number = idx + 1;
+        writeln!(
+            writer,
+            "  <div class='subtitle' id='subtitle_{number}' data-start='{}' data-end='{}'>",
+            format_timestamp(time.start, '.'),
+            format_timestamp(time.end, '.')
+        )?;
+        let page = ID.replace_all(page.trim_end(), format!("id='s{number}_$1'"));
+        writeln!(writer, "{page}")?;
+        writeln!(writer, "  </div>")?;
+    }
+    writer.write_all(FOOTER.as_bytes())?;
+    writer.flush()
+}
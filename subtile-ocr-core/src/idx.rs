@@ -0,0 +1,344 @@
+use std::{fs, io, iter, path::Path};
+
+use image::Rgb;
+use subtile::{time::TimeSpan, vobsub};
+
+use crate::timing::shift_span;
+
+/// Timing directives of an idx file, not applied by the `VobSub` decoder.
+#[derive(Debug, Default)]
+pub struct IdxTiming {
+    /// Global offset in milliseconds, from the `time offset:` line.
+    time_offset: i64,
+    /// Timestamps in milliseconds with the cumulated `delay:` to apply from them.
+    delays: Vec<(i64, i64)>,
+}
+
+impl IdxTiming {
+    /// Read the timing directives of the idx file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the file can't be read.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        Ok(Self::parse(&read_content(path)?))
+    }
+
+    /// Parse the timing directives of idx file `content`.
+    #[must_use]
+    pub fn parse(content: &str) -> Self {
+        let mut timing = Self::default();
+        let mut delay = 0;
+        for line in content.lines().map(str::trim) {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            match key.trim() {
+                "time offset" => {
+                    if let Some(offset) = parse_time(value) {
+                        timing.time_offset = offset;
+                    }
+                }
+                "delay" => {
+                    if let Some(value) = parse_time(value) {
+                        delay += value;
+                    }
+                }
+                "timestamp" => {
+                    let timestamp = value.split(',').next().and_then(parse_time);
+                    if let Some(timestamp) = timestamp.filter(|_| delay != 0) {
+                        timing.delays.push((timestamp, delay));
+                    }
+                }
+                _ => {}
+            }
+        }
+        timing
+    }
+
+    /// Check if the directives don't change any time.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.time_offset == 0 && self.delays.is_empty()
+    }
+
+    /// Apply the directives on the time of a subtitle.
+    #[must_use]
+    pub fn apply(&self, time: TimeSpan) -> TimeSpan {
+        shift_span(time, self.offset_at(time.start.msecs()))
+    }
+
+    /// Apply the directives on a timestamp in milliseconds.
+    #[must_use]
+    pub fn apply_msecs(&self, msecs: i64) -> i64 {
+        msecs + self.offset_at(msecs)
+    }
+
+    /// Get the offset to apply on a subtitle starting at `start` milliseconds.
+    fn offset_at(&self, start: i64) -> i64 {
+        // Tolerate the rounding between the timestamp of the index and the decoded time.
+        const TOLERANCE_MS: i64 = 10;
+        let delay = self
+            .delays
+            .iter()
+            .take_while(|(timestamp, _)| *timestamp <= start + TOLERANCE_MS)
+            .last()
+            .map_or(0, |(_, delay)| *delay);
+        self.time_offset + delay
+    }
+}
+
+/// Colors of the `custom colors: ON` line of an idx file, replacing the palette colors of the subtitles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CustomColors {
+    /// Transparency of the four colors of the subtitles, from the `tridx:` bits.
+    transparent: [bool; 4],
+    /// The four colors of the subtitles, from the `colors:` values.
+    colors: [Rgb<u8>; 4],
+}
+
+impl CustomColors {
+    /// Read the enabled custom colors of the idx file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the file can't be read.
+    pub fn read(path: &Path) -> io::Result<Option<Self>> {
+        Ok(read_content(path)?.lines().find_map(Self::parse))
+    }
+
+    /// Parse a `custom colors: ON, tridx: 1000, colors: 000000, ffffff, 808080, 000000` line.
+    ///
+    /// Return `None` for other lines, and if the custom colors are `OFF`.
+    #[must_use]
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut fields = line
+            .trim()
+            .strip_prefix("custom colors:")?
+            .split(',')
+            .map(str::trim);
+        if !fields.next()?.eq_ignore_ascii_case("on") {
+            return None;
+        }
+        let tridx = fields.next()?.strip_prefix("tridx:")?.trim();
+        let first = fields.next()?.strip_prefix("colors:")?.trim();
+        let transparent = tridx
+            .chars()
+            .map(|bit| bit == '1')
+            .collect::<Vec<_>>()
+            .try_into()
+            .ok()?;
+        let colors = iter::once(first)
+            .chain(fields)
+            .map(|color| {
+                let rgb = u32::from_str_radix(color, 16)
+                    .ok()
+                    .filter(|_| color.len() == 6)?;
+                let [_, r, g, b] = rgb.to_be_bytes();
+                Some(Rgb([r, g, b]))
+            })
+            .collect::<Option<Vec<_>>>()?
+            .try_into()
+            .ok()?;
+        Some(Self {
+            transparent,
+            colors,
+        })
+    }
+
+    /// Get `palette` with the custom colors at the `indices` of the four colors of a subtitle.
+    ///
+    /// The transparent colors are set to black, which isn't read as text.
+    #[must_use]
+    pub fn apply(&self, palette: &vobsub::Palette, indices: [u8; 4]) -> vobsub::Palette {
+        let mut palette = *palette;
+        for ((index, color), transparent) in
+            indices.into_iter().zip(self.colors).zip(self.transparent)
+        {
+            if let Some(entry) = palette.get_mut(usize::from(index)) {
+                *entry = if transparent { Rgb([0, 0, 0]) } else { color };
+            }
+        }
+        palette
+    }
+}
+
+/// Subtitle entry of an idx file.
+#[derive(Debug, Clone, Copy)]
+pub struct IdxEntry {
+    /// Timestamp in milliseconds.
+    pub timestamp: i64,
+    /// Position of the subtitle packet in the sub file.
+    pub filepos: u64,
+}
+
+impl IdxEntry {
+    /// Parse a `timestamp: ..., filepos: ...` line.
+    #[must_use]
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim().strip_prefix("timestamp:")?;
+        let (timestamp, filepos) = line.split_once(',')?;
+        let filepos = filepos.trim().strip_prefix("filepos:")?.trim();
+        Some(Self {
+            timestamp: parse_time(timestamp)?,
+            filepos: u64::from_str_radix(filepos, 16).ok()?,
+        })
+    }
+}
+
+/// Read the content of an idx file, replacing invalid `UTF-8` sequences and skipping the `BOM`.
+///
+/// # Errors
+///
+/// Will return an error if the file can't be read.
+pub fn read_content(path: &Path) -> io::Result<String> {
+    let content = fs::read(path)?;
+    let content = String::from_utf8_lossy(&content);
+    Ok(content
+        .strip_prefix('\u{FEFF}')
+        .unwrap_or(&content)
+        .to_owned())
+}
+
+/// Order of the header lines of an idx file, as written by `VobSub`.
+const HEADER_ORDER: [&str; 13] = [
+    "size",
+    "org",
+    "scale",
+    "alpha",
+    "smooth",
+    "fadein/out",
+    "align",
+    "time offset",
+    "forced subs",
+    "palette",
+    "custom colors",
+    "langidx",
+    "delay",
+];
+
+/// Normalize idx file `content` for strict parsers.
+///
+/// Line endings are converted to `LF`, trailing spaces are removed, and the header lines
+/// (before the first `id:` line) are sorted in the expected order, after the comments.
+#[must_use]
+pub fn normalize(content: &str) -> String {
+    let lines = content.lines().map(str::trim_end).collect::<Vec<_>>();
+    let header_len = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with("id:"))
+        .unwrap_or(lines.len());
+    let (header, streams) = lines.split_at(header_len);
+
+    let key_rank = |line: &str| {
+        let key = line.split_once(':').map(|(key, _)| key.trim());
+        key.and_then(|key| HEADER_ORDER.iter().position(|known| *known == key))
+            .unwrap_or(HEADER_ORDER.len())
+    };
+    let (comments, mut entries): (Vec<&str>, Vec<&str>) = header
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .partition(|line| line.trim_start().starts_with('#'));
+    entries.sort_by_key(|line| key_rank(line));
+
+    let mut normalized = String::with_capacity(content.len());
+    for line in comments.iter().chain(&entries) {
+        normalized.push_str(line.trim_start());
+        normalized.push('\n');
+    }
+    for line in streams.iter().filter(|line| !line.trim().is_empty()) {
+        if line.trim_start().starts_with("id:") {
+            normalized.push('\n');
+        }
+        normalized.push_str(line);
+        normalized.push('\n');
+    }
+    normalized
+}
+
+/// Write a normalized copy of the idx file `input` to `output`, with a copy of its sub file.
+///
+/// # Errors
+///
+/// Will return an error if the files can't be read or written.
+pub fn fix_idx(input: &Path, output: &Path) -> io::Result<()> {
+    let content = read_content(input)?;
+    fs::write(output, normalize(&content))?;
+    let (sub, fixed_sub) = (input.with_extension("sub"), output.with_extension("sub"));
+    if sub != fixed_sub {
+        fs::copy(sub, fixed_sub)?;
+    }
+    Ok(())
+}
+
+/// Parse the `timestamp: ..., filepos: ...` entries of idx file `content`.
+#[must_use]
+pub fn parse_entries(content: &str) -> Vec<IdxEntry> {
+    content.lines().filter_map(IdxEntry::parse).collect()
+}
+
+/// Display size of the subtitles of an idx stream, from a `size:` line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplaySize {
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+}
+
+impl DisplaySize {
+    /// Parse the value of a `size:` line, like `720x576`.
+    fn parse(value: &str) -> Option<Self> {
+        let (width, height) = value.trim().split_once('x')?;
+        Some(Self {
+            width: width.trim().parse().ok()?,
+            height: height.trim().parse().ok()?,
+        })
+    }
+}
+
+/// Parse the display size of each stream of idx file `content`, with the value of its `id:` line.
+///
+/// The `size:` of the header applies to all the streams, unless a stream has its own.
+#[must_use]
+pub fn display_sizes(content: &str) -> Vec<(String, DisplaySize)> {
+    let mut sizes: Vec<(String, Option<DisplaySize>)> = Vec::new();
+    let mut header_size = None;
+    for line in content.lines().map(str::trim) {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match (key.trim(), sizes.last_mut()) {
+            ("id", _) => sizes.push((value.trim().to_owned(), header_size)),
+            ("size", None) => header_size = DisplaySize::parse(value),
+            ("size", Some((_, size))) => *size = DisplaySize::parse(value).or(*size),
+            _ => {}
+        }
+    }
+    if sizes.is_empty() {
+        sizes.push((String::new(), header_size));
+    }
+    sizes
+        .into_iter()
+        .filter_map(|(id, size)| Some((id, size?)))
+        .collect()
+}
+
+/// Parse an idx time value, either `[-]hh:mm:ss:ms` or milliseconds.
+fn parse_time(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(value) => (-1, value),
+        None => (1, value),
+    };
+    let parts = value
+        .split(':')
+        .map(|part| part.trim().parse::<i64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let msecs = match parts[..] {
+        [msecs] => msecs,
+        [hours, minutes, seconds, msecs] => ((hours * 60 + minutes) * 60 + seconds) * 1000 + msecs,
+        _ => return None,
+    };
+    Some(sign * msecs)
+}
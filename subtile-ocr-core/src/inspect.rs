@@ -0,0 +1,64 @@
+use std::{error, ops::ControlFlow};
+
+use image::{DynamicImage, GrayImage};
+use subtile::time::TimeSpan;
+
+/// Error of an [`Inspector`], ending the run with [`Error::Inspect`](crate::Error::Inspect).
+pub type InspectError = Box<dyn error::Error + Send + Sync>;
+
+/// Access to the subtitle images at the steps of a run of [`run_inspected`](crate::run_inspected),
+/// for the tools working on them, like the image dumps of the subtile-ocr cli.
+///
+/// The steps after the decoding can stop the run without error by returning [`ControlFlow::Break`],
+/// e.g. to only decode the subtitles. They aren't called for the runs in batches of
+/// [`InputConfig::with_max_memory`](crate::InputConfig::with_max_memory), which never hold all the images.
+pub trait Inspector: Send {
+    /// Check if [`Inspector::raw_images`] is called, as the raw images are decoded again for it.
+    fn wants_raw_images(&self) -> bool {
+        false
+    }
+
+    /// Inspect the `images` of the subtitles at `times` as decoded from the input,
+    /// before their conversion for `OCR`.
+    ///
+    /// # Errors
+    ///
+    /// Return an error to end the run.
+    fn raw_images(
+        &mut self,
+        _times: &[TimeSpan],
+        _images: Vec<DynamicImage>,
+    ) -> Result<(), InspectError> {
+        Ok(())
+    }
+
+    /// Inspect the `images` of the subtitles at `times` converted for `OCR`,
+    /// in the timeline of the run, before the filters.
+    ///
+    /// # Errors
+    ///
+    /// Return an error to end the run.
+    fn converted_images(
+        &mut self,
+        _times: &[TimeSpan],
+        _images: &[GrayImage],
+    ) -> Result<ControlFlow<()>, InspectError> {
+        Ok(ControlFlow::Continue(()))
+    }
+
+    /// Inspect the `images` of the subtitles at `times` kept by the filters, as sent to `OCR`.
+    ///
+    /// # Errors
+    ///
+    /// Return an error to end the run.
+    fn prepared_images(
+        &mut self,
+        _times: &[TimeSpan],
+        _images: &[GrayImage],
+    ) -> Result<ControlFlow<()>, InspectError> {
+        Ok(ControlFlow::Continue(()))
+    }
+}
+
+/// No inspection, for the runs of [`run`](crate::run).
+impl Inspector for () {}
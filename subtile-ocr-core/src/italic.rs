@@ -0,0 +1,140 @@
+use image::GrayImage;
+use log::info;
+
+use crate::cue::Cue;
+
+/// Pixels darker than this are ink in the images prepared for `OCR`.
+const INK_LEVEL: u8 = 128;
+
+/// Bands of ink rows lower than this fraction of the tallest band are accents or noise, not lines.
+const MIN_LINE_HEIGHT_DIVISOR: usize = 3;
+
+/// Fewest ink pixels of a line to estimate its slant.
+const MIN_LINE_INK: usize = 50;
+
+/// Shears tried to straighten a line, in hundredths of pixel per row: from a slant to the left
+/// to one of about 22°, to the right.
+const SHEARS: std::ops::RangeInclusive<i32> = -20..=40;
+
+/// Step between the tried shears, in hundredths.
+const SHEAR_STEP: usize = 2;
+
+/// Smallest shear straightening a line for it to be italic, about 7°, in hundredths.
+const MIN_ITALIC_SHEAR: i32 = 12;
+
+/// Detect the italic lines of each of the `images` prepared for `OCR`.
+///
+/// The lines are the bands of rows with ink, from the top. The slant of a line is the shear
+/// which straightens it the most: the one giving the sharpest profile of ink by column, as
+/// the vertical strokes fall into the same columns. A line is italic if it is straightened
+/// by a shear to the right of at least 7°.
+#[must_use]
+#[profiling::function]
+pub fn detect_italics(images: &[GrayImage]) -> Vec<Vec<bool>> {
+    images.iter().map(italic_lines).collect()
+}
+
+/// Detect the italic lines of `image`.
+fn italic_lines(image: &GrayImage) -> Vec<bool> {
+    let inked = image
+        .rows()
+        .map(|mut row| row.any(|pixel| pixel.0[0] < INK_LEVEL))
+        .collect::<Vec<_>>();
+    let mut bands = Vec::new();
+    let mut start = None;
+    for (y, inked) in inked.iter().chain([&false]).enumerate() {
+        match (start, inked) {
+            (None, true) => start = Some(y),
+            (Some(top), false) => {
+                bands.push(top..y);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    let tallest = bands.iter().map(ExactSizeIterator::len).max().unwrap_or(0);
+    bands
+        .into_iter()
+        .filter(|band| band.len() * MIN_LINE_HEIGHT_DIVISOR >= tallest)
+        .map(|band| {
+            let (top, bottom) = (band.start as u32, band.end as u32);
+            let ink = image
+                .enumerate_pixels()
+                .filter(|(_, y, pixel)| (top..bottom).contains(y) && pixel.0[0] < INK_LEVEL)
+                .map(|(x, y, _)| (i64::from(x), i64::from(bottom - y)))
+                .collect::<Vec<_>>();
+            ink.len() >= MIN_LINE_INK && line_shear(&ink) >= MIN_ITALIC_SHEAR
+        })
+        .collect()
+}
+
+/// Get the shear, in hundredths of pixel per row, giving the sharpest profile of ink by column
+/// of the `ink` pixels of a line, at their column and height above its bottom.
+fn line_shear(ink: &[(i64, i64)]) -> i32 {
+    let height = ink.iter().map(|(_, y)| *y).max().unwrap_or(0);
+    let width = ink.iter().map(|(x, _)| *x).max().unwrap_or(0);
+    // Room for the columns shifted by the largest shear, to the right.
+    let margin = height * i64::from(*SHEARS.end()) / 100 + 1;
+    let mut columns = vec![0_u64; usize::try_from(width + 2 * margin + 1).unwrap_or(0)];
+    let mut best = (0_u64, 0_i32);
+    for shear in SHEARS.step_by(SHEAR_STEP) {
+        columns.fill(0);
+        for (x, y) in ink {
+            let column = x + margin - (y * i64::from(shear) + 50).div_euclid(100);
+            if let Some(count) = usize::try_from(column)
+                .ok()
+                .and_then(|column| columns.get_mut(column))
+            {
+                *count += 1;
+            }
+        }
+        let sharpness = columns.iter().map(|count| count * count).sum::<u64>();
+        // The straightest shear wins the ties, for the regular lines without vertical strokes.
+        if sharpness > best.0 || (sharpness == best.0 && shear.abs() < best.1.abs()) {
+            best = (sharpness, shear);
+        }
+    }
+    best.1
+}
+
+/// Wrap the italic lines of the `subtitles` in `<i>` tags, from the italic lines of their images.
+///
+/// The tags of a subtitle whose number of lines changed since the `OCR` (e.g. merged
+/// sentences) are only added if all its lines were italic, around the whole text.
+#[must_use]
+#[profiling::function]
+pub fn tag_italics(subtitles: Vec<Cue>) -> Vec<Cue> {
+    let mut tagged = 0;
+    let subtitles = subtitles
+        .into_iter()
+        .map(|cue| {
+            let lines = &cue.italics;
+            if !lines.contains(&true) {
+                return cue;
+            }
+            let text_lines = cue.text.lines().collect::<Vec<_>>();
+            let text = if text_lines.len() == lines.len() {
+                text_lines
+                    .iter()
+                    .zip(lines)
+                    .map(|(line, italic)| {
+                        if *italic && !line.trim().is_empty() {
+                            format!("<i>{line}</i>")
+                        } else {
+                            (*line).to_owned()
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            } else if lines.iter().all(|italic| *italic) {
+                format!("<i>{}</i>", cue.text.trim_end())
+            } else {
+                return cue;
+            };
+            tagged += 1;
+            Cue { text, ..cue }
+        })
+        .collect();
+    info!("{tagged} subtitle(s) with italic lines");
+    subtitles
+}
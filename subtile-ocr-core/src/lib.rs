@@ -0,0 +1,2068 @@
+#![doc = include_str!("../README.md")]
+
+mod ass;
+mod batch;
+mod composition;
+mod concat;
+mod config;
+mod corrections;
+mod credits;
+mod crop;
+mod cue;
+mod cue_filter;
+mod cue_index;
+mod decode_cache;
+mod diagnostics;
+mod dictionary;
+mod dvb;
+mod extract;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod filter;
+mod forced;
+mod hocr;
+mod idx;
+mod inspect;
+mod italic;
+mod mkv;
+mod ocr;
+mod ocr_output;
+mod output_format;
+mod output_template;
+mod palette;
+mod postprocess;
+mod preprocessor;
+#[cfg(feature = "python")]
+mod python;
+mod qa;
+mod reading_speed;
+mod sanity;
+mod script;
+mod selftest;
+mod sentences;
+mod split;
+mod spu;
+mod srt_writer;
+mod stats;
+mod stdin;
+mod stream;
+mod summary;
+mod tessdata;
+mod text;
+mod timing;
+mod trace;
+mod validate;
+mod watchdog;
+
+pub use crate::{
+    ass::{write_ass, AssStyle},
+    config::{
+        Config, Error as ConfigError, ImageConfig, InputConfig, OcrConfig, OutputConfig,
+        TesseractVariable, TextConfig, TimingConfig,
+    },
+    crop::Crop,
+    cue_filter::{CueFilter, Error as CueFilterError},
+    dvb::Error as DvbError,
+    hocr::write_hocr,
+    inspect::{InspectError, Inspector},
+    mkv::Error as MatroskaError,
+    ocr::{
+        process as ocr, process_with_confidence as ocr_with_confidence,
+        process_with_metrics as ocr_with_metrics, process_with_pages as ocr_with_pages,
+        Error as OcrError, OcrMetrics, OcrOpt, OcrSession, PagedRecognition, Recognition,
+        Recognizer,
+    },
+    ocr_output::OcrOutput,
+    output_format::OutputFormat,
+    preprocessor::ConvertStrategy,
+    qa::LowConfidencePolicy,
+    sentences::SentencePolicy,
+    srt_writer::SrtWriter,
+    stdin::InputFormat,
+    stream::{OcrIter, SubtitleImages, SubtitlePipeline},
+    tessdata::TessdataProbe,
+    text::{DialogueDashes, Normalization},
+    timing::{Anchor, InvalidTimePolicy, OverlapPolicy},
+    validate::ValidationPolicy,
+};
+pub use subtile::{srt::write_srt, time::TimeSpan};
+
+// Helpers of the subtile-ocr cli, not covered by semantic versioning.
+#[doc(hidden)]
+pub use crate::{
+    config::{parse_anchor, parse_crop, parse_palette},
+    corrections::learn_corrections,
+    diagnostics::DiagnosticsLogger,
+    palette::palette_from_frame,
+    selftest::{self_test, SelfTestReport, SelfTestStep},
+    timing::format_timestamp,
+};
+#[doc(hidden)]
+pub use subtile::vobsub::Palette;
+
+use cue::{Cue, Region};
+use image::{DynamicImage, GrayAlphaImage, GrayImage, Luma, LumaA};
+use log::{debug, info, warn};
+use preprocessor::{
+    downscale_to_line_height, luma_alpha_to_luma_otsu, rgb_palette_to_luminance,
+    rgba_cluster_to_luma, soft_palette_to_luma,
+};
+use rayon::{
+    iter::{IntoParallelRefIterator, ParallelIterator},
+    ThreadPoolBuildError,
+};
+use std::{
+    ffi::OsStr,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use subtile::{
+    image::{ImageArea, ImageSize, ToImage, ToOcrImage, ToOcrImageOpt},
+    pgs::{self, DecodeTimeImage, RleToImage},
+    srt,
+    vobsub::{self, conv_to_rgba, VobSubError, VobSubIndexedImage, VobSubOcrImage, VobSubToImage},
+};
+use thiserror::Error;
+use watchdog::GivenUp;
+
+/// Gather different `Error`s in a dedicated enum.
+///
+/// New variants can be added in a minor release, use [`Error::kind`] to handle errors by category.
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("Failed to create a rayon ThreadPool.")]
+    RayonThreadPool(#[from] ThreadPoolBuildError),
+
+    #[error("No input file to process.")]
+    NoInput,
+
+    #[error("Could not read the subtitles from stdin.")]
+    ReadStdin(#[source] io::Error),
+
+    #[error("The file extension '{extension}' is not managed.")]
+    InvalidFileExtension { extension: String },
+
+    #[error("The file doesn't have a valid extension, can't choose a parser.")]
+    NoFileExtension,
+
+    #[error("Failed to open Index file. If it has a BOM, CRLF line endings or unusual header lines, `--fix-idx` can write a normalized copy.")]
+    IndexOpen(#[source] VobSubError),
+
+    #[error("Failed to read Index file {}", path.display())]
+    IndexRead { path: PathBuf, source: io::Error },
+
+    #[error("Failed to create PgsParser from file")]
+    PgsParserFromFile(#[source] pgs::PgsError),
+
+    #[error("Failed to open the PGS file {}", path.display())]
+    PgsOpen { path: PathBuf, source: io::Error },
+
+    #[error("Failed to parse Pgs, `--recover` can keep the subtitles before the error")]
+    PgsParsing(#[source] pgs::PgsError),
+
+    #[error(
+        "The crop region {crop} doesn't fit in the display size {width}x{height} of the subtitles"
+    )]
+    CropOutsideDisplay { crop: Crop, width: u32, height: u32 },
+
+    #[error("Failed to read the transport stream {}", path.display())]
+    DvbRead { path: PathBuf, source: io::Error },
+
+    #[error("Failed to read the display positions of the PGS subtitles of {}", path.display())]
+    PgsPositions { path: PathBuf, source: io::Error },
+
+    #[error("Failed to parse the DVB subtitles")]
+    DvbParsing(#[source] dvb::Error),
+
+    #[error("Failed to read the subtitle track of the Matroska file {}", path.display())]
+    Matroska { path: PathBuf, source: mkv::Error },
+
+    #[error("Could not perform OCR on subtitles.")]
+    Ocr(#[from] ocr::Error),
+
+    #[error("Error happen during OCR on {0} subtitles images")]
+    OcrFails(u32),
+
+    #[error("Invalid output template")]
+    OutputTemplate(#[from] output_template::Error),
+
+    #[error("Could not generate SRT file: {message}")]
+    GenerateSrt { message: String },
+
+    #[error("Could not write SRT file {}", path.display())]
+    WriteSrtFile { path: PathBuf, source: io::Error },
+
+    #[error("Could not write SRT on stdout.")]
+    WriteSrtStdout { source: io::Error },
+
+    #[error("Could not write ASS file {}", path.display())]
+    WriteAssFile { path: PathBuf, source: io::Error },
+
+    #[error("Could not write ASS on stdout.")]
+    WriteAssStdout { source: io::Error },
+
+    #[error("Could not write hOCR file {}", path.display())]
+    WriteHocrFile { path: PathBuf, source: io::Error },
+
+    #[error("Could not write hOCR on stdout.")]
+    WriteHocrStdout { source: io::Error },
+
+    #[error("Several output formats need an output file for each, with `--output` or `--output-template`.")]
+    MultipleFormatsToStdout,
+
+    #[error("{formats} output formats for only {outputs} output file(s), each format needs its `--output` file.")]
+    MoreFormatsThanOutputs { formats: usize, outputs: usize },
+
+    #[error("{count} subtitle(s) break the SRT constraints, `--validate fix` can fix them")]
+    InvalidSubtitles { count: usize },
+
+    #[error("The `tsv` OCR results can't be written with the hOCR output format.")]
+    OcrOutputWithHocr,
+
+    #[error(
+        "The time anchors {first} and {second} go backwards, a later `src` must have a later `dst`"
+    )]
+    BackwardAnchors { first: Anchor, second: Anchor },
+
+    #[error("Could not write the OCR results in {}", path.display())]
+    WriteOcrOutput { path: PathBuf, source: io::Error },
+
+    #[error("The {format} parsing made no progress for {}s at {position}, the input is likely malformed", timeout.as_secs())]
+    ParseStalled {
+        format: &'static str,
+        position: String,
+        timeout: Duration,
+    },
+
+    #[error("{count} threads given up after a timeout are still running, no other is started")]
+    TooManyDetachedThreads { count: usize },
+
+    #[error("{count} subtitle(s) recognized with an OCR confidence below {min_confidence}%")]
+    LowConfidence { count: usize, min_confidence: u32 },
+
+    #[error("Could not write cue index file {}", path.display())]
+    WriteCueIndex { path: PathBuf, source: io::Error },
+
+    #[error("Could not write trace file {}", path.display())]
+    WriteTrace { path: PathBuf, source: io::Error },
+
+    #[error("Could not write QA report file {}", path.display())]
+    WriteQaReport { path: PathBuf, source: io::Error },
+
+    #[error("The extracted subtitles file {} must have the extension `{expected}`", path.display())]
+    ExtractExtension {
+        path: PathBuf,
+        expected: &'static str,
+    },
+
+    #[error("Could not extract subtitles to {}", path.display())]
+    Extract { path: PathBuf, source: io::Error },
+
+    #[error("Could not write the fixed idx file {}", path.display())]
+    FixIdx { path: PathBuf, source: io::Error },
+
+    #[error("The inspection of the subtitle images failed")]
+    Inspect(#[source] InspectError),
+
+    #[error("Invalid credits patterns file {}", path.display())]
+    CreditsPatterns { path: PathBuf, source: io::Error },
+
+    #[error("Invalid replace file {}", path.display())]
+    ReplaceFile { path: PathBuf, source: io::Error },
+
+    #[error("Could not read dictionary file {}", path.display())]
+    ReadDictionary { path: PathBuf, source: io::Error },
+
+    #[error("Could not read the frame screenshot {}", path.display())]
+    PaletteFrame {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+
+    #[error("Could not read corrections file {}", path.display())]
+    ReadCorrections { path: PathBuf, source: io::Error },
+
+    #[error("Could not write corrections file {}", path.display())]
+    WriteCorrections { path: PathBuf, source: io::Error },
+
+    #[error("Could not write self-test sample file {}", path.display())]
+    SelfTestSample { path: PathBuf, source: io::Error },
+
+    #[error("Self-test failed at step: {reason}")]
+    SelfTestFailed { reason: String },
+}
+
+/// Category of an [`Error`], stable between releases.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The process can't be set up (thread pool, options).
+    Setup,
+    /// The input file can't be identified or opened.
+    Input,
+    /// The subtitles can't be decoded from the input.
+    Parse,
+    /// The `OCR` of subtitles images failed.
+    Ocr,
+    /// The results can't be written.
+    Output,
+    /// The self-test didn't pass.
+    SelfTest,
+}
+
+impl Error {
+    /// Get the category of the error.
+    #[must_use]
+    pub const fn kind(&self) -> ErrorKind {
+        match self {
+            Self::RayonThreadPool(_)
+            | Self::OutputTemplate(_)
+            | Self::ExtractExtension { .. }
+            | Self::MultipleFormatsToStdout
+            | Self::MoreFormatsThanOutputs { .. }
+            | Self::OcrOutputWithHocr
+            | Self::BackwardAnchors { .. }
+            | Self::CreditsPatterns { .. }
+            | Self::ReplaceFile { .. }
+            | Self::ReadDictionary { .. } => ErrorKind::Setup,
+            Self::NoInput
+            | Self::InvalidFileExtension { .. }
+            | Self::NoFileExtension
+            | Self::ReadStdin(_)
+            | Self::IndexOpen(_)
+            | Self::IndexRead { .. }
+            | Self::PgsParserFromFile(_)
+            | Self::PgsOpen { .. }
+            | Self::CropOutsideDisplay { .. }
+            | Self::DvbRead { .. }
+            | Self::PgsPositions { .. }
+            | Self::Matroska { .. }
+            | Self::ReadCorrections { .. }
+            | Self::PaletteFrame { .. } => ErrorKind::Input,
+            Self::PgsParsing(_)
+            | Self::DvbParsing(_)
+            | Self::ParseStalled { .. }
+            | Self::TooManyDetachedThreads { .. } => ErrorKind::Parse,
+            Self::Ocr(_) | Self::OcrFails(_) => ErrorKind::Ocr,
+            Self::Inspect(_)
+            | Self::GenerateSrt { .. }
+            | Self::WriteSrtFile { .. }
+            | Self::WriteSrtStdout { .. }
+            | Self::WriteAssFile { .. }
+            | Self::WriteAssStdout { .. }
+            | Self::WriteHocrFile { .. }
+            | Self::WriteHocrStdout { .. }
+            | Self::InvalidSubtitles { .. }
+            | Self::LowConfidence { .. }
+            | Self::WriteCueIndex { .. }
+            | Self::WriteTrace { .. }
+            | Self::WriteOcrOutput { .. }
+            | Self::WriteQaReport { .. }
+            | Self::WriteCorrections { .. }
+            | Self::FixIdx { .. }
+            | Self::Extract { .. } => ErrorKind::Output,
+            Self::SelfTestSample { .. } | Self::SelfTestFailed { .. } => ErrorKind::SelfTest,
+        }
+    }
+}
+
+/// Run OCR for `opt`.
+///
+/// # Errors
+///
+/// Will return [`Error::RayonThreadPool`] if the rayon `ThreadPool` of the run can't be built.
+/// Will return [`Error::NoInput`] if no input file is set in `opt`.
+/// Will return [`Error::ReadStdin`] if the input is `-` and the subtitles can't be read from stdin.
+/// Will return [`Error::InvalidFileExtension`] if the file extension is not managed.
+/// Will return [`Error::NoFileExtension`] if the file have no extension.
+/// Will return [`Error::DvbRead`] or [`Error::DvbParsing`] if the `DVB` subtitles of a transport stream can't be read.
+/// Will return [`Error::Matroska`] if the subtitle track of a Matroska file can't be read.
+/// Will return [`Error::ParseStalled`] if the parsing of `PGS` or `VobSub` subtitles stops progressing.
+/// Will return [`Error::TooManyDetachedThreads`] if too many threads given up after a timeout are still running.
+/// Will return [`Error::CropOutsideDisplay`] if the crop region doesn't fit in the display of the subtitles.
+/// Will return [`Error::PgsPositions`] if the display positions of the `PGS` subtitles can't be read.
+/// Will return [`Error::OutputTemplate`] if the output template can't be expanded.
+/// Will return [`Error::WriteSrtFile`] of [`Error::WriteSrtStdout`] if failed to write subtitles as `srt`.
+/// Will return [`Error::WriteAssFile`] of [`Error::WriteAssStdout`] if failed to write subtitles as `ass`.
+/// Will return [`Error::WriteHocrFile`] of [`Error::WriteHocrStdout`] if failed to write the `hOCR` document.
+/// These write errors are also returned for the files of the inferred forced subtitles
+/// and of the subtitles to review.
+/// Will return [`Error::MultipleFormatsToStdout`] if several formats are requested without output files.
+/// Will return [`Error::MoreFormatsThanOutputs`] if more formats than output files are requested.
+/// Will return [`Error::OcrOutputWithHocr`] if the `tsv` OCR results are requested with the `hOCR` output format.
+/// Will return [`Error::BackwardAnchors`] if two time anchors go backwards.
+/// Will return [`Error::WriteOcrOutput`] if failed to write the OCR result files.
+/// Will return [`Error::InvalidSubtitles`] if subtitles break the `SRT` constraints with `--validate error`.
+/// Will return [`Error::LowConfidence`] if subtitles are recognized with a low confidence with `--low-confidence fail`.
+/// Will return [`Error::WriteCueIndex`] if failed to write the cue index.
+/// Will return [`Error::WriteTrace`] if failed to write the trace file.
+/// Will return [`Error::WriteQaReport`] if failed to write the QA report file.
+/// Will return [`Error::ReadCorrections`] if the corrections file can't be read.
+/// Will return [`Error::CreditsPatterns`] if the credits patterns file can't be read or is invalid.
+/// Will return [`Error::ReplaceFile`] if the replace file can't be read or is invalid.
+/// Will return [`Error::ReadDictionary`] if the dictionary file can't be read.
+/// Will return [`Error::Inspect`] if the inspection of the images by the `inspector` of [`run_inspected`] failed.
+/// Will forward error from `ocr` processing and [`check_subtitles`] if any.
+pub fn run(opt: &Config) -> Result<(), Error> {
+    run_inspected(opt, &mut ())
+}
+
+/// Run OCR for `opt` like [`run`], giving the subtitle images to the `inspector` at the steps of the run.
+///
+/// # Errors
+///
+/// Will return the same errors as [`run`].
+pub fn run_inspected(opt: &Config, inspector: &mut dyn Inspector) -> Result<(), Error> {
+    run_pipeline(opt, inspector, true).map(drop)
+}
+
+/// Run OCR for `opt`, and return the subtitles instead of writing them.
+///
+/// The other files requested by `opt` (trace, cue index, QA report, ...) are still written.
+///
+/// # Errors
+///
+/// Will return the same errors as [`run`].
+pub fn run_to_subtitles(opt: &Config) -> Result<Vec<(TimeSpan, String)>, Error> {
+    let subtitles = run_to_subtitles_with_confidence(opt)?;
+    Ok(subtitles
+        .into_iter()
+        .map(|(time, text, _)| (time, text))
+        .collect())
+}
+
+/// Run OCR for `opt` like [`run_to_subtitles`], with the `OCR` confidence of each subtitle.
+///
+/// The confidence is the mean confidence in percent of the words recognized in the subtitle
+/// image, `None` without recognized word. A subtitle merged from several images, e.g. by the
+/// [`OverlapPolicy`], has the lowest confidence of its images.
+///
+/// # Errors
+///
+/// Will return the same errors as [`run`].
+pub fn run_to_subtitles_with_confidence(
+    opt: &Config,
+) -> Result<Vec<(TimeSpan, String, Option<u32>)>, Error> {
+    run_pipeline(opt, &mut (), false)
+}
+
+/// Run OCR for `opt`, writing the subtitles in the outputs if `write_outputs`.
+#[profiling::function]
+fn run_pipeline(
+    opt: &Config,
+    inspector: &mut dyn Inspector,
+    write_outputs: bool,
+) -> Result<Vec<(TimeSpan, String, Option<u32>)>, Error> {
+    with_thread_pool(|| pipeline(opt, inspector, write_outputs))
+}
+
+/// Copy the subtitles of the input of `opt` kept by its filters to `path`, without re-encoding them.
+///
+/// The subtitles are written in a `.sup` file for a `PGS` input, or an `.idx` file (and its `.sub`) for `VobSub`.
+///
+/// # Errors
+///
+/// Will return [`Error::ExtractExtension`] if `path` doesn't have the extension of the input format.
+/// Will return [`Error::Extract`] if the subtitles can't be copied.
+/// Will return the errors of [`run`] for the reading and the decoding of the input.
+#[doc(hidden)]
+pub fn extract_subtitles(opt: &Config, path: &Path) -> Result<(), Error> {
+    with_thread_pool(|| {
+        let (spooled, _) = spool_input(opt)?;
+        let input = spooled.as_ref().map_or_else(
+            || opt.input.path.as_deref().ok_or(Error::NoInput),
+            |spooled| Ok(spooled.path()),
+        )?;
+        let expected = match input.extension().and_then(OsStr::to_str) {
+            Some("idx") => "idx",
+            _ => "sup",
+        };
+        if path.extension().and_then(OsStr::to_str) != Some(expected) {
+            return Err(Error::ExtractExtension {
+                path: absolute_path(path),
+                expected,
+            });
+        }
+        let mut qa = qa::QaReport::default();
+        let (times, images, regions) = decode_timeline(input, opt)?;
+        let (times, ..) = filter_images(times, images, regions, opt, 0, &mut None, &mut qa);
+        extract::extract(input, path, &times, opt.timing.offset).map_err(|source| {
+            Error::Extract {
+                path: absolute_path(path),
+                source,
+            }
+        })?;
+        qa.log();
+        Ok(())
+    })
+}
+
+/// Write a normalized copy of the idx file of the input of `opt` (and its sub file) to `path`,
+/// for the idx files which fail to open.
+///
+/// # Errors
+///
+/// Will return [`Error::FixIdx`] if the input isn't an idx file or if the copy can't be written.
+/// Will return [`Error::NoInput`], [`Error::ReadStdin`] or [`Error::Matroska`] if the input can't be read.
+#[doc(hidden)]
+pub fn fix_idx(opt: &Config, path: &Path) -> Result<(), Error> {
+    let (spooled, _) = spool_input(opt)?;
+    let input = spooled.as_ref().map_or_else(
+        || opt.input.path.as_deref().ok_or(Error::NoInput),
+        |spooled| Ok(spooled.path()),
+    )?;
+    let mkerr = |source| Error::FixIdx {
+        path: absolute_path(path),
+        source,
+    };
+    if input.extension().and_then(OsStr::to_str) != Some("idx") {
+        return Err(mkerr(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "only idx files can be fixed",
+        )));
+    }
+    create_parent_dirs(path).map_err(mkerr)?;
+    idx::fix_idx(input, path).map_err(mkerr)?;
+    info!("Normalized idx file written to {}", path.display());
+    Ok(())
+}
+
+/// Copy the subtitles of the input of `opt` to a temporary file if they are read from stdin
+/// or from a Matroska file, with the id of the Matroska track.
+fn spool_input(opt: &Config) -> Result<(Option<stdin::SpooledInput>, Option<usize>), Error> {
+    let cli_input = opt.input.path.as_deref().ok_or(Error::NoInput)?;
+    if cli_input == Path::new("-") {
+        let spooled =
+            stdin::SpooledInput::read(opt.input.stdin_format, opt.input.stdin_idx.as_deref())
+                .map_err(Error::ReadStdin)?;
+        Ok((Some(spooled), None))
+    } else if mkv::is_matroska(cli_input) {
+        let (spooled, track) =
+            mkv::spool_track(cli_input, opt.input.track).map_err(|source| Error::Matroska {
+                path: absolute_path(cli_input),
+                source,
+            })?;
+        Ok((Some(spooled), Some(track)))
+    } else {
+        Ok((None, None))
+    }
+}
+
+/// Run the steps of [`run_pipeline`], in the rayon thread pool of the run.
+fn pipeline(
+    opt: &Config,
+    inspector: &mut dyn Inspector,
+    write_outputs: bool,
+) -> Result<Vec<(TimeSpan, String, Option<u32>)>, Error> {
+    let mut timer = stats::StageTimer::default();
+    let cli_input = opt.input.path.as_deref().ok_or(Error::NoInput)?;
+    timing::check_anchors(&opt.timing.anchors)
+        .map_err(|(first, second)| Error::BackwardAnchors { first, second })?;
+    // Subtitles from stdin or a Matroska file are decoded from a temporary file.
+    let (spooled, track) = spool_input(opt)?;
+    let input = spooled
+        .as_ref()
+        .map_or(cli_input, stdin::SpooledInput::path);
+
+    let outputs = if write_outputs {
+        outputs(opt, cli_input, track)?
+    } else {
+        Vec::new()
+    };
+    let hocr_output = outputs
+        .iter()
+        .any(|(_, format)| *format == OutputFormat::Hocr);
+    let tsv = opt.output.ocr_output == Some(OcrOutput::Tsv);
+    if tsv && hocr_output {
+        return Err(Error::OcrOutputWithHocr);
+    }
+    let ocr_settings = OcrSettings {
+        tessdata_probe: TessdataProbe::default(),
+        hocr: hocr_output || opt.output.ocr_output == Some(OcrOutput::Hocr),
+        tsv,
+    };
+    let hocr = ocr_settings.hocr;
+    let mut qa = qa::QaReport::default();
+    let recognized = match opt.input.max_memory {
+        Some(max_memory) => {
+            batch::recognize_in_batches(input, opt, max_memory, &ocr_settings, &mut qa)?
+        }
+        None => {
+            match decode_and_recognize(input, opt, inspector, &ocr_settings, &mut qa, &mut timer)? {
+                Some(recognized) => recognized,
+                None => return Ok(Vec::new()),
+            }
+        }
+    };
+    timer.lap("OCR");
+    let Recognized {
+        times,
+        widths,
+        texts,
+        confidences,
+        pages,
+        italics,
+        regions,
+        origins,
+        metrics,
+    } = recognized;
+    metrics.log();
+    if let Some(format) = opt.output.ocr_output {
+        let dir = opt.output.ocr_output_dir.clone().unwrap_or_else(|| {
+            ocr_output::default_dir(outputs.iter().map(|(path, _)| path.as_deref()))
+        });
+        let count = ocr_output::write_pages(&dir, format, &times, &pages).map_err(|source| {
+            Error::WriteOcrOutput {
+                path: absolute_path(&dir),
+                source,
+            }
+        })?;
+        info!("{count} OCR result(s) written in {}", dir.display());
+    }
+    let hocr_pages = if hocr {
+        times
+            .iter()
+            .zip(pages)
+            .filter_map(|(time, page)| Some((*time, page?)))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let low_confidence = match opt.ocr.min_confidence {
+        Some(min_confidence) => {
+            let dictionary =
+                dictionary::Dictionary::new(&opt.ocr.lang, opt.ocr.dictionary.as_deref()).map_err(
+                    |source| Error::ReadDictionary {
+                        path: opt
+                            .ocr
+                            .dictionary
+                            .as_deref()
+                            .map(absolute_path)
+                            .unwrap_or_default(),
+                        source,
+                    },
+                )?;
+            let low_confidence =
+                qa.check_confidences(&times, &texts, &confidences, min_confidence, &dictionary);
+            let count = low_confidence.iter().flatten().count();
+            if opt.ocr.low_confidence == LowConfidencePolicy::Fail && count > 0 {
+                qa.log();
+                return Err(Error::LowConfidence {
+                    count,
+                    min_confidence,
+                });
+            }
+            low_confidence
+        }
+        None => vec![None; times.len()],
+    };
+    qa.check_texts(&times, &widths, &texts);
+    let subtitles = check_recognized(
+        times
+            .into_iter()
+            .zip(texts)
+            .zip(
+                italics
+                    .into_iter()
+                    .zip(confidences.into_iter().zip(low_confidence))
+                    .zip(regions),
+            )
+            .map(|((time, text), recognition)| (time, text, recognition)),
+        &mut qa,
+    )?
+    .into_iter()
+    .map(
+        |(time, text, ((italics, (confidence, low_confidence)), region))| {
+            let text = text::normalize(&text, opt.text.normalize);
+            Cue {
+                time,
+                text: text::normalize_dialogue_dashes(&text, opt.text.dialogue_dashes),
+                italics,
+                confidence,
+                low_confidence,
+                region,
+            }
+        },
+    )
+    .collect::<Vec<_>>();
+    let postprocessor = postprocess::PostProcessor::new(
+        &opt.ocr.lang,
+        opt.text.postprocess,
+        opt.ocr.column_separator.is_some(),
+        opt.text.replace_file.as_deref(),
+    )
+    .map_err(|source| Error::ReplaceFile {
+        path: opt
+            .text
+            .replace_file
+            .as_deref()
+            .map(absolute_path)
+            .unwrap_or_default(),
+        source,
+    })?;
+    let subtitles = postprocessor.handle(subtitles);
+    let subtitles = match &opt.text.corrections {
+        Some(path) => {
+            let model = corrections::CorrectionModel::read(path).map_err(|source| {
+                Error::ReadCorrections {
+                    path: absolute_path(path),
+                    source,
+                }
+            })?;
+            subtitles
+                .into_iter()
+                .map(|cue| Cue {
+                    text: model.apply(&cue.text),
+                    ..cue
+                })
+                .collect()
+        }
+        None => subtitles,
+    };
+    let credits =
+        credits::CreditsDetector::new(opt.text.credits_patterns.as_deref()).map_err(|source| {
+            Error::CreditsPatterns {
+                path: opt
+                    .text
+                    .credits_patterns
+                    .as_deref()
+                    .map(absolute_path)
+                    .unwrap_or_default(),
+                source,
+            }
+        })?;
+    let subtitles = credits.handle(subtitles, opt.text.strip_credits, &mut qa);
+    let subtitles = sentences::handle_split_sentences(subtitles, opt.text.split_sentences);
+    let subtitles = if opt.timing.keep_order {
+        subtitles
+    } else {
+        timing::sort_by_start(subtitles)
+    };
+    let subtitles = timing::handle_overlaps(subtitles, opt.timing.overlap);
+    let subtitles = validate::validate(
+        subtitles,
+        opt.text.validate,
+        opt.timing.keep_order,
+        opt.text.max_lines.map(NonZeroUsize::get),
+        opt.timing.invalid_times,
+        opt.timing.min_duration,
+        &mut qa,
+    )
+    .map_err(|count| Error::InvalidSubtitles { count })?;
+    let subtitles = match opt.timing.max_cps {
+        Some(max_cps) => reading_speed::check_reading_speed(
+            subtitles,
+            max_cps,
+            opt.timing.extend_fast_cues,
+            &mut qa,
+        ),
+        None => subtitles,
+    };
+    let subtitles = if opt.text.detect_italics {
+        italic::tag_italics(subtitles)
+    } else {
+        subtitles
+    };
+    let review = qa::low_confidence_subtitles(&subtitles);
+    let subtitles = if opt.ocr.low_confidence == LowConfidencePolicy::Marker {
+        qa::mark_low_confidence(subtitles)
+    } else {
+        subtitles
+    };
+    let confidences = subtitles
+        .iter()
+        .map(|cue| cue.confidence)
+        .collect::<Vec<_>>();
+    let subtitles = subtitles
+        .into_iter()
+        .map(Cue::into_subtitle)
+        .collect::<Vec<_>>();
+    timer.lap("post-process");
+
+    // Create subtitle file(s).
+    let output_names = outputs
+        .iter()
+        .map(|(path, _)| {
+            path.as_deref()
+                .map_or_else(|| "-".to_owned(), |path| path.display().to_string())
+        })
+        .collect();
+    let ass_style = AssStyle {
+        font: opt.output.ass_style.font.clone(),
+        font_size: opt.output.ass_style.font_size,
+        outline: opt.output.ass_style.outline,
+        margin_v: opt.output.ass_style.margin_v,
+    };
+    for (output, format) in outputs {
+        write_subtitles(
+            output.as_deref(),
+            format,
+            &subtitles,
+            &hocr_pages,
+            &ass_style,
+        )?;
+    }
+    if let Some(path) = &opt.output.trace {
+        trace::write_trace(path, &subtitles, &origins).map_err(|source| Error::WriteTrace {
+            path: absolute_path(path),
+            source,
+        })?;
+    }
+    if let Some(path) = &opt.output.infer_forced {
+        let forced = forced::infer_forced(&subtitles);
+        write_subtitles(
+            Some(path),
+            OutputFormat::from_path(path),
+            &forced,
+            &pages_of(&hocr_pages, &forced),
+            &ass_style,
+        )?;
+    }
+    if let Some(path) = &opt.output.confidence_review {
+        info!(
+            "{} subtitle(s) with a low OCR confidence written to {}",
+            review.len(),
+            path.display()
+        );
+        write_subtitles(
+            Some(path),
+            OutputFormat::from_path(path),
+            &review,
+            &pages_of(&hocr_pages, &review),
+            &ass_style,
+        )?;
+    }
+    if let Some(path) = &opt.output.cue_index {
+        cue_index::write_cue_index(
+            path,
+            &subtitles,
+            opt.output.cue_index_interval,
+            &opt.ocr.lang,
+        )
+        .map_err(|source| Error::WriteCueIndex {
+            path: absolute_path(path),
+            source,
+        })?;
+    }
+    timer.lap("write");
+
+    summary::log(&[summary::StreamSummary {
+        stream: summary::stream_id(input, track),
+        lang: opt.ocr.lang.clone(),
+        cues: subtitles.len(),
+        outputs: output_names,
+    }]);
+    script::check_scripts(
+        &opt.ocr.lang,
+        subtitles.iter().map(|(_, text)| text.as_str()),
+    );
+    qa.log();
+    if let Some(path) = &opt.output.qa_report {
+        qa.write(path).map_err(|source| Error::WriteQaReport {
+            path: absolute_path(path),
+            source,
+        })?;
+    }
+    timer.log(widths.len());
+
+    Ok(subtitles
+        .into_iter()
+        .zip(confidences)
+        .map(|((time, text), confidence)| (time, text, confidence))
+        .collect())
+}
+
+/// Get the `hOCR` pages of the `subtitles` kept from the `pages` of all the subtitles, matched by their start.
+fn pages_of(
+    pages: &[(TimeSpan, String)],
+    subtitles: &[(TimeSpan, String)],
+) -> Vec<(TimeSpan, String)> {
+    pages
+        .iter()
+        .filter(|(page_time, _)| {
+            subtitles
+                .iter()
+                .any(|(time, _)| time.start.msecs() == page_time.start.msecs())
+        })
+        .cloned()
+        .collect()
+}
+
+/// Settings of the `OCR` of a run, shared by the batches.
+struct OcrSettings {
+    /// Probe of the tessdata directory, done once for the run.
+    tessdata_probe: TessdataProbe,
+    /// Get the `hOCR` page of each subtitle with its text.
+    hocr: bool,
+    /// Get the `TSV` table of each subtitle with its text, if not the `hOCR` page.
+    tsv: bool,
+}
+
+impl OcrSettings {
+    /// Create the options of the `OCR` of `image_count` images, `None` if unknown
+    /// like for the batches of a run.
+    fn ocr_opt<'a>(&'a self, opt: &'a Config, image_count: Option<usize>) -> OcrOpt<'a> {
+        OcrOpt::new(
+            &opt.ocr.tessdata_dir,
+            opt.ocr.lang.as_str(),
+            &opt.ocr.variables,
+            opt.ocr.dpi,
+        )
+        .with_tessdata_probe(Some(&self.tessdata_probe))
+        .with_panic_isolation(opt.ocr.panic_isolation)
+        .with_hocr(self.hocr)
+        .with_tsv(self.tsv)
+        .with_max_instances(
+            opt.ocr
+                .max_instances
+                .or_else(|| image_count.and_then(ocr::auto_max_instances)),
+        )
+        .with_line_mode(opt.ocr.line_mode)
+        .with_single_line_psm(opt.ocr.single_line_psm)
+        .with_invert_retry(opt.ocr.invert_retry)
+        .with_column_separator(opt.ocr.column_separator.as_deref())
+        .with_control_scrubbing(opt.ocr.scrub_control_chars)
+        .with_timeout(opt.ocr.timeout)
+        .with_tesseract_threads(
+            opt.ocr
+                .tesseract_threads
+                .map(|threads| threads as usize)
+                .or_else(|| image_count.map(ocr::auto_tesseract_threads))
+                .unwrap_or(1),
+        )
+        .with_thread_limit_env(opt.ocr.thread_limit_env)
+    }
+}
+
+/// Results of the `OCR` of the subtitles, before the post-processing.
+#[derive(Default)]
+struct Recognized {
+    times: Vec<TimeSpan>,
+    /// Width of the image of each subtitle.
+    widths: Vec<u32>,
+    texts: Vec<Result<String, ocr::Error>>,
+    /// Mean confidence of the words of each subtitle.
+    confidences: Vec<Option<u32>>,
+    /// `hOCR` page or `TSV` table of each subtitle, if requested.
+    pages: Vec<Option<String>>,
+    /// Italic lines of each subtitle, if detected.
+    italics: Vec<Vec<bool>>,
+    /// Region of the display covered by the image of each subtitle, if known.
+    regions: Vec<Option<Region>>,
+    /// Decoded subtitles merged in each subtitle, for the trace file.
+    origins: Vec<(TimeSpan, String)>,
+    /// Metrics of the `OCR` of all the subtitles.
+    metrics: ocr::OcrMetrics,
+}
+
+impl Recognized {
+    /// Add the `recognitions` of the subtitles at `times`, from images of `widths`
+    /// in the display `regions`, with the `italics` lines.
+    fn extend(
+        &mut self,
+        times: Vec<TimeSpan>,
+        widths: Vec<u32>,
+        regions: Vec<Option<Region>>,
+        italics: Vec<Vec<bool>>,
+        recognitions: Vec<Result<ocr::PagedRecognition, ocr::Error>>,
+    ) {
+        self.times.extend(times);
+        self.widths.extend(widths);
+        self.regions.extend(regions);
+        self.italics.extend(italics);
+        for recognition in recognitions {
+            let (text, confidence, page) = match recognition {
+                Ok(((text, confidence), page)) => (Ok(text), confidence, page),
+                Err(err) => (Err(err), None, None),
+            };
+            self.texts.push(text);
+            self.confidences.push(confidence);
+            self.pages.push(page);
+        }
+    }
+}
+
+/// Decode all the subtitles of `input`, filter them and run their `OCR`.
+///
+/// Returns `None` if the run is stopped before the `OCR` by the `inspector`.
+fn decode_and_recognize(
+    input: &Path,
+    opt: &Config,
+    inspector: &mut dyn Inspector,
+    ocr_settings: &OcrSettings,
+    qa: &mut qa::QaReport,
+    timer: &mut stats::StageTimer,
+) -> Result<Option<Recognized>, Error> {
+    if inspector.wants_raw_images() {
+        let (times, images) = decode_raw(input, opt)?;
+        inspector
+            .raw_images(&times, images)
+            .map_err(Error::Inspect)?;
+    }
+    let (times, images, regions) = decode_timeline(input, opt)?;
+    timer.lap("decode");
+    if inspector
+        .converted_images(&times, &images)
+        .map_err(Error::Inspect)?
+        .is_break()
+    {
+        return Ok(None);
+    }
+
+    let origins = match &opt.output.trace {
+        Some(_) => {
+            trace::origins(input, &times, opt.timing.offset).map_err(|source| Error::IndexRead {
+                path: input.to_path_buf(),
+                source,
+            })?
+        }
+        None => Vec::new(),
+    };
+    let (times, images, regions) = filter_images(times, images, regions, opt, 0, &mut None, qa);
+    let (times, images, regions) = prepare_images(times, images, regions, opt, qa);
+    timer.lap("filter");
+    if inspector
+        .prepared_images(&times, &images)
+        .map_err(Error::Inspect)?
+        .is_break()
+    {
+        qa.log();
+        timer.log(images.len());
+        return Ok(None);
+    }
+
+    let ocr_opt = ocr_settings.ocr_opt(opt, Some(images.len()));
+    let widths = images.iter().map(GrayImage::width).collect::<Vec<_>>();
+    let italics = detected_italics(&images, opt);
+    let (recognitions, metrics) = ocr::process_with_metrics(images, &ocr_opt)?;
+    let mut recognized = Recognized {
+        origins,
+        metrics,
+        ..Recognized::default()
+    };
+    recognized.extend(times, widths, regions, italics, recognitions);
+    Ok(Some(recognized))
+}
+
+/// Decode all the subtitles of `input` and of the inputs appended to it, in the timeline of the run.
+///
+/// The decode cache only keeps the images, the regions of the cached subtitles are unknown.
+fn decode_timeline(input: &Path, opt: &Config) -> Result<Decoded, Error> {
+    let (times, images, regions) = match &opt.input.decode_cache {
+        Some(dir) => without_regions(decode_cache::decode_cached(dir, input, opt)?),
+        None => decode_with_regions(input, opt)?,
+    };
+    sanity::check_decoded_count(input, times.len());
+    let (times, images, regions) = concat::append_inputs(times, images, regions, opt)?;
+    let times = match opt.timing.offset {
+        Some(offset) => times
+            .into_iter()
+            .map(|time| timing::shift_span(time, offset))
+            .collect(),
+        None => times,
+    };
+    Ok((times, images, regions))
+}
+
+/// Detect the italic lines of the `images` if requested by `opt`, none otherwise.
+fn detected_italics(images: &[GrayImage], opt: &Config) -> Vec<Vec<bool>> {
+    if opt.text.detect_italics {
+        italic::detect_italics(images)
+    } else {
+        vec![Vec::new(); images.len()]
+    }
+}
+
+/// Remove the subtitles rejected by the filter expression, with the duplicated frames
+/// and the images without enough ink, then the subtitles ending before 0 and the ones with invalid times.
+///
+/// `first_index` is the index of the first subtitle in the input, for the filter expression.
+/// `last_frame` is the last decoded image, carried from one batch to the next for the duplicates check.
+/// The display `regions` of the images are kept with them.
+fn filter_images(
+    times: Vec<TimeSpan>,
+    images: Vec<GrayImage>,
+    regions: Vec<Option<Region>>,
+    opt: &Config,
+    first_index: usize,
+    last_frame: &mut Option<GrayImage>,
+    qa: &mut qa::QaReport,
+) -> Decoded {
+    let limits = CueFilter::from_limits(opt.image.min_ink_pixels, opt.image.duplicate_max_duration);
+    let cue_filter = match (limits, opt.image.filter.clone()) {
+        (Some(limits), Some(filter)) => Some(limits.and(filter)),
+        (limits, filter) => limits.or(filter),
+    };
+    let previous = last_frame.take();
+    if let Some(last) = images.last() {
+        *last_frame = Some(last.clone());
+    } else {
+        *last_frame = previous.clone();
+    }
+    let mut removed = Vec::new();
+    let (times, images) = match cue_filter {
+        Some(cue_filter) => cue_filter.apply_from(
+            first_index,
+            previous.as_ref(),
+            times,
+            images,
+            |idx, time, image| {
+                removed.push(idx - first_index);
+                let ink = filter::ink_pixels(image);
+                if ink < opt.image.min_ink_pixels {
+                    warn!(
+                        "Subtitle {} ({time:?}) has only {ink} text pixels, it is skipped",
+                        idx + 1
+                    );
+                    qa.add(
+                        time,
+                        format!(
+                            "decoded subtitle {} skipped, only {ink} text pixels",
+                            idx + 1
+                        ),
+                    );
+                } else {
+                    debug!("Subtitle {} ({time:?}) is removed by the filter", idx + 1);
+                }
+            },
+        ),
+        None => (times, images),
+    };
+    let regions = regions
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| removed.binary_search(idx).is_err())
+        .map(|(_, region)| region);
+    let (times, placed) =
+        timing::drop_before_zero(times, images.into_iter().zip(regions).collect(), qa);
+    let (times, placed) = timing::fix_invalid_times(
+        times,
+        placed,
+        opt.timing.invalid_times,
+        opt.timing.min_duration,
+        qa,
+    );
+    let (images, regions) = placed.into_iter().unzip();
+    (times, images, regions)
+}
+
+/// Correct the drift of the times, and prepare the images of the filtered subtitles for `OCR`.
+fn prepare_images(
+    times: Vec<TimeSpan>,
+    mut images: Vec<GrayImage>,
+    regions: Vec<Option<Region>>,
+    opt: &Config,
+    qa: &mut qa::QaReport,
+) -> Decoded {
+    let times = timing::apply_anchors(times, &opt.timing.anchors);
+    if opt.image.fill_hollow {
+        filter::fill_hollow_glyphs(&mut images);
+    }
+    filter::expand_edge_borders(&times, &mut images, opt.image.border, qa);
+    if opt.image.split_groups {
+        filter::split_separated_groups(times, images, regions, opt.image.border)
+    } else {
+        (times, images, regions)
+    }
+}
+
+/// Decode the subtitles of `input`, a `PGS`, `VobSub` or `DVB` file chosen by its extension, as images prepared for `OCR`.
+///
+/// # Errors
+///
+/// Will return [`Error::InvalidFileExtension`] if the file extension is not managed.
+/// Will return [`Error::NoFileExtension`] if the file have no extension.
+/// Will return [`Error::Matroska`] if the subtitle track of a Matroska file can't be read.
+/// Will forward the errors of [`process_pgs`], [`process_vobsub`] and [`process_dvb`].
+pub fn decode(input: &Path, opt: &Config) -> Result<(Vec<TimeSpan>, Vec<GrayImage>), Error> {
+    decode_with_regions(input, opt).map(|(times, images, _)| (times, images))
+}
+
+/// Decoded subtitles: their times, images prepared for `OCR` and display regions.
+pub(crate) type Decoded = (Vec<TimeSpan>, Vec<GrayImage>, Vec<Option<Region>>);
+
+/// Decode the subtitles of `input` like [`decode`], with the region of the display covered by each image.
+///
+/// The regions are known for `PGS` with a crop region or an overlap policy, and for `VobSub`.
+fn decode_with_regions(input: &Path, opt: &Config) -> Result<Decoded, Error> {
+    // The subtitle track of a Matroska file is decoded from temporary files.
+    if mkv::is_matroska(input) {
+        let (spooled, _) =
+            mkv::spool_track(input, opt.input.track).map_err(|source| Error::Matroska {
+                path: absolute_path(input),
+                source,
+            })?;
+        return decode_with_regions(spooled.path(), opt);
+    }
+    if opt.input.recover {
+        sanity::report_truncation(input);
+    }
+    match input.extension().and_then(OsStr::to_str) {
+        Some(ext) => match ext {
+            "sup" => pgs_with_regions(input, opt),
+            "idx" => vobsub_with_regions(input, opt),
+            "ts" => process_dvb(input, opt).map(without_regions),
+            ext => Err(Error::InvalidFileExtension {
+                extension: ext.into(),
+            }),
+        },
+        None => Err(Error::NoFileExtension),
+    }
+}
+
+/// Add unknown regions to the decoded `times` and `images`.
+fn without_regions((times, images): (Vec<TimeSpan>, Vec<GrayImage>)) -> Decoded {
+    let regions = vec![None; times.len()];
+    (times, images, regions)
+}
+
+/// Run `op` in a dedicated rayon thread pool.
+///
+/// The global pool is left to the application embedding the crate, which may have configured it.
+fn with_thread_pool<T, F>(op: F) -> Result<T, Error>
+where
+    F: FnOnce() -> Result<T, Error> + Send,
+    T: Send,
+{
+    let pool = rayon::ThreadPoolBuilder::new()
+        .thread_name(|idx| format!("Rayon_{idx}"))
+        .build()
+        .map_err(Error::RayonThreadPool)?;
+    pool.install(op)
+}
+
+/// Process `PGS` subtitle file `input`
+///
+/// # Errors
+///
+/// Will return [`Error::PgsOpen`] if the file can't be opened.
+/// Will return [`Error::PgsParsing`] if the parsing of subtitles failed.
+/// Will return [`Error::ParseStalled`] if the parsing of subtitles stops progressing.
+/// Will return [`Error::TooManyDetachedThreads`] if too many threads given up after a timeout are still running.
+/// Will return [`Error::CropOutsideDisplay`] if the crop region doesn't fit in the display of the stream.
+/// Will return [`Error::PgsPositions`] if the display positions of the subtitles can't be read.
+#[profiling::function]
+pub fn process_pgs(input: &Path, opt: &Config) -> Result<(Vec<TimeSpan>, Vec<GrayImage>), Error> {
+    pgs_with_regions(input, opt).map(|(times, images, _)| (times, images))
+}
+
+/// Process `PGS` subtitle file `input` like [`process_pgs`], with the display region of each image.
+fn pgs_with_regions(input: &Path, opt: &Config) -> Result<Decoded, Error> {
+    let (times, pgs_images, origins) = parse_pgs(input, opt)?;
+    let ocr_opt = ocr_opt(opt);
+    let placed = pgs_images
+        .iter()
+        .enumerate()
+        .map(|(idx, image)| (image, origins.get(idx).copied()))
+        .collect::<Vec<_>>();
+    let images = convert_images(&placed, |(image, origin)| {
+        pgs_to_ocr_image(image, *origin, opt, &ocr_opt)
+    });
+    let regions = placed
+        .iter()
+        .map(|(image, origin)| origin.map(|(_, top)| Region::new(top, image.height())))
+        .collect();
+
+    Ok((times, images, regions))
+}
+
+/// Image of a `PGS` subtitle, as decoded by `subtile` for the display sets of one object.
+pub(crate) enum PgsImage {
+    /// Run-length encoded image of the first object of the display set.
+    Rle(pgs::RleEncodedImage),
+    /// Image of all the objects of a display set showing several.
+    Composited(GrayAlphaImage),
+}
+
+impl PgsImage {
+    /// Get the image of the subtitle decoded as `rle_img`, with the `composition` of its display set.
+    fn new(
+        rle_img: pgs::RleEncodedImage,
+        composition: Option<composition::PgsComposition>,
+    ) -> Self {
+        match composition.and_then(|composition| composition.composited) {
+            Some(image) => Self::Composited(image),
+            None => Self::Rle(rle_img),
+        }
+    }
+
+    /// Get the image as decoded, for the image dumps.
+    fn to_gray_alpha(&self) -> GrayAlphaImage {
+        match self {
+            Self::Rle(rle_img) => RleToImage::new(rle_img, |pix: LumaA<u8>| pix).to_image(),
+            Self::Composited(image) => image.clone(),
+        }
+    }
+}
+
+impl ImageSize for PgsImage {
+    fn width(&self) -> u32 {
+        match self {
+            Self::Rle(rle_img) => rle_img.width(),
+            Self::Composited(image) => image.width(),
+        }
+    }
+
+    fn height(&self) -> u32 {
+        match self {
+            Self::Rle(rle_img) => rle_img.height(),
+            Self::Composited(image) => image.height(),
+        }
+    }
+}
+
+/// Parsed `PGS` subtitles: their times, images and display positions.
+type ParsedPgs = (Vec<TimeSpan>, Vec<PgsImage>, Vec<(u32, u32)>);
+
+/// Parse the `PGS` subtitles of `input`, with the display position of each image for the crop region.
+///
+/// The display sets showing several objects are composited in one image.
+/// The oversized images are skipped.
+fn parse_pgs(input: &Path, opt: &Config) -> Result<ParsedPgs, Error> {
+    let file = File::open(input).map_err(|source| Error::PgsOpen {
+        path: absolute_path(input),
+        source,
+    })?;
+
+    let recover = opt.input.recover;
+    let parsed = watchdog::watch(opt.input.parse_stall_timeout, move |progress| {
+        profiling::scope!("Parse PGS file");
+        // The parser reads through the progress, for the offset of a stall.
+        let parser =
+            pgs::SupParser::<_, DecodeTimeImage>::new(progress.reader(BufReader::new(file)));
+        let mut parsed = (Vec::new(), Vec::new());
+        for sub in parser {
+            progress.advance();
+            match sub {
+                Ok((time, image)) => {
+                    parsed.0.push(time);
+                    parsed.1.push(image);
+                }
+                // Keep the subtitles parsed before the first error, likely a truncation.
+                Err(err) if recover => {
+                    warn!(
+                        "PGS parsing stopped after {} subtitles: {err}",
+                        parsed.0.len()
+                    );
+                    break;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(parsed)
+    })
+    .map_err(|given_up| match given_up {
+        GivenUp::Stalled { parsed, offset } => Error::ParseStalled {
+            format: "PGS",
+            position: match offset {
+                Some(offset) => format!("subtitle {}, at offset {offset:#x}", parsed + 1),
+                None => format!("subtitle {}", parsed + 1),
+            },
+            timeout: opt.input.parse_stall_timeout.unwrap_or_default(),
+        },
+        GivenUp::TooManyDetached { count } => Error::TooManyDetachedThreads { count },
+    })?;
+    let (times, rle_images) = parsed.map_err(Error::PgsParsing)?;
+    let compositions = pgs_compositions(input, opt)?.collect::<Result<Vec<_>, _>>()?;
+    let (images, origins) = if compositions.len() < times.len() {
+        warn!(
+            "The compositions of the PGS subtitles don't match the decoded images, \
+             `--crop` and the compositing of the display sets of several objects are not applied"
+        );
+        let images = rle_images.into_iter().map(PgsImage::Rle).collect();
+        (images, Vec::new())
+    } else {
+        rle_images
+            .into_iter()
+            .zip(compositions)
+            .map(|(rle_img, composition)| {
+                let origin = composition.origin;
+                (PgsImage::new(rle_img, Some(composition)), origin)
+            })
+            .unzip()
+    };
+    // The positions of the oversized images removed below are removed too.
+    let origins = origins
+        .into_iter()
+        .zip(&images)
+        .filter(|(_, image)| sanity::fits_max_size(*image, opt.input.max_image_size))
+        .map(|(origin, _)| origin)
+        .collect();
+    let (times, images) = sanity::remove_oversized(times, images, opt.input.max_image_size);
+    Ok((times, images, origins))
+}
+
+/// Decode the subtitles of `input` as their images before the conversion for `OCR`, for [`Inspector::raw_images`].
+fn decode_raw(input: &Path, opt: &Config) -> Result<(Vec<TimeSpan>, Vec<DynamicImage>), Error> {
+    match input.extension().and_then(OsStr::to_str) {
+        Some("sup") => {
+            let (times, pgs_images, _) = parse_pgs(input, opt)?;
+            let images = pgs_images
+                .iter()
+                .map(|image| image.to_gray_alpha().into())
+                .collect();
+            Ok((times, images))
+        }
+        Some("idx") => {
+            let track = VobSubTrack::parse(input, opt)?;
+            let images = track
+                .images
+                .iter()
+                .map(|image| track.to_rgba(image).into())
+                .collect();
+            Ok((track.times, images))
+        }
+        Some("ts") => Ok(parse_dvb(input, opt)?
+            .into_iter()
+            .map(|(time, image)| (time, image.into()))
+            .unzip()),
+        Some(ext) => Err(Error::InvalidFileExtension {
+            extension: ext.into(),
+        }),
+        None => Err(Error::NoFileExtension),
+    }
+}
+
+/// Read the composition of each `PGS` subtitle of `input`, in the decoding order,
+/// for the display sets of several objects, `--crop` and the overlap policy.
+///
+/// # Errors
+///
+/// Will return [`Error::PgsPositions`] if the stream can't be opened again.
+pub(crate) fn pgs_compositions(input: &Path, opt: &Config) -> Result<PgsCompositions, Error> {
+    let file = File::open(input).map_err(|source| Error::PgsPositions {
+        path: absolute_path(input),
+        source,
+    })?;
+    Ok(PgsCompositions {
+        compositions: composition::PgsCompositions::new(BufReader::new(file)),
+        path: absolute_path(input),
+        crop: opt.image.crop,
+    })
+}
+
+/// Compositions of the `PGS` subtitles of a stream, read one display set at a time
+/// alongside the decoding of the subtitles.
+///
+/// The iterator returns [`Error::PgsPositions`] if the stream can't be read,
+/// and [`Error::CropOutsideDisplay`] if the crop region doesn't fit in the display of a composition.
+pub(crate) struct PgsCompositions {
+    compositions: composition::PgsCompositions<BufReader<File>>,
+    /// Path of the stream, for the errors.
+    path: PathBuf,
+    crop: Option<Crop>,
+}
+
+impl Iterator for PgsCompositions {
+    type Item = Result<composition::PgsComposition, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let composition = self.compositions.next()?;
+        Some(
+            composition
+                .map_err(|source| Error::PgsPositions {
+                    path: self.path.clone(),
+                    source,
+                })
+                .and_then(|composition| match self.crop {
+                    Some(crop) if !crop.fits(composition.display.0, composition.display.1) => {
+                        Err(Error::CropOutsideDisplay {
+                            crop,
+                            width: composition.display.0,
+                            height: composition.display.1,
+                        })
+                    }
+                    _ => Ok(composition),
+                }),
+        )
+    }
+}
+
+/// Convert the `PGS` image `pgs_image` for `OCR`, with the conversion options of `opt`.
+///
+/// The pixels out of the crop region are ignored if the display position `origin` of the image is known.
+pub(crate) fn pgs_to_ocr_image(
+    pgs_image: &PgsImage,
+    origin: Option<(u32, u32)>,
+    opt: &Config,
+    ocr_opt: &ToOcrImageOpt,
+) -> GrayImage {
+    let mut image = match pgs_image {
+        PgsImage::Composited(image) => gray_alpha_to_ocr_image(image, opt),
+        PgsImage::Rle(rle_img) => rle_to_ocr_image(rle_img, opt, ocr_opt),
+    };
+    if let (Some(crop), Some((left, top))) = (opt.image.crop, origin) {
+        crop.mask(&mut image, left, top, opt.image.border);
+    }
+    match opt.image.target_line_height {
+        Some(target_line_height) => {
+            downscale_to_line_height(image, opt.image.border, target_line_height)
+        }
+        None => image,
+    }
+}
+
+/// Convert the run-length encoded `PGS` image `rle_img` for `OCR`, without downscaling.
+fn rle_to_ocr_image(
+    rle_img: &pgs::RleEncodedImage,
+    opt: &Config,
+    ocr_opt: &ToOcrImageOpt,
+) -> GrayImage {
+    if opt.image.convert == ConvertStrategy::Otsu {
+        let image: GrayAlphaImage = RleToImage::new(rle_img, |pix: LumaA<u8>| pix).to_image();
+        luma_alpha_to_luma_otsu(&image, opt.image.border)
+    } else {
+        let (alpha_threshold, luma_threshold) =
+            (opt.image.alpha_threshold, opt.image.luma_threshold);
+        let conv_fn = |LumaA([luma, alpha]): LumaA<u8>| {
+            if alpha > alpha_threshold && luma > luma_threshold {
+                Luma([0])
+            } else {
+                Luma([255])
+            }
+        };
+        RleToImage::new(rle_img, &conv_fn).image(ocr_opt)
+    }
+}
+
+/// Process `VobSub` subtitle file `input`
+///
+/// # Errors
+///
+/// Will return [`Error::IndexOpen`] if the subtitle files can't be opened.
+/// Will return [`Error::IndexRead`] if the timing directives of the index can't be read,
+/// or if the copy of an index without palette can't be written.
+/// Will return [`Error::ParseStalled`] if the parsing of subtitles stops progressing.
+/// Will return [`Error::TooManyDetachedThreads`] if too many threads given up after a timeout are still running.
+/// Will return [`Error::CropOutsideDisplay`] if the crop region doesn't fit in a display size of the index.
+#[profiling::function]
+pub fn process_vobsub(
+    input: &Path,
+    opt: &Config,
+) -> Result<(Vec<TimeSpan>, Vec<GrayImage>), Error> {
+    vobsub_with_regions(input, opt).map(|(times, images, _)| (times, images))
+}
+
+/// Process `VobSub` subtitle file `input` like [`process_vobsub`], with the display region of each image.
+fn vobsub_with_regions(input: &Path, opt: &Config) -> Result<Decoded, Error> {
+    let track = VobSubTrack::parse(input, opt)?;
+
+    let ocr_opt = ocr_opt(opt);
+    let images_for_ocr = convert_images(&track.images, |vobsub_img| {
+        track.to_ocr_image(vobsub_img, opt, &ocr_opt)
+    });
+
+    sanity::check_display_sizes(input, &images_for_ocr, opt.image.border);
+
+    let regions = track
+        .images
+        .iter()
+        .map(|image| Some(Region::new(u32::from(image.area().top()), image.height())))
+        .collect();
+    Ok((track.times, images_for_ocr, regions))
+}
+
+/// Describe the position of the `VobSub` subtitle after the `parsed` first ones of the idx file
+/// `input`, with the offset of its packet in the sub file if the idx file lists it.
+fn vobsub_position(input: &Path, parsed: usize) -> String {
+    let filepos = idx::read_content(input).ok().and_then(|content| {
+        idx::parse_entries(&content)
+            .get(parsed)
+            .map(|entry| entry.filepos)
+    });
+    match filepos {
+        Some(filepos) => format!(
+            "subtitle {}, at offset {filepos:#x} of the sub file",
+            parsed + 1
+        ),
+        None => format!("subtitle {}", parsed + 1),
+    }
+}
+
+/// `VobSub` subtitles of an idx file, before their conversion for `OCR`.
+pub(crate) struct VobSubTrack {
+    /// Times of the subtitles, with the timing directives of the idx file.
+    pub(crate) times: Vec<TimeSpan>,
+    /// Images of the subtitles, with the indices of their colors.
+    pub(crate) images: Vec<VobSubIndexedImage>,
+    /// Palette of the idx file, or from the cli.
+    palette: vobsub::Palette,
+    /// Custom colors of the idx file, replacing the colors of each subtitle.
+    custom_colors: Option<idx::CustomColors>,
+}
+
+impl VobSubTrack {
+    /// Parse the subtitles of the idx file `input`, and its sub file.
+    ///
+    /// # Errors
+    ///
+    /// Will return the errors of [`process_vobsub`].
+    pub(crate) fn parse(input: &Path, opt: &Config) -> Result<Self, Error> {
+        // The idx files written by some tools have no palette, which can't be parsed.
+        let with_palette = palette::add_missing_palette(input, opt.input.palette.as_ref())
+            .map_err(|source| Error::IndexRead {
+                path: absolute_path(input),
+                source,
+            })?;
+        let idx = {
+            profiling::scope!("Open idx");
+            let path = with_palette
+                .as_ref()
+                .map_or(input, stdin::SpooledInput::path);
+            vobsub::Index::open(path).map_err(Error::IndexOpen)?
+        };
+        let palette = *opt.input.palette.as_ref().unwrap_or_else(|| idx.palette());
+        // The custom colors of the idx file replace the colors of each subtitle, unless the palette is given.
+        let custom_colors = match opt.input.palette {
+            Some(_) => None,
+            None => idx::CustomColors::read(input).map_err(|source| Error::IndexRead {
+                path: input.to_path_buf(),
+                source,
+            })?,
+        };
+        let recover = opt.input.recover;
+        let subtitles = watchdog::watch(opt.input.parse_stall_timeout, move |progress| {
+            profiling::scope!("Parse subtitles");
+            idx.subtitles::<(TimeSpan, VobSubIndexedImage)>()
+                .inspect(|_| progress.advance())
+                .filter_map(|sub| match sub {
+                    Ok(sub) => Some(sub),
+                    // The truncation has been reported once, instead of a warning per lost subtitle.
+                    Err(e) if recover => {
+                        debug!("unable to read subtitle: {e}");
+                        None
+                    }
+                    Err(e) => {
+                        warn!(
+            "warning: unable to read subtitle: {e}. (This can usually be safely ignored.)"
+        );
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .map_err(|given_up| match given_up {
+            // The sub file is read by the `VobSub` parser, its offset is given by the idx file.
+            GivenUp::Stalled { parsed, .. } => Error::ParseStalled {
+                format: "VobSub",
+                position: vobsub_position(input, parsed),
+                timeout: opt.input.parse_stall_timeout.unwrap_or_default(),
+            },
+            GivenUp::TooManyDetached { count } => Error::TooManyDetachedThreads { count },
+        })?;
+        let (times, images): (Vec<_>, Vec<_>) = subtitles.into_iter().unzip();
+        let times = if opt.timing.control_timing {
+            vobsub_visible_times(input, times)
+        } else {
+            times
+        };
+
+        // Apply `time offset` and `delay` directives, unless overridden from cli.
+        let times = if opt.timing.offset.is_none() {
+            let idx_timing = idx::IdxTiming::read(input).map_err(|source| Error::IndexRead {
+                path: input.to_path_buf(),
+                source,
+            })?;
+            if idx_timing.is_empty() {
+                times
+            } else {
+                times
+                    .into_iter()
+                    .map(|time| idx_timing.apply(time))
+                    .collect()
+            }
+        } else {
+            times
+        };
+        if let Some(crop) = opt.image.crop {
+            check_vobsub_crop(input, crop)?;
+        }
+        let (times, images) = sanity::remove_oversized(times, images, opt.input.max_image_size);
+        Ok(Self {
+            times,
+            images,
+            palette,
+            custom_colors,
+        })
+    }
+
+    /// Get the palette of `image`, with the custom colors if any.
+    fn palette_of(&self, image: &VobSubIndexedImage) -> vobsub::Palette {
+        self.custom_colors.as_ref().map_or(self.palette, |custom| {
+            custom.apply(&self.palette, *image.palette())
+        })
+    }
+
+    /// Render `image` in RGBA, with its palette.
+    fn to_rgba(&self, image: &VobSubIndexedImage) -> image::RgbaImage {
+        VobSubToImage::new(image, &self.palette_of(image), conv_to_rgba).to_image()
+    }
+
+    /// Convert `image` for `OCR` with the conversion strategy of `opt`.
+    pub(crate) fn to_ocr_image(
+        &self,
+        image: &VobSubIndexedImage,
+        opt: &Config,
+        ocr_opt: &ToOcrImageOpt,
+    ) -> GrayImage {
+        let mut converted = match opt.image.convert {
+            ConvertStrategy::PaletteLuma if opt.image.ink_tolerance > 0.0 => soft_palette_to_luma(
+                &self.to_rgba(image),
+                opt.image.threshold,
+                opt.image.ink_tolerance,
+                opt.image.border,
+            ),
+            ConvertStrategy::PaletteLuma => {
+                let palette = rgb_palette_to_luminance(&self.palette_of(image));
+                VobSubOcrImage::new(image, &palette).image(ocr_opt)
+            }
+            ConvertStrategy::RgbaCluster => {
+                rgba_cluster_to_luma(&self.to_rgba(image), opt.image.border)
+            }
+            // The luminance weighted by alpha is the ink level, binarized with the Otsu threshold.
+            ConvertStrategy::Otsu => {
+                soft_palette_to_luma(&self.to_rgba(image), 0.5, 0.5, opt.image.border)
+            }
+        };
+        if let Some(crop) = opt.image.crop {
+            let area = image.area();
+            let (left, top) = (u32::from(area.left()), u32::from(area.top()));
+            crop.mask(&mut converted, left, top, opt.image.border);
+        }
+        converted
+    }
+}
+
+/// Check that `crop` fits in the display sizes declared by the idx file `input`.
+///
+/// # Errors
+///
+/// Will return [`Error::IndexRead`] if the idx file can't be read.
+/// Will return [`Error::CropOutsideDisplay`] if the crop region doesn't fit in a display size.
+fn check_vobsub_crop(input: &Path, crop: Crop) -> Result<(), Error> {
+    let content = idx::read_content(input).map_err(|source| Error::IndexRead {
+        path: absolute_path(input),
+        source,
+    })?;
+    match idx::display_sizes(&content)
+        .into_iter()
+        .find(|(_, size)| !crop.fits(size.width, size.height))
+    {
+        Some((_, size)) => Err(Error::CropOutsideDisplay {
+            crop,
+            width: size.width,
+            height: size.height,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Restrict the `times` of the `VobSub` subtitles of idx file `input` to their visible interval,
+/// with the fades and display area changes of the control sequences of the sub file.
+///
+/// The times are kept if the files can't be read.
+fn vobsub_visible_times(input: &Path, times: Vec<TimeSpan>) -> Vec<TimeSpan> {
+    let sub = input.with_extension("sub");
+    let visibilities = idx::read_content(input)
+        .and_then(|content| spu::read_visibilities(&sub, &idx::parse_entries(&content)));
+    match visibilities {
+        Ok(visibilities) => spu::apply_visibilities(times, &visibilities),
+        Err(err) => {
+            warn!(
+                "Could not read the control sequences of {}: {err}, the subtitles keep the times of their start and stop commands",
+                sub.display()
+            );
+            times
+        }
+    }
+}
+
+/// Process the `DVB` subtitles of the `MPEG` transport stream `input`, like a DVB-T/S recording.
+///
+/// # Errors
+///
+/// Will return [`Error::DvbRead`] if the transport stream can't be read.
+/// Will return [`Error::DvbParsing`] if it has no `DVB` subtitles,
+/// or if the crop region doesn't fit in their display size.
+#[profiling::function]
+pub fn process_dvb(input: &Path, opt: &Config) -> Result<(Vec<TimeSpan>, Vec<GrayImage>), Error> {
+    let (times, images): (Vec<_>, Vec<_>) = parse_dvb(input, opt)?.into_iter().unzip();
+
+    let images = convert_images(&images, |image| dvb_to_ocr_image(image, opt));
+
+    Ok((times, images))
+}
+
+/// Decode the `DVB` subtitles of `input`, streaming the transport stream from the file.
+pub(crate) fn parse_dvb(
+    input: &Path,
+    opt: &Config,
+) -> Result<Vec<(TimeSpan, GrayAlphaImage)>, Error> {
+    let read_error = |source| Error::DvbRead {
+        path: absolute_path(input),
+        source,
+    };
+    let file = File::open(input).map_err(read_error)?;
+    dvb::parse(
+        BufReader::new(file),
+        opt.input.max_image_size,
+        opt.image.crop,
+    )
+    .map_err(|err| match err {
+        dvb::Error::Read(source) => read_error(source),
+        err => Error::DvbParsing(err),
+    })
+}
+
+/// Convert the decoded `DVB` image for `OCR`, with the conversion options of `opt`.
+pub(crate) fn dvb_to_ocr_image(image: &GrayAlphaImage, opt: &Config) -> GrayImage {
+    let image = gray_alpha_to_ocr_image(image, opt);
+    match opt.image.target_line_height {
+        Some(target_line_height) => {
+            downscale_to_line_height(image, opt.image.border, target_line_height)
+        }
+        None => image,
+    }
+}
+
+/// Convert the gray and alpha `image` for `OCR`, without downscaling.
+fn gray_alpha_to_ocr_image(image: &GrayAlphaImage, opt: &Config) -> GrayImage {
+    if opt.image.convert == ConvertStrategy::Otsu {
+        luma_alpha_to_luma_otsu(image, opt.image.border)
+    } else {
+        dvb::to_ocr_image(
+            image,
+            opt.image.alpha_threshold,
+            opt.image.luma_threshold,
+            opt.image.border,
+        )
+    }
+}
+
+/// Convert the decoded `images` for `OCR` in parallel with `converter`.
+///
+/// The conversion runs in the current rayon thread pool, for custom pixel mappings of the
+/// images decoded with `subtile` (e.g. keeping the anti-aliasing).
+#[must_use]
+pub fn convert_images<Img: Sync>(
+    images: &[Img],
+    converter: impl Fn(&Img) -> GrayImage + Sync + Send,
+) -> Vec<GrayImage> {
+    profiling::scope!("Convert images for OCR");
+    images.par_iter().map(converter).collect()
+}
+
+/// Create [`ToOcrImageOpt`] from [`Config`]
+fn ocr_opt(opt: &Config) -> ToOcrImageOpt {
+    ToOcrImageOpt {
+        border: opt.image.border,
+        ..Default::default()
+    }
+}
+
+/// Log errors and remove bad results.
+///
+/// Subtitles for which the `OCR` panicked or timed out are skipped with a warning, without failing the whole process.
+///
+/// # Errors
+///  Will return [`Error::OcrFails`] if the ocr return an error for at least one image.
+#[profiling::function]
+pub fn check_subtitles<In>(subtitles: In) -> Result<Vec<(TimeSpan, String)>, Error>
+where
+    In: IntoIterator<Item = (TimeSpan, Result<String, ocr::Error>)>,
+{
+    // The skipped subtitles are already logged, there is no report to write them in.
+    let subtitles = check_recognized(
+        subtitles
+            .into_iter()
+            .map(|(time, maybe_text)| (time, maybe_text, ())),
+        &mut qa::QaReport::default(),
+    )?;
+    Ok(subtitles
+        .into_iter()
+        .map(|(time, text, ())| (time, text))
+        .collect())
+}
+
+/// Log errors and remove bad results like [`check_subtitles`], keeping the `extra` data
+/// of each subtitle with its text.
+///
+/// The skipped subtitles are also flagged in `qa`, with their number.
+fn check_recognized<T>(
+    subtitles: impl IntoIterator<Item = (TimeSpan, Result<String, ocr::Error>, T)>,
+    qa: &mut qa::QaReport,
+) -> Result<Vec<(TimeSpan, String, T)>, Error> {
+    let mut ocr_error_count = 0;
+    let subtitles = subtitles
+        .into_iter()
+        .enumerate()
+        .filter_map(|(idx, (time, maybe_text, extra))| match maybe_text {
+            Ok(text) => Some((time, text, extra)),
+            Err(ocr::Error::Panic { message }) => {
+                warn!(
+                    "OCR panicked on subtitle image ({} - {time:?}), it is skipped:\n\t {message}",
+                    idx + 1,
+                );
+                qa.add(
+                    time,
+                    format!("subtitle {}: OCR panicked, skipped: {message}", idx + 1),
+                );
+                None
+            }
+            Err(ocr::Error::Timeout { timeout }) => {
+                warn!(
+                    "OCR took more than {timeout:?} on subtitle image ({} - {time:?}), it is skipped",
+                    idx + 1,
+                );
+                qa.add(
+                    time,
+                    format!("subtitle {}: OCR timed out after {timeout:?}, skipped", idx + 1),
+                );
+                None
+            }
+            Err(e) => {
+                let err = anyhow::Error::new(e); // warp in anyhow::Error to display the error stack with :#
+                warn!(
+                    "Error while running OCR on subtitle image ({} - {time:?}):\n\t {err:#}",
+                    idx + 1,
+                );
+                ocr_error_count += 1;
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if ocr_error_count > 0 {
+        Err(Error::OcrFails(ocr_error_count))
+    } else {
+        Ok(subtitles)
+    }
+}
+
+/// Get the output files with their format, or `None` for stdout.
+///
+/// The `track` of a Matroska input is used for the output template.
+///
+/// # Errors
+///
+/// Will return [`Error::OutputTemplate`] if the output template can't be expanded.
+/// Will return [`Error::MultipleFormatsToStdout`] if several formats are requested without output files.
+/// Will return [`Error::MoreFormatsThanOutputs`] if more formats than output files are requested.
+fn outputs(
+    opt: &Config,
+    input: &Path,
+    track: Option<usize>,
+) -> Result<Vec<(Option<PathBuf>, OutputFormat)>, Error> {
+    if let Some(template) = &opt.output.template {
+        let formats = if opt.output.formats.is_empty() {
+            &[OutputFormat::Srt][..]
+        } else {
+            &opt.output.formats
+        };
+        return formats
+            .iter()
+            .map(|format| {
+                let vars = output_template::TemplateVars {
+                    input,
+                    lang: &opt.ocr.lang,
+                    track,
+                    format: format.extension(),
+                };
+                Ok((Some(output_template::expand(template, &vars)?), *format))
+            })
+            .collect();
+    }
+    if opt.output.files.is_empty() {
+        return match opt.output.formats[..] {
+            [] => Ok(vec![(None, OutputFormat::Srt)]),
+            [format] => Ok(vec![(None, format)]),
+            _ => Err(Error::MultipleFormatsToStdout),
+        };
+    }
+    if opt.output.formats.len() > opt.output.files.len() {
+        return Err(Error::MoreFormatsThanOutputs {
+            formats: opt.output.formats.len(),
+            outputs: opt.output.files.len(),
+        });
+    }
+    Ok(opt
+        .output
+        .files
+        .iter()
+        .enumerate()
+        .map(|(idx, path)| {
+            let format = opt
+                .output
+                .formats
+                .get(idx)
+                .copied()
+                .unwrap_or_else(|| OutputFormat::from_path(path));
+            (Some(path.clone()), format)
+        })
+        .collect())
+}
+
+/// Write `subtitles` in `format` to the file at `path`, or to stdout if `None`.
+///
+/// The `hOCR` format is written from the `hocr_pages` instead, and the `ASS` format
+/// with `ass_style` as its default style.
+#[profiling::function]
+fn write_subtitles(
+    path: Option<&Path>,
+    format: OutputFormat,
+    subtitles: &[(TimeSpan, String)],
+    hocr_pages: &[(TimeSpan, String)],
+    ass_style: &AssStyle,
+) -> Result<(), Error> {
+    let write = |mut writer: &mut dyn Write| match format {
+        OutputFormat::Srt => srt::write_srt(&mut writer, subtitles),
+        OutputFormat::Ass => ass::write_ass(&mut writer, subtitles, ass_style),
+        OutputFormat::Hocr => hocr::write_hocr(&mut writer, hocr_pages),
+    };
+    match path {
+        Some(path) => {
+            let mkerr = |source| {
+                let path = absolute_path(path);
+                match format {
+                    OutputFormat::Srt => Error::WriteSrtFile { path, source },
+                    OutputFormat::Ass => Error::WriteAssFile { path, source },
+                    OutputFormat::Hocr => Error::WriteHocrFile { path, source },
+                }
+            };
+
+            // Write to file.
+            create_parent_dirs(path).map_err(mkerr)?;
+            let subtitle_file = File::create(path).map_err(mkerr)?;
+            let mut stream = BufWriter::new(subtitle_file);
+            write(&mut stream).map_err(mkerr)?;
+        }
+        None => {
+            // Write to stdout.
+            let mut stdout = io::stdout();
+            write(&mut stdout).map_err(|source| match format {
+                OutputFormat::Srt => Error::WriteSrtStdout { source },
+                OutputFormat::Ass => Error::WriteAssStdout { source },
+                OutputFormat::Hocr => Error::WriteHocrStdout { source },
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Create the missing parent directories of the output file `path`.
+fn create_parent_dirs(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => fs::create_dir_all(dir),
+        _ => Ok(()),
+    }
+}
+
+/// Get the absolute form of `path`, to report it without ambiguity in error messages.
+fn absolute_path(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,828 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use flate2::read::ZlibDecoder;
+use log::{debug, info, warn};
+use subtile::time::TimePoint;
+use thiserror::Error;
+
+use crate::{stdin::SpooledInput, timing::format_timestamp};
+
+/// Element ids of the Matroska elements used to find the subtitles.
+const EBML_HEADER: u64 = 0x1A45_DFA3;
+const SEGMENT: u64 = 0x1853_8067;
+const SEEK_HEAD: u64 = 0x114D_9B74;
+const INFO: u64 = 0x1549_A966;
+const TIMESTAMP_SCALE: u64 = 0x2A_D7B1;
+const TRACKS: u64 = 0x1654_AE6B;
+const TRACK_ENTRY: u64 = 0xAE;
+const TRACK_NUMBER: u64 = 0xD7;
+const TRACK_TYPE: u64 = 0x83;
+const CODEC_ID: u64 = 0x86;
+const CODEC_PRIVATE: u64 = 0x63A2;
+const LANGUAGE: u64 = 0x22_B59C;
+const NAME: u64 = 0x536E;
+const CONTENT_ENCODINGS: u64 = 0x6D80;
+const CONTENT_ENCODING: u64 = 0x6240;
+const CONTENT_ENCODING_SCOPE: u64 = 0x5032;
+const CONTENT_COMPRESSION: u64 = 0x5034;
+const CONTENT_ENCRYPTION: u64 = 0x5035;
+const CONTENT_COMP_ALGO: u64 = 0x4254;
+const CONTENT_COMP_SETTINGS: u64 = 0x4255;
+const CHAPTERS: u64 = 0x1043_A770;
+const CLUSTER: u64 = 0x1F43_B675;
+const CLUSTER_TIMESTAMP: u64 = 0xE7;
+const SIMPLE_BLOCK: u64 = 0xA3;
+const BLOCK_GROUP: u64 = 0xA0;
+const BLOCK: u64 = 0xA1;
+const CUES: u64 = 0x1C53_BB6B;
+const ATTACHMENTS: u64 = 0x1941_A469;
+const TAGS: u64 = 0x1254_C367;
+
+/// Elements of the segment, ending a cluster of unknown size.
+const SEGMENT_CHILDREN: [u64; 8] = [
+    SEEK_HEAD,
+    INFO,
+    TRACKS,
+    CHAPTERS,
+    CLUSTER,
+    CUES,
+    ATTACHMENTS,
+    TAGS,
+];
+
+/// Type of the subtitle tracks.
+const TRACK_TYPE_SUBTITLE: u64 = 0x11;
+
+/// Default duration in nanoseconds of the timestamps ticks.
+const DEFAULT_TIMESTAMP_SCALE: u64 = 1_000_000;
+
+/// Size of the `MPEG` program stream packs of a `.sub` file.
+const PACK_SIZE: usize = 2048;
+
+/// Header of the program stream packs written in the `.sub` file, with a null clock reference.
+const PACK_HEADER: [u8; 14] = [
+    0x00, 0x00, 0x01, 0xBA, 0x44, 0x00, 0x04, 0x00, 0x04, 0x01, 0x01, 0x89, 0xC3, 0xF8,
+];
+
+/// Stream id of the `PES` packets of the `VobSub` subtitles.
+const PRIVATE_STREAM_1: u8 = 0xBD;
+
+/// Sub-stream id of the first `VobSub` subtitles stream.
+const VOBSUB_SUBSTREAM: u8 = 0x20;
+
+/// Maximum decompressed size of a `VobSub` frame, as the size of a subtitle packet is 16 bits.
+const MAX_VOBSUB_FRAME_SIZE: u64 = u16::MAX as u64;
+
+/// Maximum decompressed size of a `PGS` frame, a display set of segments of at most 64 KiB each.
+///
+/// It holds the run-length encoded objects of a whole screen, with room to spare.
+const MAX_PGS_FRAME_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Maximum decompressed size of the codec private data, the header of an idx file.
+const MAX_PRIVATE_SIZE: u64 = 1024 * 1024;
+
+/// Error of a subtitle track reading in a Matroska file.
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Could not read the Matroska file or write its subtitles")]
+    Io(#[from] io::Error),
+
+    #[error("Not a Matroska file: no EBML header or segment")]
+    NotMatroska,
+
+    #[error("No VobSub or PGS subtitle track in the Matroska file")]
+    NoSubtitleTrack,
+
+    #[error(
+        "No subtitle track {track} in the Matroska file, the subtitle tracks are: {available}"
+    )]
+    TrackNotFound { track: usize, available: String },
+
+    #[error("Track {track} has the codec `{codec}`, only VobSub (`S_VOBSUB`) and PGS (`S_HDMV/PGS`) subtitles are managed")]
+    UnsupportedCodec { track: usize, codec: String },
+
+    #[error("Track {track} is encoded with an unsupported compression or encryption")]
+    UnsupportedEncoding { track: usize },
+}
+
+/// Subtitles formats of the Matroska tracks, decoded by the existing pipelines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Pgs,
+    VobSub,
+}
+
+/// Compression of the frames of a track.
+#[derive(Clone, Debug)]
+enum Compression {
+    Zlib,
+    /// The bytes removed from the start of each frame.
+    HeaderStripping(Vec<u8>),
+}
+
+/// Track of a Matroska file.
+#[derive(Clone, Debug, Default)]
+struct Track {
+    /// Id of the track, its index in the file as for `mkvmerge` and `mkvextract`.
+    id: usize,
+    /// Number of the track in the blocks.
+    number: u64,
+    kind: u64,
+    codec: String,
+    private: Vec<u8>,
+    language: String,
+    name: Option<String>,
+    compression: Option<Compression>,
+    /// The codec private data is compressed too.
+    private_compressed: bool,
+    /// The track is encrypted, or compressed with an unsupported algorithm.
+    unsupported_encoding: bool,
+}
+
+impl Track {
+    fn format(&self) -> Option<Format> {
+        match self.codec.as_str() {
+            "S_HDMV/PGS" => Some(Format::Pgs),
+            "S_VOBSUB" => Some(Format::VobSub),
+            _ => None,
+        }
+    }
+
+    /// Describe the track for the user.
+    fn description(&self) -> String {
+        let name = self
+            .name
+            .as_ref()
+            .map_or_else(String::new, |name| format!(" \"{name}\""));
+        format!("{} ({}, {}{name})", self.id, self.codec, self.language)
+    }
+
+    /// Get the maximum decompressed size of a frame of the track.
+    fn max_frame_size(&self) -> u64 {
+        match self.format() {
+            Some(Format::Pgs) => MAX_PGS_FRAME_SIZE,
+            Some(Format::VobSub) | None => MAX_VOBSUB_FRAME_SIZE,
+        }
+    }
+
+    /// Undo the compression of a frame, or of the codec private data, of at most `limit` bytes.
+    ///
+    /// The limit stops the crafted data expanding to huge sizes.
+    fn decompress(&self, data: Vec<u8>, limit: u64) -> io::Result<Vec<u8>> {
+        match &self.compression {
+            None => Ok(data),
+            Some(Compression::Zlib) => {
+                let mut decompressed = Vec::new();
+                ZlibDecoder::new(data.as_slice())
+                    .take(limit + 1)
+                    .read_to_end(&mut decompressed)?;
+                if decompressed.len() as u64 > limit {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("compressed data larger than {limit} bytes"),
+                    ));
+                }
+                Ok(decompressed)
+            }
+            Some(Compression::HeaderStripping(header)) => Ok([header.as_slice(), &data].concat()),
+        }
+    }
+}
+
+/// Check if `input` is a Matroska file, from its extension.
+#[must_use]
+pub fn is_matroska(input: &Path) -> bool {
+    input
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("mkv") || ext.eq_ignore_ascii_case("mks"))
+}
+
+/// Write the subtitle `track` of the Matroska file `input` in temporary files,
+/// to decode them as a `.sup` or an `.idx` file.
+///
+/// The `track` is the id of `mkvmerge` and `mkvextract`, the first `VobSub` or `PGS`
+/// track is used if `None`. The id of the written track is returned with its files.
+///
+/// # Errors
+///
+/// Will return [`Error::NotMatroska`] if `input` is not a Matroska file.
+/// Will return [`Error::NoSubtitleTrack`], [`Error::TrackNotFound`], [`Error::UnsupportedCodec`]
+/// or [`Error::UnsupportedEncoding`] if the track can't be used.
+/// Will return [`Error::Io`] if `input` can't be read, or the temporary files written.
+#[profiling::function]
+pub fn spool_track(input: &Path, track: Option<usize>) -> Result<(SpooledInput, usize), Error> {
+    let file = File::open(input)?;
+    let len = file.metadata()?.len();
+    let mut reader = EbmlReader::new(BufReader::new(file), len);
+    match reader.header()? {
+        Some((EBML_HEADER, Some(size))) => reader.skip(size)?,
+        _ => return Err(Error::NotMatroska),
+    }
+    let segment_end = match reader.header()? {
+        Some((SEGMENT, size)) => size.map_or(len, |size| reader.pos + size).min(len),
+        _ => return Err(Error::NotMatroska),
+    };
+
+    let mut scale = DEFAULT_TIMESTAMP_SCALE;
+    let mut selected = None;
+    let mut blocks = Vec::new();
+    while reader.pos < segment_end {
+        let Some((id, size)) = reader.header()? else {
+            break;
+        };
+        match (id, size, &selected) {
+            (CLUSTER, size, Some(track)) => {
+                reader.read_cluster(size, track, scale, &mut blocks)?;
+            }
+            (INFO, Some(size), _) => {
+                let info = children(&reader.read(size)?)?;
+                if let Some((_, value)) = info.iter().find(|(id, _)| *id == TIMESTAMP_SCALE) {
+                    scale = read_uint(value);
+                }
+            }
+            (TRACKS, Some(size), None) => {
+                let tracks = read_tracks(&reader.read(size)?)?;
+                selected = Some(select_track(tracks, track)?);
+            }
+            (_, Some(size), _) => reader.skip(size)?,
+            (id, None, _) => {
+                debug!("Element {id:#x} of unknown size, the rest of the file is ignored");
+                break;
+            }
+        }
+    }
+    let track = selected.ok_or(Error::NoSubtitleTrack)?;
+    info!(
+        "{} subtitles read from the track {}",
+        blocks.len(),
+        track.description()
+    );
+
+    let spooled = match track.format() {
+        Some(Format::Pgs) => {
+            let spooled = SpooledInput::temp("mkv", "sup")?;
+            write_sup(spooled.path(), &blocks)?;
+            spooled
+        }
+        Some(Format::VobSub) | None => {
+            let spooled = SpooledInput::temp("mkv", "idx")?;
+            let private = if track.private_compressed {
+                track.decompress(track.private.clone(), MAX_PRIVATE_SIZE)?
+            } else {
+                track.private.clone()
+            };
+            write_vobsub(spooled.path(), &private, &track.language, &blocks)?;
+            spooled
+        }
+    };
+    Ok((spooled, track.id))
+}
+
+/// Parse the track entries of the `data` of the tracks element.
+fn read_tracks(data: &[u8]) -> io::Result<Vec<Track>> {
+    children(data)?
+        .into_iter()
+        .filter(|(id, _)| *id == TRACK_ENTRY)
+        .enumerate()
+        .map(|(idx, (_, entry))| {
+            let mut track = Track {
+                id: idx,
+                language: "eng".to_owned(),
+                ..Track::default()
+            };
+            for (id, value) in children(&entry)? {
+                match id {
+                    TRACK_NUMBER => track.number = read_uint(&value),
+                    TRACK_TYPE => track.kind = read_uint(&value),
+                    CODEC_ID => track.codec = read_string(&value),
+                    CODEC_PRIVATE => track.private = value,
+                    LANGUAGE => track.language = read_string(&value),
+                    NAME => track.name = Some(read_string(&value)),
+                    CONTENT_ENCODINGS => read_encodings(&mut track, &value)?,
+                    _ => {}
+                }
+            }
+            Ok(track)
+        })
+        .collect()
+}
+
+/// Parse the content encodings of `track`.
+fn read_encodings(track: &mut Track, data: &[u8]) -> io::Result<()> {
+    for (_, encoding) in children(data)?
+        .into_iter()
+        .filter(|(id, _)| *id == CONTENT_ENCODING)
+    {
+        let mut scope = 1;
+        for (id, value) in children(&encoding)? {
+            match id {
+                CONTENT_ENCODING_SCOPE => scope = read_uint(&value),
+                CONTENT_ENCRYPTION => track.unsupported_encoding = true,
+                CONTENT_COMPRESSION => {
+                    let settings = children(&value)?;
+                    let algorithm = settings
+                        .iter()
+                        .find(|(id, _)| *id == CONTENT_COMP_ALGO)
+                        .map_or(0, |(_, value)| read_uint(value));
+                    let header = settings
+                        .into_iter()
+                        .find(|(id, _)| *id == CONTENT_COMP_SETTINGS)
+                        .map(|(_, value)| value)
+                        .unwrap_or_default();
+                    match algorithm {
+                        0 => track.compression = Some(Compression::Zlib),
+                        3 => track.compression = Some(Compression::HeaderStripping(header)),
+                        _ => track.unsupported_encoding = true,
+                    }
+                }
+                _ => {}
+            }
+        }
+        track.private_compressed = scope & 0x02 != 0;
+    }
+    Ok(())
+}
+
+/// Select the subtitle `track` in `tracks`, or the first one in a managed format.
+fn select_track(tracks: Vec<Track>, track: Option<usize>) -> Result<Track, Error> {
+    let subtitles = tracks
+        .into_iter()
+        .filter(|track| track.kind == TRACK_TYPE_SUBTITLE)
+        .collect::<Vec<_>>();
+    let available = subtitles
+        .iter()
+        .map(Track::description)
+        .collect::<Vec<_>>()
+        .join(", ");
+    info!("Subtitle tracks of the Matroska file: {available}");
+    let selected = match track {
+        Some(id) => {
+            subtitles
+                .into_iter()
+                .find(|track| track.id == id)
+                .ok_or(Error::TrackNotFound {
+                    track: id,
+                    available,
+                })?
+        }
+        None => subtitles
+            .into_iter()
+            .find(|track| track.format().is_some())
+            .ok_or(Error::NoSubtitleTrack)?,
+    };
+    if selected.format().is_none() {
+        return Err(Error::UnsupportedCodec {
+            track: selected.id,
+            codec: selected.codec,
+        });
+    }
+    if selected.unsupported_encoding {
+        return Err(Error::UnsupportedEncoding { track: selected.id });
+    }
+    Ok(selected)
+}
+
+/// Write the `PGS` `blocks` as a `.sup` file at `path`.
+///
+/// The segments of the blocks have no header, it's rebuilt with the time of their block.
+fn write_sup(path: &Path, blocks: &[(i64, Vec<u8>)]) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    for (time, data) in blocks {
+        let pts = pts(*time) as u32;
+        let mut pos = 0;
+        while pos + 3 <= data.len() {
+            let size = usize::from(u16::from_be_bytes([data[pos + 1], data[pos + 2]]));
+            let Some(segment) = data.get(pos..pos + 3 + size) else {
+                warn!(
+                    "Truncated PGS segment at {} in the Matroska file",
+                    format_timestamp(TimePoint::from_msecs(*time), ',')
+                );
+                break;
+            };
+            out.write_all(b"PG")?;
+            out.write_all(&pts.to_be_bytes())?;
+            out.write_all(&0_u32.to_be_bytes())?;
+            out.write_all(segment)?;
+            pos += 3 + size;
+        }
+    }
+    out.flush()
+}
+
+/// Write the `VobSub` `blocks` as an idx file at `path` with its `.sub` file.
+///
+/// The idx header is the codec `private` data, the packets of the blocks are
+/// written in program stream packs.
+fn write_vobsub(
+    path: &Path,
+    private: &[u8],
+    language: &str,
+    blocks: &[(i64, Vec<u8>)],
+) -> io::Result<()> {
+    let mut idx = String::from_utf8_lossy(private).into_owned();
+    if !idx.ends_with('\n') {
+        idx.push('\n');
+    }
+    idx.push_str(&format!("id: {language}, index: 0\n"));
+
+    let mut sub = BufWriter::new(File::create(path.with_extension("sub"))?);
+    let mut filepos = 0;
+    for (time, data) in blocks {
+        idx.push_str(&format!(
+            "timestamp: {}, filepos: {filepos:09x}\n",
+            format_timestamp(TimePoint::from_msecs(*time), ':')
+        ));
+        filepos += write_spu(&mut sub, pts(*time), data)?;
+    }
+    sub.flush()?;
+    fs::write(path, idx)
+}
+
+/// Write the subtitle packet `spu` shown at `pts` in program stream packs, and return their size.
+fn write_spu(out: &mut impl Write, pts: u64, spu: &[u8]) -> io::Result<u64> {
+    let pts = pts_bytes(pts);
+    let mut written = 0;
+    let mut rest = spu;
+    let mut first = true;
+    while first || !rest.is_empty() {
+        // Only the first packet has the timestamp.
+        let header_data: &[u8] = if first { &pts } else { &[] };
+        let max_payload = PACK_SIZE - PACK_HEADER.len() - 9 - header_data.len() - 1;
+        let (payload, remaining) = rest.split_at(rest.len().min(max_payload));
+        let pes_length = 3 + header_data.len() + 1 + payload.len();
+        out.write_all(&PACK_HEADER)?;
+        out.write_all(&[0x00, 0x00, 0x01, PRIVATE_STREAM_1])?;
+        out.write_all(&(pes_length as u16).to_be_bytes())?;
+        out.write_all(&[
+            0x81,
+            if first { 0x80 } else { 0x00 },
+            header_data.len() as u8,
+        ])?;
+        out.write_all(header_data)?;
+        out.write_all(&[VOBSUB_SUBSTREAM])?;
+        out.write_all(payload)?;
+        written += (PACK_HEADER.len() + 6 + pes_length) as u64;
+        rest = remaining;
+        first = false;
+    }
+    Ok(written)
+}
+
+/// Get the 90 kHz timestamp of `time` in milliseconds.
+const fn pts(time: i64) -> u64 {
+    time.unsigned_abs() * 90
+}
+
+/// Encode `pts` as in a `PES` header.
+const fn pts_bytes(pts: u64) -> [u8; 5] {
+    [
+        0x21 | ((pts >> 29) & 0x0E) as u8,
+        (pts >> 22) as u8,
+        ((pts >> 14) & 0xFE) as u8 | 0x01,
+        (pts >> 7) as u8,
+        ((pts << 1) & 0xFE) as u8 | 0x01,
+    ]
+}
+
+/// Parse the children elements of the `data` of a master element.
+fn children(data: &[u8]) -> io::Result<Vec<(u64, Vec<u8>)>> {
+    let mut reader = EbmlReader::new(Cursor::new(data), data.len() as u64);
+    let mut children = Vec::new();
+    while let Some((id, size)) = reader.header()? {
+        let size = size.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "child element of unknown size")
+        })?;
+        children.push((id, reader.read(size)?));
+    }
+    Ok(children)
+}
+
+/// Read the value of an unsigned integer element.
+fn read_uint(data: &[u8]) -> u64 {
+    data.iter()
+        .fold(0, |value, byte| (value << 8) | u64::from(*byte))
+}
+
+/// Read the value of a string element, without its padding.
+fn read_string(data: &[u8]) -> String {
+    String::from_utf8_lossy(data)
+        .trim_end_matches('\0')
+        .to_owned()
+}
+
+/// Reader of the `EBML` elements of a Matroska file.
+struct EbmlReader<R> {
+    reader: R,
+    /// Position in the file.
+    pos: u64,
+    /// Size of the file.
+    len: u64,
+}
+
+impl<R: Read + Seek> EbmlReader<R> {
+    const fn new(reader: R, len: u64) -> Self {
+        Self {
+            reader,
+            pos: 0,
+            len,
+        }
+    }
+
+    fn read_u8(&mut self) -> io::Result<u8> {
+        let mut byte = [0];
+        self.reader.read_exact(&mut byte)?;
+        self.pos += 1;
+        Ok(byte[0])
+    }
+
+    /// Read a variable size integer, with the length marker kept for the element ids.
+    ///
+    /// Return the value and its length in bytes.
+    fn read_vint(&mut self, keep_marker: bool) -> io::Result<(u64, u32)> {
+        let first = self.read_u8()?;
+        let length = first.leading_zeros() + 1;
+        if length > 8 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid EBML variable size integer",
+            ));
+        }
+        let mut value = if keep_marker {
+            u64::from(first)
+        } else {
+            u64::from(first) & (0xFF >> length)
+        };
+        for _ in 1..length {
+            value = (value << 8) | u64::from(self.read_u8()?);
+        }
+        Ok((value, length))
+    }
+
+    /// Read the id and size of the next element, `None` at the end of the data.
+    ///
+    /// The size is `None` if it's unknown, as in live streams.
+    fn header(&mut self) -> io::Result<Option<(u64, Option<u64>)>> {
+        if self.pos >= self.len {
+            return Ok(None);
+        }
+        let (id, _) = self.read_vint(true)?;
+        let (size, length) = self.read_vint(false)?;
+        let unknown = size == (1 << (7 * length)) - 1;
+        Ok(Some((id, (!unknown).then_some(size))))
+    }
+
+    /// Read the `size` bytes of an element data.
+    fn read(&mut self, size: u64) -> io::Result<Vec<u8>> {
+        if size > self.len - self.pos {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "element larger than its file",
+            ));
+        }
+        let mut data = vec![0; size as usize];
+        self.reader.read_exact(&mut data)?;
+        self.pos += size;
+        Ok(data)
+    }
+
+    fn skip(&mut self, size: u64) -> io::Result<()> {
+        self.seek(self.pos + size)
+    }
+
+    fn seek(&mut self, pos: u64) -> io::Result<()> {
+        self.reader.seek(SeekFrom::Start(pos))?;
+        self.pos = pos;
+        Ok(())
+    }
+
+    /// Read the blocks of `track` in a cluster of `size`, as their time in milliseconds and frame.
+    fn read_cluster(
+        &mut self,
+        size: Option<u64>,
+        track: &Track,
+        scale: u64,
+        blocks: &mut Vec<(i64, Vec<u8>)>,
+    ) -> io::Result<()> {
+        let end = size.map_or(self.len, |size| self.pos + size).min(self.len);
+        let mut timestamp = 0;
+        while self.pos < end {
+            let start = self.pos;
+            let Some((id, size)) = self.header()? else {
+                break;
+            };
+            // A cluster of unknown size ends at the next element of the segment.
+            if SEGMENT_CHILDREN.contains(&id) {
+                return self.seek(start);
+            }
+            let Some(size) = size else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "cluster element of unknown size",
+                ));
+            };
+            match id {
+                CLUSTER_TIMESTAMP => timestamp = read_uint(&self.read(size)?),
+                SIMPLE_BLOCK => self.read_block(size, track, scale, timestamp, blocks)?,
+                BLOCK_GROUP => {
+                    let group_end = self.pos + size;
+                    while self.pos < group_end {
+                        let Some((id, Some(size))) = self.header()? else {
+                            break;
+                        };
+                        if id == BLOCK {
+                            self.read_block(size, track, scale, timestamp, blocks)?;
+                        } else {
+                            self.skip(size)?;
+                        }
+                    }
+                    self.seek(group_end)?;
+                }
+                _ => self.skip(size)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a block of `size`, kept in `blocks` if it's a frame of `track`.
+    fn read_block(
+        &mut self,
+        size: u64,
+        track: &Track,
+        scale: u64,
+        cluster_timestamp: u64,
+        blocks: &mut Vec<(i64, Vec<u8>)>,
+    ) -> io::Result<()> {
+        let end = self.pos + size;
+        let (number, _) = self.read_vint(false)?;
+        if number != track.number {
+            return self.seek(end);
+        }
+        let relative = i16::from_be_bytes([self.read_u8()?, self.read_u8()?]);
+        let flags = self.read_u8()?;
+        let frame = self.read(end.saturating_sub(self.pos))?;
+        let ticks = cluster_timestamp as i64 + i64::from(relative);
+        let time = ticks * scale as i64 / 1_000_000;
+        // Subtitle frames are not laced by the muxers.
+        if flags & 0x06 != 0 {
+            warn!(
+                "Laced subtitle block at {} skipped",
+                format_timestamp(TimePoint::from_msecs(time), ',')
+            );
+            return Ok(());
+        }
+        blocks.push((time, track.decompress(frame, track.max_frame_size())?));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io::Write, path::PathBuf};
+
+    use flate2::{write::ZlibEncoder, Compression as Level};
+
+    use super::{
+        spool_track, Compression, Error, Track, BLOCK, BLOCK_GROUP, CLUSTER, CLUSTER_TIMESTAMP,
+        CODEC_ID, EBML_HEADER, MAX_VOBSUB_FRAME_SIZE, SEGMENT, SIMPLE_BLOCK, TRACKS, TRACK_ENTRY,
+        TRACK_NUMBER, TRACK_TYPE, TRACK_TYPE_SUBTITLE,
+    };
+
+    /// Path of the Matroska file `name` of a test, in a directory of this process.
+    fn mkv_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("subtile-ocr-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    /// Encode the element `id` with its `data`, the size on 8 bytes.
+    fn element(id: u64, data: &[u8]) -> Vec<u8> {
+        let mut element = id
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|byte| *byte == 0)
+            .collect::<Vec<_>>();
+        element.push(0x01);
+        element.extend_from_slice(&(data.len() as u64).to_be_bytes()[1..]);
+        element.extend_from_slice(data);
+        element
+    }
+
+    /// Matroska file with a `PGS` track, and a block at 1.5s with the `segment` of a `PGS` stream.
+    fn sample(segment: &[u8]) -> Vec<u8> {
+        let track = [
+            element(TRACK_NUMBER, &[1]),
+            element(TRACK_TYPE, &[TRACK_TYPE_SUBTITLE as u8]),
+            element(CODEC_ID, b"S_HDMV/PGS"),
+        ]
+        .concat();
+        let block = [&[0x81, 0x01, 0xF4, 0x80][..], segment].concat();
+        let cluster = [
+            element(CLUSTER_TIMESTAMP, &[0x03, 0xE8]),
+            element(BLOCK_GROUP, &element(BLOCK, &block)),
+            element(
+                SIMPLE_BLOCK,
+                &[&[0x82, 0x00, 0x00, 0x80][..], segment].concat(),
+            ),
+        ]
+        .concat();
+        let segment = [
+            element(TRACKS, &element(TRACK_ENTRY, &track)),
+            element(CLUSTER, &cluster),
+        ]
+        .concat();
+        [element(EBML_HEADER, &[]), element(SEGMENT, &segment)].concat()
+    }
+
+    /// End segment of a `PGS` display set, without its header.
+    const END_SEGMENT: [u8; 3] = [0x80, 0x00, 0x00];
+
+    #[test]
+    fn spool_pgs_track() {
+        let path = mkv_path("sample.mkv");
+        fs::write(&path, sample(&END_SEGMENT)).unwrap();
+        let (spooled, id) = spool_track(&path, None).unwrap();
+        assert_eq!(id, 0);
+        let sup = fs::read(spooled.path()).unwrap();
+        // The block of the other track is skipped.
+        let pts = 1500_u32 * 90;
+        let expected = [&b"PG"[..], &pts.to_be_bytes(), &[0; 4], &END_SEGMENT].concat();
+        assert_eq!(sup, expected);
+    }
+
+    #[test]
+    fn unknown_track() {
+        let path = mkv_path("unknown_track.mkv");
+        fs::write(&path, sample(&END_SEGMENT)).unwrap();
+        assert!(matches!(
+            spool_track(&path, Some(3)),
+            Err(Error::TrackNotFound { track: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn truncated_file() {
+        let data = sample(&END_SEGMENT);
+        // A file truncated in a block of another track keeps the subtitles read.
+        let path = mkv_path("truncated_other.mkv");
+        fs::write(&path, &data[..data.len() - 2]).unwrap();
+        let (spooled, _) = spool_track(&path, None).unwrap();
+        assert_eq!(
+            fs::read(spooled.path()).unwrap().len(),
+            10 + END_SEGMENT.len()
+        );
+
+        // A subtitle frame can't be truncated.
+        let path = mkv_path("truncated.mkv");
+        let block = data
+            .windows(4)
+            .position(|window| window == [0x81, 0x01, 0xF4, 0x80])
+            .unwrap();
+        fs::write(&path, &data[..block + 5]).unwrap();
+        assert!(matches!(spool_track(&path, None), Err(Error::Io(_))));
+    }
+
+    #[test]
+    fn malformed_file() {
+        let path = mkv_path("not_matroska.mkv");
+        fs::write(&path, b"not a Matroska file").unwrap();
+        assert!(matches!(spool_track(&path, None), Err(Error::NotMatroska)));
+
+        // An element id with no length marker in its first byte.
+        let path = mkv_path("malformed.mkv");
+        let data = [element(EBML_HEADER, &[]), element(SEGMENT, &[0x00, 0x00])].concat();
+        fs::write(&path, data).unwrap();
+        assert!(matches!(spool_track(&path, None), Err(Error::Io(_))));
+    }
+
+    #[test]
+    fn decompress_frames() {
+        let track = Track {
+            codec: "S_VOBSUB".to_owned(),
+            compression: Some(Compression::Zlib),
+            ..Track::default()
+        };
+        let compress = |data: &[u8]| {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Level::best());
+            encoder.write_all(data).unwrap();
+            encoder.finish().unwrap()
+        };
+        let frame = vec![7; 100];
+        let decompressed = track
+            .decompress(compress(&frame), track.max_frame_size())
+            .unwrap();
+        assert_eq!(decompressed, frame);
+
+        // A frame expanding over the limit is rejected.
+        let bomb = compress(&vec![0; MAX_VOBSUB_FRAME_SIZE as usize + 1]);
+        assert!(track.decompress(bomb, track.max_frame_size()).is_err());
+        // As is a frame which is not zlib data.
+        assert!(track
+            .decompress(vec![1, 2, 3], track.max_frame_size())
+            .is_err());
+    }
+}
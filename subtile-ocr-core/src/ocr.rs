@@ -0,0 +1,1157 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    mem,
+    ops::AddAssign,
+    panic::{self, AssertUnwindSafe},
+    str::Utf8Error,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Condvar, Mutex, PoisonError,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use image::{imageops, GrayImage};
+use leptess::{
+    leptonica::PixError,
+    tesseract::{TessInitError, TessSetVariableError},
+    LepTess, Variable,
+};
+use log::{info, trace};
+use rayon::{broadcast, prelude::*};
+use thiserror::Error;
+
+use crate::{
+    config::TesseractVariable,
+    filter, split,
+    tessdata::TessdataProbe,
+    watchdog::{self, TimedThread},
+};
+
+/// Options for orc with Tesseract
+pub struct OcrOpt<'a> {
+    tessdata_dir: &'a Option<String>,
+    tessdata_probe: Option<&'a TessdataProbe>,
+    lang: &'a str,
+    config: &'a [TesseractVariable],
+    dpi: i32,
+    isolate_panics: bool,
+    max_instances: Option<usize>,
+    tesseract_threads: usize,
+    thread_limit_env: bool,
+    line_mode: bool,
+    single_line_psm: bool,
+    invert_retry: bool,
+    column_separator: Option<&'a str>,
+    hocr: bool,
+    tsv: bool,
+    scrub_control: bool,
+    timeout: Option<Duration>,
+}
+
+impl<'a> OcrOpt<'a> {
+    /// Create a new `OcrOpt`
+    #[must_use]
+    pub fn new(
+        tessdata_dir: &'a Option<String>,
+        lang: &'a str,
+        config: &'a [TesseractVariable],
+        dpi: i32,
+    ) -> Self {
+        Self {
+            tessdata_dir,
+            tessdata_probe: None,
+            lang,
+            config,
+            dpi,
+            isolate_panics: true,
+            max_instances: None,
+            tesseract_threads: 1,
+            thread_limit_env: false,
+            line_mode: false,
+            single_line_psm: true,
+            invert_retry: true,
+            column_separator: None,
+            hocr: false,
+            tsv: false,
+            scrub_control: true,
+            timeout: None,
+        }
+    }
+
+    /// Set the maximum time of the `OCR` of an image, after which it fails with [`Error::Timeout`].
+    ///
+    /// The `OCR` of an image fails with [`Error::TooManyDetached`] if the threads of too many
+    /// abandoned `OCR`s are still running.
+    #[must_use]
+    pub const fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set if each text line of an image is sent separately to Tesseract,
+    /// with the page segmentation mode for a single line.
+    #[must_use]
+    pub const fn with_line_mode(mut self, line_mode: bool) -> Self {
+        self.line_mode = line_mode;
+        self
+    }
+
+    /// Set if the images with a single text line are recognized with the page segmentation
+    /// mode for a single line, unless another mode is set in the configuration.
+    #[must_use]
+    pub const fn with_single_line_psm(mut self, single_line_psm: bool) -> Self {
+        self.single_line_psm = single_line_psm;
+        self
+    }
+
+    /// Set if an image with many text pixels but no recognized text is recognized again inverted.
+    #[must_use]
+    pub const fn with_invert_retry(mut self, invert_retry: bool) -> Self {
+        self.invert_retry = invert_retry;
+        self
+    }
+
+    /// Set the separator written between the columns of a line, separated by wide gaps.
+    ///
+    /// Without separator, the columns are recognized as words of the same line.
+    #[must_use]
+    pub const fn with_column_separator(mut self, column_separator: Option<&'a str>) -> Self {
+        self.column_separator = column_separator;
+        self
+    }
+
+    /// Set if the `hOCR` page of Tesseract of each image, with the boxes of the lines and words,
+    /// is got with its text by [`process_with_pages`]. The text is recognized as without the page.
+    #[must_use]
+    pub const fn with_hocr(mut self, hocr: bool) -> Self {
+        self.hocr = hocr;
+        self
+    }
+
+    /// Set if the `TSV` table of Tesseract of each image, with a row for the box of each
+    /// block, paragraph, line and word, is got with its text by [`process_with_pages`].
+    /// Unused with `hOCR`.
+    #[must_use]
+    pub const fn with_tsv(mut self, tsv: bool) -> Self {
+        self.tsv = tsv;
+        self
+    }
+
+    /// Set if the control characters of the text (e.g. the form feed ending the pages),
+    /// other than the line feeds and tabs, are removed (default).
+    #[must_use]
+    pub const fn with_control_scrubbing(mut self, scrub_control: bool) -> Self {
+        self.scrub_control = scrub_control;
+        self
+    }
+
+    /// Set the number of threads each Tesseract instance can use internally (`OMP_THREAD_LIMIT`).
+    ///
+    /// It is only applied with [`OcrOpt::with_thread_limit_env`].
+    #[must_use]
+    pub const fn with_tesseract_threads(mut self, tesseract_threads: usize) -> Self {
+        self.tesseract_threads = tesseract_threads;
+        self
+    }
+
+    /// Set the probe of the tessdata directory, used without tessdata directory
+    /// if Tesseract can't be initialized with its default one.
+    #[must_use]
+    pub const fn with_tessdata_probe(mut self, tessdata_probe: Option<&'a TessdataProbe>) -> Self {
+        self.tessdata_probe = tessdata_probe;
+        self
+    }
+
+    /// Set if the `OMP_THREAD_LIMIT` environment variable of the process is set
+    /// to the number of threads of each Tesseract instance.
+    ///
+    /// Off by default, as the environment is shared with the whole application.
+    #[must_use]
+    pub const fn with_thread_limit_env(mut self, thread_limit_env: bool) -> Self {
+        self.thread_limit_env = thread_limit_env;
+        self
+    }
+
+    /// Limit the number of Tesseract instances, which by default is one per rayon thread.
+    #[must_use]
+    pub const fn with_max_instances(mut self, max_instances: Option<usize>) -> Self {
+        self.max_instances = max_instances;
+        self
+    }
+
+    /// Set if a panic during the OCR of an image only fails this image (default),
+    /// or is propagated and aborts the whole process.
+    #[must_use]
+    pub const fn with_panic_isolation(mut self, isolate_panics: bool) -> Self {
+        self.isolate_panics = isolate_panics;
+        self
+    }
+}
+
+/// Errors of the Tesseract `OCR` processing.
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("Could not initialize tesseract")]
+    Initialize(#[from] TessInitError),
+
+    #[error("Could not set tesseract variable")]
+    SetVariable(#[from] TessSetVariableError),
+
+    #[error("Could not write image to memory")]
+    WriteImage(#[from] image::ImageError),
+
+    #[error("Could not set tesseract image")]
+    SetImage(#[from] PixError),
+
+    #[error("Could not get tesseract text")]
+    GetText(#[from] Utf8Error),
+
+    #[error("Tesseract is not initialized on this thread")]
+    NotInitialized,
+
+    #[error("OCR panicked: {message}")]
+    Panic { message: String },
+
+    #[error("OCR took more than {timeout:?}, it has been abandoned")]
+    Timeout { timeout: Duration },
+
+    #[error("{count} OCR threads given up after a timeout are still running, no other is started")]
+    TooManyDetached { count: usize },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+thread_local! {
+    static TESSERACT: RefCell<Option<TesseractWrapper>> = const { RefCell::new(None) };
+}
+
+/// Check that Tesseract can be initialized with `opt` on the current thread.
+///
+/// # Errors
+///
+/// Will return an error if the initialization or the configuration of Tesseract failed.
+pub fn check_init(opt: &OcrOpt) -> Result<()> {
+    init_tesseract(opt).map(drop)
+}
+
+/// Initialize Tesseract with the tessdata directory of `opt`.
+///
+/// Without directory, the probe of `opt` is used if the default directory of Tesseract fails,
+/// and the directory it found is used directly by the next initializations.
+fn init_tesseract(opt: &OcrOpt) -> Result<TesseractWrapper> {
+    let probe = opt.tessdata_probe.filter(|_| opt.tessdata_dir.is_none());
+    if let Some(dir) = probe.and_then(TessdataProbe::dir) {
+        return TesseractWrapper::new(Some(dir), opt.lang, opt.config);
+    }
+    TesseractWrapper::new(opt.tessdata_dir.as_deref(), opt.lang, opt.config).or_else(|err| {
+        match probe.and_then(|probe| probe.probe(opt.lang)) {
+            Some(dir) => TesseractWrapper::new(Some(dir), opt.lang, opt.config),
+            None => Err(err),
+        }
+    })
+}
+
+/// Choose the number of threads used by each Tesseract instance for `image_count` images.
+///
+/// By default each image is processed by one thread, but with fewer images than
+/// threads (e.g. forced subtitles only) the idle threads are given to Tesseract.
+#[must_use]
+pub fn auto_tesseract_threads(image_count: usize) -> usize {
+    (rayon::current_num_threads() / image_count.max(1)).max(1)
+}
+
+/// Choose the maximum number of Tesseract instances for `image_count` images.
+///
+/// By default there is one instance per thread, but with fewer images than threads
+/// (e.g. forced subtitles only) the instances of the idle threads would be initialized
+/// for nothing, so there is one instance per image.
+#[must_use]
+pub fn auto_max_instances(image_count: usize) -> Option<usize> {
+    (image_count < rayon::current_num_threads()).then_some(image_count.max(1))
+}
+
+/// Text recognized in an image, with the mean confidence of its words in percent.
+///
+/// The confidence is `None` if no word was recognized.
+pub type Recognition = (String, Option<u32>);
+
+/// Recognition of an image, with its `hOCR` page or `TSV` table if requested.
+pub type PagedRecognition = (Recognition, Option<String>);
+
+/// Process subtitles images with Tesseract `OCR`.
+#[profiling::function]
+pub fn process<Img>(images: Img, opt: &OcrOpt) -> Result<Vec<Result<String>>>
+where
+    Img: IntoParallelIterator<Item = GrayImage>,
+{
+    let recognitions = process_with_confidence(images, opt)?;
+    Ok(recognitions
+        .into_iter()
+        .map(|recognition| recognition.map(|(text, _)| text))
+        .collect())
+}
+
+/// Process subtitles images with Tesseract `OCR`, keeping the mean confidence of the words of each image.
+#[profiling::function]
+pub fn process_with_confidence<Img>(images: Img, opt: &OcrOpt) -> Result<Vec<Result<Recognition>>>
+where
+    Img: IntoParallelIterator<Item = GrayImage>,
+{
+    let recognitions = process_with_pages(images, opt)?;
+    Ok(recognitions
+        .into_iter()
+        .map(|recognition| recognition.map(|(recognition, _)| recognition))
+        .collect())
+}
+
+/// Process subtitles images with Tesseract `OCR`, with the `hOCR` page or the `TSV` table
+/// of each image if requested in `opt`.
+///
+/// The page is got in addition to the text, which is recognized as without it.
+#[profiling::function]
+pub fn process_with_pages<Img>(images: Img, opt: &OcrOpt) -> Result<Vec<Result<PagedRecognition>>>
+where
+    Img: IntoParallelIterator<Item = GrayImage>,
+{
+    let (recognitions, metrics) = process_with_metrics(images, opt)?;
+    metrics.log();
+    Ok(recognitions)
+}
+
+/// Process subtitles images like [`process_with_pages`], with the metrics of the `OCR`
+/// instead of logging them.
+#[profiling::function]
+pub fn process_with_metrics<Img>(
+    images: Img,
+    opt: &OcrOpt,
+) -> Result<(Vec<Result<PagedRecognition>>, OcrMetrics)>
+where
+    Img: IntoParallelIterator<Item = GrayImage>,
+{
+    set_thread_limit_env(opt);
+    let metrics = MetricsRecorder::default();
+    let start = Instant::now();
+    let subs = match opt.max_instances {
+        Some(max) if max < rayon::current_num_threads() => {
+            process_with_pool(images, opt, max, &metrics)
+        }
+        _ => process_per_thread(images, opt, &metrics),
+    }?;
+    Ok((subs, metrics.metrics(start.elapsed())))
+}
+
+/// Set the `OMP_THREAD_LIMIT` environment variable to the threads of each instance, if enabled in `opt`.
+fn set_thread_limit_env(opt: &OcrOpt) {
+    if opt.thread_limit_env {
+        trace!(
+            "Tesseract instances use up to {} thread(s)",
+            opt.tesseract_threads
+        );
+        std::env::set_var("OMP_THREAD_LIMIT", opt.tesseract_threads.to_string());
+    }
+}
+
+/// `OCR` of several sets of images, like the batches of a run, with the same Tesseract instances.
+///
+/// Unlike calls of [`process_with_metrics`], the instances are created for the first set
+/// and kept for the next ones. There are at most one instance per rayon thread,
+/// or the maximum set in the options.
+pub struct OcrSession<'a> {
+    opt: &'a OcrOpt<'a>,
+    max: usize,
+    idle: Vec<TesseractWrapper>,
+    metrics: MetricsRecorder,
+    elapsed: Duration,
+}
+
+impl<'a> OcrSession<'a> {
+    /// Create a session configured from `opt`, without instance yet.
+    #[must_use]
+    pub fn new(opt: &'a OcrOpt<'a>) -> Self {
+        Self {
+            opt,
+            max: opt
+                .max_instances
+                .unwrap_or_else(rayon::current_num_threads)
+                .max(1),
+            idle: Vec::new(),
+            metrics: MetricsRecorder::default(),
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Process `images` like [`process_with_pages`], with the instances of the session.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if a Tesseract instance can't be initialized.
+    #[profiling::function]
+    pub fn process<Img>(&mut self, images: Img) -> Result<Vec<Result<PagedRecognition>>>
+    where
+        Img: IntoParallelIterator<Item = GrayImage>,
+    {
+        set_thread_limit_env(self.opt);
+        let start = Instant::now();
+        let pool = TesseractPool::new(self.opt, self.max, &self.metrics, mem::take(&mut self.idle));
+        let subs = recognize_in_pool(images, &pool);
+        self.idle = pool.into_idle();
+        self.elapsed += start.elapsed();
+        subs
+    }
+
+    /// Get the metrics of the `OCR` of the session so far.
+    #[must_use]
+    pub fn metrics(&self) -> OcrMetrics {
+        self.metrics.metrics(self.elapsed)
+    }
+}
+
+/// Metrics of the `OCR` of images, to compare the time spent in the initialization
+/// of the Tesseract instances to the whole `OCR`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OcrMetrics {
+    /// Number of Tesseract instances initialized.
+    pub initializations: usize,
+    /// Cumulated time of the initializations of the instances.
+    pub init_time: Duration,
+    /// Time of the whole `OCR`.
+    pub elapsed: Duration,
+    /// Number of images recognized only once inverted.
+    pub invert_rescues: usize,
+}
+
+impl OcrMetrics {
+    /// Log the metrics.
+    pub fn log(&self) {
+        info!(
+            "{} Tesseract instance(s) initialized in {:.2}s of cumulated time, OCR took {:.2}s",
+            self.initializations,
+            self.init_time.as_secs_f64(),
+            self.elapsed.as_secs_f64()
+        );
+        if self.invert_rescues > 0 {
+            info!(
+                "{} subtitle image(s) recognized only once inverted",
+                self.invert_rescues
+            );
+        }
+    }
+}
+
+impl AddAssign for OcrMetrics {
+    fn add_assign(&mut self, other: Self) {
+        self.initializations += other.initializations;
+        self.init_time += other.init_time;
+        self.elapsed += other.elapsed;
+        self.invert_rescues += other.invert_rescues;
+    }
+}
+
+/// Metrics of a run, recorded from the threads of the `OCR`.
+#[derive(Default)]
+struct MetricsRecorder {
+    initializations: AtomicUsize,
+    init_nanos: AtomicU64,
+    invert_rescues: AtomicUsize,
+}
+
+impl MetricsRecorder {
+    fn record_init(&self, duration: Duration) {
+        self.initializations.fetch_add(1, Ordering::Relaxed);
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.init_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    fn record_invert_rescue(&self) {
+        self.invert_rescues.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get the recorded metrics, with the `elapsed` time of the whole `OCR`.
+    fn metrics(&self, elapsed: Duration) -> OcrMetrics {
+        OcrMetrics {
+            initializations: self.initializations.load(Ordering::Relaxed),
+            init_time: Duration::from_nanos(self.init_nanos.load(Ordering::Relaxed)),
+            elapsed,
+            invert_rescues: self.invert_rescues.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Create a Tesseract instance configured from `opt`, recording its initialization time in `metrics`.
+fn new_tesseract(opt: &OcrOpt, metrics: &MetricsRecorder) -> Result<TesseractWrapper> {
+    let start = Instant::now();
+    let tesseract = init_tesseract(opt).map(|mut tesseract| {
+        tesseract.scrub_control = opt.scrub_control;
+        tesseract
+    });
+    metrics.record_init(start.elapsed());
+    tesseract
+}
+
+/// Process images with one Tesseract instance per rayon thread.
+fn process_per_thread<Img>(
+    images: Img,
+    opt: &OcrOpt,
+    metrics: &MetricsRecorder,
+) -> Result<Vec<Result<PagedRecognition>>>
+where
+    Img: IntoParallelIterator<Item = GrayImage>,
+{
+    // Init tesseract
+    let init = broadcast(|ctx| -> Result<()> {
+        profiling::scope!("Tesseract Init Wrapper");
+        trace!(
+            "Init tesseract with lang `{}` on thread {}",
+            opt.lang,
+            ctx.index()
+        );
+        let tesseract = new_tesseract(opt, metrics)?;
+        let old = TESSERACT.replace(Some(tesseract));
+        assert!(old.is_none());
+        Ok(())
+    })
+    .into_iter()
+    .collect::<Result<()>>();
+
+    // Process images
+    let subs = init.map(|()| {
+        images
+            .into_par_iter()
+            .map(|image| {
+                isolate_panic(
+                    opt,
+                    || {
+                        let tesseract = TESSERACT.take().ok_or(Error::NotInitialized)?;
+                        let (tesseract, text) =
+                            recognize_with_timeout(tesseract, image, opt, metrics);
+                        match tesseract {
+                            Some(tesseract) => TESSERACT.set(Some(tesseract)),
+                            None => reset_tesseract(opt, metrics),
+                        }
+                        text
+                    },
+                    || reset_tesseract(opt, metrics),
+                )
+            })
+            .collect::<Vec<Result<PagedRecognition>>>()
+    });
+
+    // Clean tesseract from Thread local vars
+    broadcast(|ctx| {
+        profiling::scope!("Tesseract Drop Wrapper");
+        trace!("Drop TesseractWrapper local var on thread {}", ctx.index());
+        if let Some(tesseract) = TESSERACT.take() {
+            drop(tesseract);
+        }
+    });
+
+    subs
+}
+
+/// Process images with a pool of at most `max` Tesseract instances, shared by the rayon threads.
+fn process_with_pool<Img>(
+    images: Img,
+    opt: &OcrOpt,
+    max: usize,
+    metrics: &MetricsRecorder,
+) -> Result<Vec<Result<PagedRecognition>>>
+where
+    Img: IntoParallelIterator<Item = GrayImage>,
+{
+    trace!("Use a pool of at most {max} Tesseract instances");
+    let pool = TesseractPool::new(opt, max.max(1), metrics, Vec::new());
+    recognize_in_pool(images, &pool)
+}
+
+/// Process images with the instances of `pool`, shared by the rayon threads.
+fn recognize_in_pool<Img>(
+    images: Img,
+    pool: &TesseractPool,
+) -> Result<Vec<Result<PagedRecognition>>>
+where
+    Img: IntoParallelIterator<Item = GrayImage>,
+{
+    let (opt, metrics) = (pool.opt, pool.metrics);
+    // Create the first instance now, to report an initialization error only once.
+    drop(pool.acquire()?);
+
+    let subs = images
+        .into_par_iter()
+        .map(|image| {
+            isolate_panic(
+                opt,
+                || {
+                    let mut pooled = pool.acquire()?;
+                    let (tesseract, text) =
+                        recognize_with_timeout(pooled.take(), image, opt, metrics);
+                    if let Some(tesseract) = tesseract {
+                        pooled.put(tesseract);
+                    }
+                    text
+                },
+                || {},
+            )
+        })
+        .collect::<Vec<Result<PagedRecognition>>>();
+    Ok(subs)
+}
+
+/// `OCR` of images one at a time with a single Tesseract instance, for streaming uses.
+///
+/// Unlike [`process`], the images don't need to be all decoded before the `OCR`,
+/// at the cost of the parallelism.
+pub struct Recognizer<'a> {
+    opt: &'a OcrOpt<'a>,
+    tesseract: Option<TesseractWrapper>,
+    metrics: MetricsRecorder,
+}
+
+impl<'a> Recognizer<'a> {
+    /// Create a recognizer configured from `opt`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the initialization or the configuration of Tesseract failed.
+    pub fn new(opt: &'a OcrOpt<'a>) -> Result<Self> {
+        let metrics = MetricsRecorder::default();
+        let tesseract = new_tesseract(opt, &metrics)?;
+        Ok(Self {
+            opt,
+            tesseract: Some(tesseract),
+            metrics,
+        })
+    }
+
+    /// Recognize the text of `image`.
+    ///
+    /// The Tesseract instance is created again after a panic or a timeout.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the `OCR` of the image failed, panicked or timed out.
+    pub fn recognize(&mut self, image: GrayImage) -> Result<String> {
+        self.recognize_with_confidence(image).map(|(text, _)| text)
+    }
+
+    /// Recognize the text of `image`, with the mean confidence of its words.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the `OCR` of the image failed, panicked or timed out.
+    pub fn recognize_with_confidence(&mut self, image: GrayImage) -> Result<Recognition> {
+        let opt = self.opt;
+        let slot = &mut self.tesseract;
+        let metrics = &self.metrics;
+        isolate_panic(
+            opt,
+            || {
+                let tesseract = match slot.take() {
+                    Some(tesseract) => tesseract,
+                    None => new_tesseract(opt, metrics)?,
+                };
+                let (tesseract, text) = recognize_with_timeout(tesseract, image, opt, metrics);
+                *slot = tesseract;
+                text.map(|(recognition, _)| recognition)
+            },
+            || {},
+        )
+    }
+}
+
+/// Run the `OCR` of one image, catching a panic if enabled in `opt`.
+///
+/// `on_panic` is called after a panic has been caught.
+fn isolate_panic<T>(
+    opt: &OcrOpt,
+    ocr: impl FnOnce() -> Result<T>,
+    on_panic: impl FnOnce(),
+) -> Result<T> {
+    if !opt.isolate_panics {
+        return ocr();
+    }
+    panic::catch_unwind(AssertUnwindSafe(ocr)).unwrap_or_else(|payload| {
+        on_panic();
+        Err(Error::Panic {
+            message: panic_message(payload.as_ref()),
+        })
+    })
+}
+
+/// Run the `OCR` of `image` with `tesseract`, giving up after the timeout of `opt` if any.
+///
+/// Tesseract can't be interrupted, so with a timeout the `OCR` runs on a [`TimedThread`],
+/// which is detached if it takes too long. The instance is only given back if the `OCR` ended,
+/// or if it wasn't started because too many threads are already detached.
+fn recognize_with_timeout(
+    tesseract: TesseractWrapper,
+    image: GrayImage,
+    opt: &OcrOpt,
+    metrics: &MetricsRecorder,
+) -> (Option<TesseractWrapper>, Result<PagedRecognition>) {
+    let (tesseract, text) = recognize_in_time(tesseract, image, opt);
+    let text = text.map(|(recognition, inverted)| {
+        if inverted {
+            metrics.record_invert_rescue();
+        }
+        recognition
+    });
+    (tesseract, text)
+}
+
+/// Run the `OCR` of `image` like [`recognize_with_timeout`], telling if the text was
+/// recognized only once inverted.
+fn recognize_in_time(
+    mut tesseract: TesseractWrapper,
+    image: GrayImage,
+    opt: &OcrOpt,
+) -> (Option<TesseractWrapper>, Result<(PagedRecognition, bool)>) {
+    let recognize_opt = RecognizeOpt::from(opt);
+    let Some(timeout) = opt.timeout else {
+        let text = recognize(&mut tesseract, image, &recognize_opt);
+        return (Some(tesseract), text);
+    };
+
+    if let Some(count) = watchdog::detached_limit_reached() {
+        return (Some(tesseract), Err(Error::TooManyDetached { count }));
+    }
+    let mut thread = TimedThread::spawn(move || {
+        let text = recognize(&mut tesseract, image, &recognize_opt);
+        (tesseract, text)
+    });
+    if let Some((tesseract, text)) = thread.wait(timeout) {
+        (Some(tesseract), text)
+    } else {
+        thread.detach();
+        (None, Err(Error::Timeout { timeout }))
+    }
+}
+
+/// Options of the `OCR` of an image, owned to be sent to the thread of a timeout.
+struct RecognizeOpt {
+    dpi: i32,
+    line_mode: bool,
+    single_line_psm: bool,
+    invert_retry: bool,
+    column_separator: Option<String>,
+    hocr: bool,
+    tsv: bool,
+}
+
+impl From<&OcrOpt<'_>> for RecognizeOpt {
+    fn from(opt: &OcrOpt) -> Self {
+        Self {
+            dpi: opt.dpi,
+            line_mode: opt.line_mode,
+            single_line_psm: opt.single_line_psm,
+            invert_retry: opt.invert_retry,
+            column_separator: opt.column_separator.map(str::to_owned),
+            hocr: opt.hocr,
+            tsv: opt.tsv,
+        }
+    }
+}
+
+/// Minimum number of text pixels of an image to retry its `OCR` inverted, if nothing was recognized.
+const INVERT_RETRY_MIN_INK: usize = 200;
+
+/// Run the `OCR` of `image` with `tesseract`, telling if the text was recognized only once inverted.
+///
+/// With `invert_retry`, an image with many text pixels but no recognized text is recognized
+/// again inverted. With `hocr` or `tsv`, the `hOCR` page or the `TSV` table of the image
+/// giving the text is got too.
+fn recognize(
+    tesseract: &mut TesseractWrapper,
+    mut image: GrayImage,
+    opt: &RecognizeOpt,
+) -> Result<(PagedRecognition, bool)> {
+    profiling::scope!("tesseract_ocr");
+    tesseract.confidences.clear();
+    let text = recognize_text(tesseract, &image, opt)?;
+    let mut recognition = (text, tesseract.take_confidence());
+    let mut inverted_rescue = false;
+    if opt.invert_retry
+        && recognition.0.trim().is_empty()
+        && filter::ink_pixels(&image) >= INVERT_RETRY_MIN_INK
+    {
+        trace!("Nothing recognized in an image with text pixels, retry inverted");
+        imageops::invert(&mut image);
+        let inverted = recognize_text(tesseract, &image, opt)?;
+        let inverted_confidence = tesseract.take_confidence();
+        if inverted.trim().is_empty() {
+            imageops::invert(&mut image);
+        } else {
+            inverted_rescue = true;
+            recognition = (inverted, inverted_confidence);
+        }
+    }
+    let page = if opt.hocr || opt.tsv {
+        // The lines may have been recognized separately, the page is of the whole image.
+        tesseract.set_image(&image, opt.dpi)?;
+        Some(if opt.hocr {
+            tesseract.get_hocr()?
+        } else {
+            tesseract.get_tsv()?
+        })
+    } else {
+        None
+    };
+    Ok(((recognition, page), inverted_rescue))
+}
+
+/// Run the `OCR` of the text of `image` with `tesseract`.
+///
+/// With a `column_separator`, the images with columns are recognized line by line.
+/// With `single_line_psm`, the images with a single line are recognized with the page
+/// segmentation mode for a single line, if the configuration keeps the default one.
+fn recognize_text(
+    tesseract: &mut TesseractWrapper,
+    image: &GrayImage,
+    opt: &RecognizeOpt,
+) -> Result<String> {
+    let column_separator = opt.column_separator.as_deref();
+    let single_line_psm = opt.single_line_psm && tesseract.base_psm == PSM_SINGLE_BLOCK;
+    if opt.line_mode || single_line_psm || column_separator.is_some() {
+        let lines = split::find_lines(image);
+        let has_columns = column_separator.is_some()
+            && lines
+                .iter()
+                .any(|line| split::find_columns(image, *line).len() > 1);
+        if (opt.line_mode && lines.len() > 1) || has_columns {
+            return recognize_lines(tesseract, image, &lines, opt.dpi, column_separator);
+        }
+        if single_line_psm && lines.len() == 1 {
+            tesseract.set_page_seg_mode(PSM_SINGLE_LINE)?;
+            let text = tesseract
+                .set_image(image, opt.dpi)
+                .and_then(|()| tesseract.get_text());
+            tesseract.reset_page_seg_mode()?;
+            return text;
+        }
+    }
+    tesseract.set_image(image, opt.dpi)?;
+    tesseract.get_text()
+}
+
+/// Run the `OCR` of each line of `image` separately, and join the results.
+///
+/// With a `column_separator`, each column of a line is also recognized separately.
+fn recognize_lines(
+    tesseract: &mut TesseractWrapper,
+    image: &GrayImage,
+    lines: &[split::Line],
+    dpi: i32,
+    column_separator: Option<&str>,
+) -> Result<String> {
+    profiling::scope!("tesseract_ocr_lines");
+    tesseract.set_page_seg_mode(PSM_SINGLE_LINE)?;
+    let recognize_line = |line: &split::Line| {
+        let Some(separator) = column_separator else {
+            tesseract.set_image(&split::crop_line(image, *line), dpi)?;
+            return tesseract.get_text();
+        };
+        let columns = split::find_columns(image, *line)
+            .iter()
+            .map(|column| {
+                tesseract.set_image(&split::crop_column(image, *line, column), dpi)?;
+                Ok(tesseract.get_text()?.trim().to_owned())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(columns.join(separator))
+    };
+    let texts = lines.iter().map(recognize_line).collect::<Result<Vec<_>>>();
+    tesseract.reset_page_seg_mode()?;
+
+    let mut text = texts?
+        .iter()
+        .map(|text| text.trim_end())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    text.push('\n');
+    Ok(text)
+}
+
+/// Replace the Tesseract instance of the current thread, which may be in a bad state after a panic.
+fn reset_tesseract(opt: &OcrOpt, metrics: &MetricsRecorder) {
+    let tesseract = new_tesseract(opt, metrics).ok();
+    TESSERACT.replace(tesseract);
+}
+
+/// Extract the message of a panic payload.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| (*message).to_owned())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic payload".to_owned())
+}
+
+/// Bounded pool of Tesseract instances, created on demand.
+struct TesseractPool<'a> {
+    opt: &'a OcrOpt<'a>,
+    max: usize,
+    metrics: &'a MetricsRecorder,
+    state: Mutex<PoolState>,
+    released: Condvar,
+}
+
+struct PoolState {
+    idle: Vec<TesseractWrapper>,
+    count: usize,
+}
+
+impl<'a> TesseractPool<'a> {
+    /// Create a pool of at most `max` instances, starting with the `idle` ones.
+    fn new(
+        opt: &'a OcrOpt<'a>,
+        max: usize,
+        metrics: &'a MetricsRecorder,
+        idle: Vec<TesseractWrapper>,
+    ) -> Self {
+        Self {
+            opt,
+            max,
+            metrics,
+            state: Mutex::new(PoolState {
+                count: idle.len(),
+                idle,
+            }),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Get the idle instances, to create another pool with them.
+    fn into_idle(self) -> Vec<TesseractWrapper> {
+        self.state
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+            .idle
+    }
+
+    /// Borrow an instance from the pool, waiting for one if the maximum number of instances is reached.
+    fn acquire(&self) -> Result<PooledTesseract<'_, 'a>> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            if let Some(tesseract) = state.idle.pop() {
+                return Ok(PooledTesseract {
+                    pool: self,
+                    tesseract: Some(tesseract),
+                });
+            }
+            if state.count < self.max {
+                state.count += 1;
+                drop(state);
+                profiling::scope!("Tesseract Init Pool Instance");
+                trace!(
+                    "Init tesseract instance for the pool with lang `{}`",
+                    self.opt.lang
+                );
+                return match new_tesseract(self.opt, self.metrics) {
+                    Ok(tesseract) => Ok(PooledTesseract {
+                        pool: self,
+                        tesseract: Some(tesseract),
+                    }),
+                    Err(err) => {
+                        self.discard();
+                        Err(err)
+                    }
+                };
+            }
+            state = self
+                .released
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Forget an instance which is not returned to the pool, to allow creating a new one.
+    fn discard(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.count -= 1;
+        self.released.notify_one();
+    }
+
+    /// Give back an instance to the pool.
+    fn release(&self, tesseract: TesseractWrapper) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.idle.push(tesseract);
+        self.released.notify_one();
+    }
+}
+
+/// Tesseract instance borrowed from a [`TesseractPool`], given back on drop.
+struct PooledTesseract<'p, 'a> {
+    pool: &'p TesseractPool<'a>,
+    tesseract: Option<TesseractWrapper>,
+}
+
+impl PooledTesseract<'_, '_> {
+    /// Take the instance, to use it on another thread.
+    fn take(&mut self) -> TesseractWrapper {
+        self.tesseract
+            .take()
+            .expect("the instance is only taken once")
+    }
+
+    /// Put back a taken instance, to give it back to the pool.
+    fn put(&mut self, tesseract: TesseractWrapper) {
+        self.tesseract = Some(tesseract);
+    }
+}
+
+impl Drop for PooledTesseract<'_, '_> {
+    fn drop(&mut self) {
+        match self.tesseract.take() {
+            Some(tesseract) if !thread::panicking() => self.pool.release(tesseract),
+            // The instance may be in a bad state after a panic, or was abandoned: don't reuse it.
+            tesseract => {
+                drop(tesseract);
+                self.pool.discard();
+            }
+        }
+    }
+}
+
+/// Tesseract page segmentation mode for a single block of text, used by default.
+const PSM_SINGLE_BLOCK: &str = "6";
+
+/// Tesseract page segmentation mode for a single text line.
+const PSM_SINGLE_LINE: &str = "7";
+
+struct TesseractWrapper {
+    leptess: LepTess,
+    /// Page segmentation mode from the configuration.
+    base_psm: String,
+    /// Page segmentation mode currently set.
+    psm: String,
+    /// `PNM` encoding of the current image, reused from one image to the next.
+    pnm: Vec<u8>,
+    /// Remove the control characters of the text, other than the line feeds and tabs.
+    scrub_control: bool,
+    /// Mean confidence of the words of each text got since the last [`Self::take_confidence`].
+    confidences: Vec<i32>,
+}
+
+impl TesseractWrapper {
+    fn new(
+        datapath: Option<&str>,
+        language: impl AsRef<str>,
+        config: &[TesseractVariable],
+    ) -> Result<Self> {
+        profiling::scope!("TesseractWrapper new");
+
+        let mut leptess = LepTess::new(datapath, language.as_ref())?;
+        // Disable learning by default, though a user could re-enable this
+        // option with `-c`. We turn this off since we are are multithreading,
+        // so this option would result in non-deterministic output.
+        leptess.set_variable(leptess::Variable::ClassifyEnableLearning, "0")?;
+        // 6 is PSM_SINGLE_BLOCK. We have preprocessed the input into individual
+        // lines, and telling Tesseract this fact greatly improves accuracy.
+        leptess.set_variable(leptess::Variable::TesseditPagesegMode, PSM_SINGLE_BLOCK)?;
+        // Avoid interpreting the characters I, l as |
+        leptess.set_variable(leptess::Variable::TesseditCharBlacklist, "|[]")?;
+        // Avoid than tesseract tried to invert the image
+        leptess.set_variable(leptess::Variable::TesseditDoInvert, "0")?;
+        // Add user options.
+        for TesseractVariable { variable, value } in config {
+            leptess.set_variable(*variable, value)?;
+        }
+        let base_psm = config
+            .iter()
+            .rev()
+            .find(|config| config.variable == Variable::TesseditPagesegMode)
+            .map_or(PSM_SINGLE_BLOCK, |config| config.value.as_str())
+            .to_owned();
+        Ok(Self {
+            leptess,
+            psm: base_psm.clone(),
+            base_psm,
+            pnm: Vec::new(),
+            scrub_control: true,
+            confidences: Vec::new(),
+        })
+    }
+
+    /// Change the page segmentation mode, if different from the current one.
+    fn set_page_seg_mode(&mut self, psm: &str) -> Result<()> {
+        if self.psm != psm {
+            self.leptess
+                .set_variable(Variable::TesseditPagesegMode, psm)?;
+            psm.clone_into(&mut self.psm);
+        }
+        Ok(())
+    }
+
+    /// Restore the page segmentation mode from the configuration.
+    fn reset_page_seg_mode(&mut self) -> Result<()> {
+        let base_psm = self.base_psm.clone();
+        self.set_page_seg_mode(&base_psm)
+    }
+
+    /// Set the tesseract image to the given image's contents.
+    ///
+    /// The image is given to Leptonica as a binary `PNM` file, written in a buffer
+    /// kept by the wrapper to avoid an allocation and an encoding per image.
+    #[profiling::function]
+    fn set_image(&mut self, image: &GrayImage, dpi: i32) -> Result<()> {
+        {
+            profiling::scope!("TesseractWrapper Pnm create");
+            let (width, height) = image.dimensions();
+            self.pnm.clear();
+            self.pnm
+                .extend_from_slice(format!("P5\n{width} {height}\n255\n").as_bytes());
+            self.pnm.extend_from_slice(image.as_raw());
+        }
+        self.leptess.set_image_from_mem(&self.pnm)?;
+        self.leptess.set_source_resolution(dpi);
+        Ok(())
+    }
+
+    /// Get text.
+    ///
+    /// The control characters, like the form feed ending the page, are removed
+    /// if enabled, to not end up in the subtitles.
+    /// The mean confidence of the words is recorded if some text was recognized.
+    #[profiling::function]
+    fn get_text(&mut self) -> Result<String> {
+        let text = self.leptess.get_utf8_text()?;
+        if !text.trim().is_empty() {
+            self.confidences.push(self.leptess.mean_text_conf());
+        }
+        if !self.scrub_control {
+            return Ok(text);
+        }
+        Ok(text
+            .chars()
+            .filter(|c| !c.is_control() || matches!(c, '\n' | '\t'))
+            .collect())
+    }
+
+    /// Get the mean of the confidences recorded by [`Self::get_text`], e.g. for each line
+    /// of an image recognized line by line, and forget them.
+    fn take_confidence(&mut self) -> Option<u32> {
+        let count = i64::try_from(self.confidences.len())
+            .ok()
+            .filter(|count| *count > 0)?;
+        let sum = self.confidences.drain(..).map(i64::from).sum::<i64>();
+        u32::try_from(sum / count).ok()
+    }
+
+    /// Get the `hOCR` page of the text, with the boxes of the lines and words.
+    #[profiling::function]
+    fn get_hocr(&mut self) -> Result<String> {
+        Ok(self.leptess.get_hocr_text(0)?)
+    }
+
+    /// Get the `TSV` table of the text, with the boxes of the blocks, paragraphs, lines and words.
+    #[profiling::function]
+    fn get_tsv(&mut self) -> Result<String> {
+        Ok(self.leptess.get_tsv_text(0)?)
+    }
+}
@@ -0,0 +1,73 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use subtile::time::TimeSpan;
+
+use crate::timing::file_timestamp;
+
+/// Format of the `OCR` results written for each subtitle, with the boxes of the words.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OcrOutput {
+    /// `hOCR` page of Tesseract.
+    Hocr,
+    /// Tab-separated table of Tesseract, with a row for each block, paragraph, line and word.
+    Tsv,
+}
+
+impl OcrOutput {
+    /// Get the file extension of the format.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Hocr => "hocr",
+            Self::Tsv => "tsv",
+        }
+    }
+}
+
+/// Get the directory of the `OCR` results next to the first output file, e.g. `movie.ocr`
+/// for `movie.srt`, or `ocr` in the current directory if the subtitles are written on stdout.
+#[must_use]
+pub fn default_dir<'a>(outputs: impl IntoIterator<Item = Option<&'a Path>>) -> PathBuf {
+    outputs
+        .into_iter()
+        .flatten()
+        .next()
+        .map_or_else(|| PathBuf::from("ocr"), |path| path.with_extension("ocr"))
+}
+
+/// Write the `OCR` result `pages` of `format` of the subtitles at `times`, one file per subtitle
+/// in the directory `dir`, and return the number of written files.
+///
+/// The files are named from the number of the subtitle on 4 digits and its times,
+/// like the dumped images. The subtitles without result, e.g. on which the `OCR` failed, are skipped.
+///
+/// # Errors
+///
+/// Will return an error if the directory can't be created or a file can't be written.
+pub fn write_pages(
+    dir: &Path,
+    format: OcrOutput,
+    times: &[TimeSpan],
+    pages: &[Option<String>],
+) -> io::Result<usize> {
+    fs::create_dir_all(dir)?;
+    let mut count = 0;
+    for (idx, (time, page)) in times.iter().zip(pages).enumerate() {
+        let Some(page) = page else {
+            continue;
+        };
+        let name = format!(
+            "{:04}_{}_{}.{}",
+            idx + 1,
+            file_timestamp(time.start.msecs()),
+            file_timestamp(time.end.msecs()),
+            format.extension()
+        );
+        fs::write(dir.join(name), page)?;
+        count += 1;
+    }
+    Ok(count)
+}
@@ -0,0 +1,37 @@
+use std::path::Path;
+
+/// Format of the output subtitle files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// `SubRip` text.
+    #[default]
+    Srt,
+    /// Advanced `SubStation` Alpha.
+    Ass,
+    /// `hOCR` document of the Tesseract results, with the boxes of the lines and words.
+    Hocr,
+}
+
+impl OutputFormat {
+    /// Get the file extension of the format, also used for the `{format}` template placeholder.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Srt => "srt",
+            Self::Ass => "ass",
+            Self::Hocr => "hocr",
+        }
+    }
+
+    /// Guess the format of an output file from its extension, `SRT` by default.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("ass") || ext.eq_ignore_ascii_case("ssa") => {
+                Self::Ass
+            }
+            Some(ext) if ext.eq_ignore_ascii_case("hocr") => Self::Hocr,
+            _ => Self::Srt,
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+/// Error of output path template expansion.
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Unknown placeholder `{{{name}}}` in output template, expected one of: {{dir}}, {{stem}}, {{lang}}, {{track}}, {{format}}")]
+    UnknownPlaceholder { name: String },
+
+    #[error("Unclosed placeholder in output template `{template}`")]
+    UnclosedPlaceholder { template: String },
+
+    #[error("The `{{track}}` placeholder of the output template needs a Matroska input")]
+    NoTrack,
+}
+
+/// Values used to expand an output path template.
+pub struct TemplateVars<'a> {
+    /// Input file path, used for `{dir}` and `{stem}`.
+    pub input: &'a Path,
+    /// Tesseract language(s), used for `{lang}`.
+    pub lang: &'a str,
+    /// Id of the subtitle track of a Matroska input, used for `{track}`.
+    pub track: Option<usize>,
+    /// Output format extension, used for `{format}`.
+    pub format: &'a str,
+}
+
+/// Expand the placeholders of an output path `template`.
+///
+/// The `{dir}` and `{stem}` placeholders are expanded as is, even if the input path
+/// is not valid UTF-8.
+///
+/// # Errors
+///
+/// Will return [`Error::UnknownPlaceholder`] if a placeholder name is not managed.
+/// Will return [`Error::UnclosedPlaceholder`] if a `{` has no matching `}`.
+/// Will return [`Error::NoTrack`] if `{track}` is used without a track in the input.
+pub fn expand(template: &str, vars: &TemplateVars) -> Result<PathBuf, Error> {
+    let mut expanded = OsString::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        expanded.push(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| Error::UnclosedPlaceholder {
+                template: template.into(),
+            })?;
+        let name = &rest[start + 1..start + end];
+        match name {
+            "dir" => {
+                let dir = vars.input.parent().unwrap_or_else(|| Path::new(""));
+                let dir = if dir.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    dir
+                };
+                expanded.push(dir);
+            }
+            "stem" => {
+                let stem = vars.input.file_stem().unwrap_or_default();
+                expanded.push(stem);
+            }
+            "lang" => expanded.push(vars.lang),
+            "track" => expanded.push(vars.track.ok_or(Error::NoTrack)?.to_string()),
+            "format" => expanded.push(vars.format),
+            _ => {
+                return Err(Error::UnknownPlaceholder { name: name.into() });
+            }
+        }
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push(rest);
+    Ok(PathBuf::from(expanded))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{expand, Error, TemplateVars};
+
+    fn vars(input: &str, track: Option<usize>) -> TemplateVars<'_> {
+        TemplateVars {
+            input: Path::new(input),
+            lang: "eng+fra",
+            track,
+            format: "srt",
+        }
+    }
+
+    #[test]
+    fn placeholders() {
+        assert_eq!(
+            expand(
+                "{dir}/{stem}.{lang}.{track}.{format}",
+                &vars("/movies/film.mkv", Some(3))
+            )
+            .unwrap(),
+            PathBuf::from("/movies/film.eng+fra.3.srt")
+        );
+        // The directory of a file name without directory is the current one.
+        assert_eq!(
+            expand("{dir}/out/{stem}.{format}", &vars("film.sup", None)).unwrap(),
+            PathBuf::from("./out/film.srt")
+        );
+        assert_eq!(
+            expand("subtitles.srt", &vars("film.sup", None)).unwrap(),
+            PathBuf::from("subtitles.srt")
+        );
+    }
+
+    #[test]
+    fn invalid_templates() {
+        assert!(matches!(
+            expand("{stem}.{name}", &vars("film.sup", None)),
+            Err(Error::UnknownPlaceholder { name }) if name == "name"
+        ));
+        assert!(matches!(
+            expand("{stem.srt", &vars("film.sup", None)),
+            Err(Error::UnclosedPlaceholder { .. })
+        ));
+        assert!(matches!(
+            expand("{stem}.{track}.srt", &vars("film.sup", None)),
+            Err(Error::NoTrack)
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_input() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let input = Path::new(OsStr::from_bytes(b"/movies/fil\xFF.sup"));
+        let vars = TemplateVars {
+            input,
+            lang: "eng",
+            track: None,
+            format: "srt",
+        };
+        assert_eq!(
+            expand("{dir}/{stem}.{format}", &vars).unwrap(),
+            Path::new(OsStr::from_bytes(b"/movies/fil\xFF.srt"))
+        );
+    }
+}
@@ -0,0 +1,276 @@
+use std::{fmt::Write as _, fs, io, path::Path};
+
+use image::{Rgb, RgbImage};
+use log::{info, warn};
+use subtile::vobsub;
+
+use crate::{idx, spu, stdin::SpooledInput, Error};
+
+/// Number of tones of a subtitle: background, fill, outline and anti-aliasing.
+const TONES: usize = 4;
+
+/// Number of iterations of the color clustering.
+const CLUSTERING_ITERATIONS: usize = 10;
+
+/// Number of subtitles of the sub file sampled to infer a missing palette.
+const PALETTE_SAMPLE: usize = 50;
+
+/// Colors given to the palette entries for their role in the subtitles: background,
+/// fill in white, outline in black and anti-aliasing in gray, for a good contrast in `OCR`.
+const ROLE_COLORS: [[u8; 3]; TONES] = [
+    [0x00, 0x00, 0x00],
+    [0xff, 0xff, 0xff],
+    [0x00, 0x00, 0x00],
+    [0x80, 0x80, 0x80],
+];
+
+/// Estimate a palette from the frame screenshot at `path`, as an idx `palette:` line.
+///
+/// # Errors
+///
+/// Will return [`Error::PaletteFrame`] if the frame can't be read.
+pub fn palette_from_frame(path: &Path) -> Result<String, Error> {
+    let frame = image::open(path)
+        .map_err(|source| Error::PaletteFrame {
+            path: crate::absolute_path(path),
+            source,
+        })?
+        .into_rgb8();
+    Ok(format_palette(&estimate_palette(&frame)))
+}
+
+/// Estimate the palette of the subtitle shown in a video `frame`.
+///
+/// The colors of the bottom third of the frame, where subtitles are usually shown, are split
+/// in four clusters. The largest one is the background, the fill is the cluster with the lowest
+/// share of pixels next to the background, the outline the one most in contact with the fill,
+/// and the last one the anti-aliasing tone.
+///
+/// The palette entries used by a subtitle are unknown, so the four tones are repeated
+/// in the 16 entries, in this order: background, fill, outline and anti-aliasing.
+#[must_use]
+pub fn estimate_palette(frame: &RgbImage) -> vobsub::Palette {
+    let (width, height) = frame.dimensions();
+    let top = height - height / 3;
+    let colors = (top..height)
+        .flat_map(|y| (0..width).map(move |x| frame.get_pixel(x, y).0.map(f32::from)))
+        .collect::<Vec<_>>();
+    if colors.is_empty() {
+        return [Rgb([0, 0, 0]); 16];
+    }
+
+    // Four-means clustering, initialized with the quartiles of luminance.
+    let luma = |color: &[f32; 3]| 0.2126 * color[0] + 0.7152 * color[1] + 0.0722 * color[2];
+    let mut by_luma = colors.iter().collect::<Vec<_>>();
+    by_luma.sort_unstable_by(|a, b| luma(a).total_cmp(&luma(b)));
+    let mut centers: [[f32; 3]; TONES] =
+        std::array::from_fn(|idx| *by_luma[(2 * idx + 1) * by_luma.len() / (2 * TONES)]);
+    let mut clusters = vec![0_usize; colors.len()];
+    for _ in 0..CLUSTERING_ITERATIONS {
+        for (cluster, color) in clusters.iter_mut().zip(&colors) {
+            *cluster = (0..TONES)
+                .min_by(|a, b| {
+                    distance(color, &centers[*a]).total_cmp(&distance(color, &centers[*b]))
+                })
+                .unwrap_or(0);
+        }
+        let mut sums = [[0.0_f32; 3]; TONES];
+        let mut counts = [0_usize; TONES];
+        for (cluster, color) in clusters.iter().zip(&colors) {
+            counts[*cluster] += 1;
+            for (sum, channel) in sums[*cluster].iter_mut().zip(color) {
+                *sum += channel;
+            }
+        }
+        for ((center, sum), count) in centers.iter_mut().zip(sums).zip(counts) {
+            if count > 0 {
+                *center = sum.map(|sum| sum / count as f32);
+            }
+        }
+    }
+
+    // Count the pixels of each cluster, and the contacts between clusters.
+    let mut counts = [0_usize; TONES];
+    let mut contacts = [[0_usize; TONES]; TONES];
+    let row_len = width as usize;
+    for (idx, cluster) in clusters.iter().enumerate() {
+        counts[*cluster] += 1;
+        let right = (idx % row_len + 1 < row_len).then_some(idx + 1);
+        let below = Some(idx + row_len).filter(|below| *below < clusters.len());
+        for neighbor in [right, below].into_iter().flatten() {
+            let neighbor = clusters[neighbor];
+            if neighbor != *cluster {
+                contacts[*cluster][neighbor] += 1;
+                contacts[neighbor][*cluster] += 1;
+            }
+        }
+    }
+
+    // Give its role to each cluster.
+    let mut remaining = (0..TONES).collect::<Vec<_>>();
+    let mut take = |key: &dyn Fn(usize) -> f32| {
+        let (pos, _) = remaining
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| key(**a).total_cmp(&key(**b)))
+            .unwrap_or((0, &0));
+        remaining.remove(pos)
+    };
+    let background = take(&|idx| counts[idx] as f32);
+    let fill = take(&|idx| {
+        if counts[idx] == 0 {
+            f32::MIN
+        } else {
+            -(contacts[background][idx] as f32 / counts[idx] as f32)
+        }
+    });
+    let outline = take(&|idx| contacts[fill][idx] as f32);
+    let anti_aliasing = remaining[0];
+
+    let tones = [background, fill, outline, anti_aliasing].map(|cluster| {
+        Rgb(centers[cluster].map(|channel| channel.round().clamp(0.0, 255.0) as u8))
+    });
+    std::array::from_fn(|idx| tones[idx % TONES])
+}
+
+/// Colors of the default palette of `VSFilter`, for the idx files without a `palette:` line.
+const DEFAULT_PALETTE: [[u8; 3]; 16] = [
+    [0x00, 0x00, 0x00],
+    [0xf0, 0xf0, 0xf0],
+    [0xcc, 0xcc, 0xcc],
+    [0x99, 0x99, 0x99],
+    [0x33, 0x33, 0xfa],
+    [0x11, 0x11, 0xbb],
+    [0xfa, 0x33, 0x33],
+    [0xbb, 0x11, 0x11],
+    [0x33, 0xfa, 0x33],
+    [0x11, 0xbb, 0x11],
+    [0xfa, 0xfa, 0x33],
+    [0xbb, 0xbb, 0x11],
+    [0xfa, 0x33, 0xfa],
+    [0xbb, 0x11, 0xbb],
+    [0x33, 0xfa, 0xfa],
+    [0x11, 0xbb, 0xbb],
+];
+
+/// Copy the idx file `input` with a `palette:` line in a temporary file, if it has none.
+///
+/// The added palette is `palette` if present, else the one inferred from the colors of the
+/// subtitles by [`infer_palette`], and the default palette of `VSFilter` if none can be inferred.
+/// The sub file is linked next to the copy, or copied if it can't be linked.
+///
+/// # Errors
+///
+/// Will return an error if the files can't be read or written.
+pub fn add_missing_palette(
+    input: &Path,
+    palette: Option<&vobsub::Palette>,
+) -> io::Result<Option<SpooledInput>> {
+    let content = idx::read_content(input)?;
+    if content
+        .lines()
+        .any(|line| line.trim_start().starts_with("palette:"))
+    {
+        return Ok(None);
+    }
+    let inferred = if palette.is_none() {
+        infer_palette(input, &content).unwrap_or_else(|err| {
+            warn!(
+                "Unable to read the colors of the subtitles of {}: {err}",
+                input.display()
+            );
+            None
+        })
+    } else {
+        None
+    };
+    let line = if let Some(palette) = palette {
+        format_palette(palette)
+    } else if let Some(inferred) = inferred {
+        info!(
+            "{} has no palette, one is inferred from the colors of its subtitles: \
+             pass the palette of the disc with `--palette` if the OCR is poor",
+            input.display()
+        );
+        format_palette(&inferred)
+    } else {
+        warn!(
+            "{} has no palette, the default one is used: pass the palette of the disc \
+             with `--palette`, or estimate it with `--palette-from-frame`, if the OCR is poor",
+            input.display()
+        );
+        format_palette(&DEFAULT_PALETTE.map(Rgb))
+    };
+    let spooled = SpooledInput::temp("palette", "idx")?;
+    fs::write(
+        spooled.path(),
+        idx::normalize(&format!("{line}\n{content}")),
+    )?;
+    let (sub, spooled_sub) = (
+        input.with_extension("sub"),
+        spooled.path().with_extension("sub"),
+    );
+    fs::hard_link(&sub, &spooled_sub).or_else(|_| fs::copy(&sub, &spooled_sub).map(drop))?;
+    Ok(Some(spooled))
+}
+
+/// Infer a palette for `OCR` from the colors of a sample of the subtitles of the idx file
+/// `input`, of `content`.
+///
+/// The control sequences of the sampled subtitles of the sub file give the palette entries of
+/// their background, fill, outline and anti-aliasing. Each entry used by the subtitles gets the
+/// color of the role it has the most, the others keep their color of the default palette.
+///
+/// Return `None` if no sampled subtitle has colors.
+///
+/// # Errors
+///
+/// Will return an error if the sub file can't be read.
+pub fn infer_palette(input: &Path, content: &str) -> io::Result<Option<vobsub::Palette>> {
+    let entries = idx::parse_entries(content);
+    let step = (entries.len() / PALETTE_SAMPLE).max(1);
+    let positions = entries
+        .iter()
+        .step_by(step)
+        .take(PALETTE_SAMPLE)
+        .map(|entry| entry.filepos);
+    let samples = spu::sample_colors(&input.with_extension("sub"), positions)?;
+    if samples.is_empty() {
+        return Ok(None);
+    }
+    let mut votes = [[0_usize; TONES]; 16];
+    for colors in &samples {
+        for (role, entry) in colors.iter().enumerate() {
+            votes[usize::from(*entry)][role] += 1;
+        }
+    }
+    let palette = std::array::from_fn(|entry| {
+        let votes = votes[entry];
+        let role = (0..TONES)
+            .filter(|role| votes[*role] > 0)
+            .max_by_key(|role| votes[*role]);
+        Rgb(role.map_or(DEFAULT_PALETTE[entry], |role| ROLE_COLORS[role]))
+    });
+    info!(
+        "Palette inferred from the colors of {} subtitles: {}",
+        samples.len(),
+        format_palette(&palette)
+    );
+    Ok(Some(palette))
+}
+
+/// Format `palette` as the `palette:` line of an idx file.
+#[must_use]
+pub fn format_palette(palette: &vobsub::Palette) -> String {
+    let mut line = String::from("palette:");
+    for (idx, Rgb([r, g, b])) in palette.iter().enumerate() {
+        let separator = if idx == 0 { " " } else { ", " };
+        let _ = write!(line, "{separator}{r:02x}{g:02x}{b:02x}");
+    }
+    line
+}
+
+/// Squared distance between two colors.
+fn distance(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum()
+}
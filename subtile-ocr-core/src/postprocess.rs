@@ -0,0 +1,250 @@
+use std::{fs, io, path::Path, sync::LazyLock};
+
+use log::info;
+use regex::{Captures, Regex};
+
+use crate::cue::Cue;
+
+/// Dots of an ellipsis split by spaces or doubled, e.g. `. . .` or `.. .`.
+static BROKEN_ELLIPSIS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\.(?: ?\.){2,}|…\.+|\.+…").unwrap_or_else(|_| unreachable!()));
+
+/// Backticks and acute accents used as apostrophes, between letters.
+static BACKTICK_APOSTROPHE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\p{L})[`´](\p{L})").unwrap_or_else(|_| unreachable!()));
+
+/// Pipes recognized instead of an `I`, next to a letter of the same word, e.g. `|t's` or `W|LL`.
+static PIPE_IN_WORD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\p{L})?\|(\p{L})?").unwrap_or_else(|_| unreachable!()));
+
+/// Uppercase words with an `l` recognized instead of an `I`, e.g. `SlLENCE`.
+static UPPERCASE_WITH_L: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b\p{Lu}*l[\p{Lu}l]*\b").unwrap_or_else(|_| unreachable!()));
+
+/// English pronoun `I` recognized as an `l`, in a contraction (e.g. `l'm`) or alone between spaces.
+///
+/// An `l` followed by another apostrophe is kept, like the French elision in `l'amour`,
+/// even with spaces before the apostrophe, like in `l 'amour`.
+static ENGLISH_L_PRONOUN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)\bl('(?:m|ll|ve|d))\b|(^| )l( +|$)(['’])?").unwrap_or_else(|_| unreachable!())
+});
+
+/// Correction of the usual Tesseract mistakes, and of the user substitutions, on the `OCR` output.
+#[derive(Debug, Default)]
+pub struct PostProcessor {
+    /// Apply the built-in heuristics.
+    heuristics: bool,
+    /// The languages include English, for the `I` pronoun.
+    english: bool,
+    /// Keep the pipes, as they separate the columns of the text.
+    keep_pipes: bool,
+    /// User substitutions, applied in order after the heuristics.
+    replacements: Vec<(Regex, String)>,
+}
+
+impl PostProcessor {
+    /// Create a post-processor for the Tesseract languages `lang` (e.g. `eng+fra`),
+    /// with the built-in heuristics if `heuristics`, and the substitutions of the file at `path`, if any.
+    ///
+    /// With `column_separator`, the text has columns separated by a custom separator, and the
+    /// pipes are never replaced.
+    ///
+    /// The file has one substitution per line, a regular expression and its replacement
+    /// separated by a tab, where `$1` is the first group. Empty lines and lines starting
+    /// with `#` are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the file can't be read, or if a substitution is invalid.
+    pub fn new(
+        lang: &str,
+        heuristics: bool,
+        column_separator: bool,
+        path: Option<&Path>,
+    ) -> io::Result<Self> {
+        let content = path.map(fs::read_to_string).transpose()?;
+        let replacements = content
+            .iter()
+            .flat_map(|content| content.lines())
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let (pattern, replacement) = line.split_once('\t').ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("no tab between the pattern and the replacement in `{line}`"),
+                    )
+                })?;
+                let pattern = Regex::new(pattern)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                Ok((pattern, replacement.to_owned()))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Self {
+            heuristics,
+            english: lang.split('+').any(|lang| lang == "eng"),
+            keep_pipes: column_separator,
+            replacements,
+        })
+    }
+
+    /// Correct `text`.
+    #[must_use]
+    pub fn process(&self, text: &str) -> String {
+        let mut processed = text.to_owned();
+        if self.heuristics {
+            processed = BROKEN_ELLIPSIS.replace_all(&processed, "...").into_owned();
+            processed = BACKTICK_APOSTROPHE
+                .replace_all(&processed, "$1'$2")
+                .into_owned();
+            if !self.keep_pipes {
+                processed = PIPE_IN_WORD
+                    .replace_all(&processed, |pipe: &Captures| {
+                        if pipe.get(1).is_some() || pipe.get(2).is_some() {
+                            pipe[0].replace('|', "I")
+                        } else {
+                            pipe[0].to_owned()
+                        }
+                    })
+                    .into_owned();
+            }
+            processed = UPPERCASE_WITH_L
+                .replace_all(&processed, |word: &Captures| uppercase_l_to_i(&word[0]))
+                .into_owned();
+            if self.english {
+                processed = ENGLISH_L_PRONOUN
+                    .replace_all(&processed, |pronoun: &Captures| {
+                        match (pronoun.get(1), pronoun.get(4)) {
+                            (Some(contraction), _) => format!("I{}", contraction.as_str()),
+                            (None, Some(_)) => pronoun[0].to_owned(),
+                            (None, None) => format!("{}I{}", &pronoun[2], &pronoun[3]),
+                        }
+                    })
+                    .into_owned();
+            }
+        }
+        for (pattern, replacement) in &self.replacements {
+            processed = pattern
+                .replace_all(&processed, replacement.as_str())
+                .into_owned();
+        }
+        processed
+    }
+
+    /// Correct the text of `subtitles`.
+    #[must_use]
+    #[profiling::function]
+    pub fn handle(&self, subtitles: Vec<Cue>) -> Vec<Cue> {
+        let mut changed = 0;
+        let subtitles = subtitles
+            .into_iter()
+            .map(|mut cue| {
+                let processed = self.process(&cue.text);
+                if processed != cue.text {
+                    changed += 1;
+                }
+                cue.text = processed;
+                cue
+            })
+            .collect();
+        if changed > 0 {
+            info!("{changed} subtitle(s) corrected by the post-processing");
+        }
+        subtitles
+    }
+}
+
+/// Replace the `l` of the uppercase `word` recognized instead of an `I`.
+///
+/// Only the `l` followed by a capital are replaced: a final `l`, like in `ALl`, is as likely
+/// an `L` in the wrong case. Words with a single capital, like `Il` in French, aren't uppercase words.
+fn uppercase_l_to_i(word: &str) -> String {
+    if word.chars().filter(|c| c.is_uppercase()).count() < 2 {
+        return word.to_owned();
+    }
+    let chars = word.chars().collect::<Vec<_>>();
+    chars
+        .iter()
+        .enumerate()
+        .map(|(idx, c)| {
+            let before_capital = chars[idx..]
+                .iter()
+                .find(|c| **c != 'l')
+                .is_some_and(|c| c.is_uppercase());
+            if *c == 'l' && before_capital {
+                'I'
+            } else {
+                *c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::PostProcessor;
+
+    /// Path of the substitutions file `name` of a test, in a directory of this process.
+    fn substitutions_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("subtile-ocr-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    fn process(lang: &str, text: &str) -> String {
+        PostProcessor::new(lang, true, false, None)
+            .unwrap()
+            .process(text)
+    }
+
+    #[test]
+    fn punctuation() {
+        assert_eq!(process("fra", "Attends. . . non.. ."), "Attends... non...");
+        assert_eq!(process("eng", "don`t, isn´t"), "don't, isn't");
+    }
+
+    #[test]
+    fn pipes() {
+        assert_eq!(process("eng", "|t's W|LL | no"), "It's WILL | no");
+        let processor = PostProcessor::new("eng", true, true, None).unwrap();
+        assert_eq!(processor.process("|t's a|b"), "|t's a|b");
+    }
+
+    #[test]
+    fn uppercase_words() {
+        assert_eq!(process("eng", "SlLENCE! WlLL"), "SILENCE! WILL");
+        // A final `l` may be an `L`, and a single capital isn't an uppercase word.
+        assert_eq!(process("eng", "ALl of it"), "ALl of it");
+        assert_eq!(process("fra", "Il l'a dit"), "Il l'a dit");
+        assert_eq!(process("fra", "L'ÉTÉ DE l'AlGLE"), "L'ÉTÉ DE l'AIGLE");
+    }
+
+    #[test]
+    fn english_pronoun() {
+        assert_eq!(
+            process("eng", "l'm sure l'll go, l think\nl"),
+            "I'm sure I'll go, I think\nI"
+        );
+        // Only with English.
+        assert_eq!(process("fra", "l'm"), "l'm");
+    }
+
+    #[test]
+    fn french_elision() {
+        for text in ["l'amour", "de l 'amour", "de l ’homme", "l'mer"] {
+            assert_eq!(process("eng+fra", text), text);
+        }
+    }
+
+    #[test]
+    fn substitutions() {
+        let path = substitutions_path("substitutions.txt");
+        fs::write(&path, "# Comment\n\nrn\tm\n(\\d)O\t${1}0\n").unwrap();
+        let processor = PostProcessor::new("eng", false, false, Some(&path)).unwrap();
+        assert_eq!(processor.process("rnore 1O l'm"), "more 10 l'm");
+
+        fs::write(&path, "no tab\n").unwrap();
+        assert!(PostProcessor::new("eng", false, false, Some(&path)).is_err());
+    }
+}
@@ -0,0 +1,268 @@
+use image::{imageops, GrayAlphaImage, GrayImage, Luma, RgbaImage};
+use subtile::vobsub;
+
+use crate::split;
+
+/// Minimum alpha of a pixel to be part of the subtitle.
+const MIN_ALPHA: u8 = 128;
+
+/// Number of iterations of the color clustering.
+const CLUSTERING_ITERATIONS: usize = 10;
+
+/// Strategy used to convert `VobSub` images for `OCR`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConvertStrategy {
+    /// Binarize the luminance of the palette colors.
+    #[default]
+    PaletteLuma,
+    /// Cluster the colors of the RGBA render, using alpha as mask, and keep the text fill.
+    RgbaCluster,
+    /// Binarize the luminance weighted by alpha with the threshold adapted to each image (Otsu),
+    /// also for the `PGS` and `DVB` images.
+    Otsu,
+}
+
+/// Convert an sRGB palette to a luminance palette.
+#[must_use]
+pub fn rgb_palette_to_luminance(palette: &vobsub::Palette) -> [f32; 16] {
+    palette.map(|x| {
+        let r = srgb_to_linear(x[0]);
+        let g = srgb_to_linear(x[1]);
+        let b = srgb_to_linear(x[2]);
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    })
+}
+
+/// Convert an sRGB color space channel to linear.
+fn srgb_to_linear(channel: u8) -> f32 {
+    let value = f32::from(channel) / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert the RGBA render of a subtitle to an image for `OCR`, with a white `border`.
+///
+/// The opaque pixels are split in two color clusters, the text fill and its outline.
+/// The fill is the cluster the least in contact with the transparent background,
+/// it's written in black on white.
+#[must_use]
+pub fn rgba_cluster_to_luma(image: &RgbaImage, border: u32) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let is_opaque = |x: u32, y: u32| image.get_pixel(x, y).0[3] >= MIN_ALPHA;
+    let opaque = image
+        .enumerate_pixels()
+        .filter(|(x, y, _)| is_opaque(*x, *y))
+        .map(|(x, y, pixel)| (x, y, [pixel.0[0], pixel.0[1], pixel.0[2]].map(f32::from)))
+        .collect::<Vec<_>>();
+
+    // Two-means clustering, initialized with the darkest and the brightest colors.
+    let luma = |color: &[f32; 3]| 0.2126 * color[0] + 0.7152 * color[1] + 0.0722 * color[2];
+    let by_luma =
+        |a: &&(u32, u32, [f32; 3]), b: &&(u32, u32, [f32; 3])| luma(&a.2).total_cmp(&luma(&b.2));
+    let (Some(darkest), Some(brightest)) =
+        (opaque.iter().min_by(by_luma), opaque.iter().max_by(by_luma))
+    else {
+        return GrayImage::from_pixel(width + border * 2, height + border * 2, Luma([255]));
+    };
+    let mut centers = [darkest.2, brightest.2];
+    let mut clusters = vec![0_usize; opaque.len()];
+    for _ in 0..CLUSTERING_ITERATIONS {
+        for (cluster, (_, _, color)) in clusters.iter_mut().zip(&opaque) {
+            *cluster = usize::from(distance(color, &centers[1]) < distance(color, &centers[0]));
+        }
+        for (idx, center) in centers.iter_mut().enumerate() {
+            let members = clusters
+                .iter()
+                .zip(&opaque)
+                .filter(|(cluster, _)| **cluster == idx)
+                .map(|(_, (_, _, color))| color)
+                .collect::<Vec<_>>();
+            if !members.is_empty() {
+                *center = [0, 1, 2].map(|channel| {
+                    members.iter().map(|color| color[channel]).sum::<f32>() / members.len() as f32
+                });
+            }
+        }
+    }
+
+    // The fill is the cluster with the lowest share of pixels next to the background.
+    let mut touching = [0_usize; 2];
+    let mut counts = [0_usize; 2];
+    for (cluster, (x, y, _)) in clusters.iter().zip(&opaque) {
+        counts[*cluster] += 1;
+        let touches_background = [(0, -1), (0, 1), (-1, 0), (1, 0)].iter().any(|(dx, dy)| {
+            let (Some(nx), Some(ny)) = (x.checked_add_signed(*dx), y.checked_add_signed(*dy))
+            else {
+                return true;
+            };
+            nx >= width || ny >= height || !is_opaque(nx, ny)
+        });
+        touching[*cluster] += usize::from(touches_background);
+    }
+    let ratio = |idx: usize| touching[idx] as f32 / counts[idx].max(1) as f32;
+    let fill = if counts[1] > 0 && (counts[0] == 0 || ratio(1) < ratio(0)) {
+        1
+    } else {
+        0
+    };
+
+    let mut converted = GrayImage::from_pixel(width + border * 2, height + border * 2, Luma([255]));
+    for (cluster, (x, y, _)) in clusters.iter().zip(&opaque) {
+        if *cluster == fill {
+            converted.put_pixel(x + border, y + border, Luma([0]));
+        }
+    }
+    converted
+}
+
+/// Convert the RGBA render of a subtitle to an image for `OCR`, keeping the anti-aliased edges, with a white `border`.
+///
+/// The pixels with a luminance within `tolerance` of `threshold` are mapped to gray levels
+/// instead of being kept or dropped wholesale, weighted by their alpha. The gray image
+/// is then binarized with the threshold splitting best its text and background (Otsu).
+#[must_use]
+pub fn soft_palette_to_luma(
+    image: &RgbaImage,
+    threshold: f32,
+    tolerance: f32,
+    border: u32,
+) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let low = threshold - tolerance;
+    let range = (tolerance * 2.0).max(f32::EPSILON);
+    let gray = image
+        .pixels()
+        .map(|pixel| {
+            let [r, g, b, a] = pixel.0;
+            let luminance = 0.2126 * srgb_to_linear(r)
+                + 0.7152 * srgb_to_linear(g)
+                + 0.0722 * srgb_to_linear(b);
+            let ink = ((luminance - low) / range).clamp(0.0, 1.0) * f32::from(a) / 255.0;
+            255 - (ink * 255.0).round() as u8
+        })
+        .collect::<Vec<_>>();
+
+    binarize_otsu(&gray, width, height, border)
+}
+
+/// Convert the luminance and alpha render of a subtitle to an image for `OCR`, with a white `border`.
+///
+/// The luminance weighted by alpha is binarized with the threshold splitting best
+/// the text and background of the image (Otsu), instead of fixed thresholds.
+#[must_use]
+pub fn luma_alpha_to_luma_otsu(image: &GrayAlphaImage, border: u32) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let gray = image
+        .pixels()
+        .map(|pixel| {
+            let [luma, alpha] = pixel.0;
+            255 - (u16::from(luma) * u16::from(alpha) / 255) as u8
+        })
+        .collect::<Vec<_>>();
+    binarize_otsu(&gray, width, height, border)
+}
+
+/// Binarize the `gray` levels of a `width` x `height` image with their Otsu threshold,
+/// in black on white with a `border`.
+fn binarize_otsu(gray: &[u8], width: u32, height: u32, border: u32) -> GrayImage {
+    let otsu = otsu_threshold(gray);
+    let mut converted = GrayImage::from_pixel(width + border * 2, height + border * 2, Luma([255]));
+    for (idx, value) in gray.iter().enumerate() {
+        if *value <= otsu {
+            let (x, y) = (idx as u32 % width, idx as u32 / width);
+            converted.put_pixel(x + border, y + border, Luma([0]));
+        }
+    }
+    converted
+}
+
+/// Get the gray level splitting `values` in two classes of maximal between-class variance.
+fn otsu_threshold(values: &[u8]) -> u8 {
+    let mut histogram = [0_u64; 256];
+    for value in values {
+        histogram[usize::from(*value)] += 1;
+    }
+    let total = values.len() as f64;
+    let sum = (0..=255_u8)
+        .map(|level| f64::from(level) * histogram[usize::from(level)] as f64)
+        .sum::<f64>();
+
+    let (mut best, mut best_variance) = (0, 0.0);
+    let (mut count, mut partial_sum) = (0.0, 0.0);
+    for level in 0..=255_u8 {
+        count += histogram[usize::from(level)] as f64;
+        partial_sum += f64::from(level) * histogram[usize::from(level)] as f64;
+        if count == 0.0 || count == total {
+            continue;
+        }
+        let mean_low = partial_sum / count;
+        let mean_high = (sum - partial_sum) / (total - count);
+        let variance = count * (total - count) * (mean_low - mean_high).powi(2);
+        if variance > best_variance {
+            (best, best_variance) = (level, variance);
+        }
+    }
+    best
+}
+
+/// Downscale an image prepared for `OCR`, with a `border`, so its text lines are `target_line_height` pixels high.
+///
+/// The line height is the median height of the text lines. Images with smaller lines
+/// (like `VobSub` ones) are kept, as is every image with a `target_line_height` of 0.
+/// The border is kept at its size.
+#[must_use]
+pub fn downscale_to_line_height(
+    image: GrayImage,
+    border: u32,
+    target_line_height: u32,
+) -> GrayImage {
+    let mut heights = split::find_lines(&image)
+        .into_iter()
+        .map(split::Line::height)
+        .collect::<Vec<_>>();
+    heights.sort_unstable();
+    let Some(line_height) = heights.get(heights.len() / 2).copied() else {
+        return image;
+    };
+    let (width, height) = image.dimensions();
+    if target_line_height == 0
+        || line_height <= target_line_height
+        || width <= border * 2
+        || height <= border * 2
+    {
+        return image;
+    }
+
+    let scale = |size: u32| {
+        (u64::from(size - border * 2) * u64::from(target_line_height) / u64::from(line_height))
+            .max(1)
+    };
+    let (Ok(scaled_width), Ok(scaled_height)) =
+        (u32::try_from(scale(width)), u32::try_from(scale(height)))
+    else {
+        return image;
+    };
+    let text = imageops::crop_imm(
+        &image,
+        border,
+        border,
+        width - border * 2,
+        height - border * 2,
+    );
+    let scaled = imageops::resize(&*text, scaled_width, scaled_height, imageops::Lanczos3);
+    let mut converted = GrayImage::from_pixel(
+        scaled_width + border * 2,
+        scaled_height + border * 2,
+        Luma([255]),
+    );
+    imageops::replace(&mut converted, &scaled, border.into(), border.into());
+    converted
+}
+
+/// Squared distance between two colors.
+fn distance(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum()
+}
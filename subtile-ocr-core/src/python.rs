@@ -0,0 +1,171 @@
+//! Python bindings, built with the `python` feature as the `subtile_ocr` module.
+
+use std::path::{Path, PathBuf};
+
+use image::GrayImage;
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyBytes,
+};
+use subtile::time::TimeSpan;
+
+use crate::{ocr, Config, Error, ImageConfig, InputConfig, OcrConfig, OcrOpt};
+
+/// Subtitle returned to Python: start and end in seconds, text, and `OCR` confidence in percent.
+///
+/// The confidence of a subtitle merged from several images by `pipeline` is the lowest of them.
+type PySubtitle = (f64, f64, Option<String>, Option<f32>);
+
+/// Decoded image returned to Python: start and end in seconds, width, height and grayscale pixels.
+type PyImage<'py> = (f64, f64, u32, u32, Bound<'py, PyBytes>);
+
+/// Subtitles images `OCR`.
+#[pymodule]
+fn subtile_ocr(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(decode, module)?)?;
+    module.add_function(wrap_pyfunction!(ocr_images, module)?)?;
+    module.add_function(wrap_pyfunction!(pipeline, module)?)?;
+    Ok(())
+}
+
+/// Decode the subtitles of a `.sup` or `.idx` file as images prepared for `OCR`.
+#[pyfunction]
+#[pyo3(signature = (path, border = 10))]
+fn decode(py: Python<'_>, path: PathBuf, border: u32) -> PyResult<Vec<PyImage<'_>>> {
+    let opt = config(&path).with_image(ImageConfig::default().with_border(border));
+    let (times, images) = py
+        .allow_threads(|| crate::with_thread_pool(|| crate::decode(&path, &opt)))
+        .map_err(to_py_error)?;
+    Ok(times
+        .iter()
+        .zip(images)
+        .map(|(time, image)| {
+            let (start, end) = seconds(time);
+            let (width, height) = image.dimensions();
+            (
+                start,
+                end,
+                width,
+                height,
+                PyBytes::new(py, &image.into_raw()),
+            )
+        })
+        .collect())
+}
+
+/// Run the `OCR` of images returned by `decode`.
+///
+/// The text is `None` for the images on which the `OCR` failed, and the confidence
+/// for those without recognized word.
+#[pyfunction]
+#[pyo3(name = "ocr", signature = (images, lang, tessdata_dir = None, dpi = 150, thread_limit_env = false))]
+fn ocr_images(
+    py: Python<'_>,
+    images: Vec<(f64, f64, u32, u32, Vec<u8>)>,
+    lang: String,
+    tessdata_dir: Option<String>,
+    dpi: i32,
+    thread_limit_env: bool,
+) -> PyResult<Vec<PySubtitle>> {
+    let (times, images) = images
+        .into_iter()
+        .map(|(start, end, width, height, pixels)| {
+            let image = GrayImage::from_raw(width, height, pixels).ok_or_else(|| {
+                PyValueError::new_err(format!("the pixels don't match the size {width}x{height}"))
+            })?;
+            Ok(((start, end), image))
+        })
+        .collect::<PyResult<(Vec<_>, Vec<_>)>>()?;
+
+    let config = Vec::new();
+    let recognitions = py
+        .allow_threads(|| {
+            crate::with_thread_pool(|| {
+                let ocr_opt = OcrOpt::new(&tessdata_dir, &lang, &config, dpi)
+                    .with_tesseract_threads(ocr::auto_tesseract_threads(images.len()))
+                    .with_thread_limit_env(thread_limit_env);
+                ocr::process_with_confidence(images, &ocr_opt).map_err(Error::from)
+            })
+        })
+        .map_err(to_py_error)?;
+    Ok(times
+        .into_iter()
+        .zip(recognitions)
+        .map(|((start, end), recognition)| match recognition {
+            Ok((text, confidence)) => (start, end, Some(text), confidence.map(|c| c as f32)),
+            Err(_) => (start, end, None, None),
+        })
+        .collect())
+}
+
+/// Run the whole pipeline on a `.sup` or `.idx` file, with the default values of the cli
+/// for the other options.
+#[pyfunction]
+#[pyo3(signature = (path, lang, tessdata_dir = None, dpi = 150, border = 10, threshold = 0.6, thread_limit_env = false))]
+#[allow(clippy::too_many_arguments)]
+fn pipeline(
+    py: Python<'_>,
+    path: PathBuf,
+    lang: String,
+    tessdata_dir: Option<String>,
+    dpi: i32,
+    border: u32,
+    threshold: f32,
+    thread_limit_env: bool,
+) -> PyResult<Vec<PySubtitle>> {
+    let opt = config(&path)
+        .with_image(
+            ImageConfig::default()
+                .with_border(border)
+                .with_threshold(threshold),
+        )
+        .with_ocr(
+            OcrConfig::default()
+                .with_lang(lang)
+                .with_tessdata_dir(tessdata_dir)
+                .with_dpi(dpi)
+                .with_thread_limit_env(thread_limit_env),
+        );
+    let subtitles = py
+        .allow_threads(|| crate::run_to_subtitles_with_confidence(&opt))
+        .map_err(to_py_error)?;
+    Ok(subtitles
+        .into_iter()
+        .map(|(time, text, confidence)| {
+            let (start, end) = seconds(&time);
+            (start, end, Some(text), confidence.map(|c| c as f32))
+        })
+        .collect())
+}
+
+/// Get the configuration of the `input` path, with the default values of the cli.
+fn config(input: &Path) -> Config {
+    Config::default().with_input(InputConfig::default().with_path(input))
+}
+
+/// Get the start and end of `time` in seconds.
+fn seconds(time: &TimeSpan) -> (f64, f64) {
+    (
+        time.start.msecs() as f64 / 1000.0,
+        time.end.msecs() as f64 / 1000.0,
+    )
+}
+
+/// Convert `err` to a Python exception, with its causes.
+fn to_py_error(err: Error) -> PyErr {
+    PyRuntimeError::new_err(format!("{:#}", anyhow::Error::new(err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use subtile::time::{TimePoint, TimeSpan};
+
+    use super::seconds;
+
+    #[test]
+    fn times_in_seconds() {
+        let time = TimeSpan::new(TimePoint::from_msecs(1_500), TimePoint::from_msecs(62_250));
+        assert_eq!(seconds(&time), (1.5, 62.25));
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use log::{info, warn};
+use subtile::time::TimeSpan;
+
+use crate::{cue::Cue, dictionary::Dictionary, ocr, timing::format_timestamp};
+
+/// Minimum width in pixels of a character in the images sent to `OCR`.
+const MIN_CHAR_WIDTH: u32 = 5;
+
+/// Subtitle flagged during the process, for the user to check.
+#[derive(Debug, Clone)]
+pub struct QaIssue {
+    /// Time of the subtitle.
+    pub time: TimeSpan,
+    /// Description of the issue.
+    pub message: String,
+}
+
+/// Collect the flagged subtitles of a run, reported at the end.
+#[derive(Debug, Default)]
+pub struct QaReport {
+    issues: Vec<QaIssue>,
+}
+
+impl QaReport {
+    /// Flag the subtitle at `time` with `message`.
+    pub fn add(&mut self, time: TimeSpan, message: impl Into<String>) {
+        self.issues.push(QaIssue {
+            time,
+            message: message.into(),
+        });
+    }
+
+    /// Get the flagged subtitles, in the order they were flagged.
+    #[cfg(test)]
+    pub(crate) fn issues(&self) -> &[QaIssue] {
+        &self.issues
+    }
+
+    /// Log the flagged subtitles, sorted by time.
+    pub fn log(&self) {
+        if self.issues.is_empty() {
+            return;
+        }
+        let mut issues = self.issues.iter().collect::<Vec<_>>();
+        issues.sort_by_key(|issue| issue.time.start);
+        warn!("{} subtitle(s) to check:", issues.len());
+        for issue in issues {
+            warn!(
+                "  {} --> {}: {}",
+                format_timestamp(issue.time.start, ','),
+                format_timestamp(issue.time.end, ','),
+                issue.message
+            );
+        }
+    }
+
+    /// Write the flagged subtitles, sorted by time, in the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the file can't be created or written.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut issues = self.issues.iter().collect::<Vec<_>>();
+        issues.sort_by_key(|issue| issue.time.start);
+        crate::create_parent_dirs(path)?;
+        let mut out = BufWriter::new(File::create(path)?);
+        for issue in issues {
+            writeln!(
+                out,
+                "{} --> {}\t{}",
+                format_timestamp(issue.time.start, ','),
+                format_timestamp(issue.time.end, ','),
+                issue.message
+            )?;
+        }
+        out.flush()
+    }
+
+    /// Flag the suspicious `OCR` results, with the width of their image in `widths`.
+    ///
+    /// Results are suspicious if they are empty, contain replacement characters,
+    /// have more characters than the image can hold, or digits inside words (e.g. `w0rd`).
+    /// The `OCR` errors are flagged when the subtitles are checked.
+    pub fn check_texts(
+        &mut self,
+        times: &[TimeSpan],
+        widths: &[u32],
+        texts: &[Result<String, ocr::Error>],
+    ) {
+        for (idx, ((time, width), text)) in times.iter().zip(widths).zip(texts).enumerate() {
+            let number = idx + 1;
+            let Ok(text) = text else {
+                continue;
+            };
+            if text.trim().is_empty() {
+                self.add(*time, format!("subtitle {number}: empty OCR result"));
+                continue;
+            }
+            if text.contains(char::REPLACEMENT_CHARACTER) {
+                self.add(
+                    *time,
+                    format!(
+                        "subtitle {number}: replacement character in `{}`",
+                        text.trim()
+                    ),
+                );
+            }
+            let max_chars = (width / MIN_CHAR_WIDTH) as usize;
+            let longest = text.lines().map(|line| line.chars().count()).max();
+            if let Some(longest) = longest.filter(|longest| *longest > max_chars) {
+                self.add(
+                    *time,
+                    format!(
+                        "subtitle {number}: {longest} characters on a line, \
+                         too many for an image of {width} pixels"
+                    ),
+                );
+            }
+            if let Some(word) = text
+                .split(|c: char| !c.is_alphanumeric())
+                .find(|word| has_digit_in_word(word))
+            {
+                self.add(
+                    *time,
+                    format!("subtitle {number}: digit inside word `{word}`"),
+                );
+            }
+        }
+    }
+
+    /// Flag the `OCR` results `texts` with a mean word confidence below `min_confidence`,
+    /// and return the confidence of each flagged result, in the order of `texts`.
+    ///
+    /// Short results with only words of the `dictionary` aren't flagged, as Tesseract
+    /// gives a low confidence to the isolated words (e.g. `Yes.`, `No!`).
+    pub fn check_confidences(
+        &mut self,
+        times: &[TimeSpan],
+        texts: &[Result<String, ocr::Error>],
+        confidences: &[Option<u32>],
+        min_confidence: u32,
+        dictionary: &Dictionary,
+    ) -> Vec<Option<u32>> {
+        let mut known_count = 0;
+        let mut flagged = vec![None; times.len()];
+        for (idx, ((time, text), confidence)) in
+            times.iter().zip(texts).zip(confidences).enumerate()
+        {
+            let Ok(text) = text else {
+                continue;
+            };
+            let Some(confidence) = confidence.filter(|confidence| *confidence < min_confidence)
+            else {
+                continue;
+            };
+            if dictionary.knows_short_text(text) {
+                known_count += 1;
+                continue;
+            }
+            flagged[idx] = Some(confidence);
+            self.add(
+                *time,
+                format!(
+                    "subtitle {}: OCR confidence of {confidence}% in `{}`",
+                    idx + 1,
+                    text.trim().replace('\n', " ")
+                ),
+            );
+        }
+        if known_count > 0 {
+            info!("{known_count} short subtitle(s) with a low OCR confidence are dictionary words, not flagged");
+        }
+        flagged
+    }
+}
+
+/// What to do with the subtitles flagged by `--min-confidence`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LowConfidencePolicy {
+    /// Only flag the subtitles in the report of the subtitles to check.
+    #[default]
+    Flag,
+    /// Also start the text of the subtitles with a `[low OCR confidence: NN%]` marker.
+    Marker,
+    /// Fail, after the report of the subtitles to check.
+    Fail,
+}
+
+/// Start the text of the `subtitles` with a low `OCR` confidence with a marker.
+///
+/// The marker is in brackets, shown as is in all the formats, unlike the braces
+/// of an `ASS` override block.
+#[must_use]
+pub fn mark_low_confidence(subtitles: Vec<Cue>) -> Vec<Cue> {
+    subtitles
+        .into_iter()
+        .map(|cue| match cue.low_confidence {
+            Some(confidence) => Cue {
+                text: format!("[low OCR confidence: {confidence}%] {}", cue.text),
+                ..cue
+            },
+            None => cue,
+        })
+        .collect()
+}
+
+/// Get the `subtitles` with a low `OCR` confidence, to review them separately.
+#[must_use]
+pub fn low_confidence_subtitles(subtitles: &[Cue]) -> Vec<(TimeSpan, String)> {
+    subtitles
+        .iter()
+        .filter(|cue| cue.low_confidence.is_some())
+        .map(|cue| (cue.time, cue.text.clone()))
+        .collect()
+}
+
+/// Check if `word` has a digit between two letters, a typical `OCR` confusion (e.g. `w0rd`).
+fn has_digit_in_word(word: &str) -> bool {
+    word.chars().collect::<Vec<_>>().windows(3).any(|chars| {
+        chars[0].is_alphabetic() && chars[1].is_ascii_digit() && chars[2].is_alphabetic()
+    })
+}
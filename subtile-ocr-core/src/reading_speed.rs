@@ -0,0 +1,75 @@
+use log::info;
+use subtile::time::{TimePoint, TimeSpan};
+
+use crate::{cue::Cue, qa::QaReport};
+
+/// Shortest gap in milliseconds kept before the next cue when extending a cue,
+/// about two frames, for the players to show them as two cues.
+const MIN_GAP_MS: i64 = 84;
+
+/// Count the characters read in `text`: all but the line breaks.
+fn char_count(text: &str) -> usize {
+    text.trim()
+        .chars()
+        .filter(|c| !matches!(c, '\n' | '\r'))
+        .count()
+}
+
+/// Get the reading speed of `text` shown during `time`, in characters per second.
+fn reading_speed(time: &TimeSpan, text: &str) -> f64 {
+    let duration = (time.end.msecs() - time.start.msecs()).max(1);
+    char_count(text) as f64 * 1000.0 / duration as f64
+}
+
+/// Flag the `subtitles` read faster than `max_cps` characters per second, and log the
+/// statistics of the reading speed.
+///
+/// With `extend`, a fast subtitle is first extended until it's read at `max_cps`, without
+/// getting closer than two frames to the next subtitle. The subtitles are expected sorted.
+#[profiling::function]
+pub fn check_reading_speed(
+    mut subtitles: Vec<Cue>,
+    max_cps: u32,
+    extend: bool,
+    qa: &mut QaReport,
+) -> Vec<Cue> {
+    let max_cps = f64::from(max_cps);
+    let mut extended = 0;
+    let mut too_fast = 0;
+    let mut total_cps = 0.0;
+    for idx in 0..subtitles.len() {
+        let next_start = subtitles.get(idx + 1).map(|cue| cue.time.start.msecs());
+        let Cue { time, text, .. } = &mut subtitles[idx];
+        if extend && reading_speed(time, text) > max_cps {
+            // The needed duration is rounded up, to be read at most at `max_cps`.
+            let needed = (char_count(text) as f64 * 1000.0 / max_cps).ceil() as i64;
+            let end = next_start
+                .map_or(i64::MAX, |next| next - MIN_GAP_MS)
+                .min(time.start.msecs() + needed);
+            if end > time.end.msecs() {
+                *time = TimeSpan::new(time.start, TimePoint::from_msecs(end));
+                extended += 1;
+            }
+        }
+        let cps = reading_speed(time, text);
+        total_cps += cps;
+        if cps > max_cps {
+            too_fast += 1;
+            qa.add(
+                *time,
+                format!(
+                    "subtitle {}: read at {cps:.1} characters per second, above {max_cps}",
+                    idx + 1
+                ),
+            );
+        }
+    }
+    if !subtitles.is_empty() {
+        let mean = total_cps / subtitles.len() as f64;
+        info!(
+            "Mean reading speed of {mean:.1} characters per second, {too_fast} subtitle(s) above {max_cps}, \
+             {extended} extended"
+        );
+    }
+    subtitles
+}
@@ -0,0 +1,292 @@
+use std::{fs, io, iter, path::Path};
+
+use image::GrayImage;
+use log::{debug, info, warn};
+
+use subtile::{
+    image::ImageSize,
+    time::{TimePoint, TimeSpan},
+};
+
+use crate::{idx, timing::format_timestamp};
+
+/// Size of a `PGS` segment header.
+const PGS_HEADER_SIZE: usize = 13;
+
+/// Minimum relative difference between declared and decoded subtitles to warn about.
+const MAX_DIVERGENCE_RATIO: f32 = 0.05;
+
+/// Minimum absolute difference between declared and decoded subtitles to warn about.
+const MAX_DIVERGENCE_COUNT: usize = 2;
+
+/// Compare the number of `decoded_count` subtitles to the number declared by `input`,
+/// and warn if they diverge significantly.
+///
+/// For `VobSub` the declared number is the number of `timestamp` lines of the idx file,
+/// for `PGS` the number of compositions showing at least one object.
+/// The `DVB` subtitles don't declare their number, so they are not checked.
+#[profiling::function]
+pub fn check_decoded_count(input: &Path, decoded_count: usize) {
+    if is_dvb(input) {
+        return;
+    }
+    let declared_count = match declared_count(input) {
+        Ok(count) => count,
+        Err(err) => {
+            warn!(
+                "Unable to count the subtitles declared in '{}': {err}",
+                input.display()
+            );
+            return;
+        }
+    };
+    debug!("{decoded_count} subtitles decoded, {declared_count} declared by the input");
+
+    let difference = declared_count.abs_diff(decoded_count);
+    if difference > MAX_DIVERGENCE_COUNT
+        && difference as f32 > declared_count as f32 * MAX_DIVERGENCE_RATIO
+    {
+        let declared = if is_vobsub(input) {
+            "timestamp entries in the idx file"
+        } else {
+            "compositions in the PGS stream"
+        };
+        if decoded_count < declared_count {
+            warn!(
+                "Only {decoded_count} subtitles decoded for {declared_count} {declared}: \
+                 {difference} subtitles may be missing, the input could be damaged or truncated."
+            );
+        } else {
+            warn!(
+                "{decoded_count} subtitles decoded for only {declared_count} {declared}, \
+                 the input may not be split as expected."
+            );
+        }
+    }
+}
+
+/// Check the display sizes declared by the idx file `input` against the decoded bitmaps,
+/// the `images` prepared for `OCR` with a `border`, and warn on mismatches.
+///
+/// Streams with different sizes are reported, as are the bitmaps larger than the largest display size.
+#[profiling::function]
+pub fn check_display_sizes(input: &Path, images: &[GrayImage], border: u32) {
+    let sizes = match idx::read_content(input) {
+        Ok(content) => idx::display_sizes(&content),
+        Err(err) => {
+            warn!(
+                "Unable to read the display sizes of '{}': {err}",
+                input.display()
+            );
+            return;
+        }
+    };
+    let Some(first) = sizes.first().map(|(_, size)| *size) else {
+        debug!("No display size declared in '{}'", input.display());
+        return;
+    };
+    if sizes.iter().any(|(_, size)| *size != first) {
+        warn!(
+            "The streams of the idx file have different display sizes: {}",
+            sizes
+                .iter()
+                .map(|(id, size)| format!("`{id}` {}x{}", size.width, size.height))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    let max_width = sizes.iter().map(|(_, size)| size.width).max().unwrap_or(0);
+    let max_height = sizes.iter().map(|(_, size)| size.height).max().unwrap_or(0);
+    let oversized = images
+        .iter()
+        .filter(|image| {
+            let (width, height) = image.dimensions();
+            width.saturating_sub(border * 2) > max_width
+                || height.saturating_sub(border * 2) > max_height
+        })
+        .count();
+    if oversized > 0 {
+        warn!(
+            "{oversized} subtitle bitmaps are larger than the display size {max_width}x{max_height} \
+             of the idx file, its `size:` line may be wrong."
+        );
+    }
+}
+
+/// Remove the subtitles with `images` wider or higher than `max_size`, before their conversion.
+///
+/// Such sizes come from corrupt packets. The `PGS` images are still run-length encoded,
+/// so their huge decoding is avoided, but the `VobSub` images are already decoded by
+/// the parser: only their conversion and `OCR` are avoided.
+/// Each skipped subtitle is reported with its time and declared size, then their count.
+#[profiling::function]
+pub fn remove_oversized<Image: ImageSize>(
+    times: Vec<TimeSpan>,
+    images: Vec<Image>,
+    max_size: u32,
+) -> (Vec<TimeSpan>, Vec<Image>) {
+    let mut skipped_count = 0;
+    let (times, images) = times
+        .into_iter()
+        .zip(images)
+        .filter(|(time, image)| {
+            let oversized = is_oversized(time, image, max_size);
+            if oversized {
+                skipped_count += 1;
+            }
+            !oversized
+        })
+        .unzip();
+    if skipped_count > 0 {
+        warn!(
+            "{skipped_count} subtitles skipped for their oversized images, \
+             raise `--max-image-size` if their size is expected."
+        );
+    }
+    (times, images)
+}
+
+/// Check if the image of the subtitle at `time` is wider or higher than `max_size`,
+/// reporting it if so.
+#[must_use]
+pub fn is_oversized(time: &TimeSpan, image: &impl ImageSize, max_size: u32) -> bool {
+    let (width, height) = (image.width(), image.height());
+    let oversized = !fits_max_size(image, max_size);
+    if oversized {
+        warn!(
+            "Subtitle at {} skipped: its image declares a size of {width}x{height}, \
+             over the maximum of {max_size}x{max_size}, the packet is likely corrupt.",
+            format_timestamp(time.start, ',')
+        );
+    }
+    oversized
+}
+
+/// Check if `image` is at most `max_size` wide and high.
+#[must_use]
+pub fn fits_max_size(image: &impl ImageSize, max_size: u32) -> bool {
+    image.width() <= max_size && image.height() <= max_size
+}
+
+/// Count the subtitles declared by `input`.
+fn declared_count(input: &Path) -> io::Result<usize> {
+    if is_vobsub(input) {
+        let content = idx::read_content(input)?;
+        Ok(idx::parse_entries(&content).len())
+    } else {
+        Ok(pgs_compositions(&fs::read(input)?).count())
+    }
+}
+
+/// Check if `input` is a `VobSub` idx file.
+fn is_vobsub(input: &Path) -> bool {
+    input.extension().is_some_and(|ext| ext == "idx")
+}
+
+/// Check if `input` is a transport stream with `DVB` subtitles.
+fn is_dvb(input: &Path) -> bool {
+    input.extension().is_some_and(|ext| ext == "ts")
+}
+
+/// Iterate on the payloads of the presentation composition segments of a `PGS` stream
+/// showing at least one object.
+pub fn pgs_compositions(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut pos = 0;
+    iter::from_fn(move || {
+        while pos + PGS_HEADER_SIZE <= data.len() && &data[pos..pos + 2] == b"PG" {
+            let header = &data[pos..pos + PGS_HEADER_SIZE];
+            let kind = header[10];
+            let size = usize::from(u16::from_be_bytes([header[11], header[12]]));
+            let payload = data.get(pos + PGS_HEADER_SIZE..pos + PGS_HEADER_SIZE + size);
+            pos += PGS_HEADER_SIZE + size;
+            // The number of composition objects is the 11th byte of the segment.
+            if kind == 0x16
+                && payload
+                    .and_then(|payload| payload.get(10))
+                    .is_some_and(|n| *n > 0)
+            {
+                return payload;
+            }
+        }
+        None
+    })
+}
+
+/// Report where the data of a truncated `input` ends, and which subtitles are lost.
+///
+/// For `PGS` the stream is truncated if it doesn't end with a complete display set,
+/// for `VobSub` if idx entries point after the end of the sub file.
+/// The incomplete packets of a transport stream are dropped when decoding, without report.
+#[profiling::function]
+pub fn report_truncation(input: &Path) {
+    if is_dvb(input) {
+        return;
+    }
+    let report = if is_vobsub(input) {
+        vobsub_truncation(input)
+    } else {
+        fs::read(input).map(|data| pgs_truncation(&data))
+    };
+    match report {
+        Ok(Some(report)) => warn!("'{}' is truncated: {report}", input.display()),
+        Ok(None) => info!("'{}' doesn't look truncated", input.display()),
+        Err(err) => warn!(
+            "Unable to check if '{}' is truncated: {err}",
+            input.display()
+        ),
+    }
+}
+
+/// Describe the truncation of the sub file of idx file `input`, if any.
+fn vobsub_truncation(input: &Path) -> io::Result<Option<String>> {
+    let content = idx::read_content(input)?;
+    let sub_size = fs::metadata(input.with_extension("sub"))?.len();
+    let entries = idx::parse_entries(&content);
+    let lost = entries
+        .iter()
+        .filter(|entry| entry.filepos >= sub_size)
+        .collect::<Vec<_>>();
+    let Some(first_lost) = lost.iter().min_by_key(|entry| entry.filepos) else {
+        return Ok(None);
+    };
+    let last_kept = entries
+        .iter()
+        .filter(|entry| entry.filepos < sub_size)
+        .max_by_key(|entry| entry.filepos);
+    Ok(Some(format!(
+        "the sub file ends at offset {sub_size:#x}, {}{} subtitles from {} are lost",
+        last_kept.map_or_else(String::new, |entry| format!(
+            "inside or after the subtitle at offset {:#x}, ",
+            entry.filepos
+        )),
+        lost.len(),
+        format_timestamp(TimePoint::from_msecs(first_lost.timestamp), ',')
+    )))
+}
+
+/// Describe the truncation of a `PGS` stream, if any.
+fn pgs_truncation(data: &[u8]) -> Option<String> {
+    let mut complete_end = 0;
+    let mut pos = 0;
+    while pos + PGS_HEADER_SIZE <= data.len() && &data[pos..pos + 2] == b"PG" {
+        let header = &data[pos..pos + PGS_HEADER_SIZE];
+        let size = usize::from(u16::from_be_bytes([header[11], header[12]]));
+        if pos + PGS_HEADER_SIZE + size > data.len() {
+            break;
+        }
+        pos += PGS_HEADER_SIZE + size;
+        // The end segment closes the display set.
+        if header[10] == 0x80 {
+            complete_end = pos;
+        }
+    }
+    (complete_end < data.len()).then(|| {
+        format!(
+            "the last complete display set ends at offset {complete_end:#x}, \
+             the {} following bytes (up to offset {:#x}) are ignored",
+            data.len() - complete_end,
+            data.len()
+        )
+    })
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use log::{info, warn};
+
+/// Minimum number of letters before checking the script of the output.
+const MIN_LETTERS: usize = 20;
+
+/// Minimum share of letters expected in the scripts of the requested languages.
+const MIN_EXPECTED_RATIO: f32 = 0.5;
+
+/// Writing systems detected in the `OCR` output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Script {
+    /// Latin alphabet, with its extensions.
+    Latin,
+    /// Greek alphabet.
+    Greek,
+    /// Cyrillic alphabet.
+    Cyrillic,
+    /// Hebrew alphabet.
+    Hebrew,
+    /// Arabic alphabet.
+    Arabic,
+    /// Devanagari abugida.
+    Devanagari,
+    /// Thai abugida.
+    Thai,
+    /// Korean Hangul.
+    Hangul,
+    /// Japanese Hiragana and Katakana.
+    Kana,
+    /// Chinese characters, also used in Japanese.
+    Han,
+    /// Any other letter.
+    Other,
+}
+
+impl Script {
+    /// Get the script of `c`, or `None` if it's not a letter.
+    #[must_use]
+    pub fn of(c: char) -> Option<Self> {
+        if !c.is_alphabetic() {
+            return None;
+        }
+        Some(match c {
+            'A'..='Z' | 'a'..='z' | '\u{C0}'..='\u{24F}' | '\u{1E00}'..='\u{1EFF}' => Self::Latin,
+            '\u{370}'..='\u{3FF}' | '\u{1F00}'..='\u{1FFF}' => Self::Greek,
+            '\u{400}'..='\u{52F}' => Self::Cyrillic,
+            '\u{590}'..='\u{5FF}' => Self::Hebrew,
+            '\u{600}'..='\u{6FF}' | '\u{750}'..='\u{77F}' => Self::Arabic,
+            '\u{900}'..='\u{97F}' => Self::Devanagari,
+            '\u{E00}'..='\u{E7F}' => Self::Thai,
+            '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' | '\u{AC00}'..='\u{D7AF}' => {
+                Self::Hangul
+            }
+            '\u{3040}'..='\u{30FF}' => Self::Kana,
+            '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' => Self::Han,
+            _ => Self::Other,
+        })
+    }
+}
+
+/// Get the scripts expected for a Tesseract language, or `None` if the language is unknown.
+fn language_scripts(lang: &str) -> Option<&'static [Script]> {
+    if let Some(script) = lang.strip_prefix("script/") {
+        return language_scripts(&script.to_lowercase());
+    }
+    let lang = lang.strip_suffix("_vert").unwrap_or(lang);
+    Some(match lang {
+        "afr" | "aze" | "bos" | "cat" | "ces" | "cym" | "dan" | "deu" | "eng" | "enm" | "epo"
+        | "est" | "eus" | "fin" | "fra" | "frk" | "frm" | "gle" | "glg" | "hrv" | "hun" | "ind"
+        | "isl" | "ita" | "lat" | "lav" | "lit" | "mlt" | "msa" | "nld" | "nor" | "pol" | "por"
+        | "ron" | "slk" | "slv" | "spa" | "sqi" | "swa" | "swe" | "tgl" | "tur" | "vie"
+        | "latin" => &[Script::Latin],
+        "bel" | "bul" | "kaz" | "kir" | "mkd" | "mon" | "rus" | "srp" | "tgk" | "ukr"
+        | "uzb_cyrl" | "cyrillic" => &[Script::Cyrillic],
+        "ell" | "grc" | "greek" => &[Script::Greek],
+        "heb" | "yid" | "hebrew" => &[Script::Hebrew],
+        "ara" | "fas" | "pus" | "uig" | "urd" | "arabic" => &[Script::Arabic],
+        "hin" | "mar" | "nep" | "san" | "devanagari" => &[Script::Devanagari],
+        "tha" | "thai" => &[Script::Thai],
+        "kor" | "hangul" => &[Script::Hangul],
+        "chi_sim" | "chi_tra" | "hans" | "hant" => &[Script::Han],
+        "jpn" | "japanese" => &[Script::Han, Script::Kana],
+        _ => return None,
+    })
+}
+
+/// Check that the scripts of `texts` match the Tesseract language(s) `lang`, and log a summary.
+///
+/// A warning is logged if most of the letters are not in a script of the requested
+/// language(s), which usually means that the wrong subtitle track has been selected.
+#[profiling::function]
+pub fn check_scripts<'a>(lang: &str, texts: impl IntoIterator<Item = &'a str>) {
+    let mut counts = BTreeMap::<Script, usize>::new();
+    texts
+        .into_iter()
+        .flat_map(str::chars)
+        .filter_map(Script::of)
+        .for_each(|script| *counts.entry(script).or_default() += 1);
+
+    let total = counts.values().sum::<usize>();
+    if total < MIN_LETTERS {
+        return;
+    }
+    let ratio = |count: usize| count as f32 / total as f32;
+    let summary = counts
+        .iter()
+        .map(|(script, count)| format!("{script:?} {:.0}%", ratio(*count) * 100.0))
+        .collect::<Vec<_>>()
+        .join(", ");
+    info!("Scripts of the OCR output: {summary}");
+
+    let mut expected = Vec::new();
+    for lang in lang.split('+') {
+        match language_scripts(lang) {
+            Some(scripts) => expected.extend_from_slice(scripts),
+            None => return, // can't validate against an unknown language
+        }
+    }
+    expected.sort();
+    expected.dedup();
+    let expected_count = expected
+        .iter()
+        .filter_map(|script| counts.get(script))
+        .sum::<usize>();
+    if ratio(expected_count) < MIN_EXPECTED_RATIO {
+        warn!(
+            "Only {:.0}% of the OCR output is written in the script(s) of language `{lang}` ({summary}). \
+             Is the right subtitle track or language selected?",
+            ratio(expected_count) * 100.0
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use std::{fmt, fs};
+
+use crate::{ocr, process_pgs, Config, Error, OcrOpt, TessdataProbe};
+
+/// Text rendered in the embedded sample.
+const SAMPLE_TEXT: &str = "HELLO";
+
+/// Scale factor applied on the 5x7 glyphs of the sample.
+const GLYPH_SCALE: usize = 4;
+
+/// 5x7 bitmap glyphs used to render [`SAMPLE_TEXT`].
+const GLYPHS: [(char, [&str; 7]); 4] = [
+    (
+        'H',
+        [
+            "#...#", "#...#", "#...#", "#####", "#...#", "#...#", "#...#",
+        ],
+    ),
+    (
+        'E',
+        [
+            "#####", "#....", "#....", "####.", "#....", "#....", "#####",
+        ],
+    ),
+    (
+        'L',
+        [
+            "#....", "#....", "#....", "#....", "#....", "#....", "#####",
+        ],
+    ),
+    (
+        'O',
+        [
+            ".###.", "#...#", "#...#", "#...#", "#...#", "#...#", ".###.",
+        ],
+    ),
+];
+
+/// Step of the self-test, with its result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestStep {
+    /// If the step passed.
+    pub passed: bool,
+    /// Description of the step and of its result.
+    pub message: String,
+}
+
+/// Steps of the self-test, shown as `[ok]` or `[failed]` lines.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// Steps run, in their order.
+    pub steps: Vec<SelfTestStep>,
+}
+
+impl SelfTestReport {
+    fn passed(&mut self, message: String) {
+        self.steps.push(SelfTestStep {
+            passed: true,
+            message,
+        });
+    }
+
+    fn failed(&mut self, message: String) {
+        self.steps.push(SelfTestStep {
+            passed: false,
+            message,
+        });
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            let status = if step.passed { "ok" } else { "failed" };
+            writeln!(f, "[{status}] {}", step.message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Run the whole pipeline on an embedded sample and add each step to `report`.
+///
+/// The sample is a synthetic `PGS` stream generated in memory, so this only depends on
+/// the Tesseract installation (libraries, `tessdata` and the requested language).
+///
+/// # Errors
+///
+/// Will return [`Error::SelfTestSample`] if the sample file can't be written in the temporary directory.
+/// Will return [`Error::SelfTestFailed`] if a step failed or if the recognized text is wrong.
+pub fn self_test(opt: &Config, report: &mut SelfTestReport) -> Result<(), Error> {
+    let tessdata_probe = TessdataProbe::default();
+    let ocr_opt = OcrOpt::new(
+        &opt.ocr.tessdata_dir,
+        opt.ocr.lang.as_str(),
+        &opt.ocr.variables,
+        opt.ocr.dpi,
+    )
+    .with_tessdata_probe(Some(&tessdata_probe));
+    match ocr::check_init(&ocr_opt) {
+        Ok(()) => {
+            if let Some(dir) = tessdata_probe.dir() {
+                report.passed(format!("Language data found in `{dir}`"));
+            }
+            report.passed(format!(
+                "Tesseract initialized with language `{}`",
+                opt.ocr.lang
+            ));
+        }
+        Err(err) => {
+            let err = anyhow::Error::new(err);
+            let hint = if tessdata_probe.dir().is_none() {
+                " (`--tessdata-dir` or TESSDATA_PREFIX may be needed)"
+            } else {
+                ""
+            };
+            report.failed(format!(
+                "Tesseract initialization with language `{}`: {err:#}{hint}",
+                opt.ocr.lang
+            ));
+            return Err(Error::SelfTestFailed {
+                reason: "Tesseract initialization".into(),
+            });
+        }
+    }
+
+    let path =
+        std::env::temp_dir().join(format!("subtile-ocr-selftest-{}.sup", std::process::id()));
+    fs::write(&path, sample_sup()).map_err(|source| Error::SelfTestSample {
+        path: path.clone(),
+        source,
+    })?;
+    let decoded = process_pgs(&path, opt);
+    let _ = fs::remove_file(&path);
+    let (times, images) = match decoded {
+        Ok(decoded) => decoded,
+        Err(err) => {
+            report.failed(format!("decoding of the embedded sample: {err}"));
+            return Err(Error::SelfTestFailed {
+                reason: "decoding".into(),
+            });
+        }
+    };
+    if images.len() != 1 {
+        report.failed(format!(
+            "decoding returned {} subtitles instead of 1",
+            images.len()
+        ));
+        return Err(Error::SelfTestFailed {
+            reason: "decoding".into(),
+        });
+    }
+    report.passed(format!("Sample decoded ({:?})", times[0]));
+
+    let text = ocr::process(images, &ocr_opt)?
+        .into_iter()
+        .next()
+        .transpose()?
+        .unwrap_or_default();
+    let recognized = text.split_whitespace().collect::<String>();
+    if recognized.eq_ignore_ascii_case(SAMPLE_TEXT) {
+        report.passed(format!("OCR recognized `{recognized}`"));
+        Ok(())
+    } else {
+        report.failed(format!(
+            "OCR recognized `{recognized}` instead of `{SAMPLE_TEXT}`"
+        ));
+        Err(Error::SelfTestFailed {
+            reason: "unexpected OCR result".into(),
+        })
+    }
+}
+
+/// Generate a `PGS` stream with one subtitle displaying [`SAMPLE_TEXT`] from 1s to 3s.
+fn sample_sup() -> Vec<u8> {
+    let (width, height, pixels) = render_sample();
+    let (x, y) = (100_u16, 400_u16);
+
+    let mut rle = Vec::new();
+    for line in pixels.chunks(width) {
+        encode_rle_line(line, &mut rle);
+    }
+
+    let mut sup = Vec::new();
+    let (start, end) = (90_000, 270_000); // PTS use a 90 kHz clock
+    let window = [
+        &[1, 0][..],
+        &x.to_be_bytes(),
+        &y.to_be_bytes(),
+        &(width as u16).to_be_bytes(),
+        &(height as u16).to_be_bytes(),
+    ]
+    .concat();
+
+    // Display set showing the subtitle.
+    let pcs = [
+        &720_u16.to_be_bytes()[..],
+        &480_u16.to_be_bytes(),
+        &[0x10],
+        &0_u16.to_be_bytes(),
+        &[0x80, 0, 0, 1],
+        &0_u16.to_be_bytes(),
+        &[0, 0],
+        &x.to_be_bytes(),
+        &y.to_be_bytes(),
+    ]
+    .concat();
+    push_segment(&mut sup, start, 0x16, &pcs);
+    push_segment(&mut sup, start, 0x17, &window);
+    push_segment(
+        &mut sup,
+        start,
+        0x14,
+        &[0, 0, 0, 16, 128, 128, 0, 1, 235, 128, 128, 255],
+    );
+    let ods = [
+        &0_u16.to_be_bytes()[..],
+        &[0, 0xC0],
+        &(rle.len() as u32 + 4).to_be_bytes()[1..],
+        &(width as u16).to_be_bytes(),
+        &(height as u16).to_be_bytes(),
+        &rle,
+    ]
+    .concat();
+    push_segment(&mut sup, start, 0x15, &ods);
+    push_segment(&mut sup, start, 0x80, &[]);
+
+    // Display set clearing the subtitle.
+    let pcs = [
+        &720_u16.to_be_bytes()[..],
+        &480_u16.to_be_bytes(),
+        &[0x10],
+        &1_u16.to_be_bytes(),
+        &[0, 0, 0, 0],
+    ]
+    .concat();
+    push_segment(&mut sup, end, 0x16, &pcs);
+    push_segment(&mut sup, end, 0x17, &window);
+    push_segment(&mut sup, end, 0x80, &[]);
+
+    sup
+}
+
+/// Render [`SAMPLE_TEXT`] as palette indexes: `1` for text, `0` for background.
+fn render_sample() -> (usize, usize, Vec<u8>) {
+    let margin = 2 * GLYPH_SCALE;
+    let advance = 6 * GLYPH_SCALE;
+    let width = margin * 2 + advance * SAMPLE_TEXT.len();
+    let height = margin * 2 + 7 * GLYPH_SCALE;
+
+    let mut pixels = vec![0; width * height];
+    for (idx, c) in SAMPLE_TEXT.chars().enumerate() {
+        let Some((_, rows)) = GLYPHS.iter().find(|(glyph, _)| *glyph == c) else {
+            continue;
+        };
+        for (row_idx, row) in rows.iter().enumerate() {
+            for (col_idx, _) in row.chars().enumerate().filter(|(_, p)| *p == '#') {
+                let x0 = margin + idx * advance + col_idx * GLYPH_SCALE;
+                let y0 = margin + row_idx * GLYPH_SCALE;
+                for y in y0..y0 + GLYPH_SCALE {
+                    pixels[y * width + x0..y * width + x0 + GLYPH_SCALE].fill(1);
+                }
+            }
+        }
+    }
+    (width, height, pixels)
+}
+
+/// Encode a line of palette indexes with the `PGS` run-length encoding.
+fn encode_rle_line(line: &[u8], out: &mut Vec<u8>) {
+    let mut x = 0;
+    while x < line.len() {
+        let color = line[x];
+        let run = line[x..]
+            .iter()
+            .take_while(|pixel| **pixel == color)
+            .count()
+            .min(0x3FFF);
+        match (color, run) {
+            (0, 1..=63) => out.extend([0, run as u8]),
+            (0, _) => out.extend([0, 0x40 | (run >> 8) as u8, run as u8]),
+            (_, 1..=2) => out.extend(std::iter::repeat_n(color, run)),
+            (_, 3..=63) => out.extend([0, 0x80 | run as u8, color]),
+            (_, _) => out.extend([0, 0xC0 | (run >> 8) as u8, run as u8, color]),
+        }
+        x += run;
+    }
+    out.extend([0, 0]);
+}
+
+/// Append a `PGS` segment with its header to `out`.
+fn push_segment(out: &mut Vec<u8>, pts: u32, kind: u8, payload: &[u8]) {
+    out.extend(b"PG");
+    out.extend(pts.to_be_bytes());
+    out.extend(0_u32.to_be_bytes()); // DTS
+    out.push(kind);
+    out.extend((payload.len() as u16).to_be_bytes());
+    out.extend(payload);
+}
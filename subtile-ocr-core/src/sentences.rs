@@ -0,0 +1,77 @@
+use log::info;
+use subtile::time::TimeSpan;
+
+use crate::cue::Cue;
+
+/// What to do with the sentences split across consecutive subtitles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SentencePolicy {
+    /// Write the subtitles as they are.
+    #[default]
+    Keep,
+    /// Mark the split with `...` at the end of the subtitle and the start of the next one.
+    Annotate,
+    /// Merge the subtitles of a sentence into one covering all of them, e.g. for transcripts.
+    Merge,
+}
+
+/// Characters ending a sentence.
+const TERMINAL_PUNCTUATION: &[char] = &['.', '!', '?', '…', ':', ';', '♪'];
+
+/// Characters closing a sentence after its punctuation, like quotes.
+const CLOSING: &[char] = &['"', '\'', '»', '”', '’', ')', ']'];
+
+/// Apply the sentence `policy` on `subtitles`.
+///
+/// A sentence continues in the next subtitle if the text doesn't end with terminal
+/// punctuation and the next one starts with a lowercase letter.
+#[must_use]
+#[profiling::function]
+pub fn handle_split_sentences(subtitles: Vec<Cue>, policy: SentencePolicy) -> Vec<Cue> {
+    if policy == SentencePolicy::Keep {
+        return subtitles;
+    }
+    let mut result: Vec<Cue> = Vec::with_capacity(subtitles.len());
+    let mut split_count = 0;
+    for cue in subtitles {
+        let Some(previous) = result
+            .last_mut()
+            .filter(|previous| continues(&previous.text, &cue.text))
+        else {
+            result.push(cue);
+            continue;
+        };
+        split_count += 1;
+        if policy == SentencePolicy::Merge {
+            let time = TimeSpan::new(previous.time.start, previous.time.end.max(cue.time.end));
+            let text = format!("{} {}\n", previous.text.trim_end(), cue.text.trim());
+            *previous = Cue::merged(time, text, [&*previous, &cue]);
+        } else {
+            let trimmed = previous.text.trim_end().len();
+            previous.text.truncate(trimmed);
+            previous.text.push_str("...\n");
+            let text = format!("...{}", cue.text.trim_start());
+            result.push(Cue { text, ..cue });
+        }
+    }
+
+    if split_count > 0 {
+        info!("{split_count} sentences split across subtitles have been handled with policy {policy:?}");
+    }
+    result
+}
+
+/// Check if the sentence of `text` continues in the subtitle with `next` text.
+fn continues(text: &str, next: &str) -> bool {
+    let ended = text
+        .trim_end()
+        .trim_end_matches(CLOSING)
+        .ends_with(TERMINAL_PUNCTUATION);
+    let next_lowercase = next
+        .trim_start()
+        .trim_start_matches(['.', '…', ' '])
+        .chars()
+        .next()
+        .is_some_and(char::is_lowercase);
+    !text.trim().is_empty() && !ended && next_lowercase
+}
@@ -0,0 +1,170 @@
+use std::ops::Range;
+
+use image::{GrayImage, Luma};
+
+/// Maximum luminance of a text pixel in images prepared for `OCR`.
+const INK_MAX_LUMA: u8 = 127;
+
+/// Maximum gap in pixels between rows of ink considered in the same line.
+const MAX_LINE_GAP: u32 = 2;
+
+/// Border in pixels around the cropped line images.
+const LINE_BORDER: u32 = 10;
+
+/// Minimum gap between two columns of a line, relative to the line height.
+const MIN_COLUMN_GAP_RATIO: f32 = 1.5;
+
+/// Minimum gap between two groups of lines of an image, relative to the median line height.
+const MIN_GROUP_GAP_RATIO: u32 = 3;
+
+/// Vertical extent of a text line in an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line {
+    /// First row of the line.
+    pub top: u32,
+    /// Row after the last row of the line.
+    pub bottom: u32,
+}
+
+impl Line {
+    /// Height of the line in pixels.
+    #[must_use]
+    pub const fn height(self) -> u32 {
+        self.bottom - self.top
+    }
+}
+
+/// Check if a pixel of an image prepared for `OCR` is a text pixel.
+#[must_use]
+pub const fn is_ink(pixel: Luma<u8>) -> bool {
+    pixel.0[0] <= INK_MAX_LUMA
+}
+
+/// Find the text lines of an image prepared for `OCR`, from top to bottom.
+///
+/// Small bands of ink (e.g. accents or dots separated from their letters) are merged
+/// into the closest line.
+#[must_use]
+pub fn find_lines(image: &GrayImage) -> Vec<Line> {
+    let ink_rows = image
+        .rows()
+        .map(|mut row| row.any(|pixel| is_ink(*pixel)))
+        .collect::<Vec<_>>();
+
+    // Group rows of ink, tolerating small gaps.
+    let mut lines: Vec<Line> = Vec::new();
+    for (y, _) in (0..).zip(&ink_rows).filter(|(_, has_ink)| **has_ink) {
+        match lines.last_mut() {
+            Some(line) if y - line.bottom <= MAX_LINE_GAP => line.bottom = y + 1,
+            _ => lines.push(Line {
+                top: y,
+                bottom: y + 1,
+            }),
+        }
+    }
+
+    // Merge small bands into their closest neighbor.
+    let max_height = lines.iter().copied().map(Line::height).max().unwrap_or(0);
+    let mut idx = 0;
+    while lines.len() > 1 && idx < lines.len() {
+        if lines[idx].height() * 3 >= max_height {
+            idx += 1;
+            continue;
+        }
+        let gap_before = idx
+            .checked_sub(1)
+            .map(|prev| lines[idx].top - lines[prev].bottom);
+        let gap_after = lines.get(idx + 1).map(|next| next.top - lines[idx].bottom);
+        let into = match (gap_before, gap_after) {
+            (Some(before), Some(after)) if before < after => idx - 1,
+            (Some(_), None) => idx - 1,
+            _ => idx + 1,
+        };
+        let small = lines.remove(idx);
+        let into = if into > idx { into - 1 } else { into };
+        lines[into].top = lines[into].top.min(small.top);
+        lines[into].bottom = lines[into].bottom.max(small.bottom);
+        idx = 0;
+    }
+    lines
+}
+
+/// Find the groups of lines of an image separated by a gap much larger than between lines,
+/// like a sign at the top and a dialogue at the bottom rendered in the same image.
+///
+/// Returns the vertical ranges of the groups, from top to bottom.
+#[must_use]
+pub fn find_groups(image: &GrayImage) -> Vec<Range<u32>> {
+    let lines = find_lines(image);
+    let mut heights = lines.iter().copied().map(Line::height).collect::<Vec<_>>();
+    heights.sort_unstable();
+    let Some(line_height) = heights.get(heights.len() / 2).copied() else {
+        return Vec::new();
+    };
+    let min_gap = line_height * MIN_GROUP_GAP_RATIO;
+    let mut groups: Vec<Range<u32>> = Vec::new();
+    for line in lines {
+        match groups.last_mut() {
+            Some(group) if line.top - group.end < min_gap => group.end = line.bottom,
+            _ => groups.push(line.top..line.bottom),
+        }
+    }
+    groups
+}
+
+/// Copy the `rows` of `image` into a new image of the same width, with a background `border` above and below.
+#[must_use]
+pub fn crop_rows(image: &GrayImage, rows: &Range<u32>, border: u32) -> GrayImage {
+    let height = rows.end - rows.start;
+    let mut cropped = GrayImage::from_pixel(image.width(), height + border * 2, Luma([255]));
+    for y in 0..height {
+        for x in 0..image.width() {
+            cropped.put_pixel(x, y + border, *image.get_pixel(x, rows.start + y));
+        }
+    }
+    cropped
+}
+
+/// Find the columns of `line`, separated by horizontal gaps much wider than spaces between words.
+///
+/// Returns the horizontal ranges of the columns, from left to right.
+#[must_use]
+pub fn find_columns(image: &GrayImage, line: Line) -> Vec<Range<u32>> {
+    let min_gap = (line.height() as f32 * MIN_COLUMN_GAP_RATIO) as u32;
+    let mut columns: Vec<Range<u32>> = Vec::new();
+    for x in (0..image.width())
+        .filter(|x| (line.top..line.bottom).any(|y| is_ink(*image.get_pixel(*x, y))))
+    {
+        match columns.last_mut() {
+            Some(column) if x - column.end < min_gap => column.end = x + 1,
+            _ => columns.push(x..x + 1),
+        }
+    }
+    columns
+}
+
+/// Copy `line` of `image` into a new image, with a background border.
+#[must_use]
+pub fn crop_line(image: &GrayImage, line: Line) -> GrayImage {
+    crop_column(image, line, &(0..image.width()))
+}
+
+/// Copy the `column` range of `line` of `image` into a new image, with a background border.
+#[must_use]
+pub fn crop_column(image: &GrayImage, line: Line, column: &Range<u32>) -> GrayImage {
+    let mut cropped = GrayImage::from_pixel(
+        column.end - column.start + LINE_BORDER * 2,
+        line.height() + LINE_BORDER * 2,
+        Luma([255]),
+    );
+    for y in 0..line.height() {
+        for x in column.clone() {
+            cropped.put_pixel(
+                x - column.start + LINE_BORDER,
+                y + LINE_BORDER,
+                *image.get_pixel(x, line.top + y),
+            );
+        }
+    }
+    cropped
+}
@@ -0,0 +1,450 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use log::info;
+use subtile::time::{TimePoint, TimeSpan};
+
+use crate::idx::IdxEntry;
+
+/// Stream id of the packs of an `MPEG` program stream.
+const PACK_START: u8 = 0xba;
+
+/// Stream id of the private stream 1, carrying the `VobSub` subtitle units.
+const PRIVATE_STREAM_1: u8 = 0xbd;
+
+/// Substream ids of the subtitles in the private stream 1.
+const SUBTITLE_STREAMS: std::ops::Range<u8> = 0x20..0x40;
+
+/// Size of the sub file read at the position of a subtitle, enough for the largest
+/// subtitle unit with the headers of its packets.
+const READ_SIZE: u64 = 0x1_8000;
+
+/// Commands of the control sequences of a subtitle unit.
+const FORCED_START: u8 = 0x00;
+const START: u8 = 0x01;
+const STOP: u8 = 0x02;
+const SET_COLOR: u8 = 0x03;
+const SET_CONTRAST: u8 = 0x04;
+const SET_AREA: u8 = 0x05;
+const SET_OFFSETS: u8 = 0x06;
+const CHANGE_COLOR_CONTRAST: u8 = 0x07;
+const END: u8 = 0xff;
+
+/// Maximum number of control sequences read in a subtitle unit, against looping offsets.
+const MAX_SEQUENCES: usize = 256;
+
+/// Maximum difference in milliseconds between the start of a decoded subtitle and the start
+/// computed from its idx entry, below the shortest time between two subtitles.
+const START_TOLERANCE_MS: i64 = 20;
+
+/// Interval where a subtitle is visible, from the control sequences of its unit.
+///
+/// The dates are in milliseconds from the start of the unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Visibility {
+    /// Date of the start command.
+    start: i64,
+    /// Date where the subtitle is displayed with a visible contrast, in a non-empty area.
+    shown: i64,
+    /// Date where the subtitle is hidden by a fade or an area change, before its stop command.
+    hidden: Option<i64>,
+}
+
+/// Get the palette entries of the four colors of the subtitles of the sub file `sub`
+/// at each of the `positions`, listed by the idx file.
+///
+/// The colors are in the order of their role: background, pattern (the fill of the text),
+/// first and second emphasis (usually the outline and the anti-aliasing). The subtitles
+/// which can't be read or have no color command are skipped.
+///
+/// # Errors
+///
+/// Will return an error if the sub file can't be opened or read.
+pub fn sample_colors(sub: &Path, positions: impl Iterator<Item = u64>) -> io::Result<Vec<[u8; 4]>> {
+    let mut file = BufReader::new(File::open(sub)?);
+    let mut samples = Vec::new();
+    let mut data = Vec::new();
+    for position in positions {
+        file.seek(SeekFrom::Start(position))?;
+        data.clear();
+        (&mut file).take(READ_SIZE).read_to_end(&mut data)?;
+        if let Some(colors) = reassemble(&data).as_deref().and_then(unit_colors) {
+            samples.push(colors);
+        }
+    }
+    Ok(samples)
+}
+
+/// Get the visible interval of the subtitles of the sub file `sub` listed by the idx `entries`,
+/// with the start of each subtitle in milliseconds, sorted by start.
+///
+/// Only the subtitles with a fade or an area change hiding them between their start and stop
+/// commands are listed. The subtitles which can't be read are skipped.
+///
+/// # Errors
+///
+/// Will return an error if the sub file can't be opened or read.
+pub fn read_visibilities(sub: &Path, entries: &[IdxEntry]) -> io::Result<Vec<(i64, Visibility)>> {
+    let mut file = BufReader::new(File::open(sub)?);
+    let mut visibilities = Vec::new();
+    let mut data = Vec::new();
+    for entry in entries {
+        file.seek(SeekFrom::Start(entry.filepos))?;
+        data.clear();
+        (&mut file).take(READ_SIZE).read_to_end(&mut data)?;
+        let visibility = reassemble(&data).as_deref().and_then(unit_visibility);
+        if let Some(visibility) = visibility.filter(|visibility| {
+            visibility.shown != visibility.start || visibility.hidden.is_some()
+        }) {
+            visibilities.push((entry.timestamp + visibility.start, visibility));
+        }
+    }
+    visibilities.sort_unstable_by_key(|(start, _)| *start);
+    Ok(visibilities)
+}
+
+/// Restrict the `times` of the decoded subtitles to their visible interval in `visibilities`,
+/// listed by [`read_visibilities`].
+///
+/// The subtitles are matched by their start, the ones without visible interval are unchanged.
+#[must_use]
+#[profiling::function]
+pub fn apply_visibilities(
+    times: Vec<TimeSpan>,
+    visibilities: &[(i64, Visibility)],
+) -> Vec<TimeSpan> {
+    if visibilities.is_empty() {
+        return times;
+    }
+    let mut adjusted_count = 0;
+    let times = times
+        .into_iter()
+        .map(|time| {
+            let start = time.start.msecs();
+            let idx = visibilities.partition_point(|(unit_start, _)| *unit_start < start);
+            let nearest = [idx.checked_sub(1), Some(idx)]
+                .into_iter()
+                .flatten()
+                .filter_map(|idx| visibilities.get(idx))
+                .min_by_key(|(unit_start, _)| (unit_start - start).abs())
+                .filter(|(unit_start, _)| (unit_start - start).abs() <= START_TOLERANCE_MS);
+            let Some((_, visibility)) = nearest else {
+                return time;
+            };
+            let shown = start + visibility.shown - visibility.start;
+            let end = time.end.msecs();
+            let hidden = visibility
+                .hidden
+                .map_or(end, |hidden| end.min(start + hidden - visibility.start));
+            if hidden <= shown {
+                return time;
+            }
+            adjusted_count += 1;
+            TimeSpan::new(TimePoint::from_msecs(shown), TimePoint::from_msecs(hidden))
+        })
+        .collect();
+    if adjusted_count > 0 {
+        info!("{adjusted_count} subtitle(s) restricted to the interval visible with their fades and display area");
+    }
+    times
+}
+
+/// Reassemble the subtitle unit starting in the first subtitle packet of `data`.
+fn reassemble(data: &[u8]) -> Option<Vec<u8>> {
+    let mut unit = Vec::new();
+    let mut substream = None;
+    let mut pos = 0;
+    while pos + 6 <= data.len() {
+        if data[pos..pos + 3] != [0, 0, 1] {
+            return None;
+        }
+        let id = data[pos + 3];
+        if id == PACK_START {
+            // `MPEG-2` pack header with its stuffing bytes, or `MPEG-1` one.
+            pos += if data.get(pos + 4)? & 0xc0 == 0x40 {
+                14 + usize::from(data.get(pos + 13)? & 0x07)
+            } else {
+                12
+            };
+            continue;
+        }
+        let length = usize::from(u16::from_be_bytes([data[pos + 4], data[pos + 5]]));
+        let packet = data.get(pos + 6..pos + 6 + length)?;
+        pos += 6 + length;
+        if id != PRIVATE_STREAM_1 {
+            continue;
+        }
+        // The `PES` header of `MPEG-2`, then the substream id.
+        let header_length = usize::from(*packet.get(2)?);
+        let (&stream, payload) = packet.get(3 + header_length..)?.split_first()?;
+        if !SUBTITLE_STREAMS.contains(&stream) || *substream.get_or_insert(stream) != stream {
+            continue;
+        }
+        unit.extend_from_slice(payload);
+        if let [high, low, ..] = unit[..] {
+            let size = usize::from(u16::from_be_bytes([high, low]));
+            if unit.len() >= size {
+                unit.truncate(size);
+                return Some(unit);
+            }
+        }
+    }
+    None
+}
+
+/// Convert a `delay` of a control sequence, in 1024 ticks of 90 kHz, to milliseconds.
+fn delay_msecs(delay: u16) -> i64 {
+    i64::from(delay) * 1024 / 90
+}
+
+/// Get the visible interval of the subtitle `unit`, following its control sequences.
+///
+/// The subtitle is visible while it is displayed (between the start and stop commands),
+/// with a contrast making its text visible, in a non-empty display area.
+fn unit_visibility(unit: &[u8]) -> Option<Visibility> {
+    let mut offset = usize::from(u16::from_be_bytes([*unit.get(2)?, *unit.get(3)?]));
+    let (mut displayed, mut contrasted, mut in_area) = (false, true, true);
+    let (mut start, mut shown) = (None, None);
+    for _ in 0..MAX_SEQUENCES {
+        let header = unit.get(offset..offset + 4)?;
+        let date = delay_msecs(u16::from_be_bytes([header[0], header[1]]));
+        let next = usize::from(u16::from_be_bytes([header[2], header[3]]));
+        let mut pos = offset + 4;
+        loop {
+            let command = *unit.get(pos)?;
+            pos += 1;
+            match command {
+                FORCED_START | START => {
+                    displayed = true;
+                    start.get_or_insert(date);
+                }
+                STOP => displayed = false,
+                SET_COLOR => pos += 2,
+                SET_CONTRAST => {
+                    // The contrasts of the emphasis colors, then of the pattern and the background.
+                    let (emphasis, pattern) = (*unit.get(pos)?, *unit.get(pos + 1)?);
+                    contrasted = emphasis != 0 || pattern >> 4 != 0;
+                    pos += 2;
+                }
+                SET_AREA => {
+                    let area = unit.get(pos..pos + 6)?;
+                    let left = u16::from(area[0]) << 4 | u16::from(area[1] >> 4);
+                    let right = u16::from(area[1] & 0x0f) << 8 | u16::from(area[2]);
+                    let top = u16::from(area[3]) << 4 | u16::from(area[4] >> 4);
+                    let bottom = u16::from(area[4] & 0x0f) << 8 | u16::from(area[5]);
+                    in_area = left <= right && top <= bottom;
+                    pos += 6;
+                }
+                SET_OFFSETS => pos += 4,
+                // The size of the parameters includes its own bytes.
+                CHANGE_COLOR_CONTRAST => {
+                    pos += usize::from(u16::from_be_bytes([*unit.get(pos)?, *unit.get(pos + 1)?]));
+                }
+                END => break,
+                _ => return None,
+            }
+        }
+        let visible = displayed && contrasted && in_area;
+        match shown {
+            None if visible => shown = Some(date),
+            Some(shown) if !visible => {
+                return Some(Visibility {
+                    start: start?,
+                    shown,
+                    hidden: displayed.then_some(date),
+                });
+            }
+            _ => {}
+        }
+        // The last sequence points to itself.
+        if next <= offset {
+            break;
+        }
+        offset = next;
+    }
+    Some(Visibility {
+        start: start?,
+        shown: shown?,
+        hidden: None,
+    })
+}
+
+/// Get the palette entries of the four colors set by the first control sequence of the subtitle `unit`.
+fn unit_colors(unit: &[u8]) -> Option<[u8; 4]> {
+    let offset = usize::from(u16::from_be_bytes([*unit.get(2)?, *unit.get(3)?]));
+    // The commands follow the delay and the offset of the next sequence.
+    let mut pos = offset + 4;
+    loop {
+        let command = *unit.get(pos)?;
+        pos += 1;
+        match command {
+            SET_COLOR => {
+                let (emphasis, pattern) = (*unit.get(pos)?, *unit.get(pos + 1)?);
+                return Some([pattern & 0x0f, pattern >> 4, emphasis & 0x0f, emphasis >> 4]);
+            }
+            FORCED_START | START | STOP => {}
+            SET_CONTRAST => pos += 2,
+            SET_AREA => pos += 6,
+            SET_OFFSETS => pos += 4,
+            // The end of the sequence, or a command changing the colors by area.
+            _ => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use subtile::time::{TimePoint, TimeSpan};
+
+    use super::{apply_visibilities, reassemble, unit_colors, unit_visibility, Visibility};
+
+    /// Control sequence of `delay` ticks with `commands`, followed by the sequence at `next`.
+    fn sequence(delay: u16, next: u16, commands: &[u8]) -> Vec<u8> {
+        let mut sequence = delay.to_be_bytes().to_vec();
+        sequence.extend_from_slice(&next.to_be_bytes());
+        sequence.extend_from_slice(commands);
+        sequence.push(0xff);
+        sequence
+    }
+
+    /// Subtitle unit shown with a fade in at 500ms and a fade out at 2002ms, stopped at 3003ms.
+    fn unit() -> Vec<u8> {
+        let first = [
+            0x03, 0x12, 0x34, // Colors.
+            0x04, 0x00, 0x00, // Transparent.
+            0x05, 0x00, 0x00, 0x09, 0x00, 0x00, 0x03, // Area of 10x4 pixels.
+            0x01, // Start.
+        ];
+        let fade_in = [0x04, 0xff, 0xf0];
+        let fade_out = [0x04, 0x00, 0x00];
+        let pixels = [0u8; 4];
+        let mut offset = 4 + pixels.len();
+        let mut sequences = Vec::new();
+        for (delay, commands) in [
+            (0, &first[..]),
+            (44, &fade_in[..]),
+            (176, &fade_out[..]),
+            (264, &[0x02][..]),
+        ] {
+            let size = 4 + commands.len() + 1;
+            let next = if delay == 264 { offset } else { offset + size };
+            sequences.push(sequence(delay, next as u16, commands));
+            offset += size;
+        }
+        let mut unit = Vec::new();
+        let control = 4 + pixels.len();
+        unit.extend_from_slice(&(offset as u16).to_be_bytes());
+        unit.extend_from_slice(&(control as u16).to_be_bytes());
+        unit.extend_from_slice(&pixels);
+        unit.extend(sequences.concat());
+        unit
+    }
+
+    /// `MPEG-2` pack header, followed by the subtitle `unit` split in `PES` packets of `chunk` bytes.
+    fn packets(unit: &[u8], chunk: usize) -> Vec<u8> {
+        let mut data = vec![0, 0, 1, 0xba, 0x44, 0, 4, 0, 4, 1, 1, 0x89, 0xc3, 0xf8];
+        for payload in unit.chunks(chunk) {
+            let length = 3 + 5 + 1 + payload.len();
+            data.extend_from_slice(&[0, 0, 1, 0xbd]);
+            data.extend_from_slice(&(length as u16).to_be_bytes());
+            data.extend_from_slice(&[0x81, 0x80, 5, 0x21, 0, 1, 0, 1, 0x20]);
+            data.extend_from_slice(payload);
+        }
+        data
+    }
+
+    #[test]
+    fn reassemble_unit() {
+        let unit = unit();
+        assert_eq!(reassemble(&packets(&unit, 1000)), Some(unit.clone()));
+        assert_eq!(reassemble(&packets(&unit, 7)), Some(unit));
+    }
+
+    #[test]
+    fn read_colors() {
+        assert_eq!(unit_colors(&unit()), Some([0x4, 0x3, 0x2, 0x1]));
+    }
+
+    #[test]
+    fn read_visibility() {
+        assert_eq!(
+            unit_visibility(&unit()),
+            Some(Visibility {
+                start: 0,
+                shown: 500,
+                hidden: Some(2002),
+            })
+        );
+    }
+
+    #[test]
+    fn apply_visibility() {
+        let span =
+            |start, end| TimeSpan::new(TimePoint::from_msecs(start), TimePoint::from_msecs(end));
+        let visibility = unit_visibility(&unit()).unwrap();
+        let times = apply_visibilities(
+            vec![span(1000, 4003), span(5000, 6000), span(10_010, 10_100)],
+            &[(1000, visibility), (10_000, visibility)],
+        );
+        // The third subtitle would be hidden before it is shown, it is unchanged.
+        assert_eq!(
+            times,
+            [span(1500, 3002), span(5000, 6000), span(10_010, 10_100)]
+        );
+    }
+
+    #[test]
+    fn truncated_data() {
+        let unit = unit();
+        let data = packets(&unit, 16);
+        for size in 0..data.len() {
+            assert_eq!(reassemble(&data[..size]), None);
+        }
+        for size in 0..unit.len() {
+            // A truncated unit may still have its colors, but is never read past its end.
+            let _ = unit_colors(&unit[..size]);
+            let _ = unit_visibility(&unit[..size]);
+        }
+    }
+
+    #[test]
+    fn malformed_data() {
+        // Not a start code, and a packet length beyond the data.
+        assert_eq!(reassemble(&[0, 0, 2, 0xbd, 0, 0]), None);
+        assert_eq!(reassemble(&[0, 0, 1, 0xbd, 0xff, 0xff, 0x81]), None);
+
+        // Unknown command, then a sequence pointing back to the first one.
+        let mut unit = unit();
+        let control = usize::from(u16::from_be_bytes([unit[2], unit[3]]));
+        unit[control + 4] = 0x42;
+        assert_eq!(unit_visibility(&unit), None);
+        let mut looping = self::unit();
+        let second = usize::from(u16::from_be_bytes([
+            looping[control + 2],
+            looping[control + 3],
+        ]));
+        looping[second + 2..second + 4].copy_from_slice(&(control as u16).to_be_bytes());
+        assert_eq!(
+            unit_visibility(&looping),
+            Some(Visibility {
+                start: 0,
+                shown: 500,
+                hidden: None,
+            })
+        );
+
+        // Every byte of the control sequences changed, which never reads out of the unit.
+        let unit = self::unit();
+        for pos in control..unit.len() {
+            for byte in [0x00, 0x07, 0xff] {
+                let mut unit = unit.clone();
+                unit[pos] = byte;
+                let _ = unit_colors(&unit);
+                let _ = unit_visibility(&unit);
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::io::{self, Write};
+
+use subtile::time::TimeSpan;
+
+use crate::timing::format_timestamp;
+
+/// Write `SRT` cues one at a time, for pipelines flushing their results as they complete.
+///
+/// Cues are numbered when they are written, so the skipped ones (without text) don't leave holes.
+#[derive(Debug)]
+pub struct SrtWriter<W: Write> {
+    writer: W,
+    next_index: usize,
+}
+
+impl<W: Write> SrtWriter<W> {
+    /// Create a writer of `SRT` cues to `writer`.
+    #[must_use]
+    pub const fn new(writer: W) -> Self {
+        Self {
+            writer,
+            next_index: 1,
+        }
+    }
+
+    /// Write a cue with `text` displayed during `time`, skipped if `text` is empty.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if writing fails.
+    pub fn write_cue(&mut self, time: TimeSpan, text: &str) -> io::Result<()> {
+        let text = text.trim_end();
+        if text.is_empty() {
+            return Ok(());
+        }
+        write!(
+            self.writer,
+            "{}\n{} --> {}\n{text}\n\n",
+            self.next_index,
+            format_timestamp(time.start, ','),
+            format_timestamp(time.end, ',')
+        )?;
+        self.next_index += 1;
+        Ok(())
+    }
+
+    /// Write all the cues of `subtitles`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if writing fails.
+    pub fn write_all<'a>(
+        &mut self,
+        subtitles: impl IntoIterator<Item = &'a (TimeSpan, String)>,
+    ) -> io::Result<()> {
+        for (time, text) in subtitles {
+            self.write_cue(*time, text)?;
+        }
+        Ok(())
+    }
+
+    /// Flush the written cues, to make them available before the next ones.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if flushing fails.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Number of cues written so far.
+    #[must_use]
+    pub const fn count(&self) -> usize {
+        self.next_index - 1
+    }
+
+    /// Flush the cues and get back the inner writer.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if flushing fails.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use subtile::time::{TimePoint, TimeSpan};
+
+    use super::SrtWriter;
+
+    fn span(start: i64, end: i64) -> TimeSpan {
+        TimeSpan::new(TimePoint::from_msecs(start), TimePoint::from_msecs(end))
+    }
+
+    #[test]
+    fn numbered_cues() {
+        let mut writer = SrtWriter::new(Vec::new());
+        writer
+            .write_cue(span(1_500, 3_000), "First line\nSecond line\n")
+            .unwrap();
+        // Cues without text are skipped, without hole in the numbering.
+        writer.write_cue(span(4_000, 5_000), " \n").unwrap();
+        writer
+            .write_all(&[(span(3_723_004, 3_725_000), "Last".to_owned())])
+            .unwrap();
+        assert_eq!(writer.count(), 2);
+        let srt = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(
+            srt,
+            "1\n00:00:01,500 --> 00:00:03,000\nFirst line\nSecond line\n\n\
+             2\n01:02:03,004 --> 01:02:05,000\nLast\n\n"
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use std::time::{Duration, Instant};
+
+use log::info;
+
+/// Wall-clock time of the stages of a run, logged in the summary.
+#[derive(Debug)]
+pub struct StageTimer {
+    start: Instant,
+    last: Instant,
+    stages: Vec<(&'static str, Duration)>,
+}
+
+impl Default for StageTimer {
+    fn default() -> Self {
+        let now = Instant::now();
+        Self {
+            start: now,
+            last: now,
+            stages: Vec::new(),
+        }
+    }
+}
+
+impl StageTimer {
+    /// Record the time since the end of the previous stage as the duration of stage `name`.
+    pub fn lap(&mut self, name: &'static str) {
+        let now = Instant::now();
+        self.stages.push((name, now - self.last));
+        self.last = now;
+    }
+
+    /// Log the duration of each stage, and the number of `cue_count` subtitles processed per second.
+    pub fn log(&self, cue_count: usize) {
+        let total = self.last - self.start;
+        let stages = self
+            .stages
+            .iter()
+            .map(|(name, duration)| format!("{name} {:.2}s", duration.as_secs_f64()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let rate = cue_count as f64 / total.as_secs_f64().max(f64::EPSILON);
+        info!(
+            "Timings: {stages}; total {:.2}s for {cue_count} subtitles ({rate:.1} subtitles/s)",
+            total.as_secs_f64()
+        );
+    }
+}
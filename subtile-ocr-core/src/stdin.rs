@@ -0,0 +1,128 @@
+use std::{
+    collections::hash_map::RandomState,
+    fs,
+    hash::{BuildHasher, Hasher},
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use log::info;
+
+/// Format of the subtitles read from stdin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputFormat {
+    /// `PGS` stream, as in a `.sup` file.
+    Sup,
+    /// `VobSub` stream, as in a `.sub` file, with its idx file given separately.
+    Vobsub,
+    /// `MPEG` transport stream with `DVB` subtitles, as in a `.ts` file.
+    Ts,
+}
+
+/// Number of tries to create a temporary directory with a name not taken yet.
+const TEMP_DIR_TRIES: usize = 16;
+
+/// Number of the next temporary directory of the process.
+static TEMP_DIR_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Subtitles read from stdin or a container into a temporary file, removed when dropped.
+///
+/// The parsers only open files, so the stream is fully buffered before decoding.
+/// The files are in a directory of their own, created with a unique name and only
+/// accessible by the user, so concurrent spools don't share their files.
+#[derive(Debug)]
+pub struct SpooledInput {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl SpooledInput {
+    /// Read the subtitles of `format` from stdin, with the `idx` file for `VobSub`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the idx file is missing or can't be read,
+    /// or if stdin can't be read or written in the temporary directory.
+    pub fn read(format: Option<InputFormat>, idx: Option<&Path>) -> io::Result<Self> {
+        let format = format.ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                "`--input-format` is needed to read from stdin",
+            )
+        })?;
+        let spooled = match format {
+            InputFormat::Sup => Self::temp("stdin", "sup")?,
+            InputFormat::Ts => Self::temp("stdin", "ts")?,
+            InputFormat::Vobsub => {
+                let idx = idx.ok_or_else(|| {
+                    io::Error::new(
+                        ErrorKind::InvalidInput,
+                        "`--idx` is needed to read a VobSub stream from stdin",
+                    )
+                })?;
+                let spooled = Self::temp("stdin", "idx")?;
+                fs::copy(idx, &spooled.path)?;
+                spooled
+            }
+        };
+
+        let stream_path = match format {
+            InputFormat::Sup | InputFormat::Ts => spooled.path.clone(),
+            InputFormat::Vobsub => spooled.path.with_extension("sub"),
+        };
+        let mut stream = fs::File::options()
+            .write(true)
+            .create_new(true)
+            .open(stream_path)?;
+        let size = io::copy(&mut io::stdin().lock(), &mut stream)?;
+        info!("{size} bytes of subtitles read from stdin");
+        Ok(spooled)
+    }
+
+    /// Create the spooled input of a `source` at a path with the extension `ext` in a new
+    /// temporary directory, for its files to be written by the caller.
+    ///
+    /// The name of the directory has the process id, a counter and a random number, and
+    /// its creation fails if the name is taken, e.g. by a symbolic link: another name is tried then.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the directory can't be created.
+    pub(crate) fn temp(source: &str, ext: &str) -> io::Result<Self> {
+        let mut builder = fs::DirBuilder::new();
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        let mut tries = 0;
+        loop {
+            let dir = std::env::temp_dir().join(format!(
+                "subtile-ocr-{source}-{}-{}-{:016x}",
+                std::process::id(),
+                TEMP_DIR_COUNTER.fetch_add(1, Ordering::Relaxed),
+                RandomState::new().build_hasher().finish()
+            ));
+            match builder.create(&dir) {
+                Ok(()) => {
+                    let path = dir.join(format!("{source}.{ext}"));
+                    return Ok(Self { dir, path });
+                }
+                Err(err) if err.kind() == ErrorKind::AlreadyExists && tries < TEMP_DIR_TRIES => {
+                    tries += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Path of the temporary file to process, the idx file for `VobSub`.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for SpooledInput {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
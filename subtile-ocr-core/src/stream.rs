@@ -0,0 +1,218 @@
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::BufReader,
+    iter, mem,
+    path::{Path, PathBuf},
+};
+
+use image::GrayImage;
+use log::warn;
+use subtile::{
+    pgs::{DecodeTimeImage, SupParser},
+    time::TimeSpan,
+};
+
+use crate::{
+    absolute_path, dvb_to_ocr_image, mkv,
+    ocr::{OcrOpt, Recognizer},
+    ocr_opt, parse_dvb, pgs_compositions, pgs_to_ocr_image, sanity,
+    stdin::SpooledInput,
+    Config, Error, PgsImage, VobSubTrack,
+};
+
+/// Subtitles decoded by a [`SubtitlePipeline`], with their images prepared for `OCR`.
+pub type SubtitleImages<'a> = Box<dyn Iterator<Item = Result<(TimeSpan, GrayImage), Error>> + 'a>;
+
+/// Lazy decoding of the subtitles of a file, with their images prepared for `OCR`.
+///
+/// Unlike [`decode`](crate::decode), the images are converted one at a time as the iterator
+/// advances, so custom steps can run between the decoding and the `OCR` without holding all
+/// the images in memory. The times are the ones given by `decode`: the time offset, the filters
+/// and the timing fixes of [`run`](crate::run) are left to the caller.
+pub struct SubtitlePipeline<'a> {
+    opt: &'a Config,
+    input: Option<PathBuf>,
+}
+
+impl<'a> SubtitlePipeline<'a> {
+    /// Create a pipeline decoding the input of `opt`, with its decoding options.
+    #[must_use]
+    pub const fn new(opt: &'a Config) -> Self {
+        Self { opt, input: None }
+    }
+
+    /// Set the subtitle file to decode, instead of the input of the options.
+    #[must_use]
+    pub fn with_input(mut self, input: impl Into<PathBuf>) -> Self {
+        self.input = Some(input.into());
+        self
+    }
+
+    /// Decode the subtitles lazily.
+    ///
+    /// `PGS` subtitles are parsed as the iterator advances, `VobSub` and `DVB` subtitles
+    /// are parsed here and only their images are converted lazily.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::NoInput`] if no input is set.
+    /// Will return the errors of [`decode`](crate::decode) if the file can't be opened or parsed,
+    /// the parsing errors of `PGS` subtitles are given by the iterator.
+    pub fn images(&self) -> Result<SubtitleImages<'a>, Error> {
+        let input = self
+            .input
+            .as_deref()
+            .or(self.opt.input.path.as_deref())
+            .ok_or(Error::NoInput)?;
+        images(input, self.opt)
+    }
+}
+
+/// Decode the subtitles of `input` lazily.
+fn images<'a>(input: &Path, opt: &'a Config) -> Result<SubtitleImages<'a>, Error> {
+    // The subtitle track of a Matroska file is decoded from temporary files, kept until the end.
+    if mkv::is_matroska(input) {
+        let (spooled, _) =
+            mkv::spool_track(input, opt.input.track).map_err(|source| Error::Matroska {
+                path: absolute_path(input),
+                source,
+            })?;
+        let images = images(spooled.path(), opt)?;
+        return Ok(Box::new(WithSpooled {
+            images,
+            _spooled: spooled,
+        }));
+    }
+    if opt.input.recover {
+        sanity::report_truncation(input);
+    }
+    match input.extension().and_then(OsStr::to_str) {
+        Some("sup") => pgs_images(input, opt),
+        Some("idx") => vobsub_images(input, opt),
+        Some("ts") => dvb_images(input, opt),
+        Some(ext) => Err(Error::InvalidFileExtension {
+            extension: ext.into(),
+        }),
+        None => Err(Error::NoFileExtension),
+    }
+}
+
+/// Decode the `PGS` subtitles of `input` as the iterator advances.
+fn pgs_images<'a>(input: &Path, opt: &'a Config) -> Result<SubtitleImages<'a>, Error> {
+    let mut parser = SupParser::<BufReader<File>, DecodeTimeImage>::from_file(input)
+        .map_err(Error::PgsParserFromFile)?;
+    let mut compositions = pgs_compositions(input, opt)?;
+    let ocr_opt = ocr_opt(opt);
+    let mut parsed = 0;
+    let mut failed = false;
+    Ok(Box::new(iter::from_fn(move || {
+        while !failed {
+            match parser.next()? {
+                Ok((time, rle_img)) => {
+                    // The compositions are read alongside the subtitles, a shorter stream of compositions
+                    // leaves the last subtitles without position nor compositing.
+                    let composition = match compositions.next().transpose() {
+                        Ok(composition) => composition,
+                        Err(err) => {
+                            failed = true;
+                            return Some(Err(err));
+                        }
+                    };
+                    let origin = composition.as_ref().map(|composition| composition.origin);
+                    let pgs_image = PgsImage::new(rle_img, composition);
+                    if sanity::is_oversized(&time, &pgs_image, opt.input.max_image_size) {
+                        continue;
+                    }
+                    parsed += 1;
+                    let image = pgs_to_ocr_image(&pgs_image, origin, opt, &ocr_opt);
+                    return Some(Ok((time, image)));
+                }
+                // Keep the subtitles parsed before the first error, likely a truncation.
+                Err(err) if opt.input.recover => {
+                    warn!("PGS parsing stopped after {parsed} subtitles: {err}");
+                    failed = true;
+                }
+                Err(err) => {
+                    failed = true;
+                    return Some(Err(Error::PgsParsing(err)));
+                }
+            }
+        }
+        None
+    })))
+}
+
+/// Parse the `VobSub` subtitles of `input`, converting their images as the iterator advances.
+fn vobsub_images<'a>(input: &Path, opt: &'a Config) -> Result<SubtitleImages<'a>, Error> {
+    let mut track = VobSubTrack::parse(input, opt)?;
+    let subtitles = mem::take(&mut track.times)
+        .into_iter()
+        .zip(mem::take(&mut track.images));
+    let ocr_opt = ocr_opt(opt);
+    Ok(Box::new(subtitles.map(move |(time, image)| {
+        Ok((time, track.to_ocr_image(&image, opt, &ocr_opt)))
+    })))
+}
+
+/// Parse the `DVB` subtitles of `input`, converting their images as the iterator advances.
+fn dvb_images<'a>(input: &Path, opt: &'a Config) -> Result<SubtitleImages<'a>, Error> {
+    let subtitles = parse_dvb(input, opt)?;
+    Ok(Box::new(subtitles.into_iter().map(move |(time, image)| {
+        Ok((time, dvb_to_ocr_image(&image, opt)))
+    })))
+}
+
+/// Subtitles decoded from the temporary files of a Matroska track, removed once dropped.
+struct WithSpooled<'a> {
+    images: SubtitleImages<'a>,
+    _spooled: SpooledInput,
+}
+
+impl Iterator for WithSpooled<'_> {
+    type Item = Result<(TimeSpan, GrayImage), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.images.next()
+    }
+}
+
+/// Iterator adapter running the `OCR` of the images of subtitles one at a time,
+/// e.g. of the [`SubtitlePipeline`] images.
+///
+/// The errors of the decoding are forwarded, and the `OCR` of an image can fail without
+/// ending the iteration. The post-processing of the text by [`run`](crate::run) is not applied.
+pub struct OcrIter<'a, I> {
+    images: I,
+    recognizer: Recognizer<'a>,
+}
+
+impl<'a, I> OcrIter<'a, I>
+where
+    I: Iterator<Item = Result<(TimeSpan, GrayImage), Error>>,
+{
+    /// Create the adapter of `images`, recognized with the options of `opt`.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::Ocr`] if Tesseract can't be initialized with `opt`.
+    pub fn new(images: I, opt: &'a OcrOpt<'a>) -> Result<Self, Error> {
+        let recognizer = Recognizer::new(opt)?;
+        Ok(Self { images, recognizer })
+    }
+}
+
+impl<I> Iterator for OcrIter<'_, I>
+where
+    I: Iterator<Item = Result<(TimeSpan, GrayImage), Error>>,
+{
+    type Item = Result<(TimeSpan, String), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let subtitle = self.images.next()?;
+        Some(subtitle.and_then(|(time, image)| {
+            let text = self.recognizer.recognize(image)?;
+            Ok((time, text))
+        }))
+    }
+}
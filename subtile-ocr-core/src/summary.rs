@@ -0,0 +1,78 @@
+use std::{ffi::OsStr, path::Path};
+
+use log::{info, warn};
+
+use crate::idx;
+
+/// Target of the log records of the summary of the recognized streams, recorded in the
+/// `JSON` diagnostics whatever the log level.
+pub const SUMMARY_TARGET: &str = "subtile_ocr::summary";
+
+/// Stream of the input recognized by a run, and what was written from it.
+pub struct StreamSummary {
+    /// Stream in the input: the Matroska track id, and the `id:` line of an idx file.
+    pub stream: String,
+    /// Language of the `OCR`.
+    pub lang: String,
+    /// Number of subtitles written.
+    pub cues: usize,
+    /// Output files of the subtitles, `-` for stdout.
+    pub outputs: Vec<String>,
+}
+
+/// Describe the stream decoded from `input`, which is the temporary file of
+/// the `track` for a Matroska input.
+#[must_use]
+pub fn stream_id(input: &Path, track: Option<usize>) -> String {
+    let mut parts = Vec::new();
+    if let Some(track) = track {
+        parts.push(format!("track {track}"));
+    }
+    if input.extension().and_then(OsStr::to_str) == Some("idx") {
+        match idx::read_content(input) {
+            Ok(content) => parts.extend(
+                content
+                    .lines()
+                    .filter_map(|line| line.trim().strip_prefix("id:"))
+                    .map(|id| format!("id: {}", id.trim())),
+            ),
+            Err(err) => warn!("Unable to read the stream ids of the idx file: {err}"),
+        }
+    }
+    if parts.is_empty() {
+        "-".to_owned()
+    } else {
+        parts.join(", ")
+    }
+}
+
+/// Log the table of the recognized `streams`, to check that the intended track was recognized.
+pub fn log(streams: &[StreamSummary]) {
+    let width = streams
+        .iter()
+        .map(|stream| stream.stream.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("stream".len());
+    info!(
+        target: SUMMARY_TARGET,
+        "{:<width$}  {:<8}  {:>6}  output",
+        "stream",
+        "language",
+        "cues"
+    );
+    for stream in streams {
+        let outputs = if stream.outputs.is_empty() {
+            "none".to_owned()
+        } else {
+            stream.outputs.join(", ")
+        };
+        info!(
+            target: SUMMARY_TARGET,
+            "{:<width$}  {:<8}  {:>6}  {outputs}",
+            stream.stream,
+            stream.lang,
+            stream.cues
+        );
+    }
+}
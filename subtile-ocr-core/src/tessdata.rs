@@ -0,0 +1,106 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use log::{info, warn};
+
+/// Tessdata directory probed once for a run, when Tesseract can't be initialized
+/// with its default directory.
+///
+/// Set on an [`OcrOpt`](crate::OcrOpt) without tessdata directory, the usual install
+/// locations are probed for the language data after the first failed initialization,
+/// and the directory found is used by the next ones.
+#[derive(Debug, Default)]
+pub struct TessdataProbe(OnceLock<Option<String>>);
+
+impl TessdataProbe {
+    /// Get the tessdata directory found with the data of `lang`, probing it on the first call.
+    pub(crate) fn probe(&self, lang: &str) -> Option<&str> {
+        self.0.get_or_init(|| probe_tessdata_dir(lang)).as_deref()
+    }
+
+    /// Get the tessdata directory found by the probe, `None` if it didn't run or found nothing.
+    #[must_use]
+    pub fn dir(&self) -> Option<&str> {
+        self.0.get().and_then(Option::as_deref)
+    }
+}
+
+/// Probe the usual install locations for a tessdata directory with the data of `lang`.
+fn probe_tessdata_dir(lang: &str) -> Option<String> {
+    let candidates = candidate_dirs();
+    let found = candidates.iter().find(|dir| has_languages(dir, lang));
+    // Tesseract takes the tessdata directory as a UTF-8 string.
+    if let Some(dir) = found.filter(|dir| dir.to_str().is_none()) {
+        warn!(
+            "The tessdata directory `{}` has the data of `{lang}`, but its path is not valid UTF-8 as expected by Tesseract",
+            dir.display(),
+        );
+        return None;
+    }
+    let found = found.and_then(|dir| dir.to_str());
+    match found {
+        Some(dir) => info!(
+            "Tesseract can't be initialized with its default tessdata directory, using `{dir}` found with the data of `{lang}`"
+        ),
+        None => warn!(
+            "No tessdata directory with the data of `{lang}` found in: {}",
+            candidates
+                .iter()
+                .map(|dir| dir.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+    found.map(str::to_owned)
+}
+
+/// Usual tessdata directories of Linux distributions, macOS package managers and the Windows installer.
+fn candidate_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(prefix) = env::var_os("TESSDATA_PREFIX").map(PathBuf::from) {
+        // Depending on the Tesseract version, the prefix is the tessdata directory or its parent.
+        dirs.push(prefix.join("tessdata"));
+        dirs.push(prefix);
+    }
+    for share in ["/usr/share", "/usr/local/share", "/opt/homebrew/share"] {
+        let share = Path::new(share);
+        dirs.push(share.join("tessdata"));
+        // Debian and Ubuntu install the data in a directory per Tesseract version.
+        if let Ok(entries) = fs::read_dir(share.join("tesseract-ocr")) {
+            let mut versions = entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path().join("tessdata"))
+                .collect::<Vec<_>>();
+            versions.sort_unstable_by(|a, b| b.cmp(a));
+            dirs.extend(versions);
+        }
+        dirs.push(share.join("tesseract-ocr").join("tessdata"));
+    }
+    for var in ["ProgramFiles", "ProgramFiles(x86)"] {
+        if let Some(program_files) = env::var_os(var) {
+            dirs.push(
+                Path::new(&program_files)
+                    .join("Tesseract-OCR")
+                    .join("tessdata"),
+            );
+        }
+    }
+    if let Some(local_app_data) = env::var_os("LOCALAPPDATA") {
+        dirs.push(
+            Path::new(&local_app_data)
+                .join("Programs")
+                .join("Tesseract-OCR")
+                .join("tessdata"),
+        );
+    }
+    dirs
+}
+
+/// Check if `dir` has the data of all the languages of `lang`, like `eng+fra`.
+fn has_languages(dir: &Path, lang: &str) -> bool {
+    lang.split('+')
+        .all(|lang| dir.join(format!("{lang}.traineddata")).is_file())
+}
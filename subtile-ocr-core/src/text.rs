@@ -0,0 +1,109 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// Unicode normalization applied on the `OCR` output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Normalization {
+    /// Canonical composition.
+    #[default]
+    Nfc,
+    /// Canonical composition, with the Latin ligatures and full-width forms replaced by their
+    /// plain characters, for Latin scripts. The full-width punctuation of `CJK` scripts is lost.
+    NfcFolded,
+    /// Compatibility composition, which also replaces other compatibility characters (e.g. `½`, `²`).
+    Nfkc,
+    /// Keep the text as recognized.
+    Off,
+}
+
+/// Normalize `text` following `normalization`.
+#[must_use]
+pub fn normalize(text: &str, normalization: Normalization) -> String {
+    match normalization {
+        Normalization::Nfc => text.nfc().collect(),
+        Normalization::NfcFolded => text.chars().flat_map(expand_compatibility).nfc().collect(),
+        Normalization::Nfkc => text.nfkc().collect(),
+        Normalization::Off => text.to_owned(),
+    }
+}
+
+/// Replace the compatibility characters that Tesseract emits for plain text:
+/// Latin ligatures and full-width forms.
+fn expand_compatibility(c: char) -> impl Iterator<Item = char> {
+    let replacement: &'static str = match c {
+        '\u{FB00}' => "ff",
+        '\u{FB01}' => "fi",
+        '\u{FB02}' => "fl",
+        '\u{FB03}' => "ffi",
+        '\u{FB04}' => "ffl",
+        '\u{FB05}' | '\u{FB06}' => "st",
+        '\u{3000}' => " ",
+        _ => "",
+    };
+    // Full-width ASCII forms are shifted from the ASCII range.
+    let full_width = ('\u{FF01}'..='\u{FF5E}')
+        .contains(&c)
+        .then(|| char::from_u32(u32::from(c) - 0xFF01 + 0x21))
+        .flatten();
+    let single = match (replacement.is_empty(), full_width) {
+        (true, Some(ascii)) => Some(ascii),
+        (true, None) => Some(c),
+        (false, _) => None,
+    };
+    replacement.chars().chain(single)
+}
+
+/// Convention for the dashes starting the lines of a dialogue, at each change of speaker.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DialogueDashes {
+    /// Keep the dashes as recognized.
+    #[default]
+    Keep,
+    /// Hyphen followed by a space (`- Yes.`), the usual English convention.
+    Hyphen,
+    /// Hyphen directly followed by the text (`-Yes.`).
+    HyphenTight,
+    /// En dash followed by a space (`– Oui.`), the French cinema convention.
+    EnDash,
+}
+
+impl DialogueDashes {
+    /// Marker of a new speaker at the start of a line.
+    const fn marker(self) -> Option<&'static str> {
+        match self {
+            Self::Keep => None,
+            Self::Hyphen => Some("- "),
+            Self::HyphenTight => Some("-"),
+            Self::EnDash => Some("\u{2013} "),
+        }
+    }
+}
+
+/// Dashes recognized by Tesseract at the start of the dialogue lines.
+const DASHES: &[char] = &[
+    '-', '\u{2010}', '\u{2011}', '\u{2012}', '\u{2013}', '\u{2014}', '\u{2212}',
+];
+
+/// Write the dashes starting the dialogue lines of `text` with the `style` convention.
+///
+/// A leading dash directly followed by a digit is kept, as it is likely a negative number.
+#[must_use]
+pub fn normalize_dialogue_dashes(text: &str, style: DialogueDashes) -> String {
+    let Some(marker) = style.marker() else {
+        return text.to_owned();
+    };
+    let mut normalized = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_start();
+        let rest = content.strip_prefix(DASHES).filter(|rest| {
+            !rest.starts_with(DASHES) && !rest.starts_with(|c: char| c.is_ascii_digit())
+        });
+        match rest {
+            Some(rest) if !rest.trim().is_empty() => {
+                normalized.push_str(marker);
+                normalized.push_str(rest.trim_start_matches([' ', '\t']));
+            }
+            _ => normalized.push_str(line),
+        }
+    }
+    normalized
+}
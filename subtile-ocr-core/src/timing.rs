@@ -0,0 +1,588 @@
+use std::fmt;
+
+use log::{info, warn};
+use subtile::time::{TimePoint, TimeSpan};
+
+use crate::{cue::Cue, qa::QaReport};
+
+/// How to write subtitles displayed at the same time (e.g. two speakers, top and bottom).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverlapPolicy {
+    /// Write overlapping subtitles as they are.
+    #[default]
+    Keep,
+    /// Merge overlapping subtitles of distinct regions into one cue covering all of them.
+    Merge,
+    /// Split overlapping subtitles of distinct regions into successive cues,
+    /// each with all the texts visible at that time.
+    Split,
+}
+
+/// What to do with the subtitles ending before or when they start, rejected by several players.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InvalidTimePolicy {
+    /// Extend the subtitles to the minimum duration.
+    #[default]
+    Repair,
+    /// Remove the subtitles.
+    Drop,
+}
+
+/// Time in milliseconds of a point of the input (`src`), and the time it must have in the output (`dst`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Anchor {
+    /// Time in the input.
+    pub src: i64,
+    /// Corrected time.
+    pub dst: i64,
+}
+
+impl fmt::Display for Anchor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "src={},dst={}",
+            format_timestamp(TimePoint::from_msecs(self.src), '.'),
+            format_timestamp(TimePoint::from_msecs(self.dst), '.')
+        )
+    }
+}
+
+/// Check that the `anchors` keep the order of the times, each later `src` having a later `dst`.
+///
+/// # Errors
+///
+/// Return the first two anchors out of order, by `src`.
+pub fn check_anchors(anchors: &[Anchor]) -> Result<(), (Anchor, Anchor)> {
+    let mut anchors = anchors.to_vec();
+    anchors.sort_by_key(|anchor| (anchor.src, anchor.dst));
+    anchors.dedup();
+    anchors
+        .windows(2)
+        .find(|pair| pair[1].src == pair[0].src || pair[1].dst <= pair[0].dst)
+        .map_or(Ok(()), |pair| Err((pair[0], pair[1])))
+}
+
+/// Correct the drift of `times` with a piecewise-linear correction through the `anchors`.
+///
+/// A single anchor shifts all the times. Before the first anchor and after the last one,
+/// the correction of the nearest segment between two anchors is extended.
+/// The anchors must be checked with [`check_anchors`], and the corrected times before 0 are clamped at 0.
+#[must_use]
+#[profiling::function]
+pub fn apply_anchors(times: Vec<TimeSpan>, anchors: &[Anchor]) -> Vec<TimeSpan> {
+    let mut anchors = anchors.to_vec();
+    anchors.sort_by_key(|anchor| anchor.src);
+    anchors.dedup_by_key(|anchor| anchor.src);
+    let correct = |msecs: i64| {
+        let segment = match anchors[..] {
+            [] => return msecs,
+            [anchor] => return msecs + anchor.dst - anchor.src,
+            _ => {
+                let next = anchors
+                    .iter()
+                    .position(|anchor| anchor.src > msecs)
+                    .unwrap_or(anchors.len())
+                    .clamp(1, anchors.len() - 1);
+                (anchors[next - 1], anchors[next])
+            }
+        };
+        let (from, to) = segment;
+        let ratio = (to.dst - from.dst) as f64 / (to.src - from.src) as f64;
+        from.dst + ((msecs - from.src) as f64 * ratio).round() as i64
+    };
+    if !anchors.is_empty() {
+        info!("Times corrected with {} anchor(s)", anchors.len());
+    }
+    times
+        .into_iter()
+        .map(|time| {
+            TimeSpan::new(
+                TimePoint::from_msecs(correct(time.start.msecs()).max(0)),
+                TimePoint::from_msecs(correct(time.end.msecs()).max(0)),
+            )
+        })
+        .collect()
+}
+
+/// Repair or drop the subtitles with a zero or negative duration, following `policy`.
+///
+/// Repaired subtitles last `min_duration_ms`. Dropped subtitles are flagged in `qa`.
+#[must_use]
+#[profiling::function]
+pub fn fix_invalid_times<T>(
+    times: Vec<TimeSpan>,
+    items: Vec<T>,
+    policy: InvalidTimePolicy,
+    min_duration_ms: u32,
+    qa: &mut QaReport,
+) -> (Vec<TimeSpan>, Vec<T>) {
+    let mut invalid_count = 0;
+    let (times, items) = times
+        .into_iter()
+        .zip(items)
+        .enumerate()
+        .filter_map(|(idx, (time, item))| {
+            if time.end > time.start {
+                return Some((time, item));
+            }
+            invalid_count += 1;
+            match policy {
+                InvalidTimePolicy::Repair => {
+                    let end =
+                        TimePoint::from_msecs(time.start.msecs() + i64::from(min_duration_ms));
+                    Some((TimeSpan::new(time.start, end), item))
+                }
+                InvalidTimePolicy::Drop => {
+                    qa.add(
+                        time,
+                        format!(
+                            "decoded subtitle {} skipped, it ends before it starts",
+                            idx + 1
+                        ),
+                    );
+                    None
+                }
+            }
+        })
+        .unzip();
+
+    if invalid_count > 0 {
+        info!("{invalid_count} subtitles with a zero or negative duration have been handled with policy {policy:?}");
+    }
+    (times, items)
+}
+
+/// Remove the subtitles ending before 0, e.g. after a negative time offset, and start the others at 0 at the earliest.
+///
+/// The removed subtitles are flagged in `qa`.
+#[must_use]
+#[profiling::function]
+pub fn drop_before_zero<T>(
+    times: Vec<TimeSpan>,
+    items: Vec<T>,
+    qa: &mut QaReport,
+) -> (Vec<TimeSpan>, Vec<T>) {
+    let mut dropped_count = 0;
+    let (times, items) = times
+        .into_iter()
+        .zip(items)
+        .filter_map(|(time, item)| {
+            if time.end.msecs() <= 0 {
+                dropped_count += 1;
+                qa.add(time, "decoded subtitle skipped, it ends before 00:00:00");
+                return None;
+            }
+            let start = TimePoint::from_msecs(time.start.msecs().max(0));
+            Some((TimeSpan::new(start, time.end), item))
+        })
+        .unzip();
+
+    if dropped_count > 0 {
+        warn!("{dropped_count} subtitles ending before 00:00:00 have been removed");
+    }
+    (times, items)
+}
+
+/// Sort `subtitles` by start time, as players reject out of order cues.
+///
+/// The subtitles starting before a previous one, e.g. after a ripping glitch, are reported.
+#[must_use]
+#[profiling::function]
+pub fn sort_by_start(mut subtitles: Vec<Cue>) -> Vec<Cue> {
+    let mut latest_start = None;
+    let reordered_count = subtitles
+        .iter()
+        .filter(|cue| {
+            let reordered = latest_start.is_some_and(|latest| cue.time.start < latest);
+            latest_start = latest_start.max(Some(cue.time.start));
+            reordered
+        })
+        .count();
+    if reordered_count > 0 {
+        warn!("{reordered_count} subtitles starting before a previous one have been reordered by start time");
+        subtitles.sort_by_key(|cue| cue.time.start);
+    }
+    subtitles
+}
+
+/// Apply the overlap `policy` on `subtitles`.
+///
+/// Only the subtitles shown at the same time in distinct regions of the display are merged or split,
+/// like two speakers at the top and the bottom. The overlapping subtitles in the same region,
+/// e.g. after a timing glitch, are kept as they are. Subtitles of unknown region are handled
+/// as if in distinct regions.
+///
+/// Subtitles are sorted by start time if the policy is not [`OverlapPolicy::Keep`].
+#[must_use]
+#[profiling::function]
+pub fn handle_overlaps(mut subtitles: Vec<Cue>, policy: OverlapPolicy) -> Vec<Cue> {
+    if policy == OverlapPolicy::Keep {
+        return subtitles;
+    }
+    subtitles.sort_by_key(|cue| cue.time.start);
+
+    let mut result = Vec::with_capacity(subtitles.len());
+    let mut group: Vec<Cue> = Vec::new();
+    let mut overlap_count = 0;
+    let mut same_region_count = 0;
+    for cue in subtitles {
+        // The group is sorted by start time, a subtitle overlaps the ones ending after its start.
+        let mut overlapped = group.iter().filter(|other| cue.time.start < other.time.end);
+        let overlaps = overlapped.clone().next().is_some();
+        if overlapped.any(|other| in_same_region(other, &cue)) {
+            same_region_count += 1;
+            flush_group(&mut group, policy, &mut result);
+        } else if overlaps {
+            overlap_count += 1;
+        } else {
+            flush_group(&mut group, policy, &mut result);
+        }
+        group.push(cue);
+    }
+    flush_group(&mut group, policy, &mut result);
+
+    if overlap_count > 0 {
+        info!("{overlap_count} subtitles overlapping a previous one have been handled with policy {policy:?}");
+    }
+    if same_region_count > 0 {
+        warn!("{same_region_count} subtitles overlapping a previous one in the same region of the display are kept as they are");
+    }
+    result
+}
+
+/// Check if the subtitles `cue` and `other` are displayed in the same region, when both are known.
+fn in_same_region(cue: &Cue, other: &Cue) -> bool {
+    match (cue.region, other.region) {
+        (Some(region), Some(other)) => region.intersects(other),
+        _ => false,
+    }
+}
+
+/// Write the subtitles of an overlapping `group` into `out`, and empty the group.
+fn flush_group(group: &mut Vec<Cue>, policy: OverlapPolicy, out: &mut Vec<Cue>) {
+    if group.len() < 2 {
+        out.append(group);
+        return;
+    }
+
+    match policy {
+        OverlapPolicy::Keep => out.append(group),
+        OverlapPolicy::Merge => {
+            let start = group[0].time.start;
+            let end = group.iter().map(|cue| cue.time.end).max().unwrap_or(start);
+            let text = join_texts(group.iter());
+            out.push(Cue::merged(TimeSpan::new(start, end), text, group.iter()));
+            group.clear();
+        }
+        OverlapPolicy::Split => {
+            let mut bounds = group
+                .iter()
+                .flat_map(|cue| [cue.time.start, cue.time.end])
+                .collect::<Vec<_>>();
+            bounds.sort();
+            bounds.dedup();
+            for window in bounds.windows(2) {
+                let (start, end) = (window[0], window[1]);
+                let visible = group
+                    .iter()
+                    .filter(|cue| cue.time.start <= start && cue.time.end >= end)
+                    .collect::<Vec<_>>();
+                if !visible.is_empty() {
+                    let text = join_texts(visible.iter().copied());
+                    out.push(Cue::merged(TimeSpan::new(start, end), text, visible));
+                }
+            }
+            group.clear();
+        }
+    }
+}
+
+/// Join the texts of several subtitles, one after the other.
+fn join_texts<'a>(cues: impl IntoIterator<Item = &'a Cue>) -> String {
+    let mut joined = cues
+        .into_iter()
+        .map(|cue| cue.text.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n");
+    joined.push('\n');
+    joined
+}
+
+/// Format `time` as `HH:MM:SS<separator>mmm`.
+#[must_use]
+pub fn format_timestamp(time: TimePoint, decimal_separator: char) -> String {
+    let msecs = time.msecs();
+    let sign = if msecs < 0 { "-" } else { "" };
+    let msecs = msecs.abs();
+    format!(
+        "{sign}{:02}:{:02}:{:02}{decimal_separator}{:03}",
+        msecs / 3_600_000,
+        msecs / 60_000 % 60,
+        msecs / 1000 % 60,
+        msecs % 1000
+    )
+}
+
+/// Format a time in milliseconds as `HH-MM-SS.mmm`, usable in file names.
+pub(crate) fn file_timestamp(msecs: i64) -> String {
+    format_timestamp(TimePoint::from_msecs(msecs), '.').replace(':', "-")
+}
+
+/// Shift `time` by `offset_ms` milliseconds.
+///
+/// The shifted time can be negative, the subtitles ending before 0 are removed by [`drop_before_zero`].
+#[must_use]
+pub fn shift_span(time: TimeSpan, offset_ms: i64) -> TimeSpan {
+    if offset_ms == 0 {
+        return time;
+    }
+    TimeSpan::new(
+        TimePoint::from_msecs(time.start.msecs() + offset_ms),
+        TimePoint::from_msecs(time.end.msecs() + offset_ms),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use subtile::time::{TimePoint, TimeSpan};
+
+    use super::{
+        apply_anchors, check_anchors, drop_before_zero, file_timestamp, fix_invalid_times,
+        format_timestamp, handle_overlaps, shift_span, Anchor, InvalidTimePolicy, OverlapPolicy,
+    };
+    use crate::{
+        cue::{Cue, Region},
+        qa::QaReport,
+    };
+
+    fn span(start: i64, end: i64) -> TimeSpan {
+        TimeSpan::new(TimePoint::from_msecs(start), TimePoint::from_msecs(end))
+    }
+
+    fn cue(start: i64, end: i64, text: &str, top: Option<u32>) -> Cue {
+        Cue {
+            time: span(start, end),
+            text: format!("{text}\n"),
+            italics: vec![false],
+            confidence: None,
+            low_confidence: None,
+            region: top.map(|top| Region::new(top, 50)),
+        }
+    }
+
+    fn spans(cues: &[Cue]) -> Vec<(i64, i64, &str)> {
+        cues.iter()
+            .map(|cue| {
+                (
+                    cue.time.start.msecs(),
+                    cue.time.end.msecs(),
+                    cue.text.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merge_distinct_regions() {
+        let cues = vec![
+            cue(1000, 3000, "Bottom", Some(400)),
+            cue(2000, 4000, "Top", Some(20)),
+            cue(5000, 6000, "After", Some(400)),
+        ];
+        let merged = handle_overlaps(cues, OverlapPolicy::Merge);
+        assert_eq!(
+            spans(&merged),
+            [(1000, 4000, "Bottom\nTop\n"), (5000, 6000, "After\n")]
+        );
+        assert_eq!(
+            merged[0].region,
+            Some(Region {
+                top: 20,
+                bottom: 450
+            })
+        );
+        assert_eq!(merged[0].italics, [false, false]);
+    }
+
+    #[test]
+    fn split_distinct_regions() {
+        let cues = vec![
+            cue(1000, 3000, "Bottom", Some(400)),
+            cue(2000, 4000, "Top", Some(20)),
+        ];
+        let split = handle_overlaps(cues, OverlapPolicy::Split);
+        assert_eq!(
+            spans(&split),
+            [
+                (1000, 2000, "Bottom\n"),
+                (2000, 3000, "Bottom\nTop\n"),
+                (3000, 4000, "Top\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn keep_overlaps_in_same_region() {
+        for policy in [OverlapPolicy::Merge, OverlapPolicy::Split] {
+            let cues = vec![
+                cue(1000, 3000, "First", Some(400)),
+                cue(2000, 4000, "Second", Some(420)),
+            ];
+            let handled = handle_overlaps(cues, policy);
+            assert_eq!(
+                spans(&handled),
+                [(1000, 3000, "First\n"), (2000, 4000, "Second\n")],
+                "{policy:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_regions_are_distinct() {
+        let cues = vec![
+            cue(1000, 3000, "First", None),
+            cue(2000, 4000, "Second", Some(400)),
+        ];
+        let merged = handle_overlaps(cues, OverlapPolicy::Merge);
+        assert_eq!(spans(&merged), [(1000, 4000, "First\nSecond\n")]);
+        assert_eq!(merged[0].region, None);
+    }
+
+    #[test]
+    fn keep_policy_leaves_the_order() {
+        let cues = vec![
+            cue(2000, 4000, "Second", Some(20)),
+            cue(1000, 3000, "First", Some(400)),
+        ];
+        let kept = handle_overlaps(cues.clone(), OverlapPolicy::Keep);
+        assert_eq!(kept, cues);
+    }
+
+    #[test]
+    fn negative_offset() {
+        let times = [span(500, 1500), span(1500, 2500), span(3000, 4000)]
+            .into_iter()
+            .map(|time| shift_span(time, -2000))
+            .collect::<Vec<_>>();
+        let mut qa = QaReport::default();
+        let (times, items) = drop_before_zero(times, vec!['a', 'b', 'c'], &mut qa);
+        assert_eq!(times, [span(0, 500), span(1000, 2000)]);
+        assert_eq!(items, ['b', 'c']);
+        let issues = qa.issues();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].time, span(-1500, -500));
+    }
+
+    #[test]
+    fn positive_offset() {
+        assert_eq!(shift_span(span(500, 1500), 250), span(750, 1750));
+        assert_eq!(shift_span(span(500, 1500), 0), span(500, 1500));
+    }
+
+    #[test]
+    fn single_anchor_shifts() {
+        let anchors = [Anchor {
+            src: 10_000,
+            dst: 9_000,
+        }];
+        let times = apply_anchors(vec![span(500, 1500), span(20_000, 21_000)], &anchors);
+        assert_eq!(times, [span(0, 500), span(19_000, 20_000)]);
+    }
+
+    #[test]
+    fn anchors_correct_piecewise() {
+        let anchors = [
+            Anchor {
+                src: 100_000,
+                dst: 101_000,
+            },
+            Anchor { src: 0, dst: 0 },
+            Anchor {
+                src: 200_000,
+                dst: 201_000,
+            },
+        ];
+        check_anchors(&anchors).unwrap();
+        let times = apply_anchors(
+            vec![
+                span(50_000, 52_000),
+                span(150_000, 152_000),
+                span(300_000, 302_000),
+            ],
+            &anchors,
+        );
+        assert_eq!(
+            times,
+            [
+                span(50_500, 52_520),
+                span(151_000, 153_000),
+                span(301_000, 303_000)
+            ]
+        );
+    }
+
+    #[test]
+    fn backward_anchors() {
+        let first = Anchor {
+            src: 1000,
+            dst: 5000,
+        };
+        let backward = Anchor {
+            src: 2000,
+            dst: 4000,
+        };
+        assert_eq!(check_anchors(&[backward, first]), Err((first, backward)));
+        let same_src = Anchor {
+            src: 1000,
+            dst: 6000,
+        };
+        assert_eq!(check_anchors(&[first, same_src]), Err((first, same_src)));
+        assert_eq!(check_anchors(&[first, first]), Ok(()));
+        assert_eq!(
+            first.to_string(),
+            "src=00:00:01.000,dst=00:00:05.000".to_owned()
+        );
+    }
+
+    #[test]
+    fn repair_invalid_times() {
+        let times = vec![span(1000, 2000), span(3000, 3000), span(5000, 4000)];
+        let mut qa = QaReport::default();
+        let (times, items) = fix_invalid_times(
+            times,
+            vec![1, 2, 3],
+            InvalidTimePolicy::Repair,
+            500,
+            &mut qa,
+        );
+        assert_eq!(
+            times,
+            [span(1000, 2000), span(3000, 3500), span(5000, 5500)]
+        );
+        assert_eq!(items, [1, 2, 3]);
+        assert!(qa.issues().is_empty());
+    }
+
+    #[test]
+    fn drop_invalid_times() {
+        let times = vec![span(1000, 2000), span(3000, 3000), span(5000, 4000)];
+        let mut qa = QaReport::default();
+        let (times, items) =
+            fix_invalid_times(times, vec![1, 2, 3], InvalidTimePolicy::Drop, 500, &mut qa);
+        assert_eq!(times, [span(1000, 2000)]);
+        assert_eq!(items, [1]);
+        assert_eq!(qa.issues().len(), 2);
+    }
+
+    #[test]
+    fn timestamps() {
+        let time = TimePoint::from_msecs(3_723_004);
+        assert_eq!(format_timestamp(time, ','), "01:02:03,004");
+        assert_eq!(
+            format_timestamp(TimePoint::from_msecs(-1_500), '.'),
+            "-00:00:01.500"
+        );
+        assert_eq!(file_timestamp(3_723_004), "01-02-03.004");
+    }
+}
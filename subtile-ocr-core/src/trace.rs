@@ -0,0 +1,100 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use subtile::time::TimeSpan;
+
+use crate::{
+    idx::{self, IdxTiming},
+    timing::format_timestamp,
+};
+
+/// Find where each decoded subtitle comes from in `input`.
+///
+/// For `VobSub` this is the packet position given by the idx file, for `PGS` the
+/// number of the subtitle in the stream. `time_offset` is the offset from cli, if any,
+/// which replaces the idx timing directives.
+///
+/// # Errors
+///
+/// Will return an error if the idx file can't be read.
+#[profiling::function]
+pub fn origins(
+    input: &Path,
+    times: &[TimeSpan],
+    time_offset: Option<i64>,
+) -> io::Result<Vec<(TimeSpan, String)>> {
+    let is_vobsub = input.extension().is_some_and(|ext| ext == "idx");
+    if !is_vobsub {
+        return Ok(times
+            .iter()
+            .enumerate()
+            .map(|(idx, time)| (*time, format!("display set #{}", idx + 1)))
+            .collect());
+    }
+
+    let content = idx::read_content(input)?;
+    let idx_timing = IdxTiming::parse(&content);
+    let entries = idx::parse_entries(&content)
+        .into_iter()
+        .map(|entry| {
+            // Shift the entry time like the subtitles times.
+            let timestamp = match time_offset {
+                Some(offset) => entry.timestamp + offset,
+                None => idx_timing.apply_msecs(entry.timestamp),
+            };
+            (timestamp, entry.filepos)
+        })
+        .collect::<Vec<_>>();
+
+    Ok(times
+        .iter()
+        .map(|time| {
+            let start = time.start.msecs();
+            let origin = entries
+                .iter()
+                .min_by_key(|(timestamp, _)| (timestamp - start).abs())
+                .map_or_else(
+                    || "unknown".to_owned(),
+                    |(_, filepos)| format!("filepos 0x{filepos:09X}"),
+                );
+            (*time, origin)
+        })
+        .collect())
+}
+
+/// Write, for each subtitle, the origins of the decoded subtitles displayed at the same time.
+///
+/// # Errors
+///
+/// Will return an error if the file can't be created or written.
+#[profiling::function]
+pub fn write_trace(
+    path: &Path,
+    subtitles: &[(TimeSpan, String)],
+    origins: &[(TimeSpan, String)],
+) -> io::Result<()> {
+    crate::create_parent_dirs(path)?;
+    let mut out = BufWriter::new(File::create(path)?);
+    for (idx, (time, _)) in subtitles.iter().enumerate() {
+        let sources = origins
+            .iter()
+            .filter(|(origin_time, _)| {
+                let overlaps = origin_time.start < time.end && origin_time.end > time.start;
+                overlaps || origin_time.start == time.start
+            })
+            .map(|(_, origin)| origin.as_str())
+            .collect::<Vec<_>>();
+        writeln!(
+            out,
+            "{}\t{} --> {}\t{}",
+            idx + 1,
+            format_timestamp(time.start, ','),
+            format_timestamp(time.end, ','),
+            sources.join("; ")
+        )?;
+    }
+    out.flush()
+}
@@ -0,0 +1,252 @@
+use log::warn;
+use subtile::time::TimeSpan;
+
+use crate::{
+    cue::Cue,
+    qa::QaReport,
+    timing::{self, format_timestamp, InvalidTimePolicy},
+};
+
+/// What to do with the cues breaking the `SRT` constraints before writing them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ValidationPolicy {
+    /// Report each invalid cue, and write the cues as they are.
+    #[default]
+    Report,
+    /// Fix the invalid cues, reporting each adjustment.
+    Fix,
+    /// Fail, reporting each invalid cue.
+    Error,
+    /// Write the cues as they are.
+    Off,
+}
+
+/// Check `subtitles` against the `SRT` constraints, following `policy`.
+///
+/// Cues must start in order, last at least one millisecond, have a text and, if
+/// `max_lines` is set, at most this number of lines. With [`ValidationPolicy::Fix`],
+/// cues are sorted by start time, cues without text are removed, extra lines are joined
+/// to the last allowed one, and the cues that don't last are handled like the decoded
+/// subtitles, with [`timing::fix_invalid_times`] and the `invalid_times` policy.
+/// With `keep_order`, cues out of order are only reported, and never sorted.
+///
+/// # Errors
+///
+/// Will return the number of invalid cues with [`ValidationPolicy::Error`], if any.
+#[profiling::function]
+pub fn validate(
+    mut subtitles: Vec<Cue>,
+    policy: ValidationPolicy,
+    keep_order: bool,
+    max_lines: Option<usize>,
+    invalid_times: InvalidTimePolicy,
+    min_duration_ms: u32,
+    qa: &mut QaReport,
+) -> Result<Vec<Cue>, usize> {
+    if policy == ValidationPolicy::Off {
+        return Ok(subtitles);
+    }
+    let report = |idx: usize, time: TimeSpan, message: &str| {
+        warn!(
+            "cue {} ({} --> {}) {message}",
+            idx + 1,
+            format_timestamp(time.start, ','),
+            format_timestamp(time.end, ',')
+        );
+    };
+    let fix = policy == ValidationPolicy::Fix;
+    let mut invalid_count = 0;
+
+    let unordered = subtitles
+        .windows(2)
+        .position(|pair| pair[1].time.start < pair[0].time.start);
+    if let Some(idx) = unordered {
+        let time = subtitles[idx + 1].time;
+        if keep_order {
+            report(
+                idx + 1,
+                time,
+                "starts before the previous one, the order is kept",
+            );
+        } else if fix {
+            report(
+                idx + 1,
+                time,
+                "starts before the previous one, cues are sorted",
+            );
+            subtitles.sort_by_key(|cue| cue.time.start);
+        } else {
+            report(idx + 1, time, "starts before the previous one");
+            invalid_count += 1;
+        }
+    }
+
+    let mut valid = Vec::with_capacity(subtitles.len());
+    for (idx, mut cue) in subtitles.into_iter().enumerate() {
+        let time = cue.time;
+        if cue.text.trim().is_empty() {
+            invalid_count += 1;
+            if fix {
+                report(idx, time, "has no text, it is removed");
+                continue;
+            }
+            report(idx, time, "has no text");
+        }
+        if time.end <= time.start {
+            invalid_count += 1;
+            if fix {
+                report(
+                    idx,
+                    time,
+                    &format!("doesn't last, it is handled with policy {invalid_times:?}"),
+                );
+            } else {
+                report(idx, time, "doesn't last");
+            }
+        }
+        let line_count = cue.text.trim_end().lines().count();
+        if let Some(max_lines) = max_lines.filter(|&max_lines| line_count > max_lines) {
+            invalid_count += 1;
+            if fix {
+                report(
+                    idx,
+                    time,
+                    &format!(
+                        "has {line_count} lines, the last ones are joined to keep {max_lines}"
+                    ),
+                );
+                cue.text = join_extra_lines(&cue.text, max_lines);
+            } else {
+                report(idx, time, &format!("has {line_count} lines"));
+            }
+        }
+        valid.push(cue);
+    }
+
+    match policy {
+        ValidationPolicy::Fix => {
+            let times = valid.iter().map(|cue| cue.time).collect();
+            let (times, cues) =
+                timing::fix_invalid_times(times, valid, invalid_times, min_duration_ms, qa);
+            Ok(times
+                .into_iter()
+                .zip(cues)
+                .map(|(time, cue)| Cue { time, ..cue })
+                .collect())
+        }
+        ValidationPolicy::Error if invalid_count > 0 => Err(invalid_count),
+        _ => Ok(valid),
+    }
+}
+
+/// Join the lines of `text` after the `max_lines - 1` first ones, to have at most `max_lines` lines.
+fn join_extra_lines(text: &str, max_lines: usize) -> String {
+    let lines = text.trim_end().lines().map(str::trim).collect::<Vec<_>>();
+    let kept = max_lines.saturating_sub(1).min(lines.len());
+    let mut joined = lines[..kept].join("\n");
+    if kept > 0 {
+        joined.push('\n');
+    }
+    joined.push_str(&lines[kept..].join(" "));
+    joined.push('\n');
+    joined
+}
+
+#[cfg(test)]
+mod tests {
+    use subtile::time::{TimePoint, TimeSpan};
+
+    use super::{join_extra_lines, validate, ValidationPolicy};
+    use crate::{cue::Cue, qa::QaReport, timing::InvalidTimePolicy};
+
+    fn cue(start: i64, end: i64, text: &str) -> Cue {
+        Cue {
+            time: TimeSpan::new(TimePoint::from_msecs(start), TimePoint::from_msecs(end)),
+            text: text.to_owned(),
+            italics: vec![false],
+            confidence: None,
+            low_confidence: None,
+            region: None,
+        }
+    }
+
+    /// Cues breaking each constraint: out of order, without text, not lasting and with 3 lines.
+    fn invalid_cues() -> Vec<Cue> {
+        vec![
+            cue(2000, 3000, "Second\n"),
+            cue(1000, 1500, "First\n"),
+            cue(4000, 5000, " \n"),
+            cue(6000, 6000, "Instant\n"),
+            cue(7000, 8000, "One\nTwo\nThree\n"),
+        ]
+    }
+
+    fn check(policy: ValidationPolicy, keep_order: bool) -> Result<Vec<(i64, i64, String)>, usize> {
+        let mut qa = QaReport::default();
+        let cues = validate(
+            invalid_cues(),
+            policy,
+            keep_order,
+            Some(2),
+            InvalidTimePolicy::Repair,
+            500,
+            &mut qa,
+        )?;
+        Ok(cues
+            .into_iter()
+            .map(|cue| (cue.time.start.msecs(), cue.time.end.msecs(), cue.text))
+            .collect())
+    }
+
+    #[test]
+    fn report_and_off_keep_the_cues() {
+        let cues = invalid_cues()
+            .into_iter()
+            .map(|cue| (cue.time.start.msecs(), cue.time.end.msecs(), cue.text))
+            .collect::<Vec<_>>();
+        assert_eq!(check(ValidationPolicy::Report, false), Ok(cues.clone()));
+        assert_eq!(check(ValidationPolicy::Off, false), Ok(cues));
+    }
+
+    #[test]
+    fn error_counts_the_invalid_cues() {
+        assert_eq!(check(ValidationPolicy::Error, false), Err(4));
+        // The order is only reported.
+        assert_eq!(check(ValidationPolicy::Error, true), Err(3));
+        assert_eq!(
+            validate(
+                vec![cue(1000, 2000, "Valid\n")],
+                ValidationPolicy::Error,
+                false,
+                Some(2),
+                InvalidTimePolicy::Repair,
+                500,
+                &mut QaReport::default(),
+            )
+            .map(|cues| cues.len()),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn fix() {
+        assert_eq!(
+            check(ValidationPolicy::Fix, false),
+            Ok(vec![
+                (1000, 1500, "First\n".to_owned()),
+                (2000, 3000, "Second\n".to_owned()),
+                (6000, 6500, "Instant\n".to_owned()),
+                (7000, 8000, "One\nTwo Three\n".to_owned()),
+            ])
+        );
+        let kept_order = check(ValidationPolicy::Fix, true).unwrap();
+        assert_eq!(kept_order[0].2, "Second\n");
+    }
+
+    #[test]
+    fn join_lines() {
+        assert_eq!(join_extra_lines("a\n b\nc \n", 2), "a\nb c\n");
+        assert_eq!(join_extra_lines("a\nb\n", 1), "a b\n");
+        assert_eq!(join_extra_lines("a\nb\n", 0), "a b\n");
+    }
+}
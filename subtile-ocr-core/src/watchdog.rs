@@ -0,0 +1,340 @@
+use std::{
+    io::{self, BufRead, Read, Seek, SeekFrom},
+    panic,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Arc, Mutex, PoisonError,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Interval between two checks of the progress of a parsing.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Maximum number of threads detached after a timeout and still running, in the whole process.
+///
+/// A detached thread keeps its memory (e.g. a Tesseract instance) until it ends, if ever,
+/// so no thread is started with a timeout once this number is reached: the parsing or
+/// the `OCR` which needs one fails instead.
+pub const MAX_DETACHED_THREADS: usize = 8;
+
+/// Number of threads detached after a timeout and still running.
+static DETACHED_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Get the number of detached threads still running, if it reached [`MAX_DETACHED_THREADS`].
+pub fn detached_limit_reached() -> Option<usize> {
+    let count = DETACHED_THREADS.load(Ordering::Relaxed);
+    (count >= MAX_DETACHED_THREADS).then_some(count)
+}
+
+/// State of a [`TimedThread`], shared with the thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ThreadState {
+    Running,
+    Detached,
+    Ended,
+}
+
+/// Mark the end of the thread of a [`TimedThread`], even if its task panicked.
+struct EndGuard(Arc<Mutex<ThreadState>>);
+
+impl Drop for EndGuard {
+    fn drop(&mut self) {
+        let mut state = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if *state == ThreadState::Detached {
+            DETACHED_THREADS.fetch_sub(1, Ordering::Relaxed);
+        }
+        *state = ThreadState::Ended;
+    }
+}
+
+/// Task run on a dedicated thread, to wait for its result with a timeout.
+///
+/// The tasks can't be interrupted: the thread of a task given up is detached,
+/// and counted until it ends for [`MAX_DETACHED_THREADS`].
+pub struct TimedThread<T> {
+    receiver: Receiver<T>,
+    handle: Option<JoinHandle<()>>,
+    state: Arc<Mutex<ThreadState>>,
+}
+
+impl<T: Send + 'static> TimedThread<T> {
+    /// Run `task` on a new thread.
+    pub fn spawn(task: impl FnOnce() -> T + Send + 'static) -> Self {
+        let state = Arc::new(Mutex::new(ThreadState::Running));
+        let (sender, receiver) = mpsc::channel();
+        let handle = {
+            let end = EndGuard(state.clone());
+            thread::spawn(move || {
+                let _end = end;
+                let _ = sender.send(task());
+            })
+        };
+        Self {
+            receiver,
+            handle: Some(handle),
+            state,
+        }
+    }
+
+    /// Wait for the result of the task during `timeout`, `None` if it isn't available yet.
+    ///
+    /// A panic of the task is forwarded, like if it ran on the current thread.
+    pub fn wait(&mut self, timeout: Duration) -> Option<T> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(result) => Some(result),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => {
+                let payload = self
+                    .handle
+                    .take()
+                    .and_then(|handle| handle.join().err())
+                    .unwrap_or_else(|| Box::new("the thread ended without result"));
+                panic::resume_unwind(payload)
+            }
+        }
+    }
+
+    /// Give up the task, its thread is left running until it ends.
+    pub fn detach(self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if *state == ThreadState::Running {
+            *state = ThreadState::Detached;
+            DETACHED_THREADS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Progress of a parser, shared with its watchdog.
+#[derive(Clone, Debug, Default)]
+pub struct Progress(Arc<ProgressState>);
+
+/// State of a [`Progress`].
+#[derive(Debug, Default)]
+struct ProgressState {
+    /// Number of subtitles given by the parser.
+    parsed: AtomicUsize,
+    /// Byte offset of the reader of the parser, if read through [`Progress::reader`].
+    offset: AtomicU64,
+    /// Whether the parser reads through [`Progress::reader`].
+    reading: AtomicBool,
+    /// Whether the parsing has been given up.
+    given_up: AtomicBool,
+}
+
+impl Progress {
+    /// Count a subtitle given by the parser, parsed or not.
+    pub fn advance(&self) {
+        self.0.parsed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get the number of subtitles given by the parser.
+    #[must_use]
+    pub fn get(&self) -> usize {
+        self.0.parsed.load(Ordering::Relaxed)
+    }
+
+    /// Wrap the `reader` of the parser, to track its byte offset and to stop it once given up.
+    pub fn reader<R>(&self, reader: R) -> ProgressReader<R> {
+        self.0.reading.store(true, Ordering::Relaxed);
+        ProgressReader {
+            reader,
+            progress: self.clone(),
+        }
+    }
+
+    /// Get the byte offset of the reader of the parser, if read through [`Progress::reader`].
+    #[must_use]
+    pub fn offset(&self) -> Option<u64> {
+        self.0
+            .reading
+            .load(Ordering::Relaxed)
+            .then(|| self.0.offset.load(Ordering::Relaxed))
+    }
+
+    /// Give up the parsing: the reads of its [`ProgressReader`] fail from now on.
+    fn give_up(&self) {
+        self.0.given_up.store(true, Ordering::Relaxed);
+    }
+
+    /// Move the byte offset of the reader by `amount` bytes.
+    fn read(&self, amount: usize) {
+        self.0
+            .offset
+            .fetch_add(u64::try_from(amount).unwrap_or(u64::MAX), Ordering::Relaxed);
+    }
+
+    /// Fail if the parsing has been given up.
+    fn check(&self) -> io::Result<()> {
+        if self.0.given_up.load(Ordering::Relaxed) {
+            Err(io::Error::other(
+                "the parsing has been given up after a stall",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Reader of a parser watched with [`watch`], tracking its byte offset and failing once given up.
+pub struct ProgressReader<R> {
+    reader: R,
+    progress: Progress,
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.progress.check()?;
+        let read = self.reader.read(buf)?;
+        self.progress.read(read);
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for ProgressReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.progress.check()?;
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.progress.read(amount);
+        self.reader.consume(amount);
+    }
+}
+
+impl<R: Seek> Seek for ProgressReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.progress.check()?;
+        let offset = self.reader.seek(pos)?;
+        self.progress.0.offset.store(offset, Ordering::Relaxed);
+        Ok(offset)
+    }
+}
+
+/// Parsing given up by the watchdog.
+#[derive(Debug, Clone, Copy)]
+pub enum GivenUp {
+    /// The parser made no progress during the timeout.
+    Stalled {
+        /// Number of subtitles given by the parser before it stalled.
+        parsed: usize,
+        /// Byte offset of the reader of the parser when it stalled, if read through [`Progress::reader`].
+        offset: Option<u64>,
+    },
+    /// Too many threads given up are still running to start another one.
+    TooManyDetached {
+        /// Number of threads given up and still running.
+        count: usize,
+    },
+}
+
+/// Run `parse`, giving up if it doesn't advance its [`Progress`] during `stall_timeout`.
+///
+/// The parsers can't be interrupted, so with a timeout the parsing runs on a [`TimedThread`],
+/// which is detached if it stalls. Without timeout, `parse` runs on the current thread.
+///
+/// A parser reading through [`Progress::reader`] gets an error at its next read once given up,
+/// which ends its thread. A parser stalled without reading, e.g. looping on data already read,
+/// keeps its detached thread running until it ends, or until the process exits: these threads
+/// are limited by [`MAX_DETACHED_THREADS`].
+///
+/// # Errors
+///
+/// Will return [`GivenUp::Stalled`] if the parsing made no progress during `stall_timeout`,
+/// and [`GivenUp::TooManyDetached`] if [`MAX_DETACHED_THREADS`] is reached.
+pub fn watch<T, F>(stall_timeout: Option<Duration>, parse: F) -> Result<T, GivenUp>
+where
+    F: FnOnce(&Progress) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let progress = Progress::default();
+    let Some(stall_timeout) = stall_timeout else {
+        return Ok(parse(&progress));
+    };
+    if let Some(count) = detached_limit_reached() {
+        return Err(GivenUp::TooManyDetached { count });
+    }
+
+    let mut thread = {
+        let progress = progress.clone();
+        TimedThread::spawn(move || parse(&progress))
+    };
+    let mut parsed = progress.get();
+    let mut last_progress = Instant::now();
+    loop {
+        if let Some(result) = thread.wait(POLL_INTERVAL) {
+            return Ok(result);
+        }
+        let current = progress.get();
+        if current != parsed {
+            parsed = current;
+            last_progress = Instant::now();
+        } else if last_progress.elapsed() >= stall_timeout {
+            let offset = progress.offset();
+            progress.give_up();
+            thread.detach();
+            return Err(GivenUp::Stalled { parsed, offset });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{self, Read},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::{watch, GivenUp, Progress};
+
+    #[test]
+    fn reader_offset() {
+        let progress = Progress::default();
+        assert_eq!(progress.offset(), None);
+        let mut reader = progress.reader(io::repeat(0));
+        reader.read_exact(&mut [0; 10]).unwrap();
+        assert_eq!(progress.offset(), Some(10));
+        progress.give_up();
+        assert!(reader.read(&mut [0; 10]).is_err());
+        assert_eq!(progress.offset(), Some(10));
+    }
+
+    #[test]
+    fn stalled_reader_is_stopped() {
+        let ended = Arc::new(AtomicBool::new(false));
+        let parse_ended = ended.clone();
+        let given_up = watch(Some(Duration::from_millis(300)), move |progress| {
+            let mut reader = progress.reader(io::repeat(0));
+            reader.read_exact(&mut [0; 4]).unwrap();
+            // Stalled parser, reading nothing until it is given up.
+            while reader.read(&mut []).is_ok() {
+                thread::sleep(Duration::from_millis(10));
+            }
+            parse_ended.store(true, Ordering::Relaxed);
+        })
+        .unwrap_err();
+        assert!(matches!(
+            given_up,
+            GivenUp::Stalled {
+                parsed: 0,
+                offset: Some(4)
+            }
+        ));
+        let start = Instant::now();
+        while !ended.load(Ordering::Relaxed) {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "parser not stopped"
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}